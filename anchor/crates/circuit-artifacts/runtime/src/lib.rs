@@ -0,0 +1,192 @@
+//! Witness calculator with the exports of the wasm circom 2 generates, so the snarkjs
+//! witness calculator and ark-circom load it as they load those. The circuit is the witness
+//! program embedded at build time; the witness is computed once every input is set.
+//!
+//! Values go through the shared memory as 8 little-endian 32 bit limbs. Errors call the
+//! `exceptionHandler` import with the circom error code, then trap.
+
+#[path = "../../src/program.rs"]
+#[allow(dead_code)]
+mod program;
+
+use std::cell::RefCell;
+
+use num_bigint::BigUint;
+use program::{Failure, Program};
+
+const FIELD_NUM_LEN32: usize = 8;
+
+// Error codes of the circom runtime
+const SIGNAL_NOT_FOUND: i32 = 1;
+const TOO_MANY_SIGNALS: i32 = 2;
+const SIGNAL_ALREADY_SET: i32 = 3;
+const ASSERT_FAILED: i32 = 4;
+const ACCESS_OUT_OF_BOUNDS: i32 = 6;
+
+#[link(wasm_import_module = "runtime")]
+extern "C" {
+    #[link_name = "exceptionHandler"]
+    fn exception_handler(code: i32);
+}
+
+struct State {
+    program: Program,
+    witness: Vec<BigUint>,
+    set: Vec<bool>,
+    remaining: u32,
+    shared: [u32; FIELD_NUM_LEN32],
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::new());
+}
+
+impl State {
+    fn new() -> Self {
+        let program = Program::from_bytes(include_bytes!(env!("WITNESS_PROGRAM")))
+            .expect("invalid witness program");
+        let mut state = Self {
+            witness: Vec::new(),
+            set: Vec::new(),
+            remaining: 0,
+            shared: [0; FIELD_NUM_LEN32],
+            program,
+        };
+        state.reset();
+        state
+    }
+
+    fn reset(&mut self) {
+        let n_wires = self.program.n_wires as usize;
+        self.witness = vec![BigUint::from(0u8); n_wires];
+        self.witness[0] = BigUint::from(1u8);
+        self.set = vec![false; n_wires];
+        self.remaining = self.program.input_size();
+    }
+
+    fn read_shared(&mut self, value: &BigUint) {
+        self.shared = [0; FIELD_NUM_LEN32];
+        for (limb, digit) in self.shared.iter_mut().zip(value.to_u32_digits()) {
+            *limb = digit;
+        }
+    }
+}
+
+fn fail(code: i32) -> ! {
+    unsafe { exception_handler(code) };
+    core::arch::wasm32::unreachable()
+}
+
+fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
+    STATE.with(|state| f(&mut state.borrow_mut()))
+}
+
+#[no_mangle]
+pub extern "C" fn getVersion() -> u32 {
+    2
+}
+
+#[no_mangle]
+pub extern "C" fn getMinorVersion() -> u32 {
+    1
+}
+
+#[no_mangle]
+pub extern "C" fn getPatchVersion() -> u32 {
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn getFieldNumLen32() -> u32 {
+    FIELD_NUM_LEN32 as u32
+}
+
+#[no_mangle]
+pub extern "C" fn getRawPrime() {
+    with_state(|state| {
+        let prime = state.program.prime.clone();
+        state.read_shared(&prime)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn readSharedRWMemory(index: u32) -> u32 {
+    with_state(|state| state.shared[index as usize % FIELD_NUM_LEN32])
+}
+
+#[no_mangle]
+pub extern "C" fn writeSharedRWMemory(index: u32, value: u32) {
+    with_state(|state| state.shared[index as usize % FIELD_NUM_LEN32] = value)
+}
+
+#[no_mangle]
+pub extern "C" fn init(_sanity_check: u32) {
+    with_state(State::reset)
+}
+
+#[no_mangle]
+pub extern "C" fn getInputSignalSize(hash_msb: u32, hash_lsb: u32) -> i32 {
+    let hash = (hash_msb as u64) << 32 | hash_lsb as u64;
+    with_state(|state| match state.program.input(hash) {
+        Some(input) => input.size as i32,
+        None => -1,
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn getInputSize() -> u32 {
+    with_state(|state| state.program.input_size())
+}
+
+#[no_mangle]
+pub extern "C" fn setInputSignal(hash_msb: u32, hash_lsb: u32, position: u32) {
+    let hash = (hash_msb as u64) << 32 | hash_lsb as u64;
+    let result = with_state(|state| {
+        let input = state.program.input(hash).ok_or(SIGNAL_NOT_FOUND)?.clone();
+        if position >= input.size {
+            return Err(ACCESS_OUT_OF_BOUNDS);
+        }
+        if state.remaining == 0 {
+            return Err(TOO_MANY_SIGNALS);
+        }
+        let wire = (input.wire + position) as usize;
+        if state.set[wire] {
+            return Err(SIGNAL_ALREADY_SET);
+        }
+        let value = BigUint::from_slice(&state.shared) % &state.program.prime;
+        state.witness[wire] = value;
+        state.set[wire] = true;
+        state.remaining -= 1;
+        if state.remaining > 0 {
+            return Ok(());
+        }
+        let State {
+            program, witness, ..
+        } = state;
+        program.run(witness).map_err(|failure| match failure {
+            Failure::DivisionByZero | Failure::Unsatisfied(_) => ASSERT_FAILED,
+        })
+    });
+    if let Err(code) = result {
+        fail(code);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn getWitnessSize() -> u32 {
+    with_state(|state| state.program.n_wires)
+}
+
+#[no_mangle]
+pub extern "C" fn getWitness(index: u32) {
+    with_state(|state| {
+        let value = state.witness[index as usize].clone();
+        state.read_shared(&value)
+    })
+}
+
+/// No error messages are kept, the exception code is all there is.
+#[no_mangle]
+pub extern "C" fn getMessageChar() -> u32 {
+    0
+}
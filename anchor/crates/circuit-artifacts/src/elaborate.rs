@@ -0,0 +1,1048 @@
+//! Elaboration of the main component: runs the templates with every parameter and var known,
+//! leaving the signals as symbols. `<==` and `===` give quadratic constraints, `<==` and
+//! `<--` give the steps computing a signal, ordered by what they read.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use num_bigint::BigUint;
+
+use crate::parse::{
+    Access, AssignOp, Definition, Expr, Main, SignalKind, Source, Stmt, StmtKind, Target,
+};
+use crate::program::{BinOp, Field, UnOp};
+use crate::{Error, Result};
+
+pub type Sym = Rc<Node>;
+
+/// An expression over signals. Operations on constants are folded as they are built.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Node {
+    Const(BigUint),
+    Signal(usize),
+    Binary(BinOp, Sym, Sym),
+    Unary(UnOp, Sym),
+    Ternary(Sym, Sym, Sym),
+}
+
+/// A linear combination of signals, signal 0 being the constant one.
+pub type Lc = BTreeMap<usize, BigUint>;
+
+/// A component member and the access that follows it, as in `c.out[1]`.
+type Member<'a> = (&'a str, &'a [Access]);
+
+/// `a * b = c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub a: Lc,
+    pub b: Lc,
+    pub c: Lc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signal {
+    /// Full name, `main.component.signal[index]`.
+    pub name: String,
+    /// Index of the component instance declaring it.
+    pub component: usize,
+}
+
+/// The elaborated main component.
+#[derive(Debug)]
+pub struct Circuit {
+    pub field: Field,
+    /// Every signal, 0 being the constant one.
+    pub signals: Vec<Signal>,
+    /// Outputs of the main component in declaration order.
+    pub outputs: Vec<usize>,
+    pub public_inputs: Vec<usize>,
+    pub private_inputs: Vec<usize>,
+    /// Inputs of the main component by name, each with its signals in order.
+    pub inputs: Vec<(String, Vec<usize>)>,
+    pub constraints: Vec<Constraint>,
+    /// The steps computing every signal but the inputs, in evaluation order.
+    pub steps: Vec<(usize, Sym)>,
+}
+
+#[derive(Debug, Clone)]
+enum Val {
+    Scalar(Sym),
+    Array(Vec<Val>),
+}
+
+/// Signals declared together, flattened in row-major order.
+#[derive(Debug)]
+struct SignalArray {
+    dims: Vec<usize>,
+    ids: Vec<usize>,
+    kind: SignalKind,
+}
+
+/// The inputs and outputs of a component instance, in declaration order.
+#[derive(Debug)]
+struct Instance {
+    signals: Vec<(String, Rc<SignalArray>)>,
+}
+
+#[derive(Debug)]
+struct ComponentArray {
+    dims: Vec<usize>,
+    instances: Vec<Option<Rc<Instance>>>,
+}
+
+#[derive(Debug, Clone)]
+enum Binding {
+    Var(Val),
+    Signal(Rc<SignalArray>),
+    Component(Rc<RefCell<ComponentArray>>),
+}
+
+/// The running template instance or function call.
+struct Frame {
+    scopes: Vec<HashMap<String, Binding>>,
+    /// Name of the component instance, empty in functions.
+    prefix: String,
+    component: usize,
+    signals: Vec<(String, Rc<SignalArray>)>,
+}
+
+enum Flow {
+    Next,
+    Return(Val),
+}
+
+/// `a * b + c`, or a linear combination.
+enum Quad {
+    Lin(Lc),
+    Quad(Lc, Lc, Lc),
+}
+
+struct Elaborator<'a> {
+    field: Field,
+    templates: HashMap<&'a str, &'a Definition>,
+    functions: HashMap<&'a str, &'a Definition>,
+    signals: Vec<Signal>,
+    assigned: Vec<bool>,
+    constraints: Vec<Constraint>,
+    steps: Vec<(usize, Sym)>,
+    components: usize,
+    /// Definition and line being run, for errors.
+    location: (String, usize),
+}
+
+/// Elaborates the main component of the sources, the first one and its includes.
+pub fn elaborate(sources: &[Source], prime: BigUint) -> Result<Circuit> {
+    let mut templates = HashMap::new();
+    let mut functions = HashMap::new();
+    for source in sources {
+        for (definitions, map) in [
+            (&source.templates, &mut templates),
+            (&source.functions, &mut functions),
+        ] {
+            for definition in definitions {
+                if map.insert(definition.name.as_str(), definition).is_some() {
+                    return Err(Error::Compile(format!(
+                        "`{}` is defined twice",
+                        definition.name
+                    )));
+                }
+            }
+        }
+    }
+    let main = sources
+        .first()
+        .and_then(|source| source.main.as_ref())
+        .ok_or_else(|| Error::Compile("no main component".to_string()))?;
+
+    let mut elaborator = Elaborator {
+        field: Field::new(prime),
+        templates,
+        functions,
+        signals: vec![Signal {
+            name: "one".to_string(),
+            component: 0,
+        }],
+        assigned: vec![true],
+        constraints: Vec::new(),
+        steps: Vec::new(),
+        components: 0,
+        location: ("main".to_string(), 0),
+    };
+    elaborator.main(main)
+}
+
+fn konst(value: BigUint) -> Sym {
+    Rc::new(Node::Const(value))
+}
+
+fn as_const(sym: &Sym) -> Option<&BigUint> {
+    match &**sym {
+        Node::Const(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn collect_signals(sym: &Sym, signals: &mut Vec<usize>) {
+    match &**sym {
+        Node::Const(_) => {}
+        Node::Signal(id) => signals.push(*id),
+        Node::Binary(_, a, b) => {
+            collect_signals(a, signals);
+            collect_signals(b, signals);
+        }
+        Node::Unary(_, a) => collect_signals(a, signals),
+        Node::Ternary(cond, then, otherwise) => {
+            collect_signals(cond, signals);
+            collect_signals(then, signals);
+            collect_signals(otherwise, signals);
+        }
+    }
+}
+
+fn flatten(val: Val, out: &mut Vec<Sym>) {
+    match val {
+        Val::Scalar(sym) => out.push(sym),
+        Val::Array(items) => items.into_iter().for_each(|item| flatten(item, out)),
+    }
+}
+
+fn index_suffix(dims: &[usize], mut flat: usize) -> String {
+    let mut indices = vec![0; dims.len()];
+    for (index, dim) in indices.iter_mut().zip(dims).rev() {
+        *index = flat % dim;
+        flat /= dim;
+    }
+    indices.iter().map(|index| format!("[{}]", index)).collect()
+}
+
+impl<'a> Elaborator<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error::Compile(format!(
+            "{}:{}: {}",
+            self.location.0,
+            self.location.1,
+            message.into()
+        )))
+    }
+
+    fn main(&mut self, main: &'a Main) -> Result<Circuit> {
+        let definition = *self
+            .templates
+            .get(main.template.as_str())
+            .ok_or_else(|| Error::Compile(format!("no template `{}`", main.template)))?;
+        let mut frame = Frame {
+            scopes: vec![HashMap::new()],
+            prefix: String::new(),
+            component: 0,
+            signals: Vec::new(),
+        };
+        let args = main
+            .args
+            .iter()
+            .map(|arg| self.eval(&mut frame, arg))
+            .collect::<Result<Vec<_>>>()?;
+        let instance = self.instantiate(definition, args, "main".to_string())?;
+
+        for name in &main.public {
+            if !instance
+                .signals
+                .iter()
+                .any(|(signal, array)| signal == name && array.kind == SignalKind::Input)
+            {
+                return Err(Error::Compile(format!(
+                    "public signal `{}` is not an input of `{}`",
+                    name, main.template
+                )));
+            }
+        }
+        let mut outputs: Vec<usize> = Vec::new();
+        let mut public_inputs: Vec<usize> = Vec::new();
+        let mut private_inputs: Vec<usize> = Vec::new();
+        let mut inputs = Vec::new();
+        for (name, array) in &instance.signals {
+            match array.kind {
+                SignalKind::Output => outputs.extend(&array.ids),
+                SignalKind::Input if main.public.contains(name) => public_inputs.extend(&array.ids),
+                SignalKind::Input => private_inputs.extend(&array.ids),
+                SignalKind::Intermediate => continue,
+            }
+            if array.kind == SignalKind::Input {
+                inputs.push((name.clone(), array.ids.clone()));
+            }
+        }
+        for id in public_inputs.iter().chain(&private_inputs) {
+            if self.assigned[*id] {
+                return Err(Error::Compile(format!(
+                    "input `{}` of the main component is assigned",
+                    self.signals[*id].name
+                )));
+            }
+            self.assigned[*id] = true;
+        }
+        if let Some(id) = self.assigned.iter().position(|assigned| !assigned) {
+            return Err(Error::Compile(format!(
+                "signal `{}` is never assigned",
+                self.signals[id].name
+            )));
+        }
+
+        let steps = self.ordered_steps()?;
+        Ok(Circuit {
+            field: self.field.clone(),
+            signals: std::mem::take(&mut self.signals),
+            outputs,
+            public_inputs,
+            private_inputs,
+            inputs,
+            constraints: std::mem::take(&mut self.constraints),
+            steps,
+        })
+    }
+
+    /// The steps, each after the ones computing the signals it reads.
+    fn ordered_steps(&self) -> Result<Vec<(usize, Sym)>> {
+        let mut step_of = vec![None; self.signals.len()];
+        for (index, (id, _)) in self.steps.iter().enumerate() {
+            step_of[*id] = Some(index);
+        }
+        // 0 not visited, 1 in progress, 2 done
+        let mut state = vec![0u8; self.steps.len()];
+        let mut ordered = Vec::with_capacity(self.steps.len());
+        for start in 0..self.steps.len() {
+            let mut stack = vec![(start, false)];
+            while let Some((index, expanded)) = stack.pop() {
+                if expanded {
+                    state[index] = 2;
+                    ordered.push(self.steps[index].clone());
+                    continue;
+                }
+                match state[index] {
+                    2 => continue,
+                    1 => {
+                        return Err(Error::Compile(format!(
+                            "signal `{}` depends on itself",
+                            self.signals[self.steps[index].0].name
+                        )))
+                    }
+                    _ => {}
+                }
+                state[index] = 1;
+                stack.push((index, true));
+                let mut reads = Vec::new();
+                collect_signals(&self.steps[index].1, &mut reads);
+                for dependency in reads.into_iter().filter_map(|id| step_of[id]) {
+                    if state[dependency] == 1 {
+                        return Err(Error::Compile(format!(
+                            "signal `{}` depends on itself",
+                            self.signals[self.steps[dependency].0].name
+                        )));
+                    }
+                    if state[dependency] == 0 {
+                        stack.push((dependency, false));
+                    }
+                }
+            }
+        }
+        Ok(ordered)
+    }
+
+    fn instantiate(
+        &mut self,
+        definition: &'a Definition,
+        args: Vec<Val>,
+        prefix: String,
+    ) -> Result<Rc<Instance>> {
+        if args.len() != definition.params.len() {
+            return self.error(format!(
+                "`{}` takes {} parameters",
+                definition.name,
+                definition.params.len()
+            ));
+        }
+        let component = self.components;
+        self.components += 1;
+        let params = definition
+            .params
+            .iter()
+            .cloned()
+            .zip(args.into_iter().map(Binding::Var))
+            .collect();
+        let mut frame = Frame {
+            scopes: vec![params],
+            prefix,
+            component,
+            signals: Vec::new(),
+        };
+        let location = std::mem::replace(&mut self.location, (definition.name.clone(), 0));
+        if let Flow::Return(_) = self.exec_block(&mut frame, &definition.body)? {
+            return self.error("templates do not return values");
+        }
+        self.location = location;
+        let signals = frame
+            .signals
+            .into_iter()
+            .filter(|(_, array)| array.kind != SignalKind::Intermediate)
+            .collect();
+        Ok(Rc::new(Instance { signals }))
+    }
+
+    fn call(&mut self, definition: &'a Definition, args: Vec<Val>) -> Result<Val> {
+        if args.len() != definition.params.len() {
+            return self.error(format!(
+                "`{}` takes {} arguments",
+                definition.name,
+                definition.params.len()
+            ));
+        }
+        let params = definition
+            .params
+            .iter()
+            .cloned()
+            .zip(args.into_iter().map(Binding::Var))
+            .collect();
+        let mut frame = Frame {
+            scopes: vec![params],
+            prefix: String::new(),
+            component: usize::MAX,
+            signals: Vec::new(),
+        };
+        let location = std::mem::replace(&mut self.location, (definition.name.clone(), 0));
+        let result = match self.exec_block(&mut frame, &definition.body)? {
+            Flow::Return(val) => val,
+            Flow::Next => return self.error("function ends without returning"),
+        };
+        self.location = location;
+        Ok(result)
+    }
+
+    fn exec_block(&mut self, frame: &mut Frame, stmts: &'a [Stmt]) -> Result<Flow> {
+        frame.scopes.push(HashMap::new());
+        for stmt in stmts {
+            if let Flow::Return(val) = self.exec(frame, stmt)? {
+                frame.scopes.pop();
+                return Ok(Flow::Return(val));
+            }
+        }
+        frame.scopes.pop();
+        Ok(Flow::Next)
+    }
+
+    fn declare(&mut self, frame: &mut Frame, name: &str, binding: Binding) -> Result<()> {
+        let scope = frame.scopes.last_mut().unwrap();
+        if scope.insert(name.to_string(), binding).is_some() {
+            return self.error(format!("`{}` is declared twice", name));
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, frame: &Frame, name: &str) -> Result<Binding> {
+        match frame.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            Some(binding) => Ok(binding.clone()),
+            None => self.error(format!("`{}` is not declared", name)),
+        }
+    }
+
+    fn dims(&mut self, frame: &mut Frame, dims: &'a [Expr]) -> Result<Vec<usize>> {
+        dims.iter().map(|dim| self.eval_usize(frame, dim)).collect()
+    }
+
+    fn exec(&mut self, frame: &mut Frame, stmt: &'a Stmt) -> Result<Flow> {
+        self.location.1 = stmt.line;
+        match &stmt.kind {
+            StmtKind::Signal {
+                kind,
+                name,
+                dims,
+                init,
+            } => {
+                if frame.component == usize::MAX {
+                    return self.error("signals cannot be declared in functions");
+                }
+                let dims = self.dims(frame, dims)?;
+                let len: usize = dims.iter().product();
+                let ids = (0..len)
+                    .map(|flat| {
+                        self.signals.push(Signal {
+                            name: format!("{}.{}{}", frame.prefix, name, index_suffix(&dims, flat)),
+                            component: frame.component,
+                        });
+                        self.assigned.push(false);
+                        self.signals.len() - 1
+                    })
+                    .collect();
+                let array = Rc::new(SignalArray {
+                    dims,
+                    ids,
+                    kind: *kind,
+                });
+                self.declare(frame, name, Binding::Signal(array.clone()))?;
+                frame.signals.push((name.clone(), array.clone()));
+                if let Some((op, value)) = init {
+                    let value = self.eval(frame, value)?;
+                    self.assign_signals(&array.ids, *op, value)?;
+                }
+            }
+            StmtKind::Var { name, dims, init } => {
+                let dims = self.dims(frame, dims)?;
+                let val = match init {
+                    Some(init) => self.eval(frame, init)?,
+                    None => dims
+                        .iter()
+                        .rev()
+                        .fold(Val::Scalar(konst(BigUint::from(0u8))), |item, dim| {
+                            Val::Array(vec![item; *dim])
+                        }),
+                };
+                self.declare(frame, name, Binding::Var(val))?;
+            }
+            StmtKind::Component { name, dims, init } => {
+                let dims = self.dims(frame, dims)?;
+                let len = dims.iter().product();
+                let array = Rc::new(RefCell::new(ComponentArray {
+                    dims,
+                    instances: vec![None; len],
+                }));
+                self.declare(frame, name, Binding::Component(array))?;
+                if let Some(init) = init {
+                    self.assign_component(frame, name, &[], init)?;
+                }
+            }
+            StmtKind::Assign { target, op, value } => self.assign(frame, target, *op, value)?,
+            StmtKind::Equal(lhs, rhs) => {
+                let (lhs, rhs) = (self.eval(frame, lhs)?, self.eval(frame, rhs)?);
+                let (mut lhs_items, mut rhs_items) = (Vec::new(), Vec::new());
+                flatten(lhs, &mut lhs_items);
+                flatten(rhs, &mut rhs_items);
+                if lhs_items.len() != rhs_items.len() {
+                    return self.error("`===` on values of different sizes");
+                }
+                for (lhs, rhs) in lhs_items.into_iter().zip(rhs_items) {
+                    self.constrain(lhs, rhs)?;
+                }
+            }
+            StmtKind::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let flow = if self.eval_bool(frame, cond)? {
+                    self.exec_block(frame, then)?
+                } else {
+                    self.exec_block(frame, otherwise)?
+                };
+                return Ok(flow);
+            }
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body,
+            } => {
+                frame.scopes.push(HashMap::new());
+                let flow = self.exec_loop(frame, Some(init), cond, Some(step), body);
+                frame.scopes.pop();
+                return flow;
+            }
+            StmtKind::While { cond, body } => return self.exec_loop(frame, None, cond, None, body),
+            StmtKind::Return(value) => return Ok(Flow::Return(self.eval(frame, value)?)),
+            StmtKind::Assert(cond) => {
+                if !self.eval_bool(frame, cond)? {
+                    return self.error("assertion failed");
+                }
+            }
+            StmtKind::Log => {}
+            StmtKind::Block(stmts) => return self.exec_block(frame, stmts),
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec_loop(
+        &mut self,
+        frame: &mut Frame,
+        init: Option<&'a Stmt>,
+        cond: &'a Expr,
+        step: Option<&'a Stmt>,
+        body: &'a [Stmt],
+    ) -> Result<Flow> {
+        if let Some(init) = init {
+            self.exec(frame, init)?;
+        }
+        while self.eval_bool(frame, cond)? {
+            if let Flow::Return(val) = self.exec_block(frame, body)? {
+                return Ok(Flow::Return(val));
+            }
+            if let Some(step) = step {
+                self.exec(frame, step)?;
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn assign(
+        &mut self,
+        frame: &mut Frame,
+        target: &'a Target,
+        op: AssignOp,
+        value: &'a Expr,
+    ) -> Result<()> {
+        match self.lookup(frame, &target.name)? {
+            Binding::Var(_) => {
+                let indices = match self.indices(frame, &target.access)? {
+                    (indices, None) => indices,
+                    _ => return self.error("vars have no members"),
+                };
+                let value = match op {
+                    AssignOp::Var => self.eval(frame, value)?,
+                    AssignOp::Compound(op) => {
+                        let current = self.read(frame, target)?;
+                        let value = self.eval(frame, value)?;
+                        Val::Scalar(self.binary(op, self.scalar(current)?, self.scalar(value)?)?)
+                    }
+                    _ => return self.error(format!("`{}` is a var, not a signal", target.name)),
+                };
+                let scope = frame
+                    .scopes
+                    .iter_mut()
+                    .rev()
+                    .find(|scope| scope.contains_key(&target.name))
+                    .unwrap();
+                let Some(Binding::Var(slot)) = scope.get_mut(&target.name) else {
+                    unreachable!()
+                };
+                let mut slot = slot;
+                for index in indices {
+                    slot = match slot {
+                        Val::Array(items) if index < items.len() => &mut items[index],
+                        _ => return self.error(format!("`{}` index out of range", target.name)),
+                    };
+                }
+                *slot = value;
+            }
+            Binding::Component(_) if !target.access.iter().any(is_member) => {
+                if op != AssignOp::Var {
+                    return self.error("components are assigned with `=`");
+                }
+                self.assign_component(frame, &target.name, &target.access, value)?;
+            }
+            _ => {
+                if !matches!(op, AssignOp::Constrain | AssignOp::Witness) {
+                    return self.error(format!(
+                        "signals are assigned with `<==` or `<--`, not as `{}`",
+                        target.name
+                    ));
+                }
+                let ids = self.signal_ids(frame, target)?;
+                let value = self.eval(frame, value)?;
+                self.assign_signals(&ids, op, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Instantiates the template called in `value` as the component `name`, at the indices
+    /// in `access`.
+    fn assign_component(
+        &mut self,
+        frame: &mut Frame,
+        name: &str,
+        access: &'a [Access],
+        value: &'a Expr,
+    ) -> Result<()> {
+        let Binding::Component(array) = self.lookup(frame, name)? else {
+            return self.error(format!("`{}` is not a component", name));
+        };
+        let (template, args) = match value {
+            Expr::Call(template, args) => (template, args),
+            _ => return self.error("expected a template instantiation"),
+        };
+        let definition = match self.templates.get(template.as_str()) {
+            Some(definition) => *definition,
+            None => return self.error(format!("no template `{}`", template)),
+        };
+        let (indices, _) = self.indices(frame, access)?;
+        let flat = self.flat_index(&array.borrow().dims, &indices, name)?;
+        if array.borrow().instances[flat].is_some() {
+            return self.error(format!("component `{}` is assigned twice", name));
+        }
+        let args = args
+            .iter()
+            .map(|arg| self.eval(frame, arg))
+            .collect::<Result<Vec<_>>>()?;
+        let prefix = format!(
+            "{}.{}{}",
+            frame.prefix,
+            name,
+            index_suffix(&array.borrow().dims, flat)
+        );
+        let instance = self.instantiate(definition, args, prefix)?;
+        array.borrow_mut().instances[flat] = Some(instance);
+        Ok(())
+    }
+
+    fn assign_signals(&mut self, ids: &[usize], op: AssignOp, value: Val) -> Result<()> {
+        let mut values = Vec::new();
+        flatten(value, &mut values);
+        if values.len() != ids.len() {
+            return self.error("assignment of a value of another size");
+        }
+        for (id, value) in ids.iter().zip(values) {
+            if self.assigned[*id] {
+                return self.error(format!(
+                    "signal `{}` is assigned twice",
+                    self.signals[*id].name
+                ));
+            }
+            self.assigned[*id] = true;
+            self.steps.push((*id, value.clone()));
+            if op == AssignOp::Constrain {
+                self.constrain(Rc::new(Node::Signal(*id)), value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates the indices of an access list up to a member, returning the member name.
+    fn indices(
+        &mut self,
+        frame: &mut Frame,
+        access: &'a [Access],
+    ) -> Result<(Vec<usize>, Option<Member<'a>>)> {
+        let mut indices = Vec::new();
+        for (position, item) in access.iter().enumerate() {
+            match item {
+                Access::Index(index) => indices.push(self.eval_usize(frame, index)?),
+                Access::Member(name) => {
+                    return Ok((indices, Some((name.as_str(), &access[position + 1..]))))
+                }
+            }
+        }
+        Ok((indices, None))
+    }
+
+    fn flat_index(&self, dims: &[usize], indices: &[usize], name: &str) -> Result<usize> {
+        if indices.len() != dims.len() {
+            return self.error(format!("`{}` needs {} indices", name, dims.len()));
+        }
+        let mut flat = 0;
+        for (index, dim) in indices.iter().zip(dims) {
+            if index >= dim {
+                return self.error(format!("`{}` index out of range", name));
+            }
+            flat = flat * dim + index;
+        }
+        Ok(flat)
+    }
+
+    /// The signals selected by leading indices, with the dimensions left.
+    fn select<'s>(
+        &self,
+        array: &'s SignalArray,
+        indices: &[usize],
+        name: &str,
+    ) -> Result<(&'s [usize], &'s [usize])> {
+        if indices.len() > array.dims.len() {
+            return self.error(format!("`{}` has {} dimensions", name, array.dims.len()));
+        }
+        let rest = &array.dims[indices.len()..];
+        let len: usize = rest.iter().product();
+        let mut offset = 0;
+        for (index, dim) in indices.iter().zip(&array.dims) {
+            if index >= dim {
+                return self.error(format!("`{}` index out of range", name));
+            }
+            offset = offset * dim + index;
+        }
+        offset *= len;
+        Ok((rest, &array.ids[offset..offset + len]))
+    }
+
+    /// The signal array a target names, with the indices applied to it.
+    fn resolve_signals(
+        &mut self,
+        frame: &mut Frame,
+        target: &'a Target,
+    ) -> Result<(Rc<SignalArray>, Vec<usize>)> {
+        let (indices, member) = self.indices(frame, &target.access)?;
+        match (self.lookup(frame, &target.name)?, member) {
+            (Binding::Signal(array), None) => Ok((array, indices)),
+            (Binding::Component(components), Some((member, rest))) => {
+                let flat = self.flat_index(&components.borrow().dims, &indices, &target.name)?;
+                let instance = match &components.borrow().instances[flat] {
+                    Some(instance) => instance.clone(),
+                    None => {
+                        return self.error(format!("component `{}` is not assigned", target.name))
+                    }
+                };
+                let array = match instance.signals.iter().find(|(name, _)| name == member) {
+                    Some((_, array)) => array.clone(),
+                    None => {
+                        return self.error(format!("`{}` has no signal `{}`", target.name, member))
+                    }
+                };
+                let (rest, None) = self.indices(frame, rest)? else {
+                    return self.error("signals have no members");
+                };
+                Ok((array, rest))
+            }
+            _ => self.error(format!("`{}` is not a signal", target.name)),
+        }
+    }
+
+    fn signal_ids(&mut self, frame: &mut Frame, target: &'a Target) -> Result<Vec<usize>> {
+        let (array, indices) = self.resolve_signals(frame, target)?;
+        let (_, ids) = self.select(&array, &indices, &target.name)?;
+        Ok(ids.to_vec())
+    }
+
+    fn read(&mut self, frame: &mut Frame, target: &'a Target) -> Result<Val> {
+        if let Binding::Var(mut val) = self.lookup(frame, &target.name)? {
+            let (indices, None) = self.indices(frame, &target.access)? else {
+                return self.error("vars have no members");
+            };
+            for index in indices {
+                val = match val {
+                    Val::Array(mut items) if index < items.len() => items.swap_remove(index),
+                    _ => return self.error(format!("`{}` index out of range", target.name)),
+                };
+            }
+            return Ok(val);
+        }
+        let (array, indices) = self.resolve_signals(frame, target)?;
+        let (dims, ids) = self.select(&array, &indices, &target.name)?;
+        fn build(dims: &[usize], ids: &[usize]) -> Val {
+            match dims.split_first() {
+                None => Val::Scalar(Rc::new(Node::Signal(ids[0]))),
+                Some((dim, rest)) => {
+                    let len = ids.len() / dim;
+                    Val::Array(ids.chunks(len.max(1)).map(|ids| build(rest, ids)).collect())
+                }
+            }
+        }
+        Ok(build(dims, ids))
+    }
+
+    fn scalar(&self, val: Val) -> Result<Sym> {
+        match val {
+            Val::Scalar(sym) => Ok(sym),
+            Val::Array(_) => self.error("expected a value, not an array"),
+        }
+    }
+
+    fn eval_const(&mut self, frame: &mut Frame, expr: &'a Expr) -> Result<BigUint> {
+        let sym = self.eval(frame, expr).and_then(|val| self.scalar(val))?;
+        match as_const(&sym) {
+            Some(value) => Ok(value.clone()),
+            None => self.error("expected a value known at compile time"),
+        }
+    }
+
+    fn eval_usize(&mut self, frame: &mut Frame, expr: &'a Expr) -> Result<usize> {
+        let value = self.eval_const(frame, expr)?;
+        match usize::try_from(&value) {
+            Ok(value) => Ok(value),
+            Err(_) => self.error("index out of range"),
+        }
+    }
+
+    fn eval_bool(&mut self, frame: &mut Frame, expr: &'a Expr) -> Result<bool> {
+        Ok(self.eval_const(frame, expr)? != BigUint::from(0u8))
+    }
+
+    fn eval(&mut self, frame: &mut Frame, expr: &'a Expr) -> Result<Val> {
+        Ok(match expr {
+            Expr::Number(value) => Val::Scalar(konst(self.field.reduce(value.clone()))),
+            Expr::Ref(target) => self.read(frame, target)?,
+            Expr::Call(name, args) => {
+                let definition = match self.functions.get(name.as_str()) {
+                    Some(definition) => *definition,
+                    None if self.templates.contains_key(name.as_str()) => {
+                        return self.error("templates are instantiated by assigning a component")
+                    }
+                    None => return self.error(format!("no function `{}`", name)),
+                };
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(frame, arg))
+                    .collect::<Result<Vec<_>>>()?;
+                self.call(definition, args)?
+            }
+            Expr::Array(items) => Val::Array(
+                items
+                    .iter()
+                    .map(|item| self.eval(frame, item))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.eval(frame, lhs).and_then(|val| self.scalar(val))?;
+                let rhs = self.eval(frame, rhs).and_then(|val| self.scalar(val))?;
+                Val::Scalar(self.binary(*op, lhs, rhs)?)
+            }
+            Expr::Unary(op, operand) => {
+                let operand = self.eval(frame, operand).and_then(|val| self.scalar(val))?;
+                Val::Scalar(match as_const(&operand) {
+                    Some(value) => konst(self.field.unary(*op, value)),
+                    None => Rc::new(Node::Unary(*op, operand)),
+                })
+            }
+            Expr::Ternary(cond, then, otherwise) => {
+                let cond = self.eval(frame, cond).and_then(|val| self.scalar(val))?;
+                if let Some(cond) = as_const(&cond) {
+                    let branch = if *cond != BigUint::from(0u8) {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    return self.eval(frame, branch);
+                }
+                let then = self.eval(frame, then).and_then(|val| self.scalar(val))?;
+                let otherwise = self
+                    .eval(frame, otherwise)
+                    .and_then(|val| self.scalar(val))?;
+                Val::Scalar(Rc::new(Node::Ternary(cond, then, otherwise)))
+            }
+        })
+    }
+
+    fn binary(&self, op: BinOp, lhs: Sym, rhs: Sym) -> Result<Sym> {
+        if let (Some(lhs), Some(rhs)) = (as_const(&lhs), as_const(&rhs)) {
+            return match self.field.binary(op, lhs, rhs) {
+                Some(value) => Ok(konst(value)),
+                None => self.error("division by zero"),
+            };
+        }
+        Ok(Rc::new(Node::Binary(op, lhs, rhs)))
+    }
+
+    /// Adds `lhs = rhs` as a constraint.
+    fn constrain(&mut self, lhs: Sym, rhs: Sym) -> Result<()> {
+        let difference = Rc::new(Node::Binary(BinOp::Sub, lhs, rhs));
+        let constraint = match self.quad(&difference) {
+            Some(Quad::Lin(lc)) => {
+                match lc.keys().next() {
+                    None => return Ok(()),
+                    Some(0) if lc.len() == 1 => return self.error("constraint never holds"),
+                    _ => {}
+                }
+                Constraint {
+                    a: Lc::new(),
+                    b: Lc::new(),
+                    c: lc,
+                }
+            }
+            Some(Quad::Quad(a, b, c)) => Constraint {
+                a,
+                b,
+                c: self.scale(&c, &(&self.field.prime - 1u8)),
+            },
+            None => return self.error("non-quadratic constraint"),
+        };
+        self.constraints.push(constraint);
+        Ok(())
+    }
+
+    fn scale(&self, lc: &Lc, factor: &BigUint) -> Lc {
+        lc.iter()
+            .map(|(id, coeff)| (*id, self.field.reduce(coeff * factor)))
+            .filter(|(_, coeff)| *coeff != BigUint::from(0u8))
+            .collect()
+    }
+
+    fn add(&self, lhs: &Lc, rhs: &Lc) -> Lc {
+        let mut sum = lhs.clone();
+        for (id, coeff) in rhs {
+            let entry = sum.entry(*id).or_insert_with(|| BigUint::from(0u8));
+            *entry = self.field.reduce(&*entry + coeff);
+        }
+        sum.retain(|_, coeff| *coeff != BigUint::from(0u8));
+        sum
+    }
+
+    /// The value of a linear combination of the constant alone.
+    fn lc_const(lc: &Lc) -> Option<BigUint> {
+        match lc.keys().next() {
+            None => Some(BigUint::from(0u8)),
+            Some(0) if lc.len() == 1 => Some(lc[&0].clone()),
+            _ => None,
+        }
+    }
+
+    fn quad_scale(&self, quad: Quad, factor: &BigUint) -> Quad {
+        match quad {
+            Quad::Lin(lc) => Quad::Lin(self.scale(&lc, factor)),
+            Quad::Quad(a, b, c) => {
+                self.normalize(self.scale(&a, factor), b, self.scale(&c, factor))
+            }
+        }
+    }
+
+    fn normalize(&self, a: Lc, b: Lc, c: Lc) -> Quad {
+        if a.is_empty() || b.is_empty() {
+            Quad::Lin(c)
+        } else {
+            Quad::Quad(a, b, c)
+        }
+    }
+
+    fn quad(&self, sym: &Sym) -> Option<Quad> {
+        let minus_one = &self.field.prime - 1u8;
+        Some(match &**sym {
+            Node::Const(value) => {
+                Quad::Lin(self.scale(&Lc::from([(0, value.clone())]), &1u8.into()))
+            }
+            Node::Signal(id) => Quad::Lin(Lc::from([(*id, BigUint::from(1u8))])),
+            Node::Unary(UnOp::Neg, operand) => self.quad_scale(self.quad(operand)?, &minus_one),
+            Node::Binary(op @ (BinOp::Add | BinOp::Sub), lhs, rhs) => {
+                let lhs = self.quad(lhs)?;
+                let mut rhs = self.quad(rhs)?;
+                if *op == BinOp::Sub {
+                    rhs = self.quad_scale(rhs, &minus_one);
+                }
+                match (lhs, rhs) {
+                    (Quad::Lin(lhs), Quad::Lin(rhs)) => Quad::Lin(self.add(&lhs, &rhs)),
+                    (Quad::Quad(a, b, c), Quad::Lin(lin))
+                    | (Quad::Lin(lin), Quad::Quad(a, b, c)) => Quad::Quad(a, b, self.add(&c, &lin)),
+                    (Quad::Quad(..), Quad::Quad(..)) => return None,
+                }
+            }
+            Node::Binary(BinOp::Mul, lhs, rhs) => {
+                let (lhs, rhs) = (self.quad(lhs)?, self.quad(rhs)?);
+                match (lhs, rhs) {
+                    (Quad::Lin(lhs), Quad::Lin(rhs)) => {
+                        match (Self::lc_const(&lhs), Self::lc_const(&rhs)) {
+                            (Some(factor), _) => Quad::Lin(self.scale(&rhs, &factor)),
+                            (_, Some(factor)) => Quad::Lin(self.scale(&lhs, &factor)),
+                            _ => Quad::Quad(lhs, rhs, Lc::new()),
+                        }
+                    }
+                    (quad @ Quad::Quad(..), Quad::Lin(lin))
+                    | (Quad::Lin(lin), quad @ Quad::Quad(..)) => {
+                        self.quad_scale(quad, &Self::lc_const(&lin)?)
+                    }
+                    (Quad::Quad(..), Quad::Quad(..)) => return None,
+                }
+            }
+            Node::Binary(BinOp::Div, lhs, rhs) => {
+                let divisor = as_const(rhs)?;
+                let inverse = self.field.binary(BinOp::Div, &1u8.into(), divisor)?;
+                self.quad_scale(self.quad(lhs)?, &inverse)
+            }
+            Node::Binary(BinOp::Pow, base, exponent) => {
+                let exponent = as_const(exponent)?;
+                if *exponent == BigUint::from(1u8) {
+                    self.quad(base)?
+                } else if *exponent == BigUint::from(2u8) {
+                    let product = Rc::new(Node::Binary(BinOp::Mul, base.clone(), base.clone()));
+                    self.quad(&product)?
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        })
+    }
+}
+
+fn is_member(access: &Access) -> bool {
+    matches!(access, Access::Member(_))
+}
@@ -0,0 +1,180 @@
+//! Writers of the compiled circuit: the iden3 `r1cs` binary format, the `.sym` signal list
+//! and the witness program run by the wasm.
+
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+
+use crate::elaborate::{Lc, Node, Sym};
+use crate::program::{self, fnv1a, Instr, Program, Step};
+use crate::Compiled;
+
+/// Length of a field element.
+const FIELD_LEN: usize = 32;
+
+fn field_bytes(value: &BigUint) -> [u8; FIELD_LEN] {
+    let mut bytes = [0u8; FIELD_LEN];
+    let le = value.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+fn section(out: &mut Vec<u8>, kind: u32, contents: &[u8]) {
+    out.extend(kind.to_le_bytes());
+    out.extend((contents.len() as u64).to_le_bytes());
+    out.extend(contents);
+}
+
+/// The `r1cs` file, wire labels being the wire indices.
+pub fn r1cs(compiled: &Compiled) -> Vec<u8> {
+    let circuit = &compiled.circuit;
+    let mut header = Vec::new();
+    header.extend((FIELD_LEN as u32).to_le_bytes());
+    header.extend(field_bytes(&circuit.field.prime));
+    header.extend((compiled.wires.len() as u32).to_le_bytes());
+    header.extend((circuit.outputs.len() as u32).to_le_bytes());
+    header.extend((circuit.public_inputs.len() as u32).to_le_bytes());
+    header.extend((circuit.private_inputs.len() as u32).to_le_bytes());
+    header.extend((compiled.wires.len() as u64).to_le_bytes());
+    header.extend((circuit.constraints.len() as u32).to_le_bytes());
+
+    let mut constraints = Vec::new();
+    for constraint in &circuit.constraints {
+        for lc in [&constraint.a, &constraint.b, &constraint.c] {
+            let mut terms: Vec<_> = lc
+                .iter()
+                .map(|(signal, coeff)| (compiled.wire_of[*signal], coeff))
+                .collect();
+            terms.sort_by_key(|(wire, _)| *wire);
+            constraints.extend((terms.len() as u32).to_le_bytes());
+            for (wire, coeff) in terms {
+                constraints.extend(wire.to_le_bytes());
+                constraints.extend(field_bytes(coeff));
+            }
+        }
+    }
+
+    let labels: Vec<u8> = (0..compiled.wires.len() as u64)
+        .flat_map(u64::to_le_bytes)
+        .collect();
+
+    let mut out = b"r1cs".to_vec();
+    out.extend(1u32.to_le_bytes());
+    out.extend(3u32.to_le_bytes());
+    section(&mut out, 1, &header);
+    section(&mut out, 2, &constraints);
+    section(&mut out, 3, &labels);
+    out
+}
+
+/// The `.sym` file: label, wire, component and name of every signal, in wire order.
+pub fn sym(compiled: &Compiled) -> String {
+    compiled
+        .wires
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(wire, signal)| {
+            let signal = &compiled.circuit.signals[*signal];
+            format!("{},{},{},{}\n", wire, wire, signal.component, signal.name)
+        })
+        .collect()
+}
+
+struct Consts {
+    values: Vec<BigUint>,
+    index: HashMap<BigUint, u32>,
+}
+
+impl Consts {
+    fn get(&mut self, value: &BigUint) -> u32 {
+        if let Some(index) = self.index.get(value) {
+            return *index;
+        }
+        self.values.push(value.clone());
+        self.index
+            .insert(value.clone(), self.values.len() as u32 - 1);
+        self.values.len() as u32 - 1
+    }
+
+    fn terms(&mut self, lc: &Lc, wire_of: &[u32]) -> program::Terms {
+        lc.iter()
+            .map(|(signal, coeff)| (wire_of[*signal], self.get(coeff)))
+            .collect()
+    }
+}
+
+fn emit(sym: &Sym, wire_of: &[u32], consts: &mut Consts, code: &mut Vec<Instr>) {
+    match &**sym {
+        Node::Const(value) => code.push(Instr::Const(consts.get(value))),
+        Node::Signal(signal) => code.push(Instr::Wire(wire_of[*signal])),
+        Node::Binary(op, lhs, rhs) => {
+            emit(lhs, wire_of, consts, code);
+            emit(rhs, wire_of, consts, code);
+            code.push(Instr::Binary(*op));
+        }
+        Node::Unary(op, operand) => {
+            emit(operand, wire_of, consts, code);
+            code.push(Instr::Unary(*op));
+        }
+        Node::Ternary(cond, then, otherwise) => {
+            emit(cond, wire_of, consts, code);
+            let jump_if_zero = code.len();
+            code.push(Instr::JumpIfZero(0));
+            emit(then, wire_of, consts, code);
+            let jump = code.len();
+            code.push(Instr::Jump(0));
+            code[jump_if_zero] = Instr::JumpIfZero(code.len() as u32);
+            emit(otherwise, wire_of, consts, code);
+            code[jump] = Instr::Jump(code.len() as u32);
+        }
+    }
+}
+
+/// The witness program, the inputs keyed by the hash of their names.
+pub fn program(compiled: &Compiled) -> Program {
+    let circuit = &compiled.circuit;
+    let wire_of = &compiled.wire_of;
+    let mut consts = Consts {
+        values: Vec::new(),
+        index: HashMap::new(),
+    };
+    let inputs = circuit
+        .inputs
+        .iter()
+        .map(|(name, signals)| program::Input {
+            hash: fnv1a(name),
+            wire: wire_of[signals[0]],
+            size: signals.len() as u32,
+        })
+        .collect();
+    let steps = circuit
+        .steps
+        .iter()
+        .map(|(signal, sym)| {
+            let mut code = Vec::new();
+            emit(sym, wire_of, &mut consts, &mut code);
+            Step {
+                wire: wire_of[*signal],
+                code,
+            }
+        })
+        .collect();
+    let constraints = circuit
+        .constraints
+        .iter()
+        .map(|constraint| program::Constraint {
+            a: consts.terms(&constraint.a, wire_of),
+            b: consts.terms(&constraint.b, wire_of),
+            c: consts.terms(&constraint.c, wire_of),
+        })
+        .collect();
+    Program {
+        prime: circuit.field.prime.clone(),
+        n_wires: compiled.wires.len() as u32,
+        inputs,
+        consts: consts.values,
+        steps,
+        constraints,
+    }
+}
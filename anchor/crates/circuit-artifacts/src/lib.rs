@@ -0,0 +1,164 @@
+//! Builds the circom artifacts of the circuits without the circom toolchain: compiles the
+//! circom subset the circuits and circomlib use to an `r1cs`, a `sym` and a witness calculator
+//! wasm with the interface of the circom generated ones.
+//!
+//! The Groth16 setup is not done here, snarkjs runs it on the `r1cs`:
+//!
+//! ```text
+//! snarkjs groth16 setup swap.r1cs pot13_final.ptau swap_0000.zkey
+//! snarkjs zkey contribute swap_0000.zkey swap_0001.zkey
+//! snarkjs zkey export verificationkey swap_0001.zkey verification_key.json
+//! ```
+//!
+//! The constraints are not simplified the way `circom --O1` does, so the `r1cs` keeps the
+//! signals circom would substitute away. `tests/upstream` holds a circuit compiled by circom
+//! to check the witnesses against.
+
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use num_bigint::BigUint;
+
+pub mod elaborate;
+pub mod formats;
+pub mod parse;
+pub mod program;
+pub mod wasm;
+
+#[cfg(test)]
+mod tests;
+
+use elaborate::Circuit;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(String),
+    /// A source file is not in the supported circom subset.
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    /// The circuit cannot be elaborated, a constraint is not quadratic or a signal is never
+    /// assigned for instance.
+    Compile(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            Error::Compile(err) => write!(f, "failed to compile: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Scalar field of BN254, the prime circom defaults to.
+pub fn bn254_prime() -> BigUint {
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+        .parse()
+        .unwrap()
+}
+
+/// Directory of the circomlib 2.0.5 templates the circuits include, standing in for
+/// `node_modules/circomlib/circuits`.
+pub fn circomlib_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("circomlib")
+}
+
+pub(crate) fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| Error::Io(format!("{}: {}", path.display(), err)))
+}
+
+pub(crate) fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| Error::Io(format!("{}: {}", parent.display(), err)))?;
+    }
+    fs::write(path, contents).map_err(|err| Error::Io(format!("{}: {}", path.display(), err)))
+}
+
+/// Parses a circuit and the files it includes, the circuit first.
+pub fn load(path: &Path) -> Result<Vec<parse::Source>> {
+    let mut sources = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let canonical = path
+            .canonicalize()
+            .map_err(|err| Error::Io(format!("{}: {}", path.display(), err)))?;
+        if !seen.insert(canonical) {
+            continue;
+        }
+        let source = parse::parse(&path.display().to_string(), &read(&path)?)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for include in source.includes.iter().rev() {
+            pending.push(
+                match include.strip_prefix("node_modules/circomlib/circuits/") {
+                    Some(name) => circomlib_dir().join(name),
+                    None => dir.join(include),
+                },
+            );
+        }
+        sources.push(source);
+    }
+    Ok(sources)
+}
+
+/// A compiled circuit with its wires laid out as circom does: the constant one, the outputs,
+/// the public inputs, the private inputs, then every other signal.
+#[derive(Debug)]
+pub struct Compiled {
+    pub circuit: Circuit,
+    /// Signal of every wire.
+    pub wires: Vec<usize>,
+    /// Wire of every signal.
+    pub wire_of: Vec<u32>,
+}
+
+impl Compiled {
+    pub fn new(circuit: Circuit) -> Self {
+        let mut wires = vec![0];
+        wires.extend(&circuit.outputs);
+        wires.extend(&circuit.public_inputs);
+        wires.extend(&circuit.private_inputs);
+        let mut placed = vec![false; circuit.signals.len()];
+        for wire in &wires {
+            placed[*wire] = true;
+        }
+        wires.extend((0..circuit.signals.len()).filter(|signal| !placed[*signal]));
+        let mut wire_of = vec![0; circuit.signals.len()];
+        for (wire, signal) in wires.iter().enumerate() {
+            wire_of[*signal] = wire as u32;
+        }
+        Self {
+            circuit,
+            wires,
+            wire_of,
+        }
+    }
+
+    /// Outputs and public inputs, the public signals of a proof.
+    pub fn n_public(&self) -> usize {
+        self.circuit.outputs.len() + self.circuit.public_inputs.len()
+    }
+}
+
+/// Parses and elaborates a circuit.
+pub fn compile(path: &Path) -> Result<Compiled> {
+    let sources = load(path)?;
+    let circuit = elaborate::elaborate(&sources, bn254_prime())?;
+    Ok(Compiled::new(circuit))
+}
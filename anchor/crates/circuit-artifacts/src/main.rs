@@ -0,0 +1,64 @@
+//! circuit-artifacts compile <circuit.circom> <out-dir>
+//!     Writes `<name>.r1cs`, `<name>.sym` and `<name>_js/<name>.wasm` to `out-dir`.
+
+use std::{env, fs, path::Path, process};
+
+use circuit_artifacts::{compile, formats, wasm, Error, Result};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["compile", circuit, out_dir] => compile_circuit(Path::new(circuit), Path::new(out_dir)),
+        _ => Err(Error::Io(
+            "usage: circuit-artifacts compile <circuit.circom> <out-dir>".to_string(),
+        )),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn compile_circuit(circuit: &Path, out_dir: &Path) -> Result<()> {
+    let name = circuit
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| Error::Io(format!("{}: not a circuit file", circuit.display())))?;
+    let compiled = compile(circuit)?;
+    write(
+        &out_dir.join(format!("{}.r1cs", name)),
+        formats::r1cs(&compiled),
+    )?;
+    write(
+        &out_dir.join(format!("{}.sym", name)),
+        formats::sym(&compiled),
+    )?;
+    let build_dir = env::temp_dir().join(format!("circuit-artifacts-{}", process::id()));
+    wasm::build(
+        &formats::program(&compiled),
+        &build_dir,
+        &out_dir
+            .join(format!("{}_js", name))
+            .join(format!("{}.wasm", name)),
+    )?;
+    let _ = fs::remove_dir_all(&build_dir);
+
+    let circuit = &compiled.circuit;
+    println!("wires: {}", compiled.wires.len());
+    println!("constraints: {}", circuit.constraints.len());
+    println!("public outputs: {}", circuit.outputs.len());
+    println!("public inputs: {}", circuit.public_inputs.len());
+    println!("private inputs: {}", circuit.private_inputs.len());
+    Ok(())
+}
+
+fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| Error::Io(format!("{}: {}", parent.display(), err)))?;
+    }
+    fs::write(path, contents).map_err(|err| Error::Io(format!("{}: {}", path.display(), err)))
+}
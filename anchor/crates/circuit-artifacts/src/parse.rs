@@ -0,0 +1,725 @@
+//! Parser of the circom subset the circuits are written in: templates and functions, signals,
+//! vars and components with array dimensions, `for`, `while` and `if`, the `<==`, `<--`,
+//! `===` and var assignment operators, and expressions with circom's precedences.
+
+use num_bigint::BigUint;
+use num_traits::Num;
+
+use crate::program::{BinOp, UnOp};
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Number(BigUint),
+    Ref(Target),
+    /// A function call, or a template instantiation on the right of a component assignment.
+    Call(String, Vec<Expr>),
+    Array(Vec<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Unary(UnOp, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// A variable, signal or component, with the indices and members accessed on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub name: String,
+    pub access: Vec<Access>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    Index(Expr),
+    Member(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalKind {
+    Input,
+    Output,
+    Intermediate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssignOp {
+    /// `=`
+    Var,
+    /// `+=` and the like, `++` and `--`.
+    Compound(BinOp),
+    /// `<==` and `==>`
+    Constrain,
+    /// `<--` and `-->`
+    Witness,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stmt {
+    pub line: usize,
+    pub kind: StmtKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StmtKind {
+    Signal {
+        kind: SignalKind,
+        name: String,
+        dims: Vec<Expr>,
+        init: Option<(AssignOp, Expr)>,
+    },
+    Var {
+        name: String,
+        dims: Vec<Expr>,
+        init: Option<Expr>,
+    },
+    Component {
+        name: String,
+        dims: Vec<Expr>,
+        init: Option<Expr>,
+    },
+    Assign {
+        target: Target,
+        op: AssignOp,
+        value: Expr,
+    },
+    /// `===`
+    Equal(Expr, Expr),
+    If {
+        cond: Expr,
+        then: Vec<Stmt>,
+        otherwise: Vec<Stmt>,
+    },
+    For {
+        init: Box<Stmt>,
+        cond: Expr,
+        step: Box<Stmt>,
+        body: Vec<Stmt>,
+    },
+    While {
+        cond: Expr,
+        body: Vec<Stmt>,
+    },
+    Return(Expr),
+    Assert(Expr),
+    /// `log(...)`, ignored.
+    Log,
+    Block(Vec<Stmt>),
+}
+
+/// A template or a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Main {
+    /// Input signals of the main template that are public.
+    pub public: Vec<String>,
+    pub template: String,
+    pub args: Vec<Expr>,
+}
+
+/// A parsed source file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Source {
+    pub includes: Vec<String>,
+    pub templates: Vec<Definition>,
+    pub functions: Vec<Definition>,
+    pub main: Option<Main>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(BigUint),
+    Str(String),
+    Punct(&'static str),
+}
+
+/// Longest first, so the lexer matches `<==` before `<=` and `<`.
+const PUNCTS: &[&str] = &[
+    "<==", "==>", "<--", "-->", "===", "**=", "<<=", ">>=", "==", "!=", "<=", ">=", "&&", "||",
+    "<<", ">>", "**", "++", "--", "+=", "-=", "*=", "/=", "\\=", "%=", "&=", "|=", "^=", "+", "-",
+    "*", "/", "\\", "%", "<", ">", "=", "!", "~", "&", "|", "^", "?", ":", ";", ",", ".", "(", ")",
+    "[", "]", "{", "}",
+];
+
+fn lex(file: &str, text: &str) -> Result<Vec<(Token, usize)>> {
+    let error = |line: usize, message: String| Error::Parse {
+        file: file.to_string(),
+        line,
+        message,
+    };
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c == b'\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_ascii_whitespace() {
+            i += 1;
+        } else if text[i..].starts_with("//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+        } else if text[i..].starts_with("/*") {
+            let end = text[i + 2..]
+                .find("*/")
+                .ok_or_else(|| error(line, "unterminated comment".to_string()))?;
+            line += text[i..i + 2 + end].matches('\n').count();
+            i += end + 4;
+        } else if c == b'"' {
+            let end = text[i + 1..]
+                .find('"')
+                .ok_or_else(|| error(line, "unterminated string".to_string()))?;
+            tokens.push((Token::Str(text[i + 1..i + 1 + end].to_string()), line));
+            i += end + 2;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            let literal = &text[start..i];
+            let number = match literal.strip_prefix("0x") {
+                Some(hex) => BigUint::from_str_radix(hex, 16),
+                None => BigUint::from_str_radix(literal, 10),
+            }
+            .map_err(|_| error(line, format!("invalid number `{}`", literal)))?;
+            tokens.push((Token::Number(number), line));
+        } else if c.is_ascii_alphabetic() || c == b'_' || c == b'$' {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'$')
+            {
+                i += 1;
+            }
+            tokens.push((Token::Ident(text[start..i].to_string()), line));
+        } else {
+            let punct = PUNCTS
+                .iter()
+                .find(|punct| text[i..].starts_with(**punct))
+                .ok_or_else(|| error(line, format!("unexpected character `{}`", c as char)))?;
+            tokens.push((Token::Punct(punct), line));
+            i += punct.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Binary operators by precedence, lowest first. `**` binds tighter than all of them and is
+/// parsed apart as it is right associative.
+const PRECEDENCE: [&[(&str, BinOp)]; 9] = [
+    &[("||", BinOp::Or)],
+    &[("&&", BinOp::And)],
+    &[("|", BinOp::BitOr)],
+    &[("^", BinOp::BitXor)],
+    &[("&", BinOp::BitAnd)],
+    &[("==", BinOp::Eq), ("!=", BinOp::Neq)],
+    &[
+        ("<", BinOp::Lt),
+        (">", BinOp::Gt),
+        ("<=", BinOp::Le),
+        (">=", BinOp::Ge),
+    ],
+    &[("<<", BinOp::Shl), (">>", BinOp::Shr)],
+    &[("+", BinOp::Add), ("-", BinOp::Sub)],
+];
+const MUL_OPS: [(&str, BinOp); 4] = [
+    ("*", BinOp::Mul),
+    ("/", BinOp::Div),
+    ("\\", BinOp::IntDiv),
+    ("%", BinOp::Mod),
+];
+const COMPOUND_OPS: [(&str, BinOp); 12] = [
+    ("+=", BinOp::Add),
+    ("-=", BinOp::Sub),
+    ("*=", BinOp::Mul),
+    ("/=", BinOp::Div),
+    ("\\=", BinOp::IntDiv),
+    ("%=", BinOp::Mod),
+    ("**=", BinOp::Pow),
+    ("<<=", BinOp::Shl),
+    (">>=", BinOp::Shr),
+    ("&=", BinOp::BitAnd),
+    ("|=", BinOp::BitOr),
+    ("^=", BinOp::BitXor),
+];
+
+struct Parser<'a> {
+    file: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+pub fn parse(file: &str, text: &str) -> Result<Source> {
+    let mut parser = Parser {
+        file,
+        tokens: lex(file, text)?,
+        pos: 0,
+    };
+    parser.source()
+}
+
+impl<'a> Parser<'a> {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error::Parse {
+            file: self.file.to_string(),
+            line: self.line(),
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn is_ident(&self, ident: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(i)) if i == ident)
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> bool {
+        let found = self.is_ident(ident);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<()> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", punct))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok(ident)
+            }
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Str(string)) => {
+                let string = string.clone();
+                self.pos += 1;
+                Ok(string)
+            }
+            _ => self.error("expected a string"),
+        }
+    }
+
+    fn source(&mut self) -> Result<Source> {
+        let mut source = Source::default();
+        while self.peek().is_some() {
+            if self.eat_ident("pragma") {
+                while !self.eat_punct(";") {
+                    if self.peek().is_none() {
+                        return self.error("expected `;`");
+                    }
+                    self.pos += 1;
+                }
+            } else if self.eat_ident("include") {
+                source.includes.push(self.string()?);
+                self.expect(";")?;
+            } else if self.eat_ident("template") {
+                source.templates.push(self.definition()?);
+            } else if self.eat_ident("function") {
+                source.functions.push(self.definition()?);
+            } else if self.eat_ident("component") {
+                if !self.eat_ident("main") || source.main.is_some() {
+                    return self.error("expected a single main component");
+                }
+                source.main = Some(self.main()?);
+            } else {
+                return self.error("expected a template, function or main component");
+            }
+        }
+        Ok(source)
+    }
+
+    fn definition(&mut self) -> Result<Definition> {
+        let name = self.ident()?;
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while !self.eat_punct(")") {
+            if !params.is_empty() {
+                self.expect(",")?;
+            }
+            params.push(self.ident()?);
+        }
+        let body = self.block()?;
+        Ok(Definition { name, params, body })
+    }
+
+    fn main(&mut self) -> Result<Main> {
+        let mut public = Vec::new();
+        if self.eat_punct("{") {
+            if !self.eat_ident("public") {
+                return self.error("expected `public`");
+            }
+            self.expect("[")?;
+            while !self.eat_punct("]") {
+                if !public.is_empty() {
+                    self.expect(",")?;
+                }
+                public.push(self.ident()?);
+            }
+            self.expect("}")?;
+        }
+        self.expect("=")?;
+        let template = self.ident()?;
+        let args = self.args()?;
+        self.expect(";")?;
+        Ok(Main {
+            public,
+            template,
+            args,
+        })
+    }
+
+    fn args(&mut self) -> Result<Vec<Expr>> {
+        self.expect("(")?;
+        let mut args = Vec::new();
+        while !self.eat_punct(")") {
+            if !args.is_empty() {
+                self.expect(",")?;
+            }
+            args.push(self.expr()?);
+        }
+        Ok(args)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>> {
+        self.expect("{")?;
+        let mut stmts = Vec::new();
+        while !self.eat_punct("}") {
+            if self.peek().is_none() {
+                return self.error("expected `}`");
+            }
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    /// A block, or a single statement as the body of `if`, `for` and `while`.
+    fn body(&mut self) -> Result<Vec<Stmt>> {
+        if self.is_punct("{") {
+            self.block()
+        } else {
+            Ok(vec![self.stmt()?])
+        }
+    }
+
+    fn dims(&mut self) -> Result<Vec<Expr>> {
+        let mut dims = Vec::new();
+        while self.eat_punct("[") {
+            dims.push(self.expr()?);
+            self.expect("]")?;
+        }
+        Ok(dims)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt> {
+        let line = self.line();
+        let kind = if self.is_punct("{") {
+            StmtKind::Block(self.block()?)
+        } else if self.eat_ident("if") {
+            self.expect("(")?;
+            let cond = self.expr()?;
+            self.expect(")")?;
+            let then = self.body()?;
+            let otherwise = if self.eat_ident("else") {
+                self.body()?
+            } else {
+                Vec::new()
+            };
+            StmtKind::If {
+                cond,
+                then,
+                otherwise,
+            }
+        } else if self.eat_ident("for") {
+            self.expect("(")?;
+            let init = Box::new(self.simple_stmt()?);
+            self.expect(";")?;
+            let cond = self.expr()?;
+            self.expect(";")?;
+            let step = Box::new(self.simple_stmt()?);
+            self.expect(")")?;
+            let body = self.body()?;
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body,
+            }
+        } else if self.eat_ident("while") {
+            self.expect("(")?;
+            let cond = self.expr()?;
+            self.expect(")")?;
+            StmtKind::While {
+                cond,
+                body: self.body()?,
+            }
+        } else {
+            let kind = if self.eat_ident("return") {
+                StmtKind::Return(self.expr()?)
+            } else if self.eat_ident("assert") {
+                self.expect("(")?;
+                let cond = self.expr()?;
+                self.expect(")")?;
+                StmtKind::Assert(cond)
+            } else if self.eat_ident("log") {
+                self.expect("(")?;
+                while !self.eat_punct(")") {
+                    if self.peek().is_none() {
+                        return self.error("expected `)`");
+                    }
+                    self.pos += 1;
+                }
+                StmtKind::Log
+            } else {
+                return self.simple_stmt().and_then(|stmt| {
+                    self.expect(";")?;
+                    Ok(stmt)
+                });
+            };
+            self.expect(";")?;
+            kind
+        };
+        Ok(Stmt { line, kind })
+    }
+
+    /// A declaration or assignment, without the `;`. Also the init and step of a `for`.
+    fn simple_stmt(&mut self) -> Result<Stmt> {
+        let line = self.line();
+        let kind = if self.eat_ident("signal") {
+            let kind = if self.eat_ident("input") {
+                SignalKind::Input
+            } else if self.eat_ident("output") {
+                SignalKind::Output
+            } else {
+                SignalKind::Intermediate
+            };
+            let name = self.ident()?;
+            let dims = self.dims()?;
+            let init = if self.eat_punct("<==") {
+                Some((AssignOp::Constrain, self.expr()?))
+            } else if self.eat_punct("<--") {
+                Some((AssignOp::Witness, self.expr()?))
+            } else {
+                None
+            };
+            StmtKind::Signal {
+                kind,
+                name,
+                dims,
+                init,
+            }
+        } else if self.eat_ident("var") {
+            let name = self.ident()?;
+            let dims = self.dims()?;
+            let init = if self.eat_punct("=") {
+                Some(self.expr()?)
+            } else {
+                None
+            };
+            StmtKind::Var { name, dims, init }
+        } else if self.eat_ident("component") {
+            let name = self.ident()?;
+            let dims = self.dims()?;
+            let init = if self.eat_punct("=") {
+                Some(self.expr()?)
+            } else {
+                None
+            };
+            StmtKind::Component { name, dims, init }
+        } else {
+            let lhs = self.expr()?;
+            let target = |parser: &Self, expr: Expr| match expr {
+                Expr::Ref(target) => Ok(target),
+                _ => parser.error("expected a variable, signal or component"),
+            };
+            let op = match self.peek() {
+                Some(Token::Punct(op)) => *op,
+                _ => return self.error("expected an assignment"),
+            };
+            self.pos += 1;
+            match op {
+                "===" => StmtKind::Equal(lhs, self.expr()?),
+                "==>" | "-->" => StmtKind::Assign {
+                    target: {
+                        let expr = self.expr()?;
+                        target(self, expr)?
+                    },
+                    op: if op == "==>" {
+                        AssignOp::Constrain
+                    } else {
+                        AssignOp::Witness
+                    },
+                    value: lhs,
+                },
+                "++" | "--" => StmtKind::Assign {
+                    target: target(self, lhs)?,
+                    op: AssignOp::Compound(if op == "++" { BinOp::Add } else { BinOp::Sub }),
+                    value: Expr::Number(1u8.into()),
+                },
+                _ => {
+                    let op = match op {
+                        "=" => AssignOp::Var,
+                        "<==" => AssignOp::Constrain,
+                        "<--" => AssignOp::Witness,
+                        _ => match COMPOUND_OPS.iter().find(|(punct, _)| *punct == op) {
+                            Some((_, op)) => AssignOp::Compound(*op),
+                            None => return self.error(format!("unexpected `{}`", op)),
+                        },
+                    };
+                    StmtKind::Assign {
+                        target: target(self, lhs)?,
+                        op,
+                        value: self.expr()?,
+                    }
+                }
+            }
+        };
+        Ok(Stmt { line, kind })
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let cond = self.binary(0)?;
+        if self.eat_punct("?") {
+            let then = self.expr()?;
+            self.expect(":")?;
+            let otherwise = self.expr()?;
+            return Ok(Expr::Ternary(
+                Box::new(cond),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+        Ok(cond)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        if level == PRECEDENCE.len() {
+            return self.product();
+        }
+        let mut lhs = self.binary(level + 1)?;
+        while let Some((_, op)) = PRECEDENCE[level]
+            .iter()
+            .find(|(punct, _)| self.is_punct(punct))
+        {
+            self.pos += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        let mut lhs = self.power()?;
+        while let Some((_, op)) = MUL_OPS.iter().find(|(punct, _)| self.is_punct(punct)) {
+            self.pos += 1;
+            let rhs = self.power()?;
+            lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.unary()?;
+        if self.eat_punct("**") {
+            let exponent = self.power()?;
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        for (punct, op) in [("-", UnOp::Neg), ("!", UnOp::Not), ("~", UnOp::BitNot)] {
+            if self.eat_punct(punct) {
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+        if self.eat_punct("+") {
+            return self.unary();
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.peek().cloned() {
+            Some(Token::Number(number)) => {
+                self.pos += 1;
+                Ok(Expr::Number(number))
+            }
+            Some(Token::Punct("(")) => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Punct("[")) => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                while !self.eat_punct("]") {
+                    if !items.is_empty() {
+                        self.expect(",")?;
+                    }
+                    items.push(self.expr()?);
+                }
+                Ok(Expr::Array(items))
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                if self.is_punct("(") {
+                    return Ok(Expr::Call(name, self.args()?));
+                }
+                let mut access = Vec::new();
+                loop {
+                    if self.eat_punct("[") {
+                        access.push(Access::Index(self.expr()?));
+                        self.expect("]")?;
+                    } else if self.eat_punct(".") {
+                        access.push(Access::Member(self.ident()?));
+                    } else {
+                        break;
+                    }
+                }
+                Ok(Expr::Ref(Target { name, access }))
+            }
+            _ => self.error("expected an expression"),
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! The witness program: the steps computing every wire of a circuit from its inputs, and the
+//! constraints checked once they are computed. The compiler writes it, the wasm runtime in
+//! `runtime/` embeds and runs it. This file is also compiled into the runtime, so it only
+//! depends on `num-bigint`.
+
+use num_bigint::BigUint;
+
+/// Binary operators, with the semantics circom gives them on field elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    IntDiv,
+    Mod,
+    Pow,
+    Shl,
+    Shr,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+const BIN_OPS: [BinOp; 20] = [
+    BinOp::Add,
+    BinOp::Sub,
+    BinOp::Mul,
+    BinOp::Div,
+    BinOp::IntDiv,
+    BinOp::Mod,
+    BinOp::Pow,
+    BinOp::Shl,
+    BinOp::Shr,
+    BinOp::BitAnd,
+    BinOp::BitOr,
+    BinOp::BitXor,
+    BinOp::Eq,
+    BinOp::Neq,
+    BinOp::Lt,
+    BinOp::Le,
+    BinOp::Gt,
+    BinOp::Ge,
+    BinOp::And,
+    BinOp::Or,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnOp {
+    Neg,
+    Not,
+    BitNot,
+}
+
+const UN_OPS: [UnOp; 3] = [UnOp::Neg, UnOp::Not, UnOp::BitNot];
+
+/// Arithmetic modulo `prime`. Values are always reduced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub prime: BigUint,
+    /// Mask of the bit length of the prime, applied by `<<` and `~`.
+    mask: BigUint,
+}
+
+impl Field {
+    pub fn new(prime: BigUint) -> Self {
+        let mask = (BigUint::from(1u8) << prime.bits()) - 1u8;
+        Self { prime, mask }
+    }
+
+    pub fn reduce(&self, value: BigUint) -> BigUint {
+        value % &self.prime
+    }
+
+    fn is_negative(&self, value: &BigUint) -> bool {
+        value > &(&self.prime >> 1)
+    }
+
+    /// `a < b` with values above `prime / 2` taken as negative, as circom compares.
+    fn less(&self, a: &BigUint, b: &BigUint) -> bool {
+        match (self.is_negative(a), self.is_negative(b)) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => a < b,
+        }
+    }
+
+    /// Shift amount of `<<` and `>>`, negative above `prime / 2`. Anything past the bit
+    /// length of the prime shifts every bit out.
+    fn shift(&self, amount: &BigUint) -> (bool, usize) {
+        let (negative, amount) = if self.is_negative(amount) {
+            (true, &self.prime - amount)
+        } else {
+            (false, amount.clone())
+        };
+        let bits = self.prime.bits() as usize + 1;
+        let amount = usize::try_from(amount).unwrap_or(bits).min(bits);
+        (negative, amount)
+    }
+
+    fn shl(&self, value: &BigUint, amount: usize) -> BigUint {
+        self.reduce((value << amount) & &self.mask)
+    }
+
+    /// `None` on a division by zero.
+    pub fn binary(&self, op: BinOp, a: &BigUint, b: &BigUint) -> Option<BigUint> {
+        let bool = |value: bool| BigUint::from(value as u8);
+        let zero = BigUint::from(0u8);
+        Some(match op {
+            BinOp::Add => self.reduce(a + b),
+            BinOp::Sub => self.reduce(a + &self.prime - b),
+            BinOp::Mul => self.reduce(a * b),
+            BinOp::Div => {
+                if *b == zero {
+                    return None;
+                }
+                self.reduce(a * b.modpow(&(&self.prime - 2u8), &self.prime))
+            }
+            BinOp::IntDiv => {
+                if *b == zero {
+                    return None;
+                }
+                a / b
+            }
+            BinOp::Mod => {
+                if *b == zero {
+                    return None;
+                }
+                a % b
+            }
+            BinOp::Pow => a.modpow(b, &self.prime),
+            BinOp::Shl | BinOp::Shr => {
+                let (negative, amount) = self.shift(b);
+                if (op == BinOp::Shl) != negative {
+                    self.shl(a, amount)
+                } else {
+                    a >> amount
+                }
+            }
+            BinOp::BitAnd => self.reduce(a & b),
+            BinOp::BitOr => self.reduce(a | b),
+            BinOp::BitXor => self.reduce(a ^ b),
+            BinOp::Eq => bool(a == b),
+            BinOp::Neq => bool(a != b),
+            BinOp::Lt => bool(self.less(a, b)),
+            BinOp::Le => bool(!self.less(b, a)),
+            BinOp::Gt => bool(self.less(b, a)),
+            BinOp::Ge => bool(!self.less(a, b)),
+            BinOp::And => bool(*a != zero && *b != zero),
+            BinOp::Or => bool(*a != zero || *b != zero),
+        })
+    }
+
+    pub fn unary(&self, op: UnOp, a: &BigUint) -> BigUint {
+        match op {
+            UnOp::Neg => self.reduce(&self.prime - a),
+            UnOp::Not => BigUint::from((*a == BigUint::from(0u8)) as u8),
+            UnOp::BitNot => self.reduce(a ^ &self.mask),
+        }
+    }
+}
+
+/// An instruction of the stack machine evaluating a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instr {
+    /// Pushes a constant of the program.
+    Const(u32),
+    /// Pushes the value of a wire.
+    Wire(u32),
+    Binary(BinOp),
+    Unary(UnOp),
+    /// Pops a value and jumps to the instruction if it is zero.
+    JumpIfZero(u32),
+    Jump(u32),
+}
+
+/// Computes a wire, its value is the one left on the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub wire: u32,
+    pub code: Vec<Instr>,
+}
+
+/// An input signal of the main component, `size` wires from `wire` on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    /// 64 bit FNV-1a hash of the signal name, the key the witness calculators look it up by.
+    pub hash: u64,
+    pub wire: u32,
+    pub size: u32,
+}
+
+/// A linear combination, pairs of a wire and the index of its coefficient in `consts`.
+pub type Terms = Vec<(u32, u32)>;
+
+/// `a * b = c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub a: Terms,
+    pub b: Terms,
+    pub c: Terms,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub prime: BigUint,
+    pub n_wires: u32,
+    pub inputs: Vec<Input>,
+    pub consts: Vec<BigUint>,
+    /// In evaluation order.
+    pub steps: Vec<Step>,
+    pub constraints: Vec<Constraint>,
+}
+
+/// Why a witness could not be computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// A division by zero in a step.
+    DivisionByZero,
+    /// The index of a constraint that does not hold.
+    Unsatisfied(usize),
+}
+
+pub fn fnv1a(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+impl Program {
+    pub fn input(&self, hash: u64) -> Option<&Input> {
+        self.inputs.iter().find(|input| input.hash == hash)
+    }
+
+    pub fn input_size(&self) -> u32 {
+        self.inputs.iter().map(|input| input.size).sum()
+    }
+
+    /// Computes the wires not set by the inputs and checks the constraints. `witness` holds
+    /// one value per wire, `1` at wire 0 and the inputs at theirs.
+    pub fn run(&self, witness: &mut [BigUint]) -> Result<(), Failure> {
+        let field = Field::new(self.prime.clone());
+        let mut stack = Vec::new();
+        for step in &self.steps {
+            let mut pc = 0;
+            while pc < step.code.len() {
+                pc += 1;
+                match step.code[pc - 1] {
+                    Instr::Const(index) => stack.push(self.consts[index as usize].clone()),
+                    Instr::Wire(wire) => stack.push(witness[wire as usize].clone()),
+                    Instr::Binary(op) => {
+                        let b = stack.pop().unwrap();
+                        let a = stack.pop().unwrap();
+                        stack.push(field.binary(op, &a, &b).ok_or(Failure::DivisionByZero)?);
+                    }
+                    Instr::Unary(op) => {
+                        let a = stack.pop().unwrap();
+                        stack.push(field.unary(op, &a));
+                    }
+                    Instr::JumpIfZero(target) => {
+                        if stack.pop().unwrap() == BigUint::from(0u8) {
+                            pc = target as usize;
+                        }
+                    }
+                    Instr::Jump(target) => pc = target as usize,
+                }
+            }
+            witness[step.wire as usize] = stack.pop().unwrap();
+        }
+
+        let eval = |terms: &Terms| {
+            terms.iter().fold(BigUint::from(0u8), |sum, (wire, coeff)| {
+                field.reduce(sum + &witness[*wire as usize] * &self.consts[*coeff as usize])
+            })
+        };
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            if field.reduce(eval(&constraint.a) * eval(&constraint.b)) != eval(&constraint.c) {
+                return Err(Failure::Unsatisfied(index));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Writer(Vec::new());
+        out.big(&self.prime);
+        out.u32(self.n_wires);
+        out.u32(self.inputs.len() as u32);
+        for input in &self.inputs {
+            out.0.extend_from_slice(&input.hash.to_le_bytes());
+            out.u32(input.wire);
+            out.u32(input.size);
+        }
+        out.u32(self.consts.len() as u32);
+        for value in &self.consts {
+            out.big(value);
+        }
+        out.u32(self.steps.len() as u32);
+        for step in &self.steps {
+            out.u32(step.wire);
+            out.u32(step.code.len() as u32);
+            for instr in &step.code {
+                let (tag, arg) = match *instr {
+                    Instr::Const(index) => (0, index),
+                    Instr::Wire(wire) => (1, wire),
+                    Instr::Binary(op) => (2, BIN_OPS.iter().position(|o| *o == op).unwrap() as u32),
+                    Instr::Unary(op) => (3, UN_OPS.iter().position(|o| *o == op).unwrap() as u32),
+                    Instr::JumpIfZero(target) => (4, target),
+                    Instr::Jump(target) => (5, target),
+                };
+                out.0.push(tag);
+                out.u32(arg);
+            }
+        }
+        out.u32(self.constraints.len() as u32);
+        for constraint in &self.constraints {
+            for terms in [&constraint.a, &constraint.b, &constraint.c] {
+                out.u32(terms.len() as u32);
+                for (wire, coeff) in terms {
+                    out.u32(*wire);
+                    out.u32(*coeff);
+                }
+            }
+        }
+        out.0
+    }
+
+    /// `None` if the bytes are not a program written by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut input = Reader(bytes);
+        let prime = input.big()?;
+        let n_wires = input.u32()?;
+        let inputs = (0..input.u32()?)
+            .map(|_| {
+                Some(Input {
+                    hash: u64::from_le_bytes(input.take(8)?.try_into().ok()?),
+                    wire: input.u32()?,
+                    size: input.u32()?,
+                })
+            })
+            .collect::<Option<_>>()?;
+        let consts = (0..input.u32()?)
+            .map(|_| input.big())
+            .collect::<Option<_>>()?;
+        let steps = (0..input.u32()?)
+            .map(|_| {
+                let wire = input.u32()?;
+                let code = (0..input.u32()?)
+                    .map(|_| {
+                        let tag = input.take(1)?[0];
+                        let arg = input.u32()?;
+                        Some(match tag {
+                            0 => Instr::Const(arg),
+                            1 => Instr::Wire(arg),
+                            2 => Instr::Binary(*BIN_OPS.get(arg as usize)?),
+                            3 => Instr::Unary(*UN_OPS.get(arg as usize)?),
+                            4 => Instr::JumpIfZero(arg),
+                            5 => Instr::Jump(arg),
+                            _ => return None,
+                        })
+                    })
+                    .collect::<Option<_>>()?;
+                Some(Step { wire, code })
+            })
+            .collect::<Option<_>>()?;
+        let constraints = (0..input.u32()?)
+            .map(|_| {
+                Some(Constraint {
+                    a: input.terms()?,
+                    b: input.terms()?,
+                    c: input.terms()?,
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            prime,
+            n_wires,
+            inputs,
+            consts,
+            steps,
+            constraints,
+        })
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn big(&mut self, value: &BigUint) {
+        let bytes = value.to_bytes_le();
+        self.u32(bytes.len() as u32);
+        self.0.extend_from_slice(&bytes);
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn big(&mut self) -> Option<BigUint> {
+        let len = self.u32()? as usize;
+        Some(BigUint::from_bytes_le(self.take(len)?))
+    }
+
+    fn terms(&mut self) -> Option<Terms> {
+        (0..self.u32()?)
+            .map(|_| Some((self.u32()?, self.u32()?)))
+            .collect()
+    }
+}
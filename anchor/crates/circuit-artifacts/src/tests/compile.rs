@@ -0,0 +1,259 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf};
+
+    use num_bigint::BigUint;
+
+    use crate::program::{fnv1a, Failure};
+    use crate::{bn254_prime, compile, formats, Compiled, Error};
+
+    const MULTIPLIER: &str = "pragma circom 2.0.0;
+
+template Multiplier() {
+    signal input a;
+    signal input b;
+    signal output c;
+
+    c <== a * b;
+}
+
+component main {public [a]} = Multiplier();
+";
+
+    fn circuits() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../circuits")
+    }
+
+    fn upstream() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/upstream")
+    }
+
+    /// Writes a circuit to a file of its own, `compile` taking a path.
+    fn compile_source(name: &str, source: &str) -> crate::Result<Compiled> {
+        let dir =
+            std::env::temp_dir().join(format!("circuit-artifacts-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.circom", name));
+        fs::write(&path, source).unwrap();
+        compile(&path)
+    }
+
+    fn witness(compiled: &Compiled, inputs: &[(&str, u64)]) -> Result<Vec<BigUint>, Failure> {
+        let program = formats::program(compiled);
+        let mut witness = vec![BigUint::from(0u8); program.n_wires as usize];
+        witness[0] = BigUint::from(1u8);
+        for (name, value) in inputs {
+            let input = program.input(fnv1a(name)).unwrap();
+            witness[input.wire as usize] = BigUint::from(*value);
+        }
+        program.run(&mut witness).map(|_| witness)
+    }
+
+    /// Wire of every signal in a `sym` file, -1 for the ones circom substituted away.
+    fn sym_wires(sym: &str) -> HashMap<String, i64> {
+        sym.lines()
+            .map(|line| {
+                let fields: Vec<&str> = line.splitn(4, ',').collect();
+                (fields[3].to_string(), fields[1].parse().unwrap())
+            })
+            .collect()
+    }
+
+    type Lc = Vec<(usize, BigUint)>;
+
+    /// Constraints of an `r1cs` file.
+    fn r1cs_constraints(r1cs: &[u8]) -> Vec<[Lc; 3]> {
+        let u32_at = |at: usize| u32::from_le_bytes(r1cs[at..at + 4].try_into().unwrap()) as usize;
+        let u64_at = |at: usize| u64::from_le_bytes(r1cs[at..at + 8].try_into().unwrap()) as usize;
+        let mut sections = HashMap::new();
+        let mut at = 12;
+        for _ in 0..u32_at(8) {
+            sections.insert(u32_at(at), at + 12);
+            at += 12 + u64_at(at + 4);
+        }
+        let header = sections[&1];
+        let field_size = u32_at(header);
+        let n_constraints = u32_at(header + 4 + field_size + 24);
+
+        let mut at = sections[&2];
+        let mut lc = || {
+            let terms = (0..u32_at(at))
+                .map(|term| {
+                    let term = at + 4 + term * (4 + field_size);
+                    let value = BigUint::from_bytes_le(&r1cs[term + 4..term + 4 + field_size]);
+                    (u32_at(term), value)
+                })
+                .collect::<Lc>();
+            at += 4 + terms.len() * (4 + field_size);
+            terms
+        };
+        (0..n_constraints).map(|_| [lc(), lc(), lc()]).collect()
+    }
+
+    /// Compiles `circuits/<name>.circom` and compares it with the checked-in `r1cs` and `sym`.
+    fn assert_compiles_to_the_checked_in_artifacts(name: &str, n_public: usize) {
+        let compiled = compile(&circuits().join(format!("{}.circom", name))).unwrap();
+
+        assert_eq!(compiled.n_public(), n_public, "{}", name);
+        assert_eq!(
+            formats::r1cs(&compiled),
+            fs::read(circuits().join(format!("{}.r1cs", name))).unwrap(),
+            "{}",
+            name
+        );
+        assert_eq!(
+            formats::sym(&compiled),
+            fs::read_to_string(circuits().join(format!("{}.sym", name))).unwrap(),
+            "{}",
+            name
+        );
+    }
+
+    #[test]
+    fn circuits_compile_to_the_checked_in_artifacts() {
+        assert_compiles_to_the_checked_in_artifacts("swap", 12);
+        assert_compiles_to_the_checked_in_artifacts("swap_exact_out", 12);
+        assert_compiles_to_the_checked_in_artifacts("route", 20);
+        assert_compiles_to_the_checked_in_artifacts("range_swap", 10);
+        assert_compiles_to_the_checked_in_artifacts("stable_swap", 13);
+    }
+
+    #[test]
+    fn swap_witness_matches_the_checked_in_proof() {
+        let compiled = compile(&circuits().join("swap.circom")).unwrap();
+        let mut inputs = vec![
+            ("privateInputAmount", 100_000),
+            ("privateMinReceived", 99_000),
+            ("privateNullifierSecret", 12_345_678_901_234_567_890),
+            ("publicBalanceX", 1_100_000),
+            ("publicBalanceY", 1_900_000),
+            ("isSwapXtoY", 1),
+            ("feeRate", 30),
+            ("protocolFeeRate", 2_000),
+            ("userHash", 987_654_321),
+            ("expirySlot", 1_000),
+        ];
+        let witness = witness(&compiled, &inputs).unwrap();
+
+        let proof: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(
+                circuits().join("../anchor/programs/darklake/src/tests/zk_proof_output.json"),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let public: Vec<BigUint> = proof["publicInputs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|signal| {
+                let bytes: Vec<u8> = serde_json::from_value(signal.clone()).unwrap();
+                BigUint::from_bytes_be(&bytes)
+            })
+            .collect();
+        assert_eq!(witness[1..=compiled.n_public()], public[..]);
+
+        // Slippage check
+        inputs[1].1 = 200_000;
+        assert!(matches!(
+            self::witness(&compiled, &inputs),
+            Err(Failure::Unsatisfied(_))
+        ));
+    }
+
+    #[test]
+    fn witnesses_satisfy_the_constraints_circom_compiled() {
+        let compiled = compile(&upstream().join("swap.circom")).unwrap();
+        let ours = sym_wires(&formats::sym(&compiled));
+        let theirs = sym_wires(&fs::read_to_string(upstream().join("swap.sym")).unwrap());
+        assert_eq!(
+            ours.keys().collect::<std::collections::BTreeSet<_>>(),
+            theirs.keys().collect()
+        );
+        let constraints = r1cs_constraints(&fs::read(upstream().join("swap.r1cs")).unwrap());
+        let prime = bn254_prime();
+
+        for (inputs, outputs) in [
+            (
+                [100_000, 99_000, 1_100_000, 1_900_000, 1],
+                [1_200_000u64, 1_741_666, 158_334],
+            ),
+            (
+                [50_000, 0, 1_100_000, 1_900_000, 0],
+                [1_071_794, 1_950_000, 28_206],
+            ),
+        ] {
+            let names = [
+                "privateInputAmount",
+                "privateMinReceived",
+                "publicBalanceX",
+                "publicBalanceY",
+                "isSwapXtoY",
+            ];
+            let inputs: Vec<(&str, u64)> = names.into_iter().zip(inputs).collect();
+            let witness = witness(&compiled, &inputs).unwrap();
+            let outputs: Vec<BigUint> = outputs.into_iter().map(BigUint::from).collect();
+            assert_eq!(witness[1..4], outputs[..]);
+
+            // Our witness laid out on the wires circom kept
+            let mut upstream_witness = vec![BigUint::from(0u8); compiled.wires.len()];
+            upstream_witness[0] = BigUint::from(1u8);
+            for (name, wire) in &theirs {
+                if *wire >= 0 {
+                    upstream_witness[*wire as usize] = witness[ours[name] as usize].clone();
+                }
+            }
+            let eval = |lc: &Lc| {
+                lc.iter().fold(BigUint::from(0u8), |sum, (wire, coeff)| {
+                    sum + coeff * &upstream_witness[*wire]
+                }) % &prime
+            };
+            for (index, [a, b, c]) in constraints.iter().enumerate() {
+                assert_eq!(
+                    eval(a) * eval(b) % &prime,
+                    eval(c),
+                    "circom constraint {} unsatisfied",
+                    index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn computes_the_witness_in_circom_wire_order() {
+        let compiled = compile_source("multiplier", MULTIPLIER).unwrap();
+        assert_eq!(compiled.n_public(), 2);
+
+        // Wires: 1, c, a, b
+        let witness = witness(&compiled, &[("a", 3), ("b", 11)]).unwrap();
+        let expected: Vec<BigUint> = [1u8, 33, 3, 11].into_iter().map(BigUint::from).collect();
+        assert_eq!(witness, expected);
+
+        let asserted = MULTIPLIER.replace("c <== a * b;", "c <== a * b;\n    a === 3;");
+        let compiled = compile_source("asserted", &asserted).unwrap();
+        assert!(matches!(
+            self::witness(&compiled, &[("a", 4), ("b", 11)]),
+            Err(Failure::Unsatisfied(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_circuits_outside_the_supported_subset() {
+        let cubic = MULTIPLIER.replace("c <== a * b;", "c <== a * b * b;");
+        assert!(matches!(
+            compile_source("cubic", &cubic),
+            Err(Error::Compile(_))
+        ));
+
+        let unassigned = MULTIPLIER.replace("c <== a * b;", "");
+        assert!(matches!(
+            compile_source("unassigned", &unassigned),
+            Err(Error::Compile(_))
+        ));
+
+        assert!(matches!(
+            compile_source("broken", "template {"),
+            Err(Error::Parse { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+//! Builds the witness calculator wasm of a circuit: the runtime in `runtime/` with the witness
+//! program embedded. Needs cargo and the `wasm32-unknown-unknown` target.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::program::Program;
+use crate::{write, Error, Result};
+
+const TARGET: &str = "wasm32-unknown-unknown";
+
+fn runtime_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("runtime")
+}
+
+/// Builds the wasm to `out`, using `build_dir` for the program and the cargo target dir.
+pub fn build(program: &Program, build_dir: &Path, out: &Path) -> Result<()> {
+    let program_path = build_dir.join("witness_program.bin");
+    write(&program_path, program.to_bytes())?;
+    let target_dir = build_dir.join("target");
+
+    let status = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+        .args(["build", "--release", "--target", TARGET, "--manifest-path"])
+        .arg(runtime_dir().join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .env("WITNESS_PROGRAM", &program_path)
+        .status()
+        .map_err(|err| Error::Io(format!("failed to run cargo: {}", err)))?;
+    if !status.success() {
+        return Err(Error::Io(format!(
+            "building the witness runtime failed: {}",
+            status
+        )));
+    }
+
+    let wasm = target_dir
+        .join(TARGET)
+        .join("release")
+        .join("witness_runtime.wasm");
+    let bytes = fs::read(&wasm).map_err(|err| Error::Io(format!("{}: {}", wasm.display(), err)))?;
+    write(out, bytes)
+}
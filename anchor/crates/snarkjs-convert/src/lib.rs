@@ -0,0 +1,276 @@
+//! Converts the JSON files written by snarkjs into the byte layout `groth16-solana` expects:
+//! 32 byte big-endian field elements, G1 points as `x || y`, G2 points as
+//! `x.c1 || x.c0 || y.c1 || y.c0`, and `pi_a` negated so the verifier can check a single
+//! pairing product.
+
+use std::fmt;
+
+use groth16_solana::groth16::Groth16Verifyingkey;
+use num_bigint::BigUint;
+use serde_json::Value;
+
+#[cfg(test)]
+mod tests;
+
+/// Length of a field element.
+pub const FIELD_LEN: usize = 32;
+/// Length of a G1 point.
+pub const G1_LEN: usize = 64;
+/// Length of a G2 point.
+pub const G2_LEN: usize = 128;
+
+/// Modulus of the BN254 base field, the coordinates of the curve points.
+const BASE_FIELD_MODULUS: &str =
+    "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+/// Modulus of the BN254 scalar field, the public signals.
+const SCALAR_FIELD_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The file is not valid JSON.
+    Json(String),
+    /// A field is missing or not of the expected shape.
+    Field(String),
+    /// A value is not a decimal number.
+    Number(String),
+    /// A value is not below the modulus of its field.
+    OutOfField(String),
+    /// The file is for another proving system or curve.
+    Unsupported(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Json(err) => write!(f, "invalid JSON: {}", err),
+            Error::Field(field) => write!(f, "missing or malformed field `{}`", field),
+            Error::Number(value) => write!(f, "`{}` is not a decimal number", value),
+            Error::OutOfField(value) => write!(f, "`{}` is not a field element", value),
+            Error::Unsupported(what) => write!(f, "unsupported {}", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A proof in the form taken by `Groth16Verifier::new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    /// `pi_a`, negated.
+    pub proof_a: [u8; G1_LEN],
+    pub proof_b: [u8; G2_LEN],
+    pub proof_c: [u8; G1_LEN],
+}
+
+/// A verifying key, owning its `ic` points unlike `Groth16Verifyingkey`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyingKey {
+    /// Number of public signals, one less than the number of `ic` points.
+    pub nr_pubinputs: usize,
+    pub alpha_g1: [u8; G1_LEN],
+    pub beta_g2: [u8; G2_LEN],
+    pub gamma_g2: [u8; G2_LEN],
+    pub delta_g2: [u8; G2_LEN],
+    pub ic: Vec<[u8; G1_LEN]>,
+}
+
+impl VerifyingKey {
+    /// The key in the form taken by `Groth16Verifier::new`.
+    pub fn groth16(&self) -> Groth16Verifyingkey<'_> {
+        Groth16Verifyingkey {
+            nr_pubinputs: self.nr_pubinputs,
+            vk_alpha_g1: self.alpha_g1,
+            vk_beta_g2: self.beta_g2,
+            vk_gamme_g2: self.gamma_g2,
+            vk_delta_g2: self.delta_g2,
+            vk_ic: &self.ic,
+        }
+    }
+
+    /// `alpha_g1 || beta_g2 || gamma_g2 || delta_g2 || ic`, the layout the admin uploads to a
+    /// `VerifyingKey` account.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(G1_LEN + 3 * G2_LEN + self.ic.len() * G1_LEN);
+        data.extend_from_slice(&self.alpha_g1);
+        data.extend_from_slice(&self.beta_g2);
+        data.extend_from_slice(&self.gamma_g2);
+        data.extend_from_slice(&self.delta_g2);
+        for ic in &self.ic {
+            data.extend_from_slice(ic);
+        }
+        data
+    }
+
+    /// Rust source declaring the key as a `VERIFYINGKEY` constant.
+    pub fn to_rust_source(&self) -> String {
+        let mut source = String::from("use groth16_solana::groth16::Groth16Verifyingkey;\n\n");
+        source.push_str("pub const VERIFYINGKEY: Groth16Verifyingkey =  Groth16Verifyingkey {\n");
+        source.push_str(&format!("\tnr_pubinputs: {},\n\n", self.nr_pubinputs));
+        for (name, bytes) in [
+            ("vk_alpha_g1", &self.alpha_g1[..]),
+            ("vk_beta_g2", &self.beta_g2[..]),
+            ("vk_gamme_g2", &self.gamma_g2[..]),
+            ("vk_delta_g2", &self.delta_g2[..]),
+        ] {
+            source.push_str(&format!("\t{}: [\n", name));
+            push_rows(&mut source, bytes, "\t\t");
+            source.push_str("\t],\n\n");
+        }
+        source.push_str("\tvk_ic: &[\n");
+        for ic in &self.ic {
+            source.push_str("\t\t[\n");
+            push_rows(&mut source, ic, "\t\t\t");
+            source.push_str("\t\t],\n");
+        }
+        source.push_str("\t]\n};");
+        source
+    }
+}
+
+/// Parses a snarkjs `proof.json`.
+pub fn parse_proof(json: &str) -> Result<Proof> {
+    let json = parse_json(json)?;
+    check_groth16_bn128(&json)?;
+
+    let pi_a = g1(&json["pi_a"], "pi_a")?;
+    let pi_b = g2(&json["pi_b"], "pi_b")?;
+    let pi_c = g1(&json["pi_c"], "pi_c")?;
+
+    Ok(Proof {
+        proof_a: negate_g1(&pi_a),
+        proof_b: pi_b,
+        proof_c: pi_c,
+    })
+}
+
+/// Parses a snarkjs `public.json`, the public signals in the order of the circuit.
+pub fn parse_public_inputs(json: &str) -> Result<Vec<[u8; FIELD_LEN]>> {
+    let json = parse_json(json)?;
+    let signals = json
+        .as_array()
+        .ok_or_else(|| Error::Field("public signals".to_string()))?;
+    signals
+        .iter()
+        .map(|signal| field(signal, "public signals", SCALAR_FIELD_MODULUS))
+        .collect()
+}
+
+/// Parses a snarkjs `verification_key.json`.
+pub fn parse_verifying_key(json: &str) -> Result<VerifyingKey> {
+    let json = parse_json(json)?;
+    check_groth16_bn128(&json)?;
+
+    let nr_pubinputs = json["nPublic"]
+        .as_u64()
+        .ok_or_else(|| Error::Field("nPublic".to_string()))? as usize;
+    let ic = json["IC"]
+        .as_array()
+        .ok_or_else(|| Error::Field("IC".to_string()))?
+        .iter()
+        .map(|point| g1(point, "IC"))
+        .collect::<Result<Vec<_>>>()?;
+    if ic.len() != nr_pubinputs + 1 {
+        return Err(Error::Field("IC".to_string()));
+    }
+
+    Ok(VerifyingKey {
+        nr_pubinputs,
+        alpha_g1: g1(&json["vk_alpha_1"], "vk_alpha_1")?,
+        beta_g2: g2(&json["vk_beta_2"], "vk_beta_2")?,
+        gamma_g2: g2(&json["vk_gamma_2"], "vk_gamma_2")?,
+        delta_g2: g2(&json["vk_delta_2"], "vk_delta_2")?,
+        ic,
+    })
+}
+
+fn parse_json(json: &str) -> Result<Value> {
+    serde_json::from_str(json).map_err(|err| Error::Json(err.to_string()))
+}
+
+fn check_groth16_bn128(json: &Value) -> Result<()> {
+    if json["protocol"] != "groth16" {
+        return Err(Error::Unsupported(format!("protocol {}", json["protocol"])));
+    }
+    if json["curve"] != "bn128" {
+        return Err(Error::Unsupported(format!("curve {}", json["curve"])));
+    }
+    Ok(())
+}
+
+/// A decimal string below `modulus`, big-endian.
+fn field(value: &Value, name: &str, modulus: &str) -> Result<[u8; FIELD_LEN]> {
+    let value = value.as_str().ok_or_else(|| Error::Field(name.to_string()))?;
+    let number = BigUint::parse_bytes(value.as_bytes(), 10)
+        .ok_or_else(|| Error::Number(value.to_string()))?;
+    if number >= modulus_of(modulus) {
+        return Err(Error::OutOfField(value.to_string()));
+    }
+    Ok(to_field(&number))
+}
+
+/// Affine point given by snarkjs in projective form `[x, y, "1"]`.
+fn g1(value: &Value, name: &str) -> Result<[u8; G1_LEN]> {
+    let coordinates = value.as_array().ok_or_else(|| Error::Field(name.to_string()))?;
+    if coordinates.len() != 3 || coordinates[2] != "1" {
+        return Err(Error::Field(name.to_string()));
+    }
+    let mut point = [0u8; G1_LEN];
+    point[..32].copy_from_slice(&field(&coordinates[0], name, BASE_FIELD_MODULUS)?);
+    point[32..].copy_from_slice(&field(&coordinates[1], name, BASE_FIELD_MODULUS)?);
+    Ok(point)
+}
+
+/// Affine point given by snarkjs as `[[x.c0, x.c1], [y.c0, y.c1], ["1", "0"]]`, with the
+/// limbs of each coordinate swapped in the output.
+fn g2(value: &Value, name: &str) -> Result<[u8; G2_LEN]> {
+    let coordinates = value.as_array().ok_or_else(|| Error::Field(name.to_string()))?;
+    if coordinates.len() != 3 || coordinates[2] != serde_json::json!(["1", "0"]) {
+        return Err(Error::Field(name.to_string()));
+    }
+    let mut point = [0u8; G2_LEN];
+    for (i, coordinate) in coordinates[..2].iter().enumerate() {
+        let limbs = coordinate
+            .as_array()
+            .filter(|limbs| limbs.len() == 2)
+            .ok_or_else(|| Error::Field(name.to_string()))?;
+        let offset = i * 2 * FIELD_LEN;
+        point[offset..offset + FIELD_LEN]
+            .copy_from_slice(&field(&limbs[1], name, BASE_FIELD_MODULUS)?);
+        point[offset + FIELD_LEN..offset + 2 * FIELD_LEN]
+            .copy_from_slice(&field(&limbs[0], name, BASE_FIELD_MODULUS)?);
+    }
+    Ok(point)
+}
+
+/// `(x, q - y)`, leaving the point at infinity as is.
+fn negate_g1(point: &[u8; G1_LEN]) -> [u8; G1_LEN] {
+    let y = BigUint::from_bytes_be(&point[32..]);
+    if y == BigUint::default() {
+        return *point;
+    }
+    let mut negated = *point;
+    negated[32..].copy_from_slice(&to_field(&(modulus_of(BASE_FIELD_MODULUS) - y)));
+    negated
+}
+
+fn modulus_of(modulus: &str) -> BigUint {
+    BigUint::parse_bytes(modulus.as_bytes(), 10).expect("modulus is a decimal number")
+}
+
+fn to_field(number: &BigUint) -> [u8; FIELD_LEN] {
+    let bytes = number.to_bytes_be();
+    let mut field = [0u8; FIELD_LEN];
+    field[FIELD_LEN - bytes.len()..].copy_from_slice(&bytes);
+    field
+}
+
+/// Bytes as rows of 32 comma-separated values.
+fn push_rows(source: &mut String, bytes: &[u8], indent: &str) {
+    for row in bytes.chunks(FIELD_LEN) {
+        let row = row.iter().map(u8::to_string).collect::<Vec<_>>().join(",");
+        source.push_str(&format!("{}{},\n", indent, row));
+    }
+}
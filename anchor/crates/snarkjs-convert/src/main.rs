@@ -0,0 +1,50 @@
+//! snarkjs-convert proof <proof.json> <public.json>
+//!     Prints the proof and public signals as the byte arrays of `zk_proof_output.json`.
+//! snarkjs-convert vk <verification_key.json>
+//!     Prints a Rust source file declaring the key as `VERIFYINGKEY`.
+//! snarkjs-convert vk-bytes <verification_key.json> <out>
+//!     Writes the key in the layout uploaded with `write_verifying_key`.
+
+use std::{env, fs, process};
+
+use serde_json::json;
+use snarkjs_convert::{parse_proof, parse_public_inputs, parse_verifying_key};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["proof", proof, public] => proof_json(proof, public).map(|json| println!("{}", json)),
+        ["vk", key] => read(key)
+            .and_then(|key| parse_verifying_key(&key).map_err(|err| err.to_string()))
+            .map(|key| println!("{}", key.to_rust_source())),
+        ["vk-bytes", key, out] => read(key)
+            .and_then(|key| parse_verifying_key(&key).map_err(|err| err.to_string()))
+            .and_then(|key| fs::write(out, key.to_bytes()).map_err(|err| err.to_string())),
+        _ => Err("usage: snarkjs-convert proof <proof.json> <public.json> | \
+                  vk <verification_key.json> | vk-bytes <verification_key.json> <out>"
+            .to_string()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn proof_json(proof: &str, public: &str) -> Result<String, String> {
+    let proof = parse_proof(&read(proof)?).map_err(|err| err.to_string())?;
+    let public_inputs = parse_public_inputs(&read(public)?).map_err(|err| err.to_string())?;
+    let json = json!({
+        "pi_a": proof.proof_a.to_vec(),
+        "pi_b": proof.proof_b.to_vec(),
+        "pi_c": proof.proof_c.to_vec(),
+        "publicInputs": public_inputs,
+    });
+    serde_json::to_string_pretty(&json).map_err(|err| err.to_string())
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use crate::{parse_proof, parse_public_inputs, parse_verifying_key, Error};
+    use groth16_solana::groth16::Groth16Verifier;
+    use serde_json::Value;
+
+    const PROOF: &str = include_str!("fixtures/proof.json");
+    const PUBLIC: &str = include_str!("fixtures/public.json");
+    const VERIFICATION_KEY: &str = include_str!("../../../../../circuits/verification_key.json");
+    /// The same proof, converted by `groth16.spec.ts`.
+    const CONVERTED: &str =
+        include_str!("../../../../programs/darklake/src/tests/zk_proof_output.json");
+
+    fn converted_bytes(name: &str) -> Vec<u8> {
+        let json: Value = serde_json::from_str(CONVERTED).unwrap();
+        json[name]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|byte| byte.as_u64().unwrap() as u8)
+            .collect()
+    }
+
+    #[test]
+    fn proof_matches_the_typescript_conversion() {
+        let proof = parse_proof(PROOF).unwrap();
+
+        assert_eq!(proof.proof_a.to_vec(), converted_bytes("pi_a"));
+        assert_eq!(proof.proof_b.to_vec(), converted_bytes("pi_b"));
+        assert_eq!(proof.proof_c.to_vec(), converted_bytes("pi_c"));
+    }
+
+    #[test]
+    fn public_inputs_match_the_typescript_conversion() {
+        let public_inputs = parse_public_inputs(PUBLIC).unwrap();
+        let json: Value = serde_json::from_str(CONVERTED).unwrap();
+        let expected: Vec<[u8; 32]> = serde_json::from_value(json["publicInputs"].clone()).unwrap();
+
+        assert_eq!(public_inputs, expected);
+    }
+
+    #[test]
+    fn converted_proof_verifies_against_the_converted_key() {
+        let proof = parse_proof(PROOF).unwrap();
+        let public_inputs: [[u8; 32]; 12] = parse_public_inputs(PUBLIC).unwrap().try_into().unwrap();
+        let key = parse_verifying_key(VERIFICATION_KEY).unwrap();
+        let key = key.groth16();
+
+        let mut verifier = Groth16Verifier::new(
+            &proof.proof_a,
+            &proof.proof_b,
+            &proof.proof_c,
+            &public_inputs,
+            &key,
+        )
+        .unwrap();
+        assert!(verifier.verify().unwrap());
+
+        let mut tampered = public_inputs;
+        tampered[0][31] ^= 1;
+        let mut verifier = Groth16Verifier::new(
+            &proof.proof_a,
+            &proof.proof_b,
+            &proof.proof_c,
+            &tampered,
+            &key,
+        )
+        .unwrap();
+        assert!(verifier.verify().is_err());
+    }
+
+    #[test]
+    fn key_bytes_follow_the_account_layout() {
+        let key = parse_verifying_key(VERIFICATION_KEY).unwrap();
+        let bytes = key.to_bytes();
+
+        assert_eq!(key.ic.len(), key.nr_pubinputs + 1);
+        assert_eq!(bytes.len(), 64 + 3 * 128 + key.ic.len() * 64);
+        assert_eq!(bytes[..64], key.alpha_g1);
+        assert_eq!(bytes[64..192], key.beta_g2);
+        assert_eq!(bytes[448..512], key.ic[0]);
+    }
+
+    #[test]
+    fn rust_source_holds_the_key_bytes() {
+        let key = parse_verifying_key(VERIFICATION_KEY).unwrap();
+        let source = key.to_rust_source();
+
+        assert!(source.starts_with("use groth16_solana::groth16::Groth16Verifyingkey;"));
+        assert!(source.contains(&format!("\tnr_pubinputs: {},", key.nr_pubinputs)));
+        let bytes: Vec<u8> = source
+            .lines()
+            .filter(|line| line.starts_with('\t') && line.trim_start().starts_with(char::is_numeric))
+            .flat_map(|line| line.trim().trim_end_matches(',').split(','))
+            .map(|byte| byte.parse().unwrap())
+            .collect();
+        assert_eq!(bytes, key.to_bytes());
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(matches!(parse_proof("{"), Err(Error::Json(_))));
+        assert!(matches!(
+            parse_proof(&PROOF.replace("groth16", "plonk")),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse_public_inputs(r#"["1", "0x2"]"#),
+            Err(Error::Number(_))
+        ));
+        assert!(matches!(
+            parse_public_inputs(
+                r#"["21888242871839275222246405745257275088548364400416034343698204186575808495617"]"#
+            ),
+            Err(Error::OutOfField(_))
+        ));
+        assert!(matches!(
+            parse_verifying_key(&VERIFICATION_KEY.replace("\"nPublic\": 12", "\"nPublic\": 13")),
+            Err(Error::Field(_))
+        ));
+    }
+}
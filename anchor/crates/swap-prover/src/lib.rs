@@ -0,0 +1,242 @@
+//! Proves swaps natively: the witness is computed by running the circom generated wasm in
+//! wasmer and the proof is built with arkworks from the snarkjs `zkey`, so no JavaScript
+//! toolchain is needed. Proofs come out in the encoding `Groth16Verifier` expects, `pi_a`
+//! already negated.
+
+use std::{fmt, fs::File, io::BufReader, path::Path};
+
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_circom::{CircomCircuit, CircomConfig, CircomReduction};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Groth16, ProvingKey};
+use ark_relations::gr1cs::{ConstraintSynthesizer, ConstraintSystem};
+use num_bigint::BigInt;
+
+#[cfg(test)]
+mod tests;
+
+/// Length of a field element.
+pub const FIELD_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// An artifact could not be read.
+    Artifact(String),
+    /// The wasm failed to compute a witness, usually because an assertion of the circuit does
+    /// not hold for the inputs.
+    Witness(String),
+    /// Proving failed.
+    Prove(String),
+    /// The witness does not satisfy the constraints, the proof would be rejected.
+    Unsatisfied,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Artifact(err) => write!(f, "failed to load artifact: {}", err),
+            Error::Witness(err) => write!(f, "failed to compute witness: {}", err),
+            Error::Prove(err) => write!(f, "failed to prove: {}", err),
+            Error::Unsatisfied => write!(f, "inputs do not satisfy the circuit"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Inputs of the swap circuit, `circuits/swap.circom`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SwapInputs {
+    pub private_input_amount: u64,
+    pub private_min_received: u64,
+    /// Random per swap, big-endian. Reusing one makes the swap fail as a replay.
+    pub private_nullifier_secret: [u8; FIELD_LEN],
+    pub public_balance_x: u64,
+    pub public_balance_y: u64,
+    pub is_swap_x_to_y: bool,
+    /// Swap fee in basis points.
+    pub fee_rate: u16,
+    /// Protocol share of the swap fee in basis points.
+    pub protocol_fee_rate: u16,
+    /// `sha256(user || destination)` with the top byte cleared, see `getUserHash`.
+    pub user_hash: [u8; FIELD_LEN],
+    /// Last slot the proof can be used in.
+    pub expiry_slot: u64,
+}
+
+impl SwapInputs {
+    /// The inputs keyed by their signal names.
+    pub fn signals(&self) -> Vec<(String, BigInt)> {
+        vec![
+            (
+                "privateInputAmount".to_string(),
+                self.private_input_amount.into(),
+            ),
+            (
+                "privateMinReceived".to_string(),
+                self.private_min_received.into(),
+            ),
+            (
+                "privateNullifierSecret".to_string(),
+                be_bytes_to_bigint(&self.private_nullifier_secret),
+            ),
+            ("publicBalanceX".to_string(), self.public_balance_x.into()),
+            ("publicBalanceY".to_string(), self.public_balance_y.into()),
+            (
+                "isSwapXtoY".to_string(),
+                u8::from(self.is_swap_x_to_y).into(),
+            ),
+            ("feeRate".to_string(), self.fee_rate.into()),
+            ("protocolFeeRate".to_string(), self.protocol_fee_rate.into()),
+            ("userHash".to_string(), be_bytes_to_bigint(&self.user_hash)),
+            ("expirySlot".to_string(), self.expiry_slot.into()),
+        ]
+    }
+}
+
+/// A proof with the public signals it was generated for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapProof {
+    /// `pi_a`, negated.
+    pub proof_a: [u8; 64],
+    pub proof_b: [u8; 128],
+    pub proof_c: [u8; 64],
+    /// Outputs of the circuit followed by its public inputs. `confidential_swap` only takes
+    /// the outputs, rebuilding the rest from the pool and the arguments.
+    pub public_signals: Vec<[u8; FIELD_LEN]>,
+}
+
+/// Loads the circuit artifacts once and proves any number of swaps with them.
+pub struct SwapProver {
+    config: CircomConfig<Fr>,
+    proving_key: ProvingKey<Bn254>,
+}
+
+impl SwapProver {
+    /// Loads `swap.wasm`, `swap.r1cs` and the `zkey` of the trusted setup.
+    pub fn new(
+        wasm: impl AsRef<Path>,
+        r1cs: impl AsRef<Path>,
+        zkey: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let config =
+            CircomConfig::<Fr>::new(wasm, r1cs).map_err(|err| Error::Artifact(err.to_string()))?;
+        let mut zkey = BufReader::new(
+            File::open(zkey.as_ref()).map_err(|err| Error::Artifact(err.to_string()))?,
+        );
+        let (proving_key, _) =
+            ark_circom::read_zkey(&mut zkey).map_err(|err| Error::Artifact(err.to_string()))?;
+        Ok(Self {
+            config,
+            proving_key,
+        })
+    }
+
+    pub fn prove(&mut self, inputs: &SwapInputs) -> Result<SwapProof> {
+        self.prove_signals(inputs.signals())
+    }
+
+    /// Proves inputs given by signal name, for circuits whose inputs differ from `SwapInputs`.
+    pub fn prove_signals(
+        &mut self,
+        inputs: impl IntoIterator<Item = (String, BigInt)>,
+    ) -> Result<SwapProof> {
+        let circuit = self.witness(inputs)?;
+        let public_signals = circuit.get_public_inputs().ok_or(Error::Unsatisfied)?;
+
+        let proof = Groth16::<Bn254, CircomReduction>::create_random_proof_with_reduction(
+            circuit,
+            &self.proving_key,
+            &mut ark_std::rand::thread_rng(),
+        )
+        .map_err(|err| Error::Prove(err.to_string()))?;
+
+        Ok(SwapProof {
+            proof_a: g1_bytes(&(-proof.a.into_group()).into()),
+            proof_b: g2_bytes(&proof.b),
+            proof_c: g1_bytes(&proof.c),
+            public_signals: public_signals.iter().map(field_bytes).collect(),
+        })
+    }
+
+    /// Public signals the circuit computes for the inputs, without proving. Fails like proving
+    /// would when the inputs do not satisfy the circuit.
+    pub fn public_signals(
+        &mut self,
+        inputs: impl IntoIterator<Item = (String, BigInt)>,
+    ) -> Result<Vec<[u8; FIELD_LEN]>> {
+        let circuit = self.witness(inputs)?;
+        let public_signals = circuit.get_public_inputs().ok_or(Error::Unsatisfied)?;
+        Ok(public_signals.iter().map(field_bytes).collect())
+    }
+
+    fn witness(
+        &mut self,
+        inputs: impl IntoIterator<Item = (String, BigInt)>,
+    ) -> Result<CircomCircuit<Fr>> {
+        let inputs = inputs.into_iter().map(|(name, value)| (name, vec![value]));
+        let witness = self
+            .config
+            .wtns
+            .calculate_witness_element::<Fr, _>(&mut self.config.store, inputs, false)
+            .map_err(|err| Error::Witness(err.to_string()))?;
+
+        let mut r1cs = self.config.r1cs.clone();
+        r1cs.wire_mapping = None;
+        let circuit = CircomCircuit {
+            r1cs,
+            witness: Some(witness),
+        };
+
+        // The prover does not check the witness, an unsatisfied one gives a proof that only
+        // fails once submitted.
+        let constraints = ConstraintSystem::<Fr>::new_ref();
+        circuit
+            .clone()
+            .generate_constraints(constraints.clone())
+            .map_err(|err| Error::Prove(err.to_string()))?;
+        if !constraints
+            .is_satisfied()
+            .map_err(|err| Error::Prove(err.to_string()))?
+        {
+            return Err(Error::Unsatisfied);
+        }
+
+        Ok(circuit)
+    }
+}
+
+fn field_bytes<F: PrimeField>(field: &F) -> [u8; FIELD_LEN] {
+    let mut bytes = [0u8; FIELD_LEN];
+    bytes.copy_from_slice(&field.into_bigint().to_bytes_be());
+    bytes
+}
+
+/// `x || y`, the point at infinity as zeros.
+fn g1_bytes(point: &G1Affine) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&field_bytes(&x));
+        bytes[32..].copy_from_slice(&field_bytes(&y));
+    }
+    bytes
+}
+
+/// `x.c1 || x.c0 || y.c1 || y.c0`, the point at infinity as zeros.
+fn g2_bytes(point: &G2Affine) -> [u8; 128] {
+    let mut bytes = [0u8; 128];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&field_bytes(&x.c1));
+        bytes[32..64].copy_from_slice(&field_bytes(&x.c0));
+        bytes[64..96].copy_from_slice(&field_bytes(&y.c1));
+        bytes[96..].copy_from_slice(&field_bytes(&y.c0));
+    }
+    bytes
+}
+
+fn be_bytes_to_bigint(bytes: &[u8]) -> BigInt {
+    BigInt::from_bytes_be(num_bigint::Sign::Plus, bytes)
+}
@@ -0,0 +1,370 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use crate::{SwapInputs, SwapProver};
+    use anchor_lang::solana_program::poseidon::{hash, Endianness, Parameters};
+    use ark_bn254::Fr;
+    use ark_circom::{circom::Constraints, CircomConfig};
+    use ark_ff::{BigInteger, Field, One, PrimeField};
+    use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+    use darklake::model::{self, ModelInputs};
+    use darklake::tick_math::{compute_swap_step, MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64};
+    use num_bigint::BigInt;
+
+    /// Number of random cases, `DIFFERENTIAL_CASES` overrides it.
+    const CASES: usize = 2_000;
+
+    fn field(value: u128) -> [u8; 32] {
+        let mut field = [0u8; 32];
+        field[16..].copy_from_slice(&value.to_be_bytes());
+        field
+    }
+
+    /// `Poseidon(secret)` as the program's `poseidon` syscall computes it.
+    fn nullifier(secret: &[u8; 32]) -> [u8; 32] {
+        hash(Parameters::Bn254X5, Endianness::BigEndian, secret)
+            .unwrap()
+            .to_bytes()
+    }
+
+    /// A value of random bit length, so small reserves and amounts come up as often as large ones.
+    fn amount(rng: &mut StdRng) -> u64 {
+        rng.gen::<u64>() >> rng.gen_range(0..64)
+    }
+
+    fn fee(rng: &mut StdRng) -> u16 {
+        match rng.gen_range(0..10) {
+            0 => rng.gen(),
+            1 => 10_000,
+            _ => rng.gen_range(0..=1_000),
+        }
+    }
+
+    fn random_inputs(rng: &mut StdRng) -> SwapInputs {
+        let mut private_nullifier_secret = [0u8; 32];
+        rng.fill(&mut private_nullifier_secret[1..]);
+        let mut user_hash = [0u8; 32];
+        rng.fill(&mut user_hash[1..]);
+
+        SwapInputs {
+            private_input_amount: amount(rng),
+            // Half the time 0, otherwise random so slippage checks fail often enough
+            private_min_received: if rng.gen() { 0 } else { amount(rng) },
+            private_nullifier_secret,
+            public_balance_x: amount(rng),
+            public_balance_y: amount(rng),
+            is_swap_x_to_y: rng.gen(),
+            fee_rate: fee(rng),
+            protocol_fee_rate: fee(rng),
+            user_hash,
+            expiry_slot: rng.gen(),
+        }
+    }
+
+    /// Runs the compiled circuit and `darklake::model` on random inputs and checks they accept
+    /// the same ones with the same outputs, and that the nullifier is the Poseidon hash the
+    /// program computes.
+    #[test]
+    fn circuit_matches_the_model() {
+        let circuits = format!("{}/../../../circuits", env!("CARGO_MANIFEST_DIR"));
+        let mut prover = SwapProver::new(
+            format!("{}/swap_js/swap.wasm", circuits),
+            format!("{}/swap.r1cs", circuits),
+            format!("{}/swap_0001.zkey", circuits),
+        )
+        .unwrap();
+        let cases = std::env::var("DIFFERENTIAL_CASES")
+            .map(|cases| cases.parse().unwrap())
+            .unwrap_or(CASES);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for case in 0..cases {
+            let inputs = random_inputs(&mut rng);
+            let expected = model::swap(&ModelInputs {
+                amount_in: inputs.private_input_amount,
+                min_received: inputs.private_min_received,
+                reserve_x: inputs.public_balance_x,
+                reserve_y: inputs.public_balance_y,
+                is_swap_x_to_y: inputs.is_swap_x_to_y,
+                fee_bps: inputs.fee_rate,
+                protocol_fee_bps: inputs.protocol_fee_rate,
+            });
+            let signals = prover.public_signals(inputs.signals());
+
+            match (expected, signals) {
+                (Ok(outputs), Ok(signals)) => {
+                    assert_eq!(
+                        signals.len(),
+                        12,
+                        "artifacts predate the current swap.circom"
+                    );
+                    // newBalanceX, newBalanceY, amountReceived, protocolFee, then the nullifier
+                    // and the public inputs
+                    assert_eq!(
+                        signals[..4],
+                        [
+                            field(outputs.new_balance_x),
+                            field(outputs.new_balance_y),
+                            field(outputs.amount_received),
+                            field(outputs.protocol_fee),
+                        ],
+                        "case {}: {:?}",
+                        case,
+                        inputs
+                    );
+                    assert_eq!(
+                        signals[4],
+                        nullifier(&inputs.private_nullifier_secret),
+                        "case {}: {:?}",
+                        case,
+                        inputs
+                    );
+                    assert_eq!(
+                        signals[5..],
+                        [
+                            field(inputs.public_balance_x as u128),
+                            field(inputs.public_balance_y as u128),
+                            field(inputs.is_swap_x_to_y as u128),
+                            field(inputs.fee_rate as u128),
+                            field(inputs.protocol_fee_rate as u128),
+                            inputs.user_hash,
+                            field(inputs.expiry_slot as u128),
+                        ],
+                        "case {}: {:?}",
+                        case,
+                        inputs
+                    );
+                }
+                (Err(_), Err(_)) => {}
+                (expected, signals) => panic!(
+                    "case {}: {:?}, model {:?}, circuit {:?}",
+                    case, inputs, expected, signals
+                ),
+            }
+        }
+    }
+
+    fn circuits() -> String {
+        format!("{}/../../../circuits", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    /// Runs the compiled range swap circuit and the program's `compute_swap_step` on random
+    /// swaps staying within the liquidity of the current price, and checks they agree on the
+    /// amount received.
+    #[test]
+    fn range_swap_circuit_matches_a_swap_step() {
+        let mut prover = SwapProver::new(
+            format!("{}/range_swap_js/range_swap.wasm", circuits()),
+            format!("{}/range_swap.r1cs", circuits()),
+            format!("{}/range_swap_0001.zkey", circuits()),
+        )
+        .unwrap();
+        // A tenth of the swap cases, the witnesses of this circuit are slower to check
+        let cases = std::env::var("DIFFERENTIAL_CASES")
+            .map(|cases| cases.parse().unwrap())
+            .unwrap_or(CASES / 10);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut compared = 0;
+
+        for case in 0..cases {
+            let amount_in = amount(&mut rng);
+            let sqrt_price_x64 = (rng.gen::<u128>() >> rng.gen_range(0..128))
+                .clamp(MIN_SQRT_PRICE_X64, MAX_SQRT_PRICE_X64);
+            // The circuit only takes liquidity below 2^80
+            let liquidity = (rng.gen::<u128>() >> 48 >> rng.gen_range(0..80)).max(1);
+            let is_swap_x_to_y: bool = rng.gen();
+            let fee_rate = fee(&mut rng).min(9_999);
+
+            // Towards the end of the price range, so the step only stops there if the input
+            // is large enough to move the price that far
+            let target = if is_swap_x_to_y {
+                MIN_SQRT_PRICE_X64
+            } else {
+                MAX_SQRT_PRICE_X64
+            };
+            let step = compute_swap_step(
+                sqrt_price_x64,
+                target,
+                liquidity,
+                amount_in as u128,
+                fee_rate,
+            );
+            let signals = prover.public_signals(vec![
+                ("privateInputAmount".to_string(), BigInt::from(amount_in)),
+                ("privateMinReceived".to_string(), BigInt::from(0u8)),
+                ("privateNullifierSecret".to_string(), BigInt::from(case)),
+                ("sqrtPriceX64".to_string(), BigInt::from(sqrt_price_x64)),
+                ("liquidity".to_string(), BigInt::from(liquidity)),
+                ("isSwapXtoY".to_string(), BigInt::from(is_swap_x_to_y as u8)),
+                ("feeRate".to_string(), BigInt::from(fee_rate)),
+                (
+                    "protocolFeeRate".to_string(),
+                    BigInt::from(fee(&mut rng).min(10_000)),
+                ),
+                ("userHash".to_string(), BigInt::from(0u8)),
+                ("expirySlot".to_string(), BigInt::from(0u8)),
+            ]);
+
+            match (step, signals) {
+                // Past the range, the program moves on to the next tick instead
+                (Ok(step), _) if step.sqrt_price_next_x64 == target => {}
+                (Ok(step), Ok(signals)) => {
+                    compared += 1;
+                    // amountIn, amountReceived, then the nullifier and the public inputs
+                    assert_eq!(
+                        signals[..2],
+                        [field(amount_in as u128), field(step.amount_out)],
+                        "case {}: {} in at {} with {} liquidity, {:?}",
+                        case,
+                        amount_in,
+                        sqrt_price_x64,
+                        liquidity,
+                        step
+                    );
+                }
+                // The program cannot pay out more than a u64 either
+                (Ok(step), Err(_)) if step.amount_out > u64::MAX as u128 => {}
+                (step, signals) => panic!(
+                    "case {}: {} in at {} with {} liquidity, x to y {}, step {:?}, circuit {:?}",
+                    case, amount_in, sqrt_price_x64, liquidity, is_swap_x_to_y, step, signals
+                ),
+            }
+        }
+        // Large inputs run past the end of the price range, most cases must not
+        assert!(
+            compared * 4 > cases,
+            "only {} of {} cases compared",
+            compared,
+            cases
+        );
+    }
+
+    /// Wire of every signal of `swap.sym` whose name starts with `prefix`, by the rest of its
+    /// name.
+    fn wires(prefix: &str) -> HashMap<String, usize> {
+        std::fs::read_to_string(format!("{}/swap.sym", circuits()))
+            .unwrap()
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.splitn(4, ',').collect();
+                let name = fields[3].strip_prefix(prefix)?;
+                Some((name.to_string(), fields[1].parse().unwrap()))
+            })
+            .collect()
+    }
+
+    /// Indices of the constraints among `constraints` the witness does not satisfy.
+    fn unsatisfied(constraints: &[&Constraints<Fr>], witness: &[Fr]) -> Vec<usize> {
+        let eval = |lc: &Vec<(usize, Fr)>| {
+            lc.iter()
+                .map(|(wire, coeff)| witness[*wire] * coeff)
+                .sum::<Fr>()
+        };
+        constraints
+            .iter()
+            .enumerate()
+            .filter(|(_, (a, b, c))| eval(a) * eval(b) != eval(c))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// A prover raising the quotient of `ReciprocalDivision` by one has to wrap the remainder
+    /// around the field. `LessThan(252)` still holds for the wrapped remainder, the range check
+    /// on it must not.
+    #[test]
+    fn division_rejects_a_wrapped_remainder() {
+        let mut config = CircomConfig::<Fr>::new(
+            format!("{}/swap_js/swap.wasm", circuits()),
+            format!("{}/swap.r1cs", circuits()),
+        )
+        .unwrap();
+        let inputs = SwapInputs {
+            private_input_amount: 100_000,
+            private_min_received: 99_000,
+            private_nullifier_secret: field(12_345_678_901_234_567_890),
+            public_balance_x: 1_100_000,
+            public_balance_y: 1_900_000,
+            is_swap_x_to_y: true,
+            fee_rate: 30,
+            protocol_fee_rate: 2_000,
+            user_hash: field(987_654_321),
+            expiry_slot: 1_000,
+        };
+        let mut witness = config
+            .wtns
+            .calculate_witness_element::<Fr, _>(
+                &mut config.store,
+                inputs
+                    .signals()
+                    .into_iter()
+                    .map(|(name, value)| (name, vec![value])),
+                false,
+            )
+            .unwrap();
+
+        // The constraints between the signals of the division computing the output amount
+        let division = wires("main.division.");
+        let division_wires: HashSet<usize> = division.values().copied().chain([0]).collect();
+        let constraints: Vec<&Constraints<Fr>> = config
+            .r1cs
+            .constraints
+            .iter()
+            .filter(|(a, b, c)| {
+                a.iter()
+                    .chain(b)
+                    .chain(c)
+                    .all(|(wire, _)| division_wires.contains(wire))
+            })
+            .collect();
+        assert!(unsatisfied(&constraints, &witness).is_empty());
+
+        // quotient + 1 with remainder - divisor keeps dividend === quotient * divisor + remainder
+        let divisor = witness[division["divisor"]];
+        let quotient = witness[division["quotient"]] + Fr::one();
+        let remainder = witness[division["remainder"]] - divisor;
+        // LessThan(252) compares through the bits of in[0] + 2^252 - in[1]
+        let shifted = remainder + Fr::from(2u8).pow([252]) - divisor;
+        let mut forged = vec![
+            ("quotient".to_string(), quotient),
+            ("remainder".to_string(), remainder),
+            ("lessThan.in[0]".to_string(), remainder),
+            ("lessThan.n2b.in".to_string(), shifted),
+            ("remainderBits.in".to_string(), remainder),
+        ];
+        for (component, value, n) in [
+            ("lessThan.n2b", shifted, 253),
+            ("remainderBits", remainder, 252),
+        ] {
+            let bits = value.into_bigint();
+            forged.extend((0..n).map(|bit| {
+                (
+                    format!("{}.out[{}]", component, bit),
+                    Fr::from(bits.get_bit(bit)),
+                )
+            }));
+        }
+        for (name, value) in forged {
+            witness[division[&name]] = value;
+        }
+
+        // Only the range check on the remainder catches it
+        let rejected = unsatisfied(&constraints, &witness);
+        let remainder_bits: HashSet<usize> = wires("main.division.remainderBits.")
+            .into_values()
+            .chain([0])
+            .collect();
+        assert!(!rejected.is_empty());
+        for index in rejected {
+            let (a, b, c) = constraints[index];
+            assert!(
+                a.iter()
+                    .chain(b)
+                    .chain(c)
+                    .all(|(wire, _)| remainder_bits.contains(wire)),
+                "constraint {} of the division rejected the witness",
+                index
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Error, SwapInputs, SwapProver};
+    use darklake::circuits::{SwapBindings, SwapCircuit, SwapPublicSignals, SWAP_OUTPUTS};
+    use num_bigint::BigInt;
+
+    const VERIFICATION_KEY: &str = include_str!("../../../../../circuits/verification_key.json");
+
+    fn circuits() -> String {
+        format!("{}/../../../circuits", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn prover() -> SwapProver {
+        let circuits = circuits();
+        SwapProver::new(
+            format!("{}/swap_js/swap.wasm", circuits),
+            format!("{}/swap.r1cs", circuits),
+            format!("{}/swap_0001.zkey", circuits),
+        )
+        .unwrap()
+    }
+
+    fn field(value: u64) -> [u8; 32] {
+        let mut field = [0u8; 32];
+        field[24..].copy_from_slice(&value.to_be_bytes());
+        field
+    }
+
+    fn inputs(min_received: u64) -> SwapInputs {
+        let mut private_nullifier_secret = [0u8; 32];
+        private_nullifier_secret[24..]
+            .copy_from_slice(&12_345_678_901_234_567_890u64.to_be_bytes());
+        SwapInputs {
+            private_input_amount: 100_000,
+            private_min_received: min_received,
+            private_nullifier_secret,
+            public_balance_x: 1_100_000,
+            public_balance_y: 1_900_000,
+            is_swap_x_to_y: true,
+            fee_rate: 30,
+            protocol_fee_rate: 2_000,
+            user_hash: field(987_654_321),
+            expiry_slot: 1_000,
+        }
+    }
+
+    /// `Poseidon(privateNullifierSecret)` of `inputs`.
+    fn nullifier() -> [u8; 32] {
+        let nullifier = BigInt::parse_bytes(
+            b"17610922722311195426938483481431943255028223790571250909270476711880232282197",
+            10,
+        )
+        .unwrap();
+        nullifier.to_bytes_be().1.try_into().unwrap()
+    }
+
+    #[test]
+    fn proof_verifies_on_chain() {
+        let inputs = inputs(99_000);
+        let proof = prover().prove(&inputs).unwrap();
+
+        // The layout `confidential_swap` rebuilds from the outputs, the pool and its arguments
+        let circuit = SwapCircuit::ConstantProduct;
+        let outputs = circuit
+            .decode_outputs(&proof.public_signals[..SWAP_OUTPUTS])
+            .unwrap();
+        let bindings = SwapBindings {
+            reserve_x: inputs.public_balance_x,
+            reserve_y: inputs.public_balance_y,
+            is_swap_x_to_y: inputs.is_swap_x_to_y,
+            fee_bps: inputs.fee_rate,
+            protocol_fee_bps: inputs.protocol_fee_rate,
+            user_hash: inputs.user_hash,
+            expiry_slot: inputs.expiry_slot,
+            amp: 0,
+        };
+        let signals = SwapPublicSignals::new(&outputs, &bindings);
+        assert_eq!(
+            signals,
+            SwapPublicSignals {
+                new_balance_x: 1_199_940,
+                new_balance_y: 1_742_103,
+                amount_received: 157_897,
+                protocol_fee: 60,
+                nullifier: nullifier(),
+                reserve_x: 1_100_000,
+                reserve_y: 1_900_000,
+                is_swap_x_to_y: true,
+                fee_bps: 30,
+                protocol_fee_bps: 2_000,
+                user_hash: field(987_654_321),
+                expiry_slot: 1_000,
+            }
+        );
+        assert_eq!(proof.public_signals, signals.to_fields());
+
+        let key = snarkjs_convert::parse_verifying_key(VERIFICATION_KEY).unwrap();
+        let key = key.groth16();
+        assert!(circuit
+            .verify(
+                &proof.proof_a,
+                &proof.proof_b,
+                &proof.proof_c,
+                &signals.to_fields(),
+                &key,
+            )
+            .unwrap());
+
+        // A pool whose reserves moved since the proof was made
+        let moved = SwapPublicSignals {
+            reserve_x: 1_100_001,
+            ..signals
+        };
+        assert!(circuit
+            .verify(
+                &proof.proof_a,
+                &proof.proof_b,
+                &proof.proof_c,
+                &moved.to_fields(),
+                &key,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn proofs_are_randomized() {
+        let mut prover = prover();
+        let first = prover.prove(&inputs(99_000)).unwrap();
+        let second = prover.prove(&inputs(99_000)).unwrap();
+
+        assert_eq!(first.public_signals, second.public_signals);
+        assert_ne!(first.proof_a, second.proof_a);
+    }
+
+    #[test]
+    fn rejects_inputs_the_circuit_does_not_accept() {
+        let result = prover().prove(&inputs(200_000));
+
+        assert!(matches!(
+            result,
+            Err(Error::Witness(_)) | Err(Error::Unsatisfied)
+        ));
+    }
+
+    #[test]
+    fn swap_inputs_use_the_circuit_signal_names() {
+        let mut user_hash = [0u8; 32];
+        user_hash[31] = 7;
+        let inputs = SwapInputs {
+            private_input_amount: 100,
+            is_swap_x_to_y: true,
+            fee_rate: 30,
+            user_hash,
+            expiry_slot: 9,
+            ..Default::default()
+        };
+        let signals = inputs.signals();
+
+        let names: Vec<&str> = signals.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "privateInputAmount",
+                "privateMinReceived",
+                "privateNullifierSecret",
+                "publicBalanceX",
+                "publicBalanceY",
+                "isSwapXtoY",
+                "feeRate",
+                "protocolFeeRate",
+                "userHash",
+                "expirySlot",
+            ]
+        );
+        assert_eq!(signals[0].1, 100.into());
+        assert_eq!(signals[5].1, 1.into());
+        assert_eq!(signals[6].1, 30.into());
+        assert_eq!(signals[8].1, 7.into());
+        assert_eq!(signals[9].1, 9.into());
+    }
+}
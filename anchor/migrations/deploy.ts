@@ -58,7 +58,7 @@ async function createPYUSDWSOLPool(provider: AnchorProvider) {
 
   // Initialize the pool
   const tx = await program.methods
-    .initializePool()
+    .initializePool(30)
     .accounts({
       // @ts-expect-error Anchor is annoying as fuck.
       pool: poolPubkey,
@@ -128,7 +128,7 @@ async function createPYUSDWSOLPool(provider: AnchorProvider) {
 
     // Initialize the pool
     const tx = await program.methods
-      .initializePool()
+      .initializePool(30)
       .accounts({
         pool: poolPubkey,
         tokenMint0: token0,
@@ -307,7 +307,11 @@ async function createPYUSDWSOLPool(provider: AnchorProvider) {
       : [WSOL_AMOUNT, PYUSD_AMOUNT];
 
     const addLiquidityTx = await program.methods
-      .addLiquidity(new anchor.BN(sortedAmount0), new anchor.BN(sortedAmount1))
+      .addLiquidity(
+        new anchor.BN(sortedAmount0),
+        new anchor.BN(sortedAmount1),
+        new anchor.BN(0)
+      )
       .accounts({
         pool: poolPubkey,
         tokenMint0: sortedToken0,
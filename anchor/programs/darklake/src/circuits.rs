@@ -0,0 +1,538 @@
+use anchor_lang::prelude::*;
+use groth16_solana::groth16::{Groth16Verifier, Groth16Verifyingkey};
+
+use crate::errors::ErrorCode;
+
+/// Slots for verifying key versions held by pools and the config, one per circuit id.
+pub const MAX_CIRCUITS: usize = 8;
+
+/// Id of the `ZKConstantProductAMM` circuit.
+pub const CIRCUIT_CONSTANT_PRODUCT: u8 = 0;
+
+/// Id of the `ZKConstantProductAMMExactOut` circuit.
+pub const CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT: u8 = 1;
+
+/// Id of the `ZKConstantProductRoute` circuit.
+pub const CIRCUIT_CONSTANT_PRODUCT_ROUTE: u8 = 2;
+
+/// Id of the `ZKRangeSwap` circuit.
+pub const CIRCUIT_RANGE_SWAP: u8 = 3;
+
+/// Id of the `ZKStableSwap` circuit.
+pub const CIRCUIT_STABLE_SWAP: u8 = 4;
+
+/// Swap circuits `confidential_swap` can verify proofs of. Each one has its own verifying
+/// keys and public signal layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapCircuit {
+    ConstantProduct,
+    /// Same outputs and public inputs as `ConstantProduct`, with the amount received and a
+    /// maximum input as private inputs instead of the input and a minimum output.
+    ConstantProductExactOut,
+    /// Exact input swap through `ROUTE_HOPS` pools, see `confidential_route_swap`.
+    ConstantProductRoute,
+    /// Exact input swap through the ticks of a concentrated pool, see
+    /// `confidential_range_swap`.
+    RangeSwap,
+    /// Exact input swap on a StableSwap pool. Same outputs as `ConstantProduct`, with the
+    /// amplification coefficient as an extra public input.
+    StableSwap,
+}
+
+/// What a swap proof establishes, decoded from the circuit outputs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapOutputs {
+    pub new_balance_x: u64,
+    pub new_balance_y: u64,
+    pub amount_received: u64,
+    pub protocol_fee: u64,
+    pub nullifier: [u8; 32],
+}
+
+/// What a route proof establishes about one of its pools.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HopOutputs {
+    pub new_balance_x: u64,
+    pub new_balance_y: u64,
+    pub protocol_fee: u64,
+}
+
+/// What a route proof establishes, decoded from the circuit outputs. Hops are in route order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RouteOutputs {
+    pub hops: Vec<HopOutputs>,
+    /// Amount the last pool pays out.
+    pub amount_received: u64,
+    pub nullifier: [u8; 32],
+}
+
+/// Public inputs of a route proof the program takes from one of the pools.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HopBindings {
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+}
+
+/// Public inputs of a route proof the program takes from the pools and the instruction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RouteBindings {
+    pub hops: Vec<HopBindings>,
+    pub user_hash: [u8; 32],
+    pub expiry_slot: u64,
+}
+
+/// What a range swap proof establishes. The program runs the swap itself, the proof only
+/// fixes the amounts and keeps the minimum received private.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RangeSwapOutputs {
+    /// Amount sent by the user, fee included.
+    pub amount_in: u64,
+    pub amount_received: u64,
+    pub nullifier: [u8; 32],
+}
+
+/// Public inputs of a range swap proof the program takes from the pool and the instruction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RangeSwapBindings {
+    pub sqrt_price_x64: u128,
+    pub liquidity: u128,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub user_hash: [u8; 32],
+    pub expiry_slot: u64,
+}
+
+/// Public inputs of a swap proof the program takes from the pool and the instruction,
+/// rather than from the caller-provided outputs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapBindings {
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub user_hash: [u8; 32],
+    pub expiry_slot: u64,
+    /// Amplification coefficient at the current slot, only bound by `StableSwap` proofs.
+    pub amp: u64,
+}
+
+impl SwapCircuit {
+    pub fn from_id(circuit_id: u8) -> Result<Self> {
+        match circuit_id {
+            CIRCUIT_CONSTANT_PRODUCT => Ok(SwapCircuit::ConstantProduct),
+            CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT => Ok(SwapCircuit::ConstantProductExactOut),
+            CIRCUIT_CONSTANT_PRODUCT_ROUTE => Ok(SwapCircuit::ConstantProductRoute),
+            CIRCUIT_RANGE_SWAP => Ok(SwapCircuit::RangeSwap),
+            CIRCUIT_STABLE_SWAP => Ok(SwapCircuit::StableSwap),
+            _ => Err(ErrorCode::UnknownCircuit.into()),
+        }
+    }
+
+    pub fn id(self) -> u8 {
+        match self {
+            SwapCircuit::ConstantProduct => CIRCUIT_CONSTANT_PRODUCT,
+            SwapCircuit::ConstantProductExactOut => CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT,
+            SwapCircuit::ConstantProductRoute => CIRCUIT_CONSTANT_PRODUCT_ROUTE,
+            SwapCircuit::RangeSwap => CIRCUIT_RANGE_SWAP,
+            SwapCircuit::StableSwap => CIRCUIT_STABLE_SWAP,
+        }
+    }
+
+    /// Whether the user fixes the amount received rather than the amount sent.
+    pub fn is_exact_out(self) -> bool {
+        match self {
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductRoute
+            | SwapCircuit::RangeSwap
+            | SwapCircuit::StableSwap => false,
+            SwapCircuit::ConstantProductExactOut => true,
+        }
+    }
+
+    /// Number of pools a proof of this circuit swaps through.
+    pub fn hops(self) -> usize {
+        match self {
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductExactOut
+            | SwapCircuit::RangeSwap
+            | SwapCircuit::StableSwap => 1,
+            SwapCircuit::ConstantProductRoute => ROUTE_HOPS,
+        }
+    }
+
+    /// Number of circuit outputs, sent by the caller.
+    pub fn outputs(self) -> usize {
+        match self {
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductExactOut
+            | SwapCircuit::StableSwap => SWAP_OUTPUTS,
+            SwapCircuit::ConstantProductRoute => ROUTE_OUTPUTS,
+            SwapCircuit::RangeSwap => RANGE_SWAP_OUTPUTS,
+        }
+    }
+
+    /// Number of public signals, outputs and public inputs, checked by the verifying key.
+    pub fn public_signals(self) -> usize {
+        match self {
+            SwapCircuit::ConstantProduct | SwapCircuit::ConstantProductExactOut => {
+                SWAP_PUBLIC_SIGNALS
+            }
+            SwapCircuit::ConstantProductRoute => ROUTE_PUBLIC_SIGNALS,
+            SwapCircuit::RangeSwap => RANGE_SWAP_PUBLIC_SIGNALS,
+            SwapCircuit::StableSwap => STABLE_SWAP_PUBLIC_SIGNALS,
+        }
+    }
+
+    /// Decodes the outputs of a single pool circuit, rejecting the wrong number of them.
+    pub fn decode_outputs(self, outputs: &[[u8; 32]]) -> Result<SwapOutputs> {
+        if outputs.len() != self.outputs() {
+            return Err(ErrorCode::InvalidPublicInputsLength.into());
+        }
+
+        match self {
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductExactOut
+            | SwapCircuit::StableSwap => Ok(SwapOutputs {
+                new_balance_x: field_to_u64(&outputs[0])?,
+                new_balance_y: field_to_u64(&outputs[1])?,
+                amount_received: field_to_u64(&outputs[2])?,
+                protocol_fee: field_to_u64(&outputs[3])?,
+                nullifier: outputs[4],
+            }),
+            SwapCircuit::ConstantProductRoute | SwapCircuit::RangeSwap => {
+                Err(ErrorCode::UnknownCircuit.into())
+            }
+        }
+    }
+
+    /// Decodes the outputs of a route circuit, rejecting the wrong number of them.
+    pub fn decode_route_outputs(self, outputs: &[[u8; 32]]) -> Result<RouteOutputs> {
+        if outputs.len() != self.outputs() {
+            return Err(ErrorCode::InvalidPublicInputsLength.into());
+        }
+
+        match self {
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductExactOut
+            | SwapCircuit::RangeSwap
+            | SwapCircuit::StableSwap => Err(ErrorCode::UnknownCircuit.into()),
+            SwapCircuit::ConstantProductRoute => {
+                // newBalanceX[hops], newBalanceY[hops], protocolFee[hops], amountReceived,
+                // nullifier
+                let hops = (0..ROUTE_HOPS)
+                    .map(|hop| {
+                        Ok(HopOutputs {
+                            new_balance_x: field_to_u64(&outputs[hop])?,
+                            new_balance_y: field_to_u64(&outputs[ROUTE_HOPS + hop])?,
+                            protocol_fee: field_to_u64(&outputs[2 * ROUTE_HOPS + hop])?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(RouteOutputs {
+                    hops,
+                    amount_received: field_to_u64(&outputs[3 * ROUTE_HOPS])?,
+                    nullifier: outputs[3 * ROUTE_HOPS + 1],
+                })
+            }
+        }
+    }
+
+    /// Lays out the public signals of a single pool circuit in the order it expects them.
+    pub fn public_signals_for(
+        self,
+        outputs: &SwapOutputs,
+        bindings: &SwapBindings,
+    ) -> Result<Vec<[u8; 32]>> {
+        match self {
+            SwapCircuit::ConstantProduct | SwapCircuit::ConstantProductExactOut => {
+                Ok(SwapPublicSignals::new(outputs, bindings).to_fields().to_vec())
+            }
+            // The constant product layout, then `amp`
+            SwapCircuit::StableSwap => {
+                let mut signals = SwapPublicSignals::new(outputs, bindings).to_fields().to_vec();
+                signals.push(u64_to_field(bindings.amp));
+                Ok(signals)
+            }
+            SwapCircuit::ConstantProductRoute | SwapCircuit::RangeSwap => {
+                Err(ErrorCode::UnknownCircuit.into())
+            }
+        }
+    }
+
+    /// Lays out the public signals of a route circuit in the order it expects them, with one
+    /// set of bindings per hop of the outputs.
+    pub fn route_public_signals_for(
+        self,
+        outputs: &RouteOutputs,
+        bindings: &RouteBindings,
+    ) -> Result<Vec<[u8; 32]>> {
+        match self {
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductExactOut
+            | SwapCircuit::RangeSwap
+            | SwapCircuit::StableSwap => Err(ErrorCode::UnknownCircuit.into()),
+            SwapCircuit::ConstantProductRoute => {
+                if outputs.hops.len() != ROUTE_HOPS || bindings.hops.len() != ROUTE_HOPS {
+                    return Err(ErrorCode::InvalidRoute.into());
+                }
+                Ok(route_public_signals(outputs, bindings))
+            }
+        }
+    }
+
+    /// Decodes the outputs of the range swap circuit, rejecting the wrong number of them.
+    pub fn decode_range_swap_outputs(self, outputs: &[[u8; 32]]) -> Result<RangeSwapOutputs> {
+        if outputs.len() != self.outputs() {
+            return Err(ErrorCode::InvalidPublicInputsLength.into());
+        }
+
+        match self {
+            SwapCircuit::RangeSwap => Ok(RangeSwapOutputs {
+                amount_in: field_to_u64(&outputs[0])?,
+                amount_received: field_to_u64(&outputs[1])?,
+                nullifier: outputs[2],
+            }),
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductExactOut
+            | SwapCircuit::ConstantProductRoute
+            | SwapCircuit::StableSwap => Err(ErrorCode::UnknownCircuit.into()),
+        }
+    }
+
+    /// Lays out the public signals of the range swap circuit in the order it expects them:
+    /// the outputs `amountIn`, `amountReceived`, `nullifier`, then the public inputs
+    /// `sqrtPriceX64`, `liquidity`, `isSwapXtoY`, `feeRate`, `protocolFeeRate`, `userHash` and
+    /// `expirySlot`.
+    pub fn range_swap_public_signals_for(
+        self,
+        outputs: &RangeSwapOutputs,
+        bindings: &RangeSwapBindings,
+    ) -> Result<Vec<[u8; 32]>> {
+        match self {
+            SwapCircuit::RangeSwap => Ok(vec![
+                u64_to_field(outputs.amount_in),
+                u64_to_field(outputs.amount_received),
+                outputs.nullifier,
+                u128_to_field(bindings.sqrt_price_x64),
+                u128_to_field(bindings.liquidity),
+                u64_to_field(bindings.is_swap_x_to_y as u64),
+                u64_to_field(bindings.fee_bps as u64),
+                u64_to_field(bindings.protocol_fee_bps as u64),
+                bindings.user_hash,
+                u64_to_field(bindings.expiry_slot),
+            ]),
+            SwapCircuit::ConstantProduct
+            | SwapCircuit::ConstantProductExactOut
+            | SwapCircuit::ConstantProductRoute
+            | SwapCircuit::StableSwap => Err(ErrorCode::UnknownCircuit.into()),
+        }
+    }
+
+    /// Verifies a proof of this circuit against `verifying_key`.
+    pub fn verify(
+        self,
+        proof_a: &[u8; 64],
+        proof_b: &[u8; 128],
+        proof_c: &[u8; 64],
+        public_signals: &[[u8; 32]],
+        verifying_key: &Groth16Verifyingkey,
+    ) -> Result<bool> {
+        if public_signals.len() != self.public_signals()
+            || verifying_key.nr_pubinputs != public_signals.len()
+        {
+            return Err(ErrorCode::InvalidPublicInputsLength.into());
+        }
+
+        match self {
+            SwapCircuit::ConstantProduct | SwapCircuit::ConstantProductExactOut => {
+                verify_proof::<SWAP_PUBLIC_SIGNALS>(
+                    proof_a,
+                    proof_b,
+                    proof_c,
+                    public_signals,
+                    verifying_key,
+                )
+            }
+            SwapCircuit::ConstantProductRoute => verify_proof::<ROUTE_PUBLIC_SIGNALS>(
+                proof_a,
+                proof_b,
+                proof_c,
+                public_signals,
+                verifying_key,
+            ),
+            SwapCircuit::RangeSwap => verify_proof::<RANGE_SWAP_PUBLIC_SIGNALS>(
+                proof_a,
+                proof_b,
+                proof_c,
+                public_signals,
+                verifying_key,
+            ),
+            SwapCircuit::StableSwap => verify_proof::<STABLE_SWAP_PUBLIC_SIGNALS>(
+                proof_a,
+                proof_b,
+                proof_c,
+                public_signals,
+                verifying_key,
+            ),
+        }
+    }
+}
+
+/// `Groth16Verifier` takes the public signals as a fixed-size array, so the count is
+/// resolved per circuit.
+fn verify_proof<const N: usize>(
+    proof_a: &[u8; 64],
+    proof_b: &[u8; 128],
+    proof_c: &[u8; 64],
+    public_signals: &[[u8; 32]],
+    verifying_key: &Groth16Verifyingkey,
+) -> Result<bool> {
+    let public_signals: &[[u8; 32]; N] = public_signals
+        .try_into()
+        .map_err(|_| ErrorCode::InvalidPublicInputsLength)?;
+
+    let mut verifier =
+        Groth16Verifier::new(proof_a, proof_b, proof_c, public_signals, verifying_key)
+            .map_err(|_| ErrorCode::InvalidGroth16Verifier)?;
+
+    verifier.verify().map_err(|_| ErrorCode::InvalidProof.into())
+}
+
+/// Number of outputs of the constant product circuits.
+pub const SWAP_OUTPUTS: usize = 5;
+
+/// Number of public signals of the constant product circuits.
+pub const SWAP_PUBLIC_SIGNALS: usize = 12;
+
+/// Number of public signals of the StableSwap circuit: those of the constant product circuits
+/// and the amplification coefficient.
+pub const STABLE_SWAP_PUBLIC_SIGNALS: usize = SWAP_PUBLIC_SIGNALS + 1;
+
+/// Public signals of the `ZKConstantProductAMM` and `ZKConstantProductAMMExactOut` circuits,
+/// which share a layout. The outputs, direction and expiry come from the caller, the rest of the
+/// public inputs from the pool account.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapPublicSignals {
+    pub new_balance_x: u64,
+    pub new_balance_y: u64,
+    pub amount_received: u64,
+    pub protocol_fee: u64,
+    pub nullifier: [u8; 32],
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub user_hash: [u8; 32],
+    pub expiry_slot: u64,
+}
+
+impl SwapPublicSignals {
+    pub fn new(outputs: &SwapOutputs, bindings: &SwapBindings) -> Self {
+        SwapPublicSignals {
+            new_balance_x: outputs.new_balance_x,
+            new_balance_y: outputs.new_balance_y,
+            amount_received: outputs.amount_received,
+            protocol_fee: outputs.protocol_fee,
+            nullifier: outputs.nullifier,
+            reserve_x: bindings.reserve_x,
+            reserve_y: bindings.reserve_y,
+            is_swap_x_to_y: bindings.is_swap_x_to_y,
+            fee_bps: bindings.fee_bps,
+            protocol_fee_bps: bindings.protocol_fee_bps,
+            user_hash: bindings.user_hash,
+            expiry_slot: bindings.expiry_slot,
+        }
+    }
+
+    /// Encodes the signals in the order snarkjs lays them out: the outputs `newBalanceX`,
+    /// `newBalanceY`, `amountReceived`, `protocolFee`, `nullifier`, followed by the public
+    /// inputs `publicBalanceX`, `publicBalanceY`, `isSwapXtoY`, `feeRate`, `protocolFeeRate`,
+    /// `userHash` and `expirySlot`.
+    pub fn to_fields(&self) -> [[u8; 32]; SWAP_PUBLIC_SIGNALS] {
+        [
+            u64_to_field(self.new_balance_x),
+            u64_to_field(self.new_balance_y),
+            u64_to_field(self.amount_received),
+            u64_to_field(self.protocol_fee),
+            self.nullifier,
+            u64_to_field(self.reserve_x),
+            u64_to_field(self.reserve_y),
+            u64_to_field(self.is_swap_x_to_y as u64),
+            u64_to_field(self.fee_bps as u64),
+            u64_to_field(self.protocol_fee_bps as u64),
+            self.user_hash,
+            u64_to_field(self.expiry_slot),
+        ]
+    }
+}
+
+/// Number of pools the route circuit swaps through.
+pub const ROUTE_HOPS: usize = 2;
+
+/// Number of outputs of the route circuit: the new balances and protocol fee of every pool,
+/// the amount received and the nullifier.
+pub const ROUTE_OUTPUTS: usize = 3 * ROUTE_HOPS + 2;
+
+/// Number of public signals of the route circuit: the outputs, the reserves, direction and fee
+/// rates of every pool, the user hash and the expiry slot.
+pub const ROUTE_PUBLIC_SIGNALS: usize = ROUTE_OUTPUTS + 5 * ROUTE_HOPS + 2;
+
+/// Encodes the public signals of the `ZKConstantProductRoute` circuit. snarkjs flattens each
+/// array signal in turn, so every output and public input lists all the hops before the next
+/// one: `newBalanceX[]`, `newBalanceY[]`, `protocolFee[]`, `amountReceived`, `nullifier`, then
+/// `publicBalanceX[]`, `publicBalanceY[]`, `isSwapXtoY[]`, `feeRate[]`, `protocolFeeRate[]`,
+/// `userHash` and `expirySlot`.
+fn route_public_signals(outputs: &RouteOutputs, bindings: &RouteBindings) -> Vec<[u8; 32]> {
+    let mut signals = Vec::with_capacity(ROUTE_PUBLIC_SIGNALS);
+    signals.extend(outputs.hops.iter().map(|hop| u64_to_field(hop.new_balance_x)));
+    signals.extend(outputs.hops.iter().map(|hop| u64_to_field(hop.new_balance_y)));
+    signals.extend(outputs.hops.iter().map(|hop| u64_to_field(hop.protocol_fee)));
+    signals.push(u64_to_field(outputs.amount_received));
+    signals.push(outputs.nullifier);
+    signals.extend(bindings.hops.iter().map(|hop| u64_to_field(hop.reserve_x)));
+    signals.extend(bindings.hops.iter().map(|hop| u64_to_field(hop.reserve_y)));
+    signals.extend(bindings.hops.iter().map(|hop| u64_to_field(hop.is_swap_x_to_y as u64)));
+    signals.extend(bindings.hops.iter().map(|hop| u64_to_field(hop.fee_bps as u64)));
+    signals.extend(bindings.hops.iter().map(|hop| u64_to_field(hop.protocol_fee_bps as u64)));
+    signals.push(bindings.user_hash);
+    signals.push(u64_to_field(bindings.expiry_slot));
+    signals
+}
+
+/// Number of outputs of the range swap circuit: the amounts sent and received and the
+/// nullifier.
+pub const RANGE_SWAP_OUTPUTS: usize = 3;
+
+/// Number of public signals of the range swap circuit: the outputs, the pool price,
+/// liquidity in range, direction and fee rates, the user hash and the expiry slot.
+pub const RANGE_SWAP_PUBLIC_SIGNALS: usize = RANGE_SWAP_OUTPUTS + 7;
+
+/// Encodes a `u64` as a big-endian field element.
+pub fn u64_to_field(value: u64) -> [u8; 32] {
+    let mut field = [0u8; 32];
+    field[24..].copy_from_slice(&value.to_be_bytes());
+    field
+}
+
+/// Encodes a `u128` as a big-endian field element.
+pub fn u128_to_field(value: u128) -> [u8; 32] {
+    let mut field = [0u8; 32];
+    field[16..].copy_from_slice(&value.to_be_bytes());
+    field
+}
+
+/// Decodes a big-endian field element, rejecting values that do not fit in a `u64`.
+pub fn field_to_u64(field: &[u8; 32]) -> Result<u64> {
+    if field[..24].iter().any(|byte| *byte != 0) {
+        return Err(ErrorCode::InvalidInput.into());
+    }
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&field[24..]);
+    Ok(u64::from_be_bytes(bytes))
+}
@@ -0,0 +1,422 @@
+//! Swaps and positions of concentrated liquidity pools.
+//!
+//! Liquidity is provided between two ticks and only trades while the price is inside that
+//! range. Swaps move the price through the ticks of the pool tick arrays, adding or removing
+//! the liquidity of every initialized tick they cross.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::FEE_DENOMINATOR;
+use crate::errors::ErrorCode;
+use crate::math::{mul_div_floor, to_u64};
+use crate::state::{Pool, Position, Tick, TickArray, TICK_ARRAY_SIZE};
+use crate::tick_math::{
+    amounts_for_liquidity, compute_swap_step, sqrt_price_at_tick, tick_at_sqrt_price, U256,
+    MAX_TICK, MIN_TICK,
+};
+
+/// Outcome of a swap through the ticks of a concentrated pool, and the pool state after it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RangeSwap {
+    /// Amount sent by the user, fee included.
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Swap fee charged on the input, protocol share included.
+    pub fee: u64,
+    /// Protocol share of the swap fee, kept outside of the reserves.
+    pub protocol_fee: u64,
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+    pub liquidity: u128,
+    /// Fee growth of the input token after the swap.
+    pub fee_growth_global_x64: u128,
+}
+
+/// Checks `tick_lower` and `tick_upper` bound a range positions of the pool can use.
+pub fn check_tick_range(tick_lower: i32, tick_upper: i32, tick_spacing: u16) -> Result<()> {
+    let spacing = tick_spacing as i32;
+    if tick_spacing == 0
+        || tick_lower >= tick_upper
+        || tick_lower < MIN_TICK
+        || tick_upper > MAX_TICK
+        || tick_lower % spacing != 0
+        || tick_upper % spacing != 0
+    {
+        return Err(ErrorCode::InvalidTick.into());
+    }
+    Ok(())
+}
+
+/// Checks the tick arrays of a swap follow each other in the swap direction, starting with
+/// the array holding the current tick.
+pub fn check_swap_tick_arrays(
+    tick_arrays: &[TickArray],
+    tick_current: i32,
+    tick_spacing: u16,
+    is_swap_x_to_y: bool,
+) -> Result<()> {
+    let first = tick_arrays.first().ok_or(ErrorCode::InvalidTickArray)?;
+    if !first.contains(tick_current, tick_spacing) {
+        return Err(ErrorCode::InvalidTickArray.into());
+    }
+
+    let step = if is_swap_x_to_y {
+        -TickArray::span(tick_spacing)
+    } else {
+        TickArray::span(tick_spacing)
+    };
+    for pair in tick_arrays.windows(2) {
+        if pair[1].start_tick_index != pair[0].start_tick_index + step {
+            return Err(ErrorCode::InvalidTickArray.into());
+        }
+    }
+    Ok(())
+}
+
+/// Adds a signed liquidity change to `liquidity`.
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    let result = if delta >= 0 {
+        liquidity.checked_add(delta as u128)
+    } else {
+        liquidity.checked_sub(delta.unsigned_abs())
+    };
+    result.ok_or(ErrorCode::MathOverflow.into())
+}
+
+/// Next initialized tick in the swap direction, down from and including the current tick or
+/// up from the tick above it, with its array and slot. Without one in the loaded arrays, the
+/// end of the last array is returned instead, with no slot.
+fn next_initialized_tick(
+    tick_arrays: &[TickArray],
+    tick_current: i32,
+    tick_spacing: u16,
+    is_swap_x_to_y: bool,
+) -> Result<(i32, Option<(usize, usize)>)> {
+    let spacing = tick_spacing as i32;
+    for (index, array) in tick_arrays.iter().enumerate() {
+        let current_slot = (tick_current - array.start_tick_index).div_euclid(spacing);
+        let found = if is_swap_x_to_y {
+            let first = current_slot.min(TICK_ARRAY_SIZE as i32 - 1);
+            (0..=first).rev().find(|slot| array.ticks[*slot as usize].is_initialized())
+        } else {
+            let first = (current_slot + 1).max(0);
+            (first..TICK_ARRAY_SIZE as i32).find(|slot| array.ticks[*slot as usize].is_initialized())
+        };
+        if let Some(slot) = found {
+            return Ok((array.start_tick_index + slot * spacing, Some((index, slot as usize))));
+        }
+    }
+
+    let last = tick_arrays.last().ok_or(ErrorCode::InvalidTickArray)?;
+    if is_swap_x_to_y {
+        Ok((last.start_tick_index, None))
+    } else {
+        Ok((last.start_tick_index + TickArray::span(tick_spacing), None))
+    }
+}
+
+/// Swaps `amount_in`, fee included, through the ticks of `pool`, flipping the fee
+/// checkpoints of every tick it crosses in `tick_arrays`. The pool itself is left unchanged,
+/// its new state is returned.
+///
+/// The whole amount must be swapped: running out of tick arrays, or of price range, fails.
+pub fn compute_range_swap(
+    pool: &Pool,
+    tick_arrays: &mut [TickArray],
+    amount_in: u64,
+    is_swap_x_to_y: bool,
+) -> Result<RangeSwap> {
+    pool.require_concentrated(true)?;
+    if amount_in == 0 {
+        return Err(ErrorCode::InvalidSwapAmount.into());
+    }
+    check_swap_tick_arrays(tick_arrays, pool.tick_current, pool.tick_spacing, is_swap_x_to_y)?;
+
+    let (mut fee_growth_in, fee_growth_out) = if is_swap_x_to_y {
+        (pool.fee_growth_global_x64_x, pool.fee_growth_global_x64_y)
+    } else {
+        (pool.fee_growth_global_x64_y, pool.fee_growth_global_x64_x)
+    };
+
+    let mut sqrt_price_x64 = pool.sqrt_price_x64;
+    let mut tick_current = pool.tick_current;
+    let mut liquidity = pool.range_liquidity;
+    let mut remaining = amount_in as u128;
+    let mut amount_out = 0u128;
+    let mut fee = 0u128;
+    let mut protocol_fee = 0u128;
+
+    while remaining > 0 {
+        let (next_tick, slot) =
+            next_initialized_tick(tick_arrays, tick_current, pool.tick_spacing, is_swap_x_to_y)?;
+        let target_tick = next_tick.clamp(MIN_TICK, MAX_TICK);
+        let sqrt_price_target_x64 = sqrt_price_at_tick(target_tick)?;
+
+        let step = compute_swap_step(
+            sqrt_price_x64,
+            sqrt_price_target_x64,
+            liquidity,
+            remaining,
+            pool.fee_bps,
+        )?;
+        remaining = remaining
+            .checked_sub(step.amount_in + step.fee_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        amount_out = amount_out
+            .checked_add(step.amount_out)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        // The protocol share leaves the pool, the rest is owed to the liquidity in range
+        let step_protocol_fee = mul_div_floor(
+            step.fee_amount,
+            pool.protocol_fee_bps as u128,
+            FEE_DENOMINATOR as u128,
+        )?;
+        fee += step.fee_amount;
+        protocol_fee += step_protocol_fee;
+        let lp_fee = step.fee_amount - step_protocol_fee;
+        if let Some(growth) = (lp_fee << 64).checked_div(liquidity) {
+            fee_growth_in = fee_growth_in.wrapping_add(growth);
+        }
+
+        sqrt_price_x64 = step.sqrt_price_next_x64;
+        if sqrt_price_x64 != sqrt_price_target_x64 {
+            tick_current = tick_at_sqrt_price(sqrt_price_x64)?;
+            continue;
+        }
+
+        match slot {
+            Some((index, slot)) => {
+                let tick = &mut tick_arrays[index].ticks[slot];
+                let (outside_x, outside_y) = if is_swap_x_to_y {
+                    (fee_growth_in, fee_growth_out)
+                } else {
+                    (fee_growth_out, fee_growth_in)
+                };
+                tick.fee_growth_outside_x64_x = outside_x.wrapping_sub(tick.fee_growth_outside_x64_x);
+                tick.fee_growth_outside_x64_y = outside_y.wrapping_sub(tick.fee_growth_outside_x64_y);
+
+                liquidity = if is_swap_x_to_y {
+                    add_liquidity_delta(liquidity, -tick.liquidity_net)?
+                } else {
+                    add_liquidity_delta(liquidity, tick.liquidity_net)?
+                };
+            }
+            // Past the last loaded tick, or the end of the price range
+            None if remaining > 0 => {
+                if target_tick != next_tick {
+                    return Err(ErrorCode::InsufficientLiquidity.into());
+                }
+                return Err(ErrorCode::InvalidTickArray.into());
+            }
+            None => {}
+        }
+        tick_current = if is_swap_x_to_y { target_tick - 1 } else { target_tick };
+    }
+
+    Ok(RangeSwap {
+        amount_in,
+        amount_out: to_u64(amount_out)?,
+        fee: to_u64(fee)?,
+        protocol_fee: to_u64(protocol_fee)?,
+        sqrt_price_x64,
+        tick_current,
+        liquidity,
+        fee_growth_global_x64: fee_growth_in,
+    })
+}
+
+/// Moves `pool` to its state after `swap`. Everything sent but the protocol fee stays in the
+/// reserves, with the liquidity providers.
+pub fn apply_range_swap(pool: &mut Pool, swap: &RangeSwap, is_swap_x_to_y: bool) -> Result<()> {
+    let reserve_increase = swap
+        .amount_in
+        .checked_sub(swap.protocol_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let (reserve_in, reserve_out, protocol_fees) = if is_swap_x_to_y {
+        (&mut pool.reserve_x, &mut pool.reserve_y, &mut pool.protocol_fees_x)
+    } else {
+        (&mut pool.reserve_y, &mut pool.reserve_x, &mut pool.protocol_fees_y)
+    };
+    *reserve_in = reserve_in
+        .checked_add(reserve_increase)
+        .ok_or(ErrorCode::MathOverflow)?;
+    *reserve_out = reserve_out
+        .checked_sub(swap.amount_out)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+    *protocol_fees = protocol_fees
+        .checked_add(swap.protocol_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    pool.sqrt_price_x64 = swap.sqrt_price_x64;
+    pool.tick_current = swap.tick_current;
+    pool.range_liquidity = swap.liquidity;
+    if is_swap_x_to_y {
+        pool.fee_growth_global_x64_x = swap.fee_growth_global_x64;
+    } else {
+        pool.fee_growth_global_x64_y = swap.fee_growth_global_x64;
+    }
+    Ok(())
+}
+
+/// LP fees earned per unit of liquidity between two ticks, for x and y.
+pub fn fee_growth_inside(
+    pool: &Pool,
+    tick_lower: i32,
+    lower: &Tick,
+    tick_upper: i32,
+    upper: &Tick,
+) -> (u128, u128) {
+    let inside = |global: u128, lower_outside: u128, upper_outside: u128| {
+        let below = if pool.tick_current >= tick_lower {
+            lower_outside
+        } else {
+            global.wrapping_sub(lower_outside)
+        };
+        let above = if pool.tick_current < tick_upper {
+            upper_outside
+        } else {
+            global.wrapping_sub(upper_outside)
+        };
+        global.wrapping_sub(below).wrapping_sub(above)
+    };
+
+    (
+        inside(
+            pool.fee_growth_global_x64_x,
+            lower.fee_growth_outside_x64_x,
+            upper.fee_growth_outside_x64_x,
+        ),
+        inside(
+            pool.fee_growth_global_x64_y,
+            lower.fee_growth_outside_x64_y,
+            upper.fee_growth_outside_x64_y,
+        ),
+    )
+}
+
+/// Applies a liquidity change to the tick a position starts or ends on. A tick becoming
+/// initialized counts all the fees so far as earned below it.
+fn update_tick(
+    pool: &Pool,
+    tick: &mut Tick,
+    tick_index: i32,
+    delta: i128,
+    is_upper: bool,
+) -> Result<()> {
+    let liquidity_gross = add_liquidity_delta(tick.liquidity_gross, delta)?;
+    if !tick.is_initialized() && liquidity_gross > 0 && tick_index <= pool.tick_current {
+        tick.fee_growth_outside_x64_x = pool.fee_growth_global_x64_x;
+        tick.fee_growth_outside_x64_y = pool.fee_growth_global_x64_y;
+    }
+    tick.liquidity_gross = liquidity_gross;
+
+    let net_delta = if is_upper { delta.checked_neg() } else { Some(delta) };
+    tick.liquidity_net = net_delta
+        .and_then(|net_delta| tick.liquidity_net.checked_add(net_delta))
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// Fees earned by `liquidity` over a fee growth increase, rounded down.
+fn fees_earned(fee_growth_delta_x64: u128, liquidity: u128) -> Result<u64> {
+    let fees = (U256::from(fee_growth_delta_x64) * U256::from(liquidity)) >> 64;
+    if fees > U256::from(u64::MAX) {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+    Ok(fees.as_u64())
+}
+
+/// Credits a position with the fees earned since its last update.
+pub fn update_position_fees(
+    position: &mut Position,
+    fee_growth_inside_x64_x: u128,
+    fee_growth_inside_x64_y: u128,
+) -> Result<()> {
+    let earned_x = fees_earned(
+        fee_growth_inside_x64_x.wrapping_sub(position.fee_growth_inside_last_x64_x),
+        position.liquidity,
+    )?;
+    let earned_y = fees_earned(
+        fee_growth_inside_x64_y.wrapping_sub(position.fee_growth_inside_last_x64_y),
+        position.liquidity,
+    )?;
+
+    position.fees_owed_x = position
+        .fees_owed_x
+        .checked_add(earned_x)
+        .ok_or(ErrorCode::MathOverflow)?;
+    position.fees_owed_y = position
+        .fees_owed_y
+        .checked_add(earned_y)
+        .ok_or(ErrorCode::MathOverflow)?;
+    position.fee_growth_inside_last_x64_x = fee_growth_inside_x64_x;
+    position.fee_growth_inside_last_x64_y = fee_growth_inside_x64_y;
+    Ok(())
+}
+
+/// Adds `delta` liquidity to a position, or removes it when negative, updating its ticks,
+/// its fees and the pool liquidity in range. Returns the amounts of x and y the change takes
+/// in, rounded up, or pays out, rounded down.
+///
+/// `lower` and `upper` are the ticks of the position range.
+pub fn modify_position(
+    pool: &mut Pool,
+    position: &mut Position,
+    lower: &mut Tick,
+    upper: &mut Tick,
+    delta: i128,
+) -> Result<(u64, u64)> {
+    let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+    check_tick_range(tick_lower, tick_upper, pool.tick_spacing)?;
+
+    update_tick(pool, lower, tick_lower, delta, false)?;
+    update_tick(pool, upper, tick_upper, delta, true)?;
+
+    let (inside_x, inside_y) = fee_growth_inside(pool, tick_lower, lower, tick_upper, upper);
+    update_position_fees(position, inside_x, inside_y)?;
+    position.liquidity = add_liquidity_delta(position.liquidity, delta)?;
+
+    if (tick_lower..tick_upper).contains(&pool.tick_current) {
+        pool.range_liquidity = add_liquidity_delta(pool.range_liquidity, delta)?;
+    }
+
+    // A tick nobody uses any more no longer stops swaps
+    for tick in [lower, upper] {
+        if !tick.is_initialized() {
+            *tick = Tick::default();
+        }
+    }
+
+    let (amount_x, amount_y) = amounts_for_liquidity(
+        pool.sqrt_price_x64,
+        sqrt_price_at_tick(tick_lower)?,
+        sqrt_price_at_tick(tick_upper)?,
+        delta.unsigned_abs(),
+        delta > 0,
+    )?;
+    Ok((to_u64(amount_x)?, to_u64(amount_y)?))
+}
+
+/// The ticks of a range from the tick arrays holding them, which may be the same array.
+pub fn range_ticks_mut<'a>(
+    tick_array_lower: &'a mut TickArray,
+    tick_array_upper: Option<&'a mut TickArray>,
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_spacing: u16,
+) -> Result<(&'a mut Tick, &'a mut Tick)> {
+    match tick_array_upper {
+        Some(tick_array_upper) => Ok((
+            tick_array_lower.tick_mut(tick_lower, tick_spacing)?,
+            tick_array_upper.tick_mut(tick_upper, tick_spacing)?,
+        )),
+        None => {
+            let lower = tick_array_lower.offset(tick_lower, tick_spacing)?;
+            let upper = tick_array_lower.offset(tick_upper, tick_spacing)?;
+            // tick_lower < tick_upper, so the upper slot comes later
+            let (head, tail) = tick_array_lower.ticks.split_at_mut(upper);
+            Ok((&mut head[lower], &mut tail[0]))
+        }
+    }
+}
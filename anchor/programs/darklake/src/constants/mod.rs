@@ -1,3 +1,46 @@
-pub mod verifying_key;
+/// Denominator for fees expressed in basis points.
+pub const FEE_DENOMINATOR: u64 = 10_000;
 
-pub use verifying_key::VERIFYINGKEY;
+/// Upper bound for a pool swap fee (10%).
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Share of the swap fee, in basis points, kept by the protocol on new pools.
+pub const DEFAULT_PROTOCOL_FEE_BPS: u16 = 2_000;
+
+/// Number of fee tiers held by the program config.
+pub const FEE_TIER_COUNT: usize = 4;
+
+/// LP tokens locked in every pool by its first deposit, so the share price cannot be
+/// inflated by the first depositor.
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+/// Fee tiers, in basis points, the program config starts with.
+pub const DEFAULT_FEE_TIERS: [u16; FEE_TIER_COUNT] = [1, 5, 30, 100];
+
+/// Lets anyone create pools instead of only the admin.
+pub const FEATURE_PERMISSIONLESS_POOLS: u8 = 1 << 0;
+
+/// Pause bit halting `confidential_swap`.
+pub const PAUSE_SWAPS: u8 = 1 << 0;
+
+/// Pause bit halting `add_liquidity`.
+pub const PAUSE_DEPOSITS: u8 = 1 << 1;
+
+/// Pause bit halting `remove_liquidity`.
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 2;
+
+/// All pause bits.
+pub const PAUSE_ALL: u8 = PAUSE_SWAPS | PAUSE_DEPOSITS | PAUSE_WITHDRAWALS;
+
+/// Curve of a pool whose reserves keep `x * y` from dropping.
+pub const CURVE_CONSTANT_PRODUCT: u8 = 0;
+
+/// Curve of a pool whose reserves keep the StableSwap invariant, see `stable_math`.
+pub const CURVE_STABLE_SWAP: u8 = 1;
+
+/// Shortest amplification ramp, about a day of slots. A new ramp can only start this long
+/// after the previous one started.
+pub const MIN_AMP_RAMP_SLOTS: u64 = 216_000;
+
+/// Factor an amplification ramp can raise or lower the coefficient by.
+pub const MAX_AMP_CHANGE: u64 = 10;
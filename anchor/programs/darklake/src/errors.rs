@@ -22,4 +22,48 @@ pub enum ErrorCode {
     InvalidMetadataAccount,
     #[msg("LP mint already initialized")]
     LpMintAlreadyInitialized,
+    #[msg("Invalid fee")]
+    InvalidFee,
+    #[msg("Swap does not preserve the pool invariant")]
+    InvariantViolated,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Insufficient liquidity")]
+    InsufficientLiquidity,
+    #[msg("Nullifier already used")]
+    NullifierAlreadyUsed,
+    #[msg("Invalid nullifier account")]
+    InvalidNullifierAccount,
+    #[msg("Proof expired")]
+    ProofExpired,
+    #[msg("Verifying key already finalized")]
+    VerifyingKeyFinalized,
+    #[msg("Verifying key not finalized")]
+    VerifyingKeyNotFinalized,
+    #[msg("Verifying key does not match its hash")]
+    VerifyingKeyHashMismatch,
+    #[msg("Unknown circuit")]
+    UnknownCircuit,
+    #[msg("Verifying key does not match the circuit or the pool")]
+    InvalidVerifyingKey,
+    #[msg("Invalid number of public inputs")]
+    InvalidPublicInputsLength,
+    #[msg("Route accounts do not form a chain of pools")]
+    InvalidRoute,
+    #[msg("Instruction does not support this pool type")]
+    InvalidPoolType,
+    #[msg("Tick out of range or not a multiple of the tick spacing")]
+    InvalidTick,
+    #[msg("Square root price out of range")]
+    InvalidSqrtPrice,
+    #[msg("Tick arrays do not cover the ticks or the swap")]
+    InvalidTickArray,
+    #[msg("Amplification coefficient out of range or changing too much")]
+    InvalidAmp,
+    #[msg("Amplification ramp too short or too soon after the previous one")]
+    InvalidAmpRamp,
+    #[msg("Pool account is not in the legacy layout")]
+    InvalidPoolLayout,
 }
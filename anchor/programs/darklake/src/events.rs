@@ -1,32 +1,302 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::FEE_TIER_COUNT;
+use crate::circuits::MAX_CIRCUITS;
+
+// Reserves and LP supply in the events below are the values after the instruction, the
+// LP supply including the liquidity locked by the first deposit.
+
 #[event]
 pub struct PoolInitialized {
+    pub pool: Pubkey,
     pub user: Pubkey,
-    pub amount_0: u64,
-    pub amount_1: u64,
-    pub liquidity: u64,
+    pub token_mint_x: Pubkey,
+    pub token_mint_y: Pubkey,
+    pub token_mint_lp: Pubkey,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub vk_versions: [u32; MAX_CIRCUITS],
+    /// Zero for a constant product pool.
+    pub tick_spacing: u16,
+    /// Starting price of a concentrated pool, Q64.64.
+    pub sqrt_price_x64: u128,
+    /// See the `CURVE_*` constants.
+    pub curve: u8,
+    /// Amplification coefficient of a StableSwap pool, zero otherwise.
+    pub amp: u32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PoolMigrated {
+    pub pool: Pubkey,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub vk_versions: [u32; MAX_CIRCUITS],
+    pub slot: u64,
+}
+
+#[event]
+pub struct PoolUpgraded {
+    pub pool: Pubkey,
+    pub token_mint_lp: Pubkey,
+    pub slot: u64,
 }
 
 #[event]
 pub struct LiquidityAdded {
+    pub pool: Pubkey,
     pub user: Pubkey,
+    pub token_mint_x: Pubkey,
+    pub token_mint_y: Pubkey,
+    /// Amount of token X transferred, at most the desired amount.
     pub amount_x: u64,
+    /// Amount of token Y transferred, at most the desired amount.
     pub amount_y: u64,
+    /// LP tokens minted to the user.
     pub liquidity: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u128,
+    pub slot: u64,
 }
 
 #[event]
 pub struct LiquidityRemoved {
+    pub pool: Pubkey,
     pub user: Pubkey,
+    pub token_mint_x: Pubkey,
+    pub token_mint_y: Pubkey,
     pub amount_x: u64,
     pub amount_y: u64,
+    /// LP tokens burned by the user.
     pub liquidity: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u128,
+    pub slot: u64,
 }
 
+/// Only carries what the swap transfers and reserve updates already make public, the
+/// proof inputs themselves are never logged.
 #[event]
 pub struct ConfidentialSwapEvent {
+    pub pool: Pubkey,
     pub user: Pubkey,
+    pub token_mint_x: Pubkey,
+    pub token_mint_y: Pubkey,
+    pub is_swap_x_to_y: bool,
+    /// Amount sent by the user, protocol fee included.
+    pub amount_in: u64,
     pub amount_out: u64,
-    pub new_sqrt_price: u128,
+    pub protocol_fee: u64,
+    /// Nullifier spent by the proof.
+    pub nullifier: [u8; 32],
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u128,
+    pub slot: u64,
+}
+
+/// Emitted by `confidential_route_swap`, with one entry per pool in route order. Like
+/// `ConfidentialSwapEvent`, it only carries what the transfers and reserve updates make public.
+#[event]
+pub struct ConfidentialRouteSwapEvent {
+    pub user: Pubkey,
+    pub pools: Vec<Pubkey>,
+    pub token_mint_in: Pubkey,
+    pub token_mint_out: Pubkey,
+    /// Amount sent by the user to the first pool, protocol fee included.
+    pub amount_in: u64,
+    /// Amount paid to the user by the last pool.
+    pub amount_out: u64,
+    pub protocol_fees: Vec<u64>,
+    /// Nullifier spent by the proof.
+    pub nullifier: [u8; 32],
+    pub reserves_x: Vec<u64>,
+    pub reserves_y: Vec<u64>,
+    pub slot: u64,
+}
+
+/// Emitted by `confidential_range_swap`. The pool state after the swap follows from the
+/// amounts, so it is public anyway.
+#[event]
+pub struct ConfidentialRangeSwapEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub token_mint_x: Pubkey,
+    pub token_mint_y: Pubkey,
+    pub is_swap_x_to_y: bool,
+    /// Amount sent by the user, fee included.
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub protocol_fee: u64,
+    /// Nullifier spent by the proof.
+    pub nullifier: [u8; 32],
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+    pub liquidity: u128,
+    pub slot: u64,
+}
+
+#[event]
+pub struct TickArrayInitialized {
+    pub pool: Pubkey,
+    pub tick_array: Pubkey,
+    pub start_tick_index: i32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PositionNftMinted {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub user: Pubkey,
+    pub position_mint: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PositionOpened {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RangeLiquidityAdded {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub user: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Liquidity added to the position.
+    pub liquidity: u128,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    /// Pool liquidity at the current price, after the deposit.
+    pub range_liquidity: u128,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RangeLiquidityRemoved {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub user: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Liquidity removed from the position.
+    pub liquidity: u128,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    /// Pool liquidity at the current price, after the withdrawal.
+    pub range_liquidity: u128,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PositionFeesCollected {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub token_mint_x: Pubkey,
+    pub token_mint_y: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub fee_tiers: [u16; FEE_TIER_COUNT],
+    pub protocol_fee_bps: u16,
+    pub features: u8,
+    pub vk_versions: [u32; MAX_CIRCUITS],
+    pub slot: u64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PoolPauseUpdated {
+    pub pool: Pubkey,
+    pub paused: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AmpRampStarted {
+    pub pool: Pubkey,
+    pub amp_initial: u32,
+    pub amp_target: u32,
+    pub start_slot: u64,
+    pub end_slot: u64,
+}
+
+#[event]
+pub struct GlobalPauseUpdated {
+    pub paused: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VerifyingKeyInitialized {
+    pub verifying_key: Pubkey,
+    pub version: u32,
+    pub circuit_id: u8,
+    pub nr_pubinputs: u8,
+    pub hash: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct VerifyingKeyWritten {
+    pub verifying_key: Pubkey,
+    pub version: u32,
+    pub offset: u32,
+    pub len: u32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VerifyingKeyFinalized {
+    pub verifying_key: Pubkey,
+    pub version: u32,
+    pub hash: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct PoolVerifyingKeyUpdated {
+    pub pool: Pubkey,
+    pub circuit_id: u8,
+    pub vk_version: u32,
+    pub slot: u64,
 }
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Config;
+use crate::errors::ErrorCode;
+use crate::events::AdminAccepted;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == pending_admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub pending_admin: Signer<'info>,
+}
+
+impl<'info> AcceptAdmin<'info> {
+    pub fn accept_admin(&mut self) -> Result<()> {
+        let previous_admin = self.config.admin;
+
+        self.config.admin = self.pending_admin.key();
+        self.config.pending_admin = Pubkey::default();
+
+        emit!(AdminAccepted {
+            previous_admin,
+            admin: self.config.admin,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -3,12 +3,17 @@ use anchor_spl::token::{Mint as SplMint, Token as SplToken, MintTo as SplMintTo,
 use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
 use anchor_spl::associated_token::AssociatedToken;
 
-use crate::state::Pool;
+use crate::state::{Config, Pool};
 use crate::errors::ErrorCode;
+use crate::constants::PAUSE_DEPOSITS;
 use crate::events::LiquidityAdded;
+use crate::math::{deposit_amounts, initial_liquidity};
+use crate::stable_math::stable_deposit;
 
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
     pub token_mint_x: Box<InterfaceAccount<'info, Mint>>,
     pub token_mint_y: Box<InterfaceAccount<'info, Mint>>,
     pub token_mint_x_program: Interface<'info, TokenInterface>,
@@ -66,28 +71,59 @@ pub struct AddLiquidity<'info> {
 impl<'info> AddLiquidity<'info> {
     pub fn add_liquidity(
         &mut self,
-        amount_x: u64,
-        amount_y: u64,
+        amount_x_desired: u64,
+        amount_y_desired: u64,
+        min_liquidity_out: u64,
     ) -> Result<()> {
         // Add this check at the beginning of the function
         if self.token_mint_x.key() >= self.token_mint_y.key() {
             return Err(ErrorCode::InvalidTokenOrder.into());
         }
 
+        self.pool.require_concentrated(false)?;
+        self.pool.require_not_paused(&self.config, PAUSE_DEPOSITS)?;
+
         let pool = &mut self.pool;
         
-        // Calculate the liquidity to be added
-        let liquidity = if pool.reserve_x == 0 && pool.reserve_y == 0 {
-            (amount_x as u128 * amount_y as u128) as u64
+        // Calculate the liquidity to be added. The first deposit sets the pool ratio and
+        // also locks MINIMUM_LIQUIDITY, which is counted in the pool liquidity but never
+        // minted. Later deposits only take the amounts matching the pool ratio, except in
+        // StableSwap pools, which take the whole amounts and charge a fee on the imbalance.
+        let (added_liquidity, liquidity, amount_x, amount_y) = if pool.is_stable() {
+            let (liquidity, added_liquidity) = stable_deposit(
+                pool.amp(Clock::get()?.slot),
+                amount_x_desired,
+                amount_y_desired,
+                pool.reserve_x,
+                pool.reserve_y,
+                pool.liquidity,
+                pool.fee_bps,
+            )?;
+            (added_liquidity, liquidity, amount_x_desired, amount_y_desired)
+        } else if pool.liquidity == 0 {
+            let (added_liquidity, liquidity) = initial_liquidity(amount_x_desired, amount_y_desired)?;
+            (added_liquidity, liquidity, amount_x_desired, amount_y_desired)
         } else {
-            let liquidity_x = amount_x * pool.reserve_y / pool.reserve_x;
-            let liquidity_y = amount_y * pool.reserve_x / pool.reserve_y;
-            liquidity_x.min(liquidity_y)
+            let (liquidity, amount_x, amount_y) = deposit_amounts(
+                amount_x_desired,
+                amount_y_desired,
+                pool.reserve_x,
+                pool.reserve_y,
+                pool.liquidity,
+            )?;
+            (liquidity as u128, liquidity, amount_x, amount_y)
         };
 
+        msg!("Amount X: {} of {}", amount_x, amount_x_desired);
+        msg!("Amount Y: {} of {}", amount_y, amount_y_desired);
+
+        if liquidity < min_liquidity_out {
+            return Err(ErrorCode::SlippageExceeded.into());
+        }
+
         // Update pool reserves
-        pool.reserve_x = pool.reserve_x.checked_add(amount_x).unwrap();
-        pool.reserve_y = pool.reserve_y.checked_add(amount_y).unwrap();
+        pool.reserve_x = pool.reserve_x.checked_add(amount_x).ok_or(ErrorCode::MathOverflow)?;
+        pool.reserve_y = pool.reserve_y.checked_add(amount_y).ok_or(ErrorCode::MathOverflow)?;
 
         // Transfer tokens from user to pool
         transfer_checked(
@@ -146,13 +182,22 @@ impl<'info> AddLiquidity<'info> {
             liquidity,
         )?;
         
-        self.pool.liquidity = self.pool.liquidity.checked_add(liquidity.into()).unwrap();
+        self.pool.liquidity = self.pool.liquidity
+            .checked_add(added_liquidity)
+            .ok_or(ErrorCode::MathOverflow)?;
 
         emit!(LiquidityAdded {
+            pool: self.pool.key(),
             user: self.user.key(),
+            token_mint_x: token_mint_x_key,
+            token_mint_y: token_mint_y_key,
             amount_x,
             amount_y,
             liquidity,
+            reserve_x: self.pool.reserve_x,
+            reserve_y: self.pool.reserve_y,
+            lp_supply: self.pool.liquidity,
+            slot: Clock::get()?.slot,
         });
 
         Ok(())
@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::TokenAccount as SplTokenAccount;
+
+use crate::state::{Config, Pool, Position, TickArray};
+use crate::errors::ErrorCode;
+use crate::constants::PAUSE_WITHDRAWALS;
+use crate::events::PositionFeesCollected;
+use crate::concentrated::{fee_growth_inside, update_position_fees};
+
+#[derive(Accounts)]
+pub struct CollectPositionFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub token_mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_y: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_x_program: Interface<'info, TokenInterface>,
+    pub token_mint_y_program: Interface<'info, TokenInterface>,
+    #[account(mut,
+        seeds = [b"pool", pool.token_mint_x.key().as_ref(), pool.token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    #[account(mut, has_one = pool)]
+    pub position: Box<Account<'info, Position>>,
+    /// The caller's token account holding the position NFT, only needed once the position has
+    /// one.
+    pub position_nft_account: Option<Box<Account<'info, SplTokenAccount>>>,
+    /// Tick array holding the lower tick of the position.
+    #[account(constraint = tick_array_lower.pool == pool.key() @ ErrorCode::InvalidTickArray)]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+    /// Tick array holding the upper tick of the position, possibly `tick_array_lower`.
+    #[account(constraint = tick_array_upper.pool == pool.key() @ ErrorCode::InvalidTickArray)]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+    #[account(mut,
+        associated_token::mint = token_mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_mint_x_program.key(),
+    )]
+    pub user_token_account_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        associated_token::mint = token_mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_mint_y_program.key(),
+    )]
+    pub user_token_account_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        associated_token::mint = token_mint_x,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_x_program.key(),
+    )]
+    pub pool_token_account_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        associated_token::mint = token_mint_y,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_y_program.key(),
+    )]
+    pub pool_token_account_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CollectPositionFees<'info> {
+    /// Sends the LP fees earned by the position to its owner.
+    pub fn collect_position_fees(&mut self) -> Result<()> {
+        self.pool.require_concentrated(true)?;
+        self.position.require_owner(
+            &self.user.key(),
+            self.position_nft_account.as_deref().map(|account| &**account),
+        )?;
+        self.pool.require_not_paused(&self.config, PAUSE_WITHDRAWALS)?;
+
+        let (tick_lower, tick_upper) = (self.position.tick_lower, self.position.tick_upper);
+        let spacing = self.pool.tick_spacing;
+        let (inside_x, inside_y) = fee_growth_inside(
+            &self.pool,
+            tick_lower,
+            self.tick_array_lower.tick(tick_lower, spacing)?,
+            tick_upper,
+            self.tick_array_upper.tick(tick_upper, spacing)?,
+        );
+        update_position_fees(&mut self.position, inside_x, inside_y)?;
+
+        let amount_x = self.position.fees_owed_x;
+        let amount_y = self.position.fees_owed_y;
+        self.position.fees_owed_x = 0;
+        self.position.fees_owed_y = 0;
+
+        // The fees were kept in the reserves until now
+        self.pool.reserve_x = self.pool.reserve_x.checked_sub(amount_x).ok_or(ErrorCode::MathOverflow)?;
+        self.pool.reserve_y = self.pool.reserve_y.checked_sub(amount_y).ok_or(ErrorCode::MathOverflow)?;
+
+        let token_mint_x_key = self.pool.token_mint_x;
+        let token_mint_y_key = self.pool.token_mint_y;
+        let pool_signer_seeds = &[
+            b"pool",
+            token_mint_x_key.as_ref(),
+            token_mint_y_key.as_ref(),
+            &[self.pool.bump],
+        ];
+
+        if amount_x > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_mint_x_program.to_account_info(),
+                    TransferChecked {
+                        from: self.pool_token_account_x.to_account_info(),
+                        to: self.user_token_account_x.to_account_info(),
+                        authority: self.pool.to_account_info(),
+                        mint: self.token_mint_x.to_account_info(),
+                    },
+                    &[&pool_signer_seeds[..]],
+                ),
+                amount_x,
+                self.token_mint_x.decimals,
+            )?;
+        }
+
+        if amount_y > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_mint_y_program.to_account_info(),
+                    TransferChecked {
+                        from: self.pool_token_account_y.to_account_info(),
+                        to: self.user_token_account_y.to_account_info(),
+                        authority: self.pool.to_account_info(),
+                        mint: self.token_mint_y.to_account_info(),
+                    },
+                    &[&pool_signer_seeds[..]],
+                ),
+                amount_y,
+                self.token_mint_y.decimals,
+            )?;
+        }
+
+        emit!(PositionFeesCollected {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
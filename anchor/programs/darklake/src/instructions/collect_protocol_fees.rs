@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::associated_token::AssociatedToken;
+
+use crate::state::{Config, Pool};
+use crate::errors::ErrorCode;
+use crate::events::ProtocolFeesCollected;
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+        has_one = treasury @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub token_mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_y: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_x_program: Interface<'info, TokenInterface>,
+    pub token_mint_y_program: Interface<'info, TokenInterface>,
+    #[account(mut,
+        seeds = [b"pool", token_mint_x.key().as_ref(), token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut,
+        associated_token::mint = token_mint_x,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_x_program.key(),
+    )]
+    pub pool_token_account_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        associated_token::mint = token_mint_y,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_y_program.key(),
+    )]
+    pub pool_token_account_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Checked against the config, only used as the owner of the treasury token accounts.
+    pub treasury: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        associated_token::mint = token_mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_mint_x_program,
+        payer = admin
+    )]
+    pub treasury_token_account_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        associated_token::mint = token_mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_mint_y_program,
+        payer = admin
+    )]
+    pub treasury_token_account_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CollectProtocolFees<'info> {
+    pub fn collect_protocol_fees(&mut self) -> Result<()> {
+        let amount_x = self.pool.protocol_fees_x;
+        let amount_y = self.pool.protocol_fees_y;
+
+        msg!("Collecting protocol fees");
+        msg!("Amount X: {}", amount_x);
+        msg!("Amount Y: {}", amount_y);
+
+        let token_mint_x_key = self.token_mint_x.key();
+        let token_mint_y_key = self.token_mint_y.key();
+
+        let pool_signer_seeds = &[
+            b"pool",
+            token_mint_x_key.as_ref(),
+            token_mint_y_key.as_ref(),
+            &[self.pool.bump],
+        ];
+
+        if amount_x > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_mint_x_program.to_account_info(),
+                    TransferChecked {
+                        from: self.pool_token_account_x.to_account_info(),
+                        to: self.treasury_token_account_x.to_account_info(),
+                        authority: self.pool.to_account_info(),
+                        mint: self.token_mint_x.to_account_info(),
+                    },
+                    &[&pool_signer_seeds[..]],
+                ),
+                amount_x,
+                self.token_mint_x.decimals,
+            )?;
+        }
+
+        if amount_y > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_mint_y_program.to_account_info(),
+                    TransferChecked {
+                        from: self.pool_token_account_y.to_account_info(),
+                        to: self.treasury_token_account_y.to_account_info(),
+                        authority: self.pool.to_account_info(),
+                        mint: self.token_mint_y.to_account_info(),
+                    },
+                    &[&pool_signer_seeds[..]],
+                ),
+                amount_y,
+                self.token_mint_y.decimals,
+            )?;
+        }
+
+        self.pool.protocol_fees_x = 0;
+        self.pool.protocol_fees_y = 0;
+
+        emit!(ProtocolFeesCollected {
+            pool: self.pool.key(),
+            treasury: self.treasury.key(),
+            token_mint_x: token_mint_x_key,
+            token_mint_y: token_mint_y_key,
+            amount_x,
+            amount_y,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+use crate::circuits::{RangeSwapBindings, SwapCircuit};
+use crate::concentrated::{apply_range_swap, compute_range_swap};
+use crate::constants::PAUSE_SWAPS;
+use crate::errors::ErrorCode;
+use crate::events::ConfidentialRangeSwapEvent;
+use crate::instructions::{
+    check_proof_expiry, spend_nullifier, unspent_nullifier_bump, user_hash, ConfidentialSwap,
+};
+use crate::state::TickArray;
+
+/// Loads the tick arrays of a swap through `pool`, in the order given. Whether they cover
+/// the swap is checked by the swap itself.
+pub fn load_tick_arrays<'info>(
+    pool: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<Vec<Account<'info, TickArray>>> {
+    accounts
+        .iter()
+        .map(|account| {
+            let tick_array = Account::<TickArray>::try_from(account)?;
+            if tick_array.pool != *pool {
+                return Err(ErrorCode::InvalidTickArray.into());
+            }
+            Ok(tick_array)
+        })
+        .collect()
+}
+
+impl<'info> ConfidentialSwap<'info> {
+    /// Swaps through the ticks of a concentrated pool with a proof of the range swap circuit.
+    ///
+    /// The circuit proves the swap at the price and liquidity the user quoted against, within
+    /// the range of the current tick. The program runs the swap itself on the proven input and
+    /// requires the amount it pays out to be exactly the proven `amountReceived`, which fails
+    /// for swaps crossing an initialized tick.
+    #[allow(clippy::too_many_arguments)]
+    pub fn confidential_range_swap(
+        &mut self,
+        tick_arrays: &'info [AccountInfo<'info>],
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+        public_inputs: Vec<[u8; 32]>,
+        is_swap_x_to_y: bool,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        let circuit = SwapCircuit::RangeSwap;
+
+        if self.token_mint_x.key() >= self.token_mint_y.key() {
+            return Err(ErrorCode::InvalidTokenOrder.into());
+        }
+
+        self.pool.require_concentrated(true)?;
+        self.pool.require_not_paused(&self.config, PAUSE_SWAPS)?;
+        check_proof_expiry(expiry_slot, Clock::get()?.slot)?;
+
+        if self.verifying_key.circuit_id != circuit.id()
+            || self.verifying_key.version != self.pool.vk_versions[circuit.id() as usize]
+        {
+            return Err(ErrorCode::InvalidVerifyingKey.into());
+        }
+
+        let outputs = circuit.decode_range_swap_outputs(&public_inputs)?;
+        let nullifier_bump = unspent_nullifier_bump(&self.nullifier, &outputs.nullifier)?;
+
+        let destination = if is_swap_x_to_y {
+            self.user_token_account_y.key()
+        } else {
+            self.user_token_account_x.key()
+        };
+        let bindings = RangeSwapBindings {
+            sqrt_price_x64: self.pool.sqrt_price_x64,
+            liquidity: self.pool.range_liquidity,
+            is_swap_x_to_y,
+            fee_bps: self.pool.fee_bps,
+            protocol_fee_bps: self.pool.protocol_fee_bps,
+            user_hash: user_hash(&self.user.key(), &destination),
+            expiry_slot,
+        };
+        let public_signals = circuit.range_swap_public_signals_for(&outputs, &bindings)?;
+
+        let vk_ic = self.verifying_key.ic();
+        let verifying_key = self.verifying_key.groth16(&vk_ic);
+        if !circuit.verify(&proof_a, &proof_b, &proof_c, &public_signals, &verifying_key)? {
+            return Err(ErrorCode::InvalidProof.into());
+        }
+
+        let pool_key = self.pool.key();
+        let mut tick_array_accounts = load_tick_arrays(&pool_key, tick_arrays)?;
+        let mut ticks: Vec<TickArray> = tick_array_accounts
+            .iter()
+            .map(|tick_array| (**tick_array).clone())
+            .collect();
+        let swap = compute_range_swap(&self.pool, &mut ticks, outputs.amount_in, is_swap_x_to_y)?;
+        if swap.amount_out != outputs.amount_received {
+            return Err(ErrorCode::InvalidSwapAmount.into());
+        }
+
+        spend_nullifier(
+            &self.nullifier,
+            &self.user,
+            &self.system_program,
+            pool_key,
+            &outputs.nullifier,
+            nullifier_bump,
+        )?;
+
+        // Crossed ticks flipped their fee checkpoints
+        for (account, tick_array) in tick_array_accounts.iter_mut().zip(ticks) {
+            account.ticks = tick_array.ticks;
+            account.exit(&crate::ID)?;
+        }
+
+        apply_range_swap(&mut self.pool, &swap, is_swap_x_to_y)?;
+
+        self.transfer_swap_amounts(is_swap_x_to_y, swap.amount_in, swap.amount_out)?;
+
+        emit!(ConfidentialRangeSwapEvent {
+            pool: pool_key,
+            user: self.user.key(),
+            token_mint_x: self.pool.token_mint_x,
+            token_mint_y: self.pool.token_mint_y,
+            is_swap_x_to_y,
+            amount_in: swap.amount_in,
+            amount_out: swap.amount_out,
+            protocol_fee: swap.protocol_fee,
+            nullifier: outputs.nullifier,
+            sqrt_price_x64: self.pool.sqrt_price_x64,
+            tick_current: self.pool.tick_current,
+            liquidity: self.pool.range_liquidity,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
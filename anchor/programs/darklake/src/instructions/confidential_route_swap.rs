@@ -0,0 +1,376 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked};
+
+use crate::state::{Config, Pool, VerifyingKey};
+use crate::errors::ErrorCode;
+use crate::constants::PAUSE_SWAPS;
+use crate::events::ConfidentialRouteSwapEvent;
+use crate::circuits::{HopBindings, HopOutputs, RouteBindings, SwapCircuit};
+use crate::instructions::{
+    check_proof_expiry, check_swap_reserves, spend_nullifier, unspent_nullifier_bump, user_hash,
+};
+
+/// Remaining accounts taken by every pool of a route, in order: the pool, its token accounts
+/// for the mint it takes in and the mint it pays out, the mint it pays out and the token
+/// program of that mint.
+pub const ROUTE_ACCOUNTS_PER_HOP: usize = 5;
+
+/// Checks the new reserves proven for every pool of a route, in route order, and that each
+/// pool takes in exactly what the previous one pays out.
+///
+/// Returns the amount the user sends to the first pool, protocol fee included, and the
+/// amount each pool pays out. The last one must be `amount_received`.
+pub fn check_route_reserves(
+    bindings: &[HopBindings],
+    outputs: &[HopOutputs],
+    amount_received: u64,
+) -> Result<(u64, Vec<u64>)> {
+    if bindings.is_empty() || bindings.len() != outputs.len() {
+        return Err(ErrorCode::InvalidRoute.into());
+    }
+
+    let mut amount_in = 0;
+    let mut amounts_out: Vec<u64> = Vec::with_capacity(outputs.len());
+    for (hop, (binding, output)) in bindings.iter().zip(outputs).enumerate() {
+        let (reserve_in, new_reserve_in, reserve_out, new_reserve_out) = if binding.is_swap_x_to_y {
+            (binding.reserve_x, output.new_balance_x, binding.reserve_y, output.new_balance_y)
+        } else {
+            (binding.reserve_y, output.new_balance_y, binding.reserve_x, output.new_balance_x)
+        };
+
+        let amount_out = reserve_out
+            .checked_sub(new_reserve_out)
+            .ok_or(ErrorCode::InvalidSwapAmount)?;
+        check_swap_reserves(
+            binding.reserve_x,
+            binding.reserve_y,
+            output.new_balance_x,
+            output.new_balance_y,
+            amount_out,
+            output.protocol_fee,
+            binding.is_swap_x_to_y,
+            binding.fee_bps,
+        )?;
+
+        // Like a single swap, the input is the reserve increase plus the protocol fee
+        let hop_amount_in = new_reserve_in
+            .checked_sub(reserve_in)
+            .ok_or(ErrorCode::InvalidSwapAmount)?
+            .checked_add(output.protocol_fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        match hop {
+            0 => amount_in = hop_amount_in,
+            _ => {
+                if amounts_out[hop - 1] != hop_amount_in {
+                    return Err(ErrorCode::InvalidSwapAmount.into());
+                }
+            }
+        }
+        amounts_out.push(amount_out);
+    }
+
+    if amount_in == 0 || amounts_out.last() != Some(&amount_received) {
+        return Err(ErrorCode::InvalidSwapAmount.into());
+    }
+
+    Ok((amount_in, amounts_out))
+}
+
+#[derive(Accounts)]
+pub struct ConfidentialRouteSwap<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+    pub token_mint_in_program: Interface<'info, TokenInterface>,
+    pub token_mint_out_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [b"verifying_key", verifying_key.version.to_le_bytes().as_ref()],
+        bump = verifying_key.bump,
+        constraint = verifying_key.finalized @ ErrorCode::VerifyingKeyNotFinalized
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = user,
+        associated_token::token_program = token_mint_in_program.key(),
+    )]
+    pub user_token_account_in: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = user,
+        associated_token::token_program = token_mint_out_program.key(),
+    )]
+    pub user_token_account_out: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA of the proof nullifier, checked and created in the instruction handler.
+    /// An existing account means the proof was already used.
+    #[account(mut)]
+    pub nullifier: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// One pool of a route, loaded from its `ROUTE_ACCOUNTS_PER_HOP` remaining accounts.
+struct RouteHop<'info> {
+    pool: Account<'info, Pool>,
+    pool_token_account_in: &'info AccountInfo<'info>,
+    pool_token_account_out: &'info AccountInfo<'info>,
+    mint_out: InterfaceAccount<'info, Mint>,
+    token_program_out: &'info AccountInfo<'info>,
+    is_swap_x_to_y: bool,
+}
+
+impl<'info> RouteHop<'info> {
+    /// Loads a pool taking in `mint_in`, owned by `token_program_in`, checking every account
+    /// is the one the pool and the mints derive.
+    fn load(
+        accounts: &'info [AccountInfo<'info>],
+        mint_in: &Pubkey,
+        token_program_in: &Pubkey,
+    ) -> Result<Self> {
+        let [pool, pool_token_account_in, pool_token_account_out, mint_out, token_program_out] =
+            accounts
+        else {
+            return Err(ErrorCode::InvalidRoute.into());
+        };
+
+        let pool_account = Account::<Pool>::try_from(pool)?;
+        let pool_address = Pubkey::create_program_address(
+            &[
+                b"pool",
+                pool_account.token_mint_x.as_ref(),
+                pool_account.token_mint_y.as_ref(),
+                &[pool_account.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::InvalidRoute)?;
+        if pool_address != pool.key() {
+            return Err(ErrorCode::InvalidRoute.into());
+        }
+        pool_account.require_concentrated(false)?;
+        pool_account.require_stable(false)?;
+
+        // The direction follows from the mint the pool takes in
+        let is_swap_x_to_y = if *mint_in == pool_account.token_mint_x {
+            true
+        } else if *mint_in == pool_account.token_mint_y {
+            false
+        } else {
+            return Err(ErrorCode::InvalidRoute.into());
+        };
+        let expected_mint_out = if is_swap_x_to_y {
+            pool_account.token_mint_y
+        } else {
+            pool_account.token_mint_x
+        };
+        if mint_out.key() != expected_mint_out || mint_out.owner != token_program_out.key {
+            return Err(ErrorCode::InvalidRoute.into());
+        }
+        let mint_out_account = InterfaceAccount::<Mint>::try_from(mint_out)?;
+
+        if pool_token_account_in.key()
+            != get_associated_token_address_with_program_id(&pool.key(), mint_in, token_program_in)
+            || pool_token_account_out.key()
+                != get_associated_token_address_with_program_id(
+                    &pool.key(),
+                    &expected_mint_out,
+                    token_program_out.key,
+                )
+        {
+            return Err(ErrorCode::InvalidRoute.into());
+        }
+
+        Ok(RouteHop {
+            pool: pool_account,
+            pool_token_account_in,
+            pool_token_account_out,
+            mint_out: mint_out_account,
+            token_program_out,
+            is_swap_x_to_y,
+        })
+    }
+
+    fn bindings(&self) -> HopBindings {
+        HopBindings {
+            reserve_x: self.pool.reserve_x,
+            reserve_y: self.pool.reserve_y,
+            is_swap_x_to_y: self.is_swap_x_to_y,
+            fee_bps: self.pool.fee_bps,
+            protocol_fee_bps: self.pool.protocol_fee_bps,
+        }
+    }
+
+    /// Applies the proven balances, the protocol fee is tracked outside of the reserves.
+    fn apply(&mut self, outputs: &HopOutputs) -> Result<()> {
+        self.pool.reserve_x = outputs.new_balance_x;
+        self.pool.reserve_y = outputs.new_balance_y;
+        if self.is_swap_x_to_y {
+            self.pool.protocol_fees_x = self.pool.protocol_fees_x
+                .checked_add(outputs.protocol_fee)
+                .ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            self.pool.protocol_fees_y = self.pool.protocol_fees_y
+                .checked_add(outputs.protocol_fee)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Pays `amount` of the output mint out of the pool to `to`.
+    fn pay_out(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let pool_seeds = &[
+            &b"pool"[..],
+            self.pool.token_mint_x.as_ref(),
+            self.pool.token_mint_y.as_ref(),
+            &[self.pool.bump],
+        ];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program_out.clone(),
+                TransferChecked {
+                    from: self.pool_token_account_out.clone(),
+                    mint: self.mint_out.to_account_info(),
+                    to: to.clone(),
+                    authority: self.pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            amount,
+            self.mint_out.decimals,
+        )
+    }
+}
+
+impl<'info> ConfidentialRouteSwap<'info> {
+    /// Swaps through the pools in `route`, `ROUTE_ACCOUNTS_PER_HOP` accounts each, with a
+    /// single proof of the route circuit. Only the first pool takes from the user and only
+    /// the last one pays the user, the pools in between pay each other.
+    pub fn confidential_route_swap(
+        &mut self,
+        route: &'info [AccountInfo<'info>],
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+        public_inputs: Vec<[u8; 32]>,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        let circuit = SwapCircuit::ConstantProductRoute;
+        check_proof_expiry(expiry_slot, Clock::get()?.slot)?;
+
+        if route.len() != circuit.hops() * ROUTE_ACCOUNTS_PER_HOP {
+            return Err(ErrorCode::InvalidRoute.into());
+        }
+
+        // Each pool takes in the mint the previous one pays out, the first one the user input
+        let mut hops: Vec<RouteHop<'info>> = Vec::with_capacity(circuit.hops());
+        let mut mint_in = self.token_mint_in.key();
+        let mut token_program_in = self.token_mint_in_program.key();
+        for accounts in route.chunks(ROUTE_ACCOUNTS_PER_HOP) {
+            let hop = RouteHop::load(accounts, &mint_in, &token_program_in)?;
+            hop.pool.require_not_paused(&self.config, PAUSE_SWAPS)?;
+
+            // Every pool must accept the key, and no pool can be swapped against twice
+            // with the same reserves
+            if self.verifying_key.circuit_id != circuit.id()
+                || self.verifying_key.version != hop.pool.vk_versions[circuit.id() as usize]
+            {
+                return Err(ErrorCode::InvalidVerifyingKey.into());
+            }
+            if hops.iter().any(|other| other.pool.key() == hop.pool.key()) {
+                return Err(ErrorCode::InvalidRoute.into());
+            }
+
+            mint_in = hop.mint_out.key();
+            token_program_in = hop.token_program_out.key();
+            hops.push(hop);
+        }
+        if mint_in != self.token_mint_out.key() {
+            return Err(ErrorCode::InvalidRoute.into());
+        }
+
+        let outputs = circuit.decode_route_outputs(&public_inputs)?;
+
+        // Reject a replayed proof before paying for its verification
+        let nullifier_bump = unspent_nullifier_bump(&self.nullifier, &outputs.nullifier)?;
+
+        // The proof must have been made against the reserves currently held by every pool,
+        // for this signer and the account receiving the output
+        let bindings = RouteBindings {
+            hops: hops.iter().map(RouteHop::bindings).collect(),
+            user_hash: user_hash(&self.user.key(), &self.user_token_account_out.key()),
+            expiry_slot,
+        };
+        let public_signals = circuit.route_public_signals_for(&outputs, &bindings)?;
+
+        let vk_ic = self.verifying_key.ic();
+        let verifying_key = self.verifying_key.groth16(&vk_ic);
+        if !circuit.verify(&proof_a, &proof_b, &proof_c, &public_signals, &verifying_key)? {
+            return Err(ErrorCode::InvalidProof.into());
+        }
+
+        let (amount_in, amounts_out) =
+            check_route_reserves(&bindings.hops, &outputs.hops, outputs.amount_received)?;
+
+        spend_nullifier(
+            &self.nullifier,
+            &self.user,
+            &self.system_program,
+            hops[0].pool.key(),
+            &outputs.nullifier,
+            nullifier_bump,
+        )?;
+
+        for (hop, hop_outputs) in hops.iter_mut().zip(&outputs.hops) {
+            hop.apply(hop_outputs)?;
+        }
+
+        // The user pays the first pool, every pool pays the next one and the last one pays
+        // the user
+        transfer_checked(
+            CpiContext::new(
+                self.token_mint_in_program.to_account_info(),
+                TransferChecked {
+                    from: self.user_token_account_in.to_account_info(),
+                    mint: self.token_mint_in.to_account_info(),
+                    to: hops[0].pool_token_account_in.clone(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount_in,
+            self.token_mint_in.decimals,
+        )?;
+        for (hop, amount_out) in amounts_out.iter().enumerate() {
+            let to = match hops.get(hop + 1) {
+                Some(next) => next.pool_token_account_in.clone(),
+                None => self.user_token_account_out.to_account_info(),
+            };
+            hops[hop].pay_out(&to, *amount_out)?;
+        }
+
+        for hop in &hops {
+            hop.pool.exit(&crate::ID)?;
+        }
+
+        emit!(ConfidentialRouteSwapEvent {
+            user: self.user.key(),
+            pools: hops.iter().map(|hop| hop.pool.key()).collect(),
+            token_mint_in: self.token_mint_in.key(),
+            token_mint_out: self.token_mint_out.key(),
+            amount_in,
+            amount_out: outputs.amount_received,
+            protocol_fees: outputs.hops.iter().map(|hop| hop.protocol_fee).collect(),
+            nullifier: outputs.nullifier,
+            reserves_x: hops.iter().map(|hop| hop.pool.reserve_x).collect(),
+            reserves_y: hops.iter().map(|hop| hop.pool.reserve_y).collect(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -1,14 +1,144 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program::{allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked};
 use anchor_spl::associated_token::AssociatedToken;
-use groth16_solana::{self, groth16::Groth16Verifier};
 
-use crate::state::Pool;
+use crate::state::{Config, Nullifier, Pool, VerifyingKey};
 use crate::errors::ErrorCode;
-use crate::constants::VERIFYINGKEY;
+use crate::constants::{FEE_DENOMINATOR, PAUSE_SWAPS};
+use crate::events::ConfidentialSwapEvent;
+use crate::circuits::{SwapBindings, SwapCircuit, SwapOutputs};
+use crate::math::{to_u64, widening_mul};
+use crate::stable_math::compute_d;
+
+/// Checks the new reserves proven by the circuit before they replace the pool reserves.
+///
+/// The input side can only grow and the output side must drop by exactly the amount paid
+/// out. The input reserve only keeps the LP share of the fee, so the amount the user sent is
+/// `new_in - reserve_in + protocol_fee`. The invariant is checked on the fee-adjusted input
+/// the way Uniswap V2 does:
+/// `(reserve_in * 10000 + amount_in * (10000 - fee_bps)) * new_out >= reserve_in * reserve_out * 10000`,
+/// so a proof can never pay out more than the curve allows on the input net of the fee.
+#[allow(clippy::too_many_arguments)]
+pub fn check_swap_reserves(
+    reserve_x: u64,
+    reserve_y: u64,
+    new_balance_x: u64,
+    new_balance_y: u64,
+    amount_received: u64,
+    protocol_fee: u64,
+    is_swap_x_to_y: bool,
+    fee_bps: u16,
+) -> Result<()> {
+    let (reserve_in, new_reserve_in, reserve_out, new_reserve_out) = check_swap_flows(
+        reserve_x,
+        reserve_y,
+        new_balance_x,
+        new_balance_y,
+        amount_received,
+        is_swap_x_to_y,
+    )?;
+
+    let amount_in = (new_reserve_in - reserve_in) as u128 + protocol_fee as u128;
+    let adjusted_in = reserve_in as u128 * FEE_DENOMINATOR as u128
+        + amount_in * (FEE_DENOMINATOR - fee_bps as u64) as u128;
+    let invariant_after = widening_mul(adjusted_in, new_reserve_out);
+    let invariant_before =
+        widening_mul(reserve_in as u128 * FEE_DENOMINATOR as u128, reserve_out);
+    if invariant_after < invariant_before {
+        return Err(ErrorCode::InvariantViolated.into());
+    }
+
+    Ok(())
+}
+
+/// Checks the new reserves of a StableSwap pool like `check_swap_reserves`, with the StableSwap
+/// invariant under `amp` in place of the constant product. Like the circuit, the invariant is
+/// kept at the input reserve plus the input net of the fee, rounded down:
+/// `D(reserve_in + amount_in * (10000 - fee_bps) / 10000, new_out) >= D(reserve_x, reserve_y)`.
+/// A balanced StableSwap pool can lose constant product on a swap, never invariant.
+#[allow(clippy::too_many_arguments)]
+pub fn check_stable_swap_reserves(
+    reserve_x: u64,
+    reserve_y: u64,
+    new_balance_x: u64,
+    new_balance_y: u64,
+    amount_received: u64,
+    protocol_fee: u64,
+    is_swap_x_to_y: bool,
+    fee_bps: u16,
+    amp: u64,
+) -> Result<()> {
+    let (reserve_in, new_reserve_in, _, new_reserve_out) = check_swap_flows(
+        reserve_x,
+        reserve_y,
+        new_balance_x,
+        new_balance_y,
+        amount_received,
+        is_swap_x_to_y,
+    )?;
+
+    let amount_in = (new_reserve_in - reserve_in) as u128 + protocol_fee as u128;
+    let amount_in_after_fee = amount_in * (FEE_DENOMINATOR - fee_bps as u64) as u128
+        / FEE_DENOMINATOR as u128;
+    let adjusted_in = to_u64(reserve_in as u128 + amount_in_after_fee)?;
+    // The invariant is symmetric in the two balances
+    if compute_d(amp, adjusted_in, new_reserve_out)? < compute_d(amp, reserve_x, reserve_y)? {
+        return Err(ErrorCode::InvariantViolated.into());
+    }
+
+    Ok(())
+}
+
+/// Checks the input reserve grows and the output reserve drops by exactly `amount_received`,
+/// returning the reserves as `(reserve_in, new_reserve_in, reserve_out, new_reserve_out)`.
+fn check_swap_flows(
+    reserve_x: u64,
+    reserve_y: u64,
+    new_balance_x: u64,
+    new_balance_y: u64,
+    amount_received: u64,
+    is_swap_x_to_y: bool,
+) -> Result<(u64, u64, u64, u64)> {
+    let (reserve_in, new_reserve_in, reserve_out, new_reserve_out) = if is_swap_x_to_y {
+        (reserve_x, new_balance_x, reserve_y, new_balance_y)
+    } else {
+        (reserve_y, new_balance_y, reserve_x, new_balance_x)
+    };
+
+    if new_reserve_in < reserve_in {
+        return Err(ErrorCode::InvalidSwapAmount.into());
+    }
+
+    if reserve_out.checked_sub(new_reserve_out) != Some(amount_received) {
+        return Err(ErrorCode::InvalidSwapAmount.into());
+    }
+
+    Ok((reserve_in, new_reserve_in, reserve_out, new_reserve_out))
+}
+
+/// Checks a proof valid up to and including `expiry_slot` can still be used at `current_slot`.
+pub fn check_proof_expiry(expiry_slot: u64, current_slot: u64) -> Result<()> {
+    if current_slot > expiry_slot {
+        return Err(ErrorCode::ProofExpired.into());
+    }
+    Ok(())
+}
+
+/// Binds a proof to the swapping wallet and the token account receiving the output:
+/// `sha256(user || destination)` with the top byte cleared, so it always fits in the
+/// BN254 scalar field.
+pub fn user_hash(user: &Pubkey, destination: &Pubkey) -> [u8; 32] {
+    let mut hash = hashv(&[user.as_ref(), destination.as_ref()]).to_bytes();
+    hash[0] = 0;
+    hash
+}
 
 #[derive(Accounts)]
 pub struct ConfidentialSwap<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
     #[account(mut)]
     pub token_mint_x: InterfaceAccount<'info, Mint>,
     #[account(mut)]
@@ -20,6 +150,12 @@ pub struct ConfidentialSwap<'info> {
         bump
     )]
     pub pool: Account<'info, Pool>,
+    #[account(
+        seeds = [b"verifying_key", verifying_key.version.to_le_bytes().as_ref()],
+        bump = verifying_key.bump,
+        constraint = verifying_key.finalized @ ErrorCode::VerifyingKeyNotFinalized
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
     #[account(mut,
         associated_token::mint = token_mint_x,
         associated_token::authority = user,
@@ -44,156 +180,404 @@ pub struct ConfidentialSwap<'info> {
         associated_token::token_program = token_mint_y_program.key(),
     )]
     pub pool_token_account_y: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA of the proof nullifier, checked and created in the instruction handler.
+    /// An existing account means the proof was already used.
+    #[account(mut)]
+    pub nullifier: UncheckedAccount<'info>,
+    #[account(mut)]
     pub user: Signer<'info>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> ConfidentialSwap<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn confidential_swap(
         &mut self,
+        circuit_id: u8,
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+        public_inputs: Vec<[u8; 32]>,
+        is_swap_x_to_y: bool,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        // Exact output, route and range proofs have their own instructions
+        let circuit = SwapCircuit::from_id(circuit_id)?;
+        if circuit != SwapCircuit::ConstantProduct && circuit != SwapCircuit::StableSwap {
+            return Err(ErrorCode::UnknownCircuit.into());
+        }
+
+        self.swap(
+            circuit,
+            proof_a,
+            proof_b,
+            proof_c,
+            public_inputs,
+            is_swap_x_to_y,
+            expiry_slot,
+        )
+    }
+
+    /// Verifies a proof of `circuit` against the pool and moves the proven amounts. Every swap
+    /// circuit proves the same outputs, only how the user picks the amounts differs.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn swap(
+        &mut self,
+        circuit: SwapCircuit,
         proof_a: [u8; 64],
         proof_b: [u8; 128],
         proof_c: [u8; 64],
-        public_signals: [[u8; 32]; 3],
+        public_inputs: Vec<[u8; 32]>,
+        is_swap_x_to_y: bool,
+        expiry_slot: u64,
     ) -> Result<()> {
         // Check at the beginning of the function
         if self.token_mint_x.key() >= self.token_mint_y.key() {
             return Err(ErrorCode::InvalidTokenOrder.into());
         }
 
+        // StableSwap pools only take proofs of their own circuit
+        let is_stable = circuit == SwapCircuit::StableSwap;
+        self.pool.require_concentrated(false)?;
+        self.pool.require_stable(is_stable)?;
+        self.pool.require_not_paused(&self.config, PAUSE_SWAPS)?;
+        let slot = Clock::get()?.slot;
+        check_proof_expiry(expiry_slot, slot)?;
+
+        // The key must be the version of this circuit the pool accepts
+        if self.verifying_key.circuit_id != circuit.id()
+            || self.verifying_key.version != self.pool.vk_versions[circuit.id() as usize]
+        {
+            return Err(ErrorCode::InvalidVerifyingKey.into());
+        }
+
         msg!("Confidential swap started");
 
-        // Create a new Groth16Verifier instance
-        let mut verifier_result = Groth16Verifier::new(
-            &proof_a,
-            &proof_b,
-            &proof_c,
-            &public_signals,
-            &VERIFYINGKEY,
-        ).map_err(|_| ErrorCode::InvalidGroth16Verifier)?;
+        // Extract values from the circuit outputs
+        let outputs = circuit.decode_outputs(&public_inputs)?;
+        let SwapOutputs {
+            new_balance_x,
+            new_balance_y,
+            amount_received,
+            protocol_fee,
+            nullifier,
+        } = outputs;
+
+        // Reject a replayed proof before paying for its verification
+        let nullifier_bump = unspent_nullifier_bump(&self.nullifier, &nullifier)?;
+
+        // The proof must have been made against the reserves currently held by the pool,
+        // for this signer and the account receiving the output, with the direction and
+        // expiry it commits to
+        let destination = if is_swap_x_to_y {
+            self.user_token_account_y.key()
+        } else {
+            self.user_token_account_x.key()
+        };
+        let bindings = SwapBindings {
+            reserve_x: self.pool.reserve_x,
+            reserve_y: self.pool.reserve_y,
+            is_swap_x_to_y,
+            fee_bps: self.pool.fee_bps,
+            protocol_fee_bps: self.pool.protocol_fee_bps,
+            user_hash: user_hash(&self.user.key(), &destination),
+            expiry_slot,
+            amp: if is_stable { self.pool.amp(slot) } else { 0 },
+        };
+        let public_signals = circuit.public_signals_for(&outputs, &bindings)?;
 
         // Verify the proof
-        let verified = verifier_result.verify().map_err(|_| ErrorCode::InvalidProof)?;
+        let vk_ic = self.verifying_key.ic();
+        let verifying_key = self.verifying_key.groth16(&vk_ic);
+        let verified =
+            circuit.verify(&proof_a, &proof_b, &proof_c, &public_signals, &verifying_key)?;
 
         if verified {
-            // Extract values from public inputs
-            let new_balance_x = u64::from_be_bytes(public_signals[0][24..].try_into().unwrap());
-            let new_balance_y = u64::from_be_bytes(public_signals[1][24..].try_into().unwrap());
-            let amount_received = u64::from_be_bytes(public_signals[2][24..].try_into().unwrap());
+            if is_stable {
+                check_stable_swap_reserves(
+                    self.pool.reserve_x,
+                    self.pool.reserve_y,
+                    new_balance_x,
+                    new_balance_y,
+                    amount_received,
+                    protocol_fee,
+                    is_swap_x_to_y,
+                    self.pool.fee_bps,
+                    bindings.amp,
+                )?;
+            } else {
+                check_swap_reserves(
+                    self.pool.reserve_x,
+                    self.pool.reserve_y,
+                    new_balance_x,
+                    new_balance_y,
+                    amount_received,
+                    protocol_fee,
+                    is_swap_x_to_y,
+                    self.pool.fee_bps,
+                )?;
+            }
+
+            spend_nullifier(
+                &self.nullifier,
+                &self.user,
+                &self.system_program,
+                self.pool.key(),
+                &nullifier,
+                nullifier_bump,
+            )?;
 
-            let is_swap_x_to_y = self.pool.reserve_y > new_balance_y;
-            
             msg!("New balance x: {}", new_balance_x);
             msg!("New balance y: {}", new_balance_y);
             msg!("Amount received: {}", amount_received);
+            msg!("Protocol fee: {}", protocol_fee);
             msg!("Is swap X to Y: {}", is_swap_x_to_y);
             
-            // Determine swap direction and calculate amount_sent
-            let (from_user_account, to_pool_account, from_pool_account, to_user_account, from_mint, to_mint, amount_sent, from_token_program, to_token_program) = if is_swap_x_to_y {
-                (
-                    &self.user_token_account_x,
-                    &self.pool_token_account_x,
-                    &self.pool_token_account_y,
-                    &self.user_token_account_y,
-                    &self.token_mint_x,
-                    &self.token_mint_y,
-                    new_balance_x - self.pool.reserve_x,
-                    &self.token_mint_x_program,
-                    &self.token_mint_y_program,
-                )
+            // The user sends the input reserve increase plus the protocol fee, which is
+            // kept outside of the reserves
+            let (reserve_in, new_reserve_in) = if is_swap_x_to_y {
+                (self.pool.reserve_x, new_balance_x)
             } else {
-                (
-                    &self.user_token_account_y,
-                    &self.pool_token_account_y,
-                    &self.pool_token_account_x,
-                    &self.user_token_account_x,
-                    &self.token_mint_y,
-                    &self.token_mint_x,
-                    new_balance_y - self.pool.reserve_y,
-                    &self.token_mint_y_program,
-                    &self.token_mint_x_program,
-                )
+                (self.pool.reserve_y, new_balance_y)
             };
+            let amount_sent = new_reserve_in
+                .checked_sub(reserve_in)
+                .ok_or(ErrorCode::InvalidSwapAmount)?
+                .checked_add(protocol_fee)
+                .ok_or(ErrorCode::MathOverflow)?;
 
             // Ensure amount_sent is positive
-            if amount_sent <= 0 {
+            if amount_sent == 0 {
                 return Err(ErrorCode::InvalidSwapAmount.into());
             }
 
-            // Update pool reserves
+            // Update pool reserves, the protocol fee is tracked outside of them
             self.pool.reserve_x = new_balance_x;
             self.pool.reserve_y = new_balance_y;
+            if is_swap_x_to_y {
+                self.pool.protocol_fees_x = self.pool.protocol_fees_x
+                    .checked_add(protocol_fee)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            } else {
+                self.pool.protocol_fees_y = self.pool.protocol_fees_y
+                    .checked_add(protocol_fee)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            }
 
             let pool_token_mint_key_x = self.pool.token_mint_x.key();
             let pool_token_mint_key_y = self.pool.token_mint_y.key();
 
-            let pool_seeds = &[
-                &b"pool"[..], 
-                pool_token_mint_key_x.as_ref(), 
-                pool_token_mint_key_y.as_ref(),
-                &[self.pool.bump],
-            ];
+            self.transfer_swap_amounts(is_swap_x_to_y, amount_sent, amount_received)?;
 
-            let signer_seeds = &[&pool_seeds[..]];
+            emit!(ConfidentialSwapEvent {
+                pool: self.pool.key(),
+                user: self.user.key(),
+                token_mint_x: pool_token_mint_key_x,
+                token_mint_y: pool_token_mint_key_y,
+                is_swap_x_to_y,
+                amount_in: amount_sent,
+                amount_out: amount_received,
+                protocol_fee,
+                nullifier,
+                reserve_x: self.pool.reserve_x,
+                reserve_y: self.pool.reserve_y,
+                lp_supply: self.pool.liquidity,
+                slot: Clock::get()?.slot,
+            });
 
-            msg!("Performing token transfers");
-            
-            // Add these debug messages before the transfers
-            msg!("Amount sent: {}", amount_sent);
-            msg!("From user account balance: {}", from_user_account.amount);
-            msg!("To pool account balance: {}", to_pool_account.amount);
-            msg!("From pool account balance: {}", from_pool_account.amount);
-            msg!("To user account balance: {}", to_user_account.amount);
-
-            msg!("user_token_account_x: {}", self.user_token_account_x.key());
-            msg!("user_token_account_y: {}", self.user_token_account_y.key());
-            msg!("pool_token_account_x: {}", self.pool_token_account_x.key());
-            msg!("pool_token_account_y: {}", self.pool_token_account_y.key());
-
-            msg!("user_token_account_x balance: {}", self.user_token_account_x.amount);
-            msg!("user_token_account_y balance: {}", self.user_token_account_y.amount);
-            msg!("pool_token_account_x balance: {}", self.pool_token_account_x.amount);
-            msg!("pool_token_account_y balance: {}", self.pool_token_account_y.amount);
-
-            msg!("1st transfer - from: {:?}, to: {:?}", from_user_account.key(), to_pool_account.key());
-
-            // Transfer from user to pool
-            transfer_checked(
-                CpiContext::new(
-                    from_token_program.to_account_info(),
-                    TransferChecked {
-                        from: from_user_account.to_account_info(),
-                        mint: from_mint.to_account_info(),
-                        to: to_pool_account.to_account_info(),
-                        authority: self.user.to_account_info(),
-                    },
-                ),
-                amount_sent,
-                from_mint.decimals,
-            )?;
+            Ok(())
+        } else {
+            Err(ErrorCode::InvalidProof.into())
+        }
+    }
+
+    /// Moves the swap amounts between the user and the pool token accounts: `amount_sent` of
+    /// the input token in, `amount_received` of the output token out.
+    pub(crate) fn transfer_swap_amounts(
+        &self,
+        is_swap_x_to_y: bool,
+        amount_sent: u64,
+        amount_received: u64,
+    ) -> Result<()> {
+        // Determine swap direction
+        let (from_user_account, to_pool_account, from_pool_account, to_user_account, from_mint, to_mint, from_token_program, to_token_program) = if is_swap_x_to_y {
+            (
+                &self.user_token_account_x,
+                &self.pool_token_account_x,
+                &self.pool_token_account_y,
+                &self.user_token_account_y,
+                &self.token_mint_x,
+                &self.token_mint_y,
+                &self.token_mint_x_program,
+                &self.token_mint_y_program,
+            )
+        } else {
+            (
+                &self.user_token_account_y,
+                &self.pool_token_account_y,
+                &self.pool_token_account_x,
+                &self.user_token_account_x,
+                &self.token_mint_y,
+                &self.token_mint_x,
+                &self.token_mint_y_program,
+                &self.token_mint_x_program,
+            )
+        };
+
+        let pool_token_mint_key_x = self.pool.token_mint_x.key();
+        let pool_token_mint_key_y = self.pool.token_mint_y.key();
+
+        let pool_seeds = &[
+            &b"pool"[..], 
+            pool_token_mint_key_x.as_ref(), 
+            pool_token_mint_key_y.as_ref(),
+            &[self.pool.bump],
+        ];
+
+        let signer_seeds = &[&pool_seeds[..]];
+
+        msg!("Performing token transfers");
+        
+        // Add these debug messages before the transfers
+        msg!("Amount sent: {}", amount_sent);
+        msg!("From user account balance: {}", from_user_account.amount);
+        msg!("To pool account balance: {}", to_pool_account.amount);
+        msg!("From pool account balance: {}", from_pool_account.amount);
+        msg!("To user account balance: {}", to_user_account.amount);
+
+        msg!("user_token_account_x: {}", self.user_token_account_x.key());
+        msg!("user_token_account_y: {}", self.user_token_account_y.key());
+        msg!("pool_token_account_x: {}", self.pool_token_account_x.key());
+        msg!("pool_token_account_y: {}", self.pool_token_account_y.key());
+
+        msg!("user_token_account_x balance: {}", self.user_token_account_x.amount);
+        msg!("user_token_account_y balance: {}", self.user_token_account_y.amount);
+        msg!("pool_token_account_x balance: {}", self.pool_token_account_x.amount);
+        msg!("pool_token_account_y balance: {}", self.pool_token_account_y.amount);
 
-            msg!("2nd transfer - from: {:?}, to: {:?}", from_pool_account.key(), to_user_account.key());
-
-            // Transfer from pool to user
-            transfer_checked(
-                CpiContext::new_with_signer(
-                    to_token_program.to_account_info(),
-                    TransferChecked {
-                        from: from_pool_account.to_account_info(),
-                        mint: to_mint.to_account_info(),
-                        to: to_user_account.to_account_info(),
-                        authority: self.pool.to_account_info(),
+        msg!("1st transfer - from: {:?}, to: {:?}", from_user_account.key(), to_pool_account.key());
+
+        // Transfer from user to pool
+        transfer_checked(
+            CpiContext::new(
+                from_token_program.to_account_info(),
+                TransferChecked {
+                    from: from_user_account.to_account_info(),
+                    mint: from_mint.to_account_info(),
+                    to: to_pool_account.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount_sent,
+            from_mint.decimals,
+        )?;
+
+        msg!("2nd transfer - from: {:?}, to: {:?}", from_pool_account.key(), to_user_account.key());
+
+        // Transfer from pool to user
+        transfer_checked(
+            CpiContext::new_with_signer(
+                to_token_program.to_account_info(),
+                TransferChecked {
+                    from: from_pool_account.to_account_info(),
+                    mint: to_mint.to_account_info(),
+                    to: to_user_account.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_received,
+            to_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Checks the nullifier account is the PDA of `nullifier` and was not created yet,
+/// returning its bump.
+pub(crate) fn unspent_nullifier_bump(account: &AccountInfo, nullifier: &[u8; 32]) -> Result<u8> {
+    let (nullifier_address, bump) =
+        Pubkey::find_program_address(&[b"nullifier", nullifier.as_ref()], &crate::ID);
+    if nullifier_address != account.key() {
+        return Err(ErrorCode::InvalidNullifierAccount.into());
+    }
+
+    if account.owner == &crate::ID {
+        return Err(ErrorCode::NullifierAlreadyUsed.into());
+    }
+
+    Ok(bump)
+}
+
+/// Records the nullifier as spent by creating its PDA, paid for by `user`.
+pub(crate) fn spend_nullifier<'info>(
+    account: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    pool: Pubkey,
+    nullifier: &[u8; 32],
+    bump: u8,
+) -> Result<()> {
+    let space = 8 + Nullifier::INIT_SPACE;
+    let lamports = Rent::get()?.minimum_balance(space);
+    let signer_seeds: &[&[u8]] = &[b"nullifier", nullifier.as_ref(), &[bump]];
+
+    if account.lamports() == 0 {
+        create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: user.clone(),
+                    to: account.clone(),
+                },
+                &[signer_seeds],
+            ),
+            lamports,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Someone funded the address ahead of time to block the swap, claim it anyway
+        let top_up = lamports.saturating_sub(account.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: user.clone(),
+                        to: account.clone(),
                     },
-                    signer_seeds,
                 ),
-                amount_received,
-                to_mint.decimals,
+                top_up,
             )?;
-
-            Ok(())
-        } else {
-            Err(ErrorCode::InvalidProof.into())
         }
+        allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Allocate { account_to_allocate: account.clone() },
+                &[signer_seeds],
+            ),
+            space as u64,
+        )?;
+        assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Assign { account_to_assign: account.clone() },
+                &[signer_seeds],
+            ),
+            &crate::ID,
+        )?;
     }
+
+    let record = Nullifier {
+        pool,
+        user: user.key(),
+        slot: Clock::get()?.slot,
+    };
+    let mut data = account.try_borrow_mut_data()?;
+    record.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
 }
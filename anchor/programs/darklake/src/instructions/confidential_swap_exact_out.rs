@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::circuits::SwapCircuit;
+use crate::instructions::ConfidentialSwap;
+
+impl<'info> ConfidentialSwap<'info> {
+    /// Swaps with a proof of the exact output circuit, where the user fixes the amount received
+    /// and a maximum input instead of the input and a minimum output. The proof is checked
+    /// against the pool verifying key of that circuit.
+    pub fn confidential_swap_exact_out(
+        &mut self,
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+        public_inputs: Vec<[u8; 32]>,
+        is_swap_x_to_y: bool,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        self.swap(
+            SwapCircuit::ConstantProductExactOut,
+            proof_a,
+            proof_b,
+            proof_c,
+            public_inputs,
+            is_swap_x_to_y,
+            expiry_slot,
+        )
+    }
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::state::{Config, VerifyingKey};
+use crate::errors::ErrorCode;
+use crate::events::VerifyingKeyFinalized;
+
+#[derive(Accounts)]
+#[instruction(version: u32)]
+pub struct FinalizeVerifyingKey<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut,
+        seeds = [b"verifying_key", version.to_le_bytes().as_ref()],
+        bump = verifying_key.bump,
+        constraint = !verifying_key.finalized @ ErrorCode::VerifyingKeyFinalized
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> FinalizeVerifyingKey<'info> {
+    pub fn finalize_verifying_key(&mut self, version: u32) -> Result<()> {
+        let verifying_key = &mut self.verifying_key;
+
+        // Catches missing or corrupted chunks before any pool can use the key
+        if hash(&verifying_key.data).to_bytes() != verifying_key.hash {
+            return Err(ErrorCode::VerifyingKeyHashMismatch.into());
+        }
+
+        verifying_key.finalized = true;
+
+        emit!(VerifyingKeyFinalized {
+            verifying_key: verifying_key.key(),
+            version,
+            hash: verifying_key.hash,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Config;
+use crate::errors::ErrorCode;
+use crate::constants::{DEFAULT_FEE_TIERS, DEFAULT_PROTOCOL_FEE_BPS};
+use crate::events::ConfigUpdated;
+use crate::circuits::MAX_CIRCUITS;
+use crate::program::Darklake;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(init, payer = admin, space = 8 + Config::INIT_SPACE, seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Darklake>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn initialize_config(&mut self, treasury: Pubkey, bump: u8) -> Result<()> {
+        let config = &mut self.config;
+        config.admin = self.admin.key();
+        config.pending_admin = Pubkey::default();
+        config.treasury = treasury;
+        config.fee_tiers = DEFAULT_FEE_TIERS;
+        config.protocol_fee_bps = DEFAULT_PROTOCOL_FEE_BPS;
+        config.features = 0;
+        config.vk_versions = [0; MAX_CIRCUITS];
+        config.bump = bump;
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            treasury: config.treasury,
+            fee_tiers: config.fee_tiers,
+            protocol_fee_bps: config.protocol_fee_bps,
+            features: config.features,
+            vk_versions: config.vk_versions,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -6,11 +6,17 @@ use mpl_token_metadata::instructions::CreateMetadataAccountV3CpiBuilder;
 use mpl_token_metadata::types::DataV2;
 use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
 
-use crate::state::Pool;
+use crate::state::{Config, Pool};
 use crate::errors::ErrorCode;
+use crate::constants::{CURVE_CONSTANT_PRODUCT, CURVE_STABLE_SWAP, FEATURE_PERMISSIONLESS_POOLS};
+use crate::events::PoolInitialized;
+use crate::stable_math::{MAX_AMP, MIN_AMP};
+use crate::tick_math::tick_at_sqrt_price;
 
 #[derive(Accounts)]
 pub struct InitializePool<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
     #[account(init, payer = payer, space = 8 + Pool::INIT_SPACE, seeds = [b"pool", token_mint_x.key().as_ref(), token_mint_y.key().as_ref()], bump)]
     pub pool: Account<'info, Pool>,
     pub token_mint_x: InterfaceAccount<'info, Mint>,
@@ -43,17 +49,61 @@ pub struct InitializePool<'info> {
 impl<'info> InitializePool<'info> {
     pub fn initialize_pool(
         &mut self,
+        fee_bps: u16,
+        tick_spacing: u16,
+        initial_sqrt_price_x64: u128,
+        curve: u8,
+        amp: u32,
         bump: u8,
     ) -> Result<()> {
         if self.token_mint_x.key() >= self.token_mint_y.key() {
             return Err(ErrorCode::InvalidTokenOrder.into());
         }
 
+        if !self.config.has_feature(FEATURE_PERMISSIONLESS_POOLS) && self.payer.key() != self.config.admin {
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if !self.config.is_fee_tier(fee_bps) {
+            return Err(ErrorCode::InvalidFee.into());
+        }
+
         let pool = &mut self.pool;
         pool.token_mint_x = self.token_mint_x.key();
         pool.token_mint_y = self.token_mint_y.key();
+        pool.fee_bps = fee_bps;
+        pool.protocol_fee_bps = self.config.protocol_fee_bps;
+        pool.vk_versions = self.config.vk_versions;
         pool.bump = bump;
 
+        // A tick spacing makes a concentrated pool, which starts at a price instead of the
+        // ratio of its first deposit
+        if tick_spacing > 0 {
+            pool.tick_spacing = tick_spacing;
+            pool.tick_current = tick_at_sqrt_price(initial_sqrt_price_x64)?;
+            pool.sqrt_price_x64 = initial_sqrt_price_x64;
+        } else if initial_sqrt_price_x64 != 0 {
+            return Err(ErrorCode::InvalidSqrtPrice.into());
+        }
+
+        // StableSwap pools keep their reserves in one range and start without a ramp
+        match curve {
+            CURVE_CONSTANT_PRODUCT => {
+                if amp != 0 {
+                    return Err(ErrorCode::InvalidAmp.into());
+                }
+            }
+            CURVE_STABLE_SWAP if tick_spacing == 0 => {
+                if !(MIN_AMP..=MAX_AMP).contains(&(amp as u64)) {
+                    return Err(ErrorCode::InvalidAmp.into());
+                }
+            }
+            _ => return Err(ErrorCode::InvalidPoolType.into()),
+        }
+        pool.curve = curve;
+        pool.amp_initial = amp;
+        pool.amp_target = amp;
+
         let token_mint_x = self.token_mint_x.key();
         let token_mint_y = self.token_mint_y.key();
         let lp_seed = &[b"lp", token_mint_x.as_ref(), token_mint_y.as_ref()];
@@ -69,6 +119,21 @@ impl<'info> InitializePool<'info> {
             return Err(ErrorCode::LpMintAlreadyInitialized.into());
         }
 
+        emit!(PoolInitialized {
+            pool: self.pool.key(),
+            user: self.payer.key(),
+            token_mint_x,
+            token_mint_y,
+            token_mint_lp: lp_address,
+            fee_bps,
+            protocol_fee_bps: self.pool.protocol_fee_bps,
+            vk_versions: self.pool.vk_versions,
+            tick_spacing,
+            sqrt_price_x64: initial_sqrt_price_x64,
+            curve,
+            amp,
+            slot: Clock::get()?.slot,
+        });
 
         Ok(())
     }
@@ -97,7 +162,7 @@ impl<'info> InitializePool<'info> {
             ),
             lamports,
             space as u64,
-            &self.lp_token_program.key, // Mint needs to be owned by the token program
+            self.lp_token_program.key, // Mint needs to be owned by the token program
         )?;
 
         // Initialize the mint
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::events::TickArrayInitialized;
+use crate::state::{Pool, TickArray};
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint_x.key().as_ref(), pool.token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TickArray::INIT_SPACE,
+        seeds = [b"tick_array", pool.key().as_ref(), start_tick_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tick_array: Box<Account<'info, TickArray>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeTickArray<'info> {
+    pub fn initialize_tick_array(&mut self, start_tick_index: i32, bump: u8) -> Result<()> {
+        self.pool.require_concentrated(true)?;
+        TickArray::check_start_index(start_tick_index, self.pool.tick_spacing)?;
+
+        let tick_array = &mut self.tick_array;
+        tick_array.pool = self.pool.key();
+        tick_array.start_tick_index = start_tick_index;
+        tick_array.bump = bump;
+
+        emit!(TickArrayInitialized {
+            pool: self.pool.key(),
+            tick_array: self.tick_array.key(),
+            start_tick_index,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
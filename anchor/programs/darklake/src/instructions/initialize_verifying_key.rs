@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Config, VerifyingKey};
+use crate::errors::ErrorCode;
+use crate::events::VerifyingKeyInitialized;
+use crate::circuits::SwapCircuit;
+
+#[derive(Accounts)]
+#[instruction(version: u32, circuit_id: u8, nr_pubinputs: u8)]
+pub struct InitializeVerifyingKey<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(init,
+        payer = admin,
+        space = VerifyingKey::space(nr_pubinputs),
+        seeds = [b"verifying_key", version.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeVerifyingKey<'info> {
+    pub fn initialize_verifying_key(
+        &mut self,
+        version: u32,
+        circuit_id: u8,
+        nr_pubinputs: u8,
+        hash: [u8; 32],
+        bump: u8,
+    ) -> Result<()> {
+        let circuit = SwapCircuit::from_id(circuit_id)?;
+        if nr_pubinputs as usize != circuit.public_signals() {
+            return Err(ErrorCode::InvalidPublicInputsLength.into());
+        }
+
+        let verifying_key = &mut self.verifying_key;
+        verifying_key.version = version;
+        verifying_key.circuit_id = circuit_id;
+        verifying_key.nr_pubinputs = nr_pubinputs;
+        verifying_key.hash = hash;
+        verifying_key.finalized = false;
+        verifying_key.bump = bump;
+        verifying_key.data = vec![0; VerifyingKey::data_len(nr_pubinputs)];
+
+        emit!(VerifyingKeyInitialized {
+            verifying_key: verifying_key.key(),
+            version,
+            circuit_id,
+            nr_pubinputs,
+            hash,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{Config, LegacyPool, Pool};
+use crate::errors::ErrorCode;
+use crate::events::PoolMigrated;
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: A pool in the `LegacyPool` layout, checked in the instruction handler.
+    #[account(
+        mut,
+        seeds = [b"pool", token_mint_x.key().as_ref(), token_mint_y.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub pool: UncheckedAccount<'info>,
+    pub token_mint_x: InterfaceAccount<'info, Mint>,
+    pub token_mint_y: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigratePool<'info> {
+    /// Grows a pool created before the current `Pool` layout to it, the admin paying the
+    /// extra rent.
+    pub fn migrate_pool(&mut self, fee_bps: u16) -> Result<()> {
+        if !self.config.is_fee_tier(fee_bps) {
+            return Err(ErrorCode::InvalidFee.into());
+        }
+
+        let pool_info = self.pool.to_account_info();
+        let legacy = {
+            let data = pool_info.try_borrow_data()?;
+            // Pools already in the current layout are longer
+            if data.len() != 8 + LegacyPool::INIT_SPACE || data[..8] != Pool::DISCRIMINATOR {
+                return Err(ErrorCode::InvalidPoolLayout.into());
+            }
+            LegacyPool::deserialize(&mut &data[8..])?
+        };
+        let pool = legacy.migrate(fee_bps, &self.config);
+
+        let space = 8 + Pool::INIT_SPACE;
+        let lamports = Rent::get()?.minimum_balance(space).saturating_sub(pool_info.lamports());
+        if lamports > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.admin.to_account_info(),
+                        to: pool_info.clone(),
+                    },
+                ),
+                lamports,
+            )?;
+        }
+        pool_info.realloc(space, true)?;
+        pool.try_serialize(&mut &mut pool_info.try_borrow_mut_data()?[..])?;
+
+        emit!(PoolMigrated {
+            pool: self.pool.key(),
+            fee_bps,
+            protocol_fee_bps: pool.protocol_fee_bps,
+            vk_versions: pool.vk_versions,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
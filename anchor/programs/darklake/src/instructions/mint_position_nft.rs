@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount};
+use mpl_token_metadata::instructions::{
+    CreateMasterEditionV3CpiBuilder, CreateMetadataAccountV3CpiBuilder,
+};
+use mpl_token_metadata::types::DataV2;
+use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
+
+use crate::state::{Pool, Position};
+use crate::errors::ErrorCode;
+use crate::events::PositionNftMinted;
+
+#[derive(Accounts)]
+pub struct MintPositionNft<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint_x.key().as_ref(), pool.token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    #[account(mut,
+        has_one = pool,
+        constraint = position.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub position: Box<Account<'info, Position>>,
+    /// One mint per position, so a position can only get a single NFT.
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"position_mint", position.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = pool,
+        mint::freeze_authority = pool,
+        mint::token_program = token_program,
+    )]
+    pub position_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = position_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_position_token_account: Box<Account<'info, TokenAccount>>,
+    ///CHECK: Created by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), position_mint.key().as_ref()],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub metadata_account: UncheckedAccount<'info>,
+    ///CHECK: Created by the token metadata program
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            position_mint.key().as_ref(),
+            b"edition",
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: metaplex account
+    #[account(address = mpl_token_metadata::ID)]
+    pub mpl_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> MintPositionNft<'info> {
+    /// Mints the one-of-one NFT of the position to its owner. From then on the position
+    /// belongs to whoever holds the NFT.
+    pub fn mint_position_nft(&mut self) -> Result<()> {
+        self.pool.require_concentrated(true)?;
+
+        let token_mint_x_key = self.pool.token_mint_x;
+        let token_mint_y_key = self.pool.token_mint_y;
+        let pool_signer_seeds = &[
+            b"pool",
+            token_mint_x_key.as_ref(),
+            token_mint_y_key.as_ref(),
+            &[self.pool.bump],
+        ];
+
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.position_mint.to_account_info(),
+                    to: self.user_position_token_account.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                },
+                &[&pool_signer_seeds[..]],
+            ),
+            1,
+        )?;
+
+        let data = DataV2 {
+            name: "Darklake Position".to_string(),
+            symbol: "DLPOS".to_string(),
+            // TODO: Make this an API call to get the position metadata
+            uri: "https://darklake.fi".to_string(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        };
+
+        CreateMetadataAccountV3CpiBuilder::new(&self.mpl_program)
+            .metadata(&self.metadata_account.to_account_info())
+            .mint(&self.position_mint.to_account_info())
+            .mint_authority(&self.pool.to_account_info())
+            .payer(&self.user.to_account_info())
+            .update_authority(&self.pool.to_account_info(), true)
+            .is_mutable(true)
+            .data(data)
+            .system_program(&self.system_program.to_account_info())
+            .invoke_signed(&[&pool_signer_seeds[..]])?;
+
+        // The master edition takes over the mint authority, so no second token can be minted
+        CreateMasterEditionV3CpiBuilder::new(&self.mpl_program)
+            .edition(&self.master_edition.to_account_info())
+            .mint(&self.position_mint.to_account_info())
+            .update_authority(&self.pool.to_account_info())
+            .mint_authority(&self.pool.to_account_info())
+            .payer(&self.user.to_account_info())
+            .metadata(&self.metadata_account.to_account_info())
+            .token_program(&self.token_program.to_account_info())
+            .system_program(&self.system_program.to_account_info())
+            .rent(Some(&self.rent.to_account_info()))
+            .max_supply(0)
+            .invoke_signed(&[&pool_signer_seeds[..]])?;
+
+        self.position.position_mint = self.position_mint.key();
+
+        emit!(PositionNftMinted {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            user: self.user.key(),
+            position_mint: self.position_mint.key(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
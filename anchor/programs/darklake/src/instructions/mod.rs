@@ -1,11 +1,56 @@
 pub mod initialize_pool;
 pub mod add_liquidity;
 pub mod confidential_swap;
+pub mod confidential_swap_exact_out;
+pub mod confidential_route_swap;
 pub mod remove_liquidity;
 pub mod upgrade_pool;
+pub mod migrate_pool;
+pub mod collect_protocol_fees;
+pub mod initialize_config;
+pub mod propose_admin;
+pub mod accept_admin;
+pub mod update_config;
+pub mod set_pool_pause;
+pub mod set_global_pause;
+pub mod initialize_verifying_key;
+pub mod write_verifying_key;
+pub mod finalize_verifying_key;
+pub mod set_pool_verifying_key;
+pub mod quote_swap;
+pub mod confidential_range_swap;
+pub mod initialize_tick_array;
+pub mod open_range_position;
+pub mod add_range_liquidity;
+pub mod remove_range_liquidity;
+pub mod collect_position_fees;
+pub mod mint_position_nft;
+pub mod ramp_amp;
 
 pub use remove_liquidity::*;
 pub use initialize_pool::*;
 pub use add_liquidity::*;
 pub use confidential_swap::*;
-pub use upgrade_pool::*;
\ No newline at end of file
+pub use confidential_route_swap::*;
+pub use upgrade_pool::*;
+pub use migrate_pool::*;
+pub use collect_protocol_fees::*;
+pub use initialize_config::*;
+pub use propose_admin::*;
+pub use accept_admin::*;
+pub use update_config::*;
+pub use set_pool_pause::*;
+pub use set_global_pause::*;
+pub use initialize_verifying_key::*;
+pub use write_verifying_key::*;
+pub use finalize_verifying_key::*;
+pub use set_pool_verifying_key::*;
+pub use quote_swap::*;
+pub use confidential_range_swap::*;
+pub use initialize_tick_array::*;
+pub use open_range_position::*;
+pub use add_range_liquidity::*;
+pub use remove_range_liquidity::*;
+pub use collect_position_fees::*;
+pub use mint_position_nft::*;
+pub use ramp_amp::*;
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Pool, Position};
+use crate::events::PositionOpened;
+use crate::concentrated::check_tick_range;
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenRangePosition<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint_x.key().as_ref(), pool.token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [
+            b"position",
+            pool.key().as_ref(),
+            user.key().as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenRangePosition<'info> {
+    /// Creates an empty position of `user` between two ticks. Liquidity goes in with
+    /// `add_range_liquidity`.
+    pub fn open_range_position(&mut self, tick_lower: i32, tick_upper: i32, bump: u8) -> Result<()> {
+        self.pool.require_concentrated(true)?;
+        check_tick_range(tick_lower, tick_upper, self.pool.tick_spacing)?;
+
+        let position = &mut self.position;
+        position.pool = self.pool.key();
+        position.owner = self.user.key();
+        position.tick_lower = tick_lower;
+        position.tick_upper = tick_upper;
+        position.bump = bump;
+
+        emit!(PositionOpened {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            owner: self.user.key(),
+            tick_lower,
+            tick_upper,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Config;
+use crate::errors::ErrorCode;
+use crate::events::AdminProposed;
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> ProposeAdmin<'info> {
+    pub fn propose_admin(&mut self, new_admin: Pubkey) -> Result<()> {
+        self.config.pending_admin = new_admin;
+
+        emit!(AdminProposed {
+            admin: self.admin.key(),
+            pending_admin: new_admin,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+
+use crate::concentrated::{apply_range_swap, compute_range_swap};
+use crate::constants::FEE_DENOMINATOR;
+use crate::errors::ErrorCode;
+use crate::instructions::{check_stable_swap_reserves, check_swap_reserves, load_tick_arrays};
+use crate::math::{mul_div_floor, to_u64, wide_mul_div_floor};
+use crate::stable_math::{compute_d, compute_y};
+use crate::state::{Pool, TickArray};
+
+/// Outcome of a swap against the current pool reserves, as the swap circuit computes it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct SwapQuote {
+    /// Amount the user sends, protocol fee included.
+    pub amount_in: u64,
+    /// Amount the user receives, the `amountReceived` output of the circuit.
+    pub amount_out: u64,
+    /// Swap fee charged on the input, rounded down. The liquidity provider share stays in the
+    /// reserves, the circuit only accounts for it through a smaller `amount_out`.
+    pub fee: u64,
+    /// Protocol share of the swap fee, kept outside of the reserves.
+    pub protocol_fee: u64,
+    pub new_reserve_x: u64,
+    pub new_reserve_y: u64,
+}
+
+/// Quotes a swap of `amount_in` with the circuit math, failing where `confidential_swap` would
+/// fail for any proof of it.
+pub fn swap_quote(pool: &Pool, amount_in: u64, is_swap_x_to_y: bool) -> Result<SwapQuote> {
+    pool.require_concentrated(false)?;
+    pool.require_stable(false)?;
+    if amount_in == 0 {
+        return Err(ErrorCode::InvalidSwapAmount.into());
+    }
+    // The circuit cannot be satisfied past a fee of the whole input
+    if pool.fee_bps as u64 > FEE_DENOMINATOR || pool.protocol_fee_bps as u64 > FEE_DENOMINATOR {
+        return Err(ErrorCode::InvalidFee.into());
+    }
+
+    let (reserve_in, reserve_out) = if is_swap_x_to_y {
+        (pool.reserve_x, pool.reserve_y)
+    } else {
+        (pool.reserve_y, pool.reserve_x)
+    };
+
+    // protocolFee = in * fee * protocolFeeRate / 10000^2
+    let protocol_fee = mul_div_floor(
+        amount_in as u128 * pool.fee_bps as u128,
+        pool.protocol_fee_bps as u128,
+        FEE_DENOMINATOR as u128 * FEE_DENOMINATOR as u128,
+    )?;
+
+    // out = in * (10000 - fee) * y / (x * 10000 + in * (10000 - fee))
+    let amount_in_with_fee = amount_in as u128 * (FEE_DENOMINATOR - pool.fee_bps as u64) as u128;
+    let amount_out = wide_mul_div_floor(
+        amount_in_with_fee,
+        reserve_out,
+        reserve_in as u128 * FEE_DENOMINATOR as u128 + amount_in_with_fee,
+    )?;
+
+    // The protocol fee never exceeds the input, nor the output the reserve
+    let new_reserve_in = to_u64(reserve_in as u128 + amount_in as u128 - protocol_fee)?;
+    let new_reserve_out = to_u64(reserve_out as u128 - amount_out)?;
+    let (new_reserve_x, new_reserve_y) = if is_swap_x_to_y {
+        (new_reserve_in, new_reserve_out)
+    } else {
+        (new_reserve_out, new_reserve_in)
+    };
+
+    let quote = SwapQuote {
+        amount_in,
+        amount_out: to_u64(amount_out)?,
+        fee: to_u64(mul_div_floor(
+            amount_in as u128,
+            pool.fee_bps as u128,
+            FEE_DENOMINATOR as u128,
+        )?)?,
+        protocol_fee: to_u64(protocol_fee)?,
+        new_reserve_x,
+        new_reserve_y,
+    };
+
+    check_swap_reserves(
+        pool.reserve_x,
+        pool.reserve_y,
+        quote.new_reserve_x,
+        quote.new_reserve_y,
+        quote.amount_out,
+        quote.protocol_fee,
+        is_swap_x_to_y,
+        pool.fee_bps,
+    )?;
+
+    Ok(quote)
+}
+
+/// Quotes a swap of `amount_in` on a StableSwap pool at the amplification coefficient `amp`,
+/// with the math of the StableSwap circuit.
+pub fn stable_swap_quote(
+    pool: &Pool,
+    amp: u64,
+    amount_in: u64,
+    is_swap_x_to_y: bool,
+) -> Result<SwapQuote> {
+    pool.require_stable(true)?;
+    if amount_in == 0 {
+        return Err(ErrorCode::InvalidSwapAmount.into());
+    }
+
+    // The circuit cannot be satisfied past a fee of the whole input
+    if pool.fee_bps as u64 > FEE_DENOMINATOR || pool.protocol_fee_bps as u64 > FEE_DENOMINATOR {
+        return Err(ErrorCode::InvalidFee.into());
+    }
+
+    let (reserve_in, reserve_out) = if is_swap_x_to_y {
+        (pool.reserve_x, pool.reserve_y)
+    } else {
+        (pool.reserve_y, pool.reserve_x)
+    };
+
+    let protocol_fee = mul_div_floor(
+        amount_in as u128 * pool.fee_bps as u128,
+        pool.protocol_fee_bps as u128,
+        FEE_DENOMINATOR as u128 * FEE_DENOMINATOR as u128,
+    )?;
+    let amount_in_after_fee = amount_in as u128 * (FEE_DENOMINATOR - pool.fee_bps as u64) as u128
+        / FEE_DENOMINATOR as u128;
+
+    // The circuit takes the invariant and the new output balance as witnesses, the exact roots
+    // these compute
+    let invariant = compute_d(amp, pool.reserve_x, pool.reserve_y)?;
+    if invariant == 0 {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+    let new_reserve_out =
+        compute_y(amp, reserve_in as u128 + amount_in_after_fee, invariant)?;
+    let new_reserve_out = to_u64(new_reserve_out)?;
+    let amount_out = reserve_out
+        .checked_sub(new_reserve_out)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+    let new_reserve_in = to_u64(reserve_in as u128 + amount_in as u128 - protocol_fee)?;
+    let (new_reserve_x, new_reserve_y) = if is_swap_x_to_y {
+        (new_reserve_in, new_reserve_out)
+    } else {
+        (new_reserve_out, new_reserve_in)
+    };
+
+    let quote = SwapQuote {
+        amount_in,
+        amount_out,
+        fee: to_u64(mul_div_floor(
+            amount_in as u128,
+            pool.fee_bps as u128,
+            FEE_DENOMINATOR as u128,
+        )?)?,
+        protocol_fee: to_u64(protocol_fee)?,
+        new_reserve_x,
+        new_reserve_y,
+    };
+
+    check_stable_swap_reserves(
+        pool.reserve_x,
+        pool.reserve_y,
+        quote.new_reserve_x,
+        quote.new_reserve_y,
+        quote.amount_out,
+        quote.protocol_fee,
+        is_swap_x_to_y,
+        pool.fee_bps,
+        amp,
+    )?;
+
+    Ok(quote)
+}
+
+/// Quotes a swap of `amount_in` through the ticks of a concentrated pool, with the math
+/// `confidential_range_swap` runs. `tick_arrays` are left as they were.
+pub fn range_swap_quote(
+    pool: &Pool,
+    tick_arrays: &[TickArray],
+    amount_in: u64,
+    is_swap_x_to_y: bool,
+) -> Result<SwapQuote> {
+    let swap = compute_range_swap(pool, &mut tick_arrays.to_vec(), amount_in, is_swap_x_to_y)?;
+    let mut pool_after = pool.clone();
+    apply_range_swap(&mut pool_after, &swap, is_swap_x_to_y)?;
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out: swap.amount_out,
+        fee: swap.fee,
+        protocol_fee: swap.protocol_fee,
+        new_reserve_x: pool_after.reserve_x,
+        new_reserve_y: pool_after.reserve_y,
+    })
+}
+
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    #[account(
+        seeds = [b"pool", pool.token_mint_x.key().as_ref(), pool.token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+impl<'info> QuoteSwap<'info> {
+    /// Concentrated pools take the tick arrays of the swap as remaining accounts, like
+    /// `confidential_range_swap`. StableSwap pools are quoted at the amplification coefficient
+    /// of the current slot.
+    pub fn quote_swap(
+        &self,
+        tick_arrays: &'info [AccountInfo<'info>],
+        amount_in: u64,
+        is_swap_x_to_y: bool,
+    ) -> Result<SwapQuote> {
+        if self.pool.is_stable() {
+            let amp = self.pool.amp(Clock::get()?.slot);
+            return stable_swap_quote(&self.pool, amp, amount_in, is_swap_x_to_y);
+        }
+        if !self.pool.is_concentrated() {
+            return swap_quote(&self.pool, amount_in, is_swap_x_to_y);
+        }
+
+        let tick_arrays: Vec<TickArray> = load_tick_arrays(&self.pool.key(), tick_arrays)?
+            .into_iter()
+            .map(|tick_array| tick_array.into_inner())
+            .collect();
+        range_swap_quote(&self.pool, &tick_arrays, amount_in, is_swap_x_to_y)
+    }
+}
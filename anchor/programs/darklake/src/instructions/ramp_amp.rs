@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{Config, Pool};
+use crate::errors::ErrorCode;
+use crate::constants::{MAX_AMP_CHANGE, MIN_AMP_RAMP_SLOTS};
+use crate::events::AmpRampStarted;
+use crate::stable_math::{MAX_AMP, MIN_AMP};
+
+/// Starts ramping the amplification coefficient of `pool` from its value at `slot` to
+/// `target_amp` at `end_slot`.
+///
+/// Ramps last at least `MIN_AMP_RAMP_SLOTS`, start at least that long after the previous one
+/// and change the coefficient by at most a factor of `MAX_AMP_CHANGE`, so liquidity providers
+/// have time to react to a move that shifts the curve.
+pub fn start_amp_ramp(pool: &mut Pool, target_amp: u32, end_slot: u64, slot: u64) -> Result<()> {
+    pool.require_stable(true)?;
+
+    if pool.amp_ramp_start_slot != 0
+        && slot < pool.amp_ramp_start_slot.saturating_add(MIN_AMP_RAMP_SLOTS)
+    {
+        return Err(ErrorCode::InvalidAmpRamp.into());
+    }
+    if end_slot < slot.saturating_add(MIN_AMP_RAMP_SLOTS) {
+        return Err(ErrorCode::InvalidAmpRamp.into());
+    }
+
+    let current_amp = pool.amp(slot);
+    let target = target_amp as u64;
+    if !(MIN_AMP..=MAX_AMP).contains(&target)
+        || target > current_amp * MAX_AMP_CHANGE
+        || target * MAX_AMP_CHANGE < current_amp
+    {
+        return Err(ErrorCode::InvalidAmp.into());
+    }
+
+    pool.amp_initial = current_amp as u32;
+    pool.amp_target = target_amp;
+    pool.amp_ramp_start_slot = slot;
+    pool.amp_ramp_end_slot = end_slot;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub token_mint_x: InterfaceAccount<'info, Mint>,
+    pub token_mint_y: InterfaceAccount<'info, Mint>,
+    #[account(mut,
+        seeds = [b"pool", token_mint_x.key().as_ref(), token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> RampAmp<'info> {
+    pub fn ramp_amp(&mut self, target_amp: u32, end_slot: u64) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        start_amp_ramp(&mut self.pool, target_amp, end_slot, slot)?;
+
+        emit!(AmpRampStarted {
+            pool: self.pool.key(),
+            amp_initial: self.pool.amp_initial,
+            amp_target: target_amp,
+            start_slot: slot,
+            end_slot,
+        });
+
+        Ok(())
+    }
+}
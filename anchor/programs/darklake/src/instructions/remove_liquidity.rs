@@ -4,11 +4,15 @@ use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInt
 use anchor_spl::associated_token::AssociatedToken;
 
 use crate::errors::ErrorCode;
-use crate::state::Pool;
+use crate::constants::PAUSE_WITHDRAWALS;
+use crate::state::{Config, Pool};
 use crate::events::LiquidityRemoved;
+use crate::math::proportional_share;
 
 #[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
     pub token_mint_x: Box<InterfaceAccount<'info, Mint>>,
     pub token_mint_y: Box<InterfaceAccount<'info, Mint>>,
     pub token_mint_x_program: Interface<'info, TokenInterface>,
@@ -64,27 +68,25 @@ pub struct RemoveLiquidity<'info> {
 }
 
 impl<'info> RemoveLiquidity<'info> {
-    pub fn remove_liquidity(&mut self, amount: u64) -> Result<()> {
+    pub fn remove_liquidity(&mut self, amount: u64, min_amount_x: u64, min_amount_y: u64) -> Result<()> {
+        self.pool.require_concentrated(false)?;
+        self.pool.require_not_paused(&self.config, PAUSE_WITHDRAWALS)?;
+
         msg!("Removing liquidity: {}", amount);
         msg!("Reserve X: {}", self.pool.reserve_x);
         msg!("Reserve Y: {}", self.pool.reserve_y);
         msg!("Liquidity: {}", self.pool.liquidity);
 
-        let amount_x = (amount as u128)
-            .checked_mul(self.pool.reserve_x as u128)
-            .and_then(|product| product.checked_div(self.pool.liquidity as u128))
-            .and_then(|result| u64::try_from(result).ok())
-            .ok_or(ErrorCode::MathOverflow)?;
-
-        let amount_y = (amount as u128)
-            .checked_mul(self.pool.reserve_y as u128)
-            .and_then(|product| product.checked_div(self.pool.liquidity as u128))
-            .and_then(|result| u64::try_from(result).ok())
-            .ok_or(ErrorCode::MathOverflow)?;
+        let amount_x = proportional_share(amount, self.pool.reserve_x, self.pool.liquidity)?;
+        let amount_y = proportional_share(amount, self.pool.reserve_y, self.pool.liquidity)?;
 
         msg!("Amount X: {}", amount_x);
         msg!("Amount Y: {}", amount_y);
 
+        if amount_x < min_amount_x || amount_y < min_amount_y {
+            return Err(ErrorCode::SlippageExceeded.into());
+        }
+
         spl_burn(
             CpiContext::new(
                 self.token_mint_lp_program.to_account_info(),
@@ -121,7 +123,7 @@ impl<'info> RemoveLiquidity<'info> {
             amount_x,
             self.token_mint_x.decimals,
         )?;
-        self.pool.reserve_x = self.pool.reserve_x.checked_sub(amount_x).unwrap();
+        self.pool.reserve_x = self.pool.reserve_x.checked_sub(amount_x).ok_or(ErrorCode::MathOverflow)?;
 
         transfer_checked(
             CpiContext::new_with_signer(
@@ -137,15 +139,24 @@ impl<'info> RemoveLiquidity<'info> {
             amount_y,
             self.token_mint_y.decimals,
         )?;
-        self.pool.reserve_y = self.pool.reserve_y.checked_sub(amount_y).unwrap();
+        self.pool.reserve_y = self.pool.reserve_y.checked_sub(amount_y).ok_or(ErrorCode::MathOverflow)?;
 
-        self.pool.liquidity = self.pool.liquidity.checked_sub(amount.into()).unwrap();
+        self.pool.liquidity = self.pool.liquidity
+            .checked_sub(amount as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
 
         emit!(LiquidityRemoved {
+            pool: self.pool.key(),
             user: self.user.key(),
+            token_mint_x: token_mint_x_key,
+            token_mint_y: token_mint_y_key,
             amount_x,
             amount_y,
             liquidity: amount,
+            reserve_x: self.pool.reserve_x,
+            reserve_y: self.pool.reserve_y,
+            lp_supply: self.pool.liquidity,
+            slot: Clock::get()?.slot,
         });
 
         Ok(())
@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::TokenAccount as SplTokenAccount;
+
+use crate::state::{Config, Pool, Position, TickArray};
+use crate::errors::ErrorCode;
+use crate::constants::PAUSE_WITHDRAWALS;
+use crate::events::RangeLiquidityRemoved;
+use crate::concentrated::{modify_position, range_ticks_mut};
+
+#[derive(Accounts)]
+pub struct RemoveRangeLiquidity<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub token_mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_y: Box<InterfaceAccount<'info, Mint>>,
+    pub token_mint_x_program: Interface<'info, TokenInterface>,
+    pub token_mint_y_program: Interface<'info, TokenInterface>,
+    #[account(mut,
+        seeds = [b"pool", pool.token_mint_x.key().as_ref(), pool.token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    #[account(mut, has_one = pool)]
+    pub position: Box<Account<'info, Position>>,
+    /// The caller's token account holding the position NFT, only needed once the position has
+    /// one.
+    pub position_nft_account: Option<Box<Account<'info, SplTokenAccount>>>,
+    /// Tick array holding the lower tick of the position.
+    #[account(mut, constraint = tick_array_lower.pool == pool.key() @ ErrorCode::InvalidTickArray)]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+    /// Tick array holding the upper tick of the position, the same account as
+    /// `tick_array_lower` when both ticks fall in it.
+    #[account(mut, constraint = tick_array_upper.pool == pool.key() @ ErrorCode::InvalidTickArray)]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+    #[account(mut,
+        associated_token::mint = token_mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_mint_x_program.key(),
+    )]
+    pub user_token_account_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        associated_token::mint = token_mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_mint_y_program.key(),
+    )]
+    pub user_token_account_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        associated_token::mint = token_mint_x,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_x_program.key(),
+    )]
+    pub pool_token_account_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        associated_token::mint = token_mint_y,
+        associated_token::authority = pool,
+        associated_token::token_program = token_mint_y_program.key(),
+    )]
+    pub pool_token_account_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RemoveRangeLiquidity<'info> {
+    /// Withdraws `liquidity` from the position. The fees it earned stay owed to it, see
+    /// `collect_position_fees`.
+    pub fn remove_range_liquidity(
+        &mut self,
+        liquidity: u128,
+        amount_x_min: u64,
+        amount_y_min: u64,
+    ) -> Result<()> {
+        self.pool.require_concentrated(true)?;
+        self.position.require_owner(
+            &self.user.key(),
+            self.position_nft_account.as_deref().map(|account| &**account),
+        )?;
+        self.pool.require_not_paused(&self.config, PAUSE_WITHDRAWALS)?;
+
+        if liquidity == 0 || liquidity > self.position.liquidity {
+            return Err(ErrorCode::InsufficientLiquidity.into());
+        }
+        let delta = i128::try_from(liquidity).map_err(|_| ErrorCode::MathOverflow)?;
+
+        let (tick_lower, tick_upper) = (self.position.tick_lower, self.position.tick_upper);
+        let same_array = self.tick_array_lower.key() == self.tick_array_upper.key();
+        let (lower, upper) = range_ticks_mut(
+            &mut self.tick_array_lower,
+            (!same_array).then_some(&mut **self.tick_array_upper),
+            tick_lower,
+            tick_upper,
+            self.pool.tick_spacing,
+        )?;
+        let (amount_x, amount_y) =
+            modify_position(&mut self.pool, &mut self.position, lower, upper, -delta)?;
+        if same_array {
+            // Both accounts are written back, the last one wins
+            self.tick_array_upper.ticks = self.tick_array_lower.ticks;
+        }
+
+        msg!("Amount X: {}", amount_x);
+        msg!("Amount Y: {}", amount_y);
+
+        if amount_x < amount_x_min || amount_y < amount_y_min {
+            return Err(ErrorCode::SlippageExceeded.into());
+        }
+
+        self.pool.reserve_x = self.pool.reserve_x.checked_sub(amount_x).ok_or(ErrorCode::MathOverflow)?;
+        self.pool.reserve_y = self.pool.reserve_y.checked_sub(amount_y).ok_or(ErrorCode::MathOverflow)?;
+
+        let token_mint_x_key = self.pool.token_mint_x;
+        let token_mint_y_key = self.pool.token_mint_y;
+        let pool_signer_seeds = &[
+            b"pool",
+            token_mint_x_key.as_ref(),
+            token_mint_y_key.as_ref(),
+            &[self.pool.bump],
+        ];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_mint_x_program.to_account_info(),
+                TransferChecked {
+                    from: self.pool_token_account_x.to_account_info(),
+                    to: self.user_token_account_x.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                    mint: self.token_mint_x.to_account_info(),
+                },
+                &[&pool_signer_seeds[..]],
+            ),
+            amount_x,
+            self.token_mint_x.decimals,
+        )?;
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_mint_y_program.to_account_info(),
+                TransferChecked {
+                    from: self.pool_token_account_y.to_account_info(),
+                    to: self.user_token_account_y.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                    mint: self.token_mint_y.to_account_info(),
+                },
+                &[&pool_signer_seeds[..]],
+            ),
+            amount_y,
+            self.token_mint_y.decimals,
+        )?;
+
+        emit!(RangeLiquidityRemoved {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            user: self.user.key(),
+            tick_lower,
+            tick_upper,
+            liquidity,
+            amount_x,
+            amount_y,
+            range_liquidity: self.pool.range_liquidity,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
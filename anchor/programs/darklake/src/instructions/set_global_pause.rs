@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Config;
+use crate::errors::ErrorCode;
+use crate::constants::PAUSE_ALL;
+use crate::events::GlobalPauseUpdated;
+
+#[derive(Accounts)]
+pub struct SetGlobalPause<'info> {
+    #[account(mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> SetGlobalPause<'info> {
+    pub fn set_global_pause(&mut self, paused: u8) -> Result<()> {
+        if paused & !PAUSE_ALL != 0 {
+            return Err(ErrorCode::InvalidInput.into());
+        }
+
+        self.config.paused = paused;
+
+        emit!(GlobalPauseUpdated {
+            paused,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{Config, Pool};
+use crate::errors::ErrorCode;
+use crate::constants::PAUSE_ALL;
+use crate::events::PoolPauseUpdated;
+
+#[derive(Accounts)]
+pub struct SetPoolPause<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub token_mint_x: InterfaceAccount<'info, Mint>,
+    pub token_mint_y: InterfaceAccount<'info, Mint>,
+    #[account(mut,
+        seeds = [b"pool", token_mint_x.key().as_ref(), token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> SetPoolPause<'info> {
+    pub fn set_pool_pause(&mut self, paused: u8) -> Result<()> {
+        if paused & !PAUSE_ALL != 0 {
+            return Err(ErrorCode::InvalidInput.into());
+        }
+
+        self.pool.paused = paused;
+
+        emit!(PoolPauseUpdated {
+            pool: self.pool.key(),
+            paused,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{Config, Pool, VerifyingKey};
+use crate::errors::ErrorCode;
+use crate::events::PoolVerifyingKeyUpdated;
+
+#[derive(Accounts)]
+pub struct SetPoolVerifyingKey<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub token_mint_x: InterfaceAccount<'info, Mint>,
+    pub token_mint_y: InterfaceAccount<'info, Mint>,
+    #[account(mut,
+        seeds = [b"pool", token_mint_x.key().as_ref(), token_mint_y.key().as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        seeds = [b"verifying_key", verifying_key.version.to_le_bytes().as_ref()],
+        bump = verifying_key.bump,
+        constraint = verifying_key.finalized @ ErrorCode::VerifyingKeyNotFinalized
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> SetPoolVerifyingKey<'info> {
+    pub fn set_pool_verifying_key(&mut self) -> Result<()> {
+        let circuit_id = self.verifying_key.circuit_id;
+        self.pool.vk_versions[circuit_id as usize] = self.verifying_key.version;
+
+        emit!(PoolVerifyingKeyUpdated {
+            pool: self.pool.key(),
+            circuit_id,
+            vk_version: self.verifying_key.version,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
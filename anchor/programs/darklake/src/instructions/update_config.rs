@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Config;
+use crate::errors::ErrorCode;
+use crate::constants::{FEE_DENOMINATOR, FEE_TIER_COUNT, MAX_FEE_BPS};
+use crate::events::ConfigUpdated;
+use crate::circuits::MAX_CIRCUITS;
+
+/// Config fields to change. `None` leaves the current value untouched.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct UpdateConfigParams {
+    pub treasury: Option<Pubkey>,
+    pub fee_tiers: Option<[u16; FEE_TIER_COUNT]>,
+    pub protocol_fee_bps: Option<u16>,
+    pub features: Option<u8>,
+    pub vk_versions: Option<[u32; MAX_CIRCUITS]>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn update_config(&mut self, params: UpdateConfigParams) -> Result<()> {
+        let config = &mut self.config;
+
+        if let Some(treasury) = params.treasury {
+            config.treasury = treasury;
+        }
+
+        if let Some(fee_tiers) = params.fee_tiers {
+            if fee_tiers.iter().any(|fee_bps| *fee_bps > MAX_FEE_BPS) {
+                return Err(ErrorCode::InvalidFee.into());
+            }
+            config.fee_tiers = fee_tiers;
+        }
+
+        if let Some(protocol_fee_bps) = params.protocol_fee_bps {
+            if protocol_fee_bps as u64 > FEE_DENOMINATOR {
+                return Err(ErrorCode::InvalidFee.into());
+            }
+            config.protocol_fee_bps = protocol_fee_bps;
+        }
+
+        if let Some(features) = params.features {
+            config.features = features;
+        }
+
+        if let Some(vk_versions) = params.vk_versions {
+            config.vk_versions = vk_versions;
+        }
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            treasury: config.treasury,
+            fee_tiers: config.fee_tiers,
+            protocol_fee_bps: config.protocol_fee_bps,
+            features: config.features,
+            vk_versions: config.vk_versions,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -6,11 +6,14 @@ use mpl_token_metadata::instructions::CreateMetadataAccountV3CpiBuilder;
 use mpl_token_metadata::types::DataV2;
 use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
 
-use crate::state::Pool;
+use crate::state::{Config, Pool};
 use crate::errors::ErrorCode;
+use crate::events::PoolUpgraded;
 
 #[derive(Accounts)]
 pub struct UpgradePool<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
     #[account(mut, seeds = [b"pool", token_mint_x.key().as_ref(), token_mint_y.key().as_ref()], bump)]
     pub pool: Account<'info, Pool>,
     pub token_mint_x: InterfaceAccount<'info, Mint>,
@@ -30,7 +33,7 @@ pub struct UpgradePool<'info> {
         seeds::program = TOKEN_METADATA_PROGRAM_ID
     )]
     pub metadata_account: UncheckedAccount<'info>,
-    #[account(mut)]
+    #[account(mut, address = config.admin @ ErrorCode::Unauthorized)]
     pub payer: Signer<'info>,
     pub lp_token_program: Program<'info, SplToken>,
     /// CHECK: metaplex account
@@ -63,6 +66,12 @@ impl<'info> UpgradePool<'info> {
             return Err(ErrorCode::LpMintAlreadyInitialized.into());
         }
 
+        emit!(PoolUpgraded {
+            pool: self.pool.key(),
+            token_mint_lp: lp_address,
+            slot: Clock::get()?.slot,
+        });
+
         Ok(())
     }
     
@@ -90,7 +99,7 @@ impl<'info> UpgradePool<'info> {
             ),
             lamports,
             space as u64,
-            &self.lp_token_program.key, // Mint needs to be owned by the token program
+            self.lp_token_program.key, // Mint needs to be owned by the token program
         )?;
 
         // Initialize the mint
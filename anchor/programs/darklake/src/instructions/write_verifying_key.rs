@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Config, VerifyingKey};
+use crate::errors::ErrorCode;
+use crate::events::VerifyingKeyWritten;
+
+#[derive(Accounts)]
+#[instruction(version: u32)]
+pub struct WriteVerifyingKey<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut,
+        seeds = [b"verifying_key", version.to_le_bytes().as_ref()],
+        bump = verifying_key.bump,
+        constraint = !verifying_key.finalized @ ErrorCode::VerifyingKeyFinalized
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    pub admin: Signer<'info>,
+}
+
+impl<'info> WriteVerifyingKey<'info> {
+    pub fn write_verifying_key(&mut self, version: u32, offset: u32, bytes: Vec<u8>) -> Result<()> {
+        let data = &mut self.verifying_key.data;
+
+        let start = offset as usize;
+        let end = start
+            .checked_add(bytes.len())
+            .filter(|end| *end <= data.len())
+            .ok_or(ErrorCode::InvalidInput)?;
+
+        data[start..end].copy_from_slice(&bytes);
+
+        emit!(VerifyingKeyWritten {
+            verifying_key: self.verifying_key.key(),
+            version,
+            offset,
+            len: bytes.len() as u32,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
@@ -1,5 +1,23 @@
 use anchor_lang::prelude::*;
 
+pub mod instructions;
+pub mod state;
+pub mod errors;
+pub mod events;
+pub mod constants;
+pub mod math;
+pub mod tick_math;
+pub mod concentrated;
+pub mod stable_math;
+pub mod circuits;
+#[cfg(not(target_os = "solana"))]
+pub mod model;
+pub mod tests;
+
+use instructions::*;
+
+declare_id!("GKkoBt4y1oXfUDkRjZG2wj6PQxw39dxZw6MNMM6tYaBy");
+
 /// The main module for the Darklake program.
 ///
 /// This module contains the entry points for the Darklake program, which implements
@@ -8,61 +26,426 @@ use anchor_lang::prelude::*;
 /// # Parameters
 ///
 /// * `initialize_pool` - Initializes a new liquidity pool.
+///   - `fee_bps`: The swap fee in basis points, one of the config fee tiers. It is charged on the
+///     swap input and stays in the reserves, so it accrues to liquidity providers.
 ///   - `tick_spacing`: The minimum tick separation for position and tick-indexed data. It determines
 ///     the granularity of price increments in the pool. A smaller tick spacing allows for finer price
-///     movements but may increase gas costs for operations that iterate over tick ranges.
-///   - `initial_sqrt_price`: The initial square root price of the pool.
+///     movements but may increase gas costs for operations that iterate over tick ranges. Zero
+///     creates a constant product pool, anything else a concentrated liquidity pool.
+///   - `initial_sqrt_price_x64`: The initial square root price of a concentrated pool, Q64.64 in
+///     the `tick_math` range. Must be zero for a constant product pool.
+///   - `curve`: The invariant of the reserves, `CURVE_CONSTANT_PRODUCT` or `CURVE_STABLE_SWAP`.
+///     StableSwap pools trade pegged tokens close to one for one and cannot be concentrated.
+///   - `amp`: The amplification coefficient of a StableSwap pool, between `MIN_AMP` and
+///     `MAX_AMP`. The higher it is, the flatter the curve around the balanced point. Must be zero
+///     for a constant product curve.
 ///
 /// * `add_liquidity` - Adds liquidity to an existing pool.
-///   - `amount_0`: The amount of token 0 to add.
-///   - `amount_1`: The amount of token 1 to add.
+///   - `amount_0`: The desired amount of token 0 to add.
+///   - `amount_1`: The desired amount of token 1 to add. Both are maximums: once the pool holds
+///     liquidity, one side is matched to the pool ratio and only that is transferred. The amounts
+///     actually used are reported in `LiquidityAdded`.
+///   - `min_liquidity_out`: The minimum LP tokens to mint, otherwise the deposit fails.
+///
+///   StableSwap pools take both amounts whole, in any ratio. The part of a deposit that is not
+///   in the pool ratio pays half the swap fee.
+///
+///   `add_liquidity` and `remove_liquidity` only work on pools without ticks, concentrated
+///   pools fail with `InvalidPoolType`.
+///
+/// * `initialize_tick_array` - Creates the `[b"tick_array", pool, start_tick_index]` account
+///   holding `TICK_ARRAY_SIZE` ticks of a concentrated pool. Anyone can create it, paying its
+///   rent, before using the ticks in it.
+///   - `start_tick_index`: The first tick of the array, a multiple of
+///     `TICK_ARRAY_SIZE * tick_spacing`.
+///
+/// * `open_range_position` - Creates the caller's empty position between two ticks of a
+///   concentrated pool, at `[b"position", pool, owner, tick_lower, tick_upper]`.
 ///   - `tick_lower`: The lower tick of the price range.
-///   - `tick_upper`: The upper tick of the price range.
+///   - `tick_upper`: The upper tick of the price range. Both are multiples of the tick spacing.
+///
+/// * `add_range_liquidity` - Adds liquidity to a position. The liquidity only trades, and earns
+///   fees, while the price is inside the range. Only the position owner can call it, or once
+///   the position has an NFT, the holder of the NFT passing the token account holding it as
+///   `position_nft_account`, whatever address the position lives at.
+///   - `liquidity`: The liquidity to add. The amounts it takes follow from the current price.
+///   - `amount_x_max` / `amount_y_max`: The most the deposit may take, otherwise it fails with
+///     `SlippageExceeded`.
+///
+/// * `mint_position_nft` - Mints a one-of-one NFT for a position, with token metadata and a
+///   master edition, to the position owner. Whoever holds the NFT owns the position from then
+///   on, so transferring the NFT transfers the position. A position can only get one NFT.
+///
+/// * `remove_range_liquidity` - Withdraws liquidity from a position. Only the position owner
+///   can call it, or once the position has an NFT, the holder of the NFT passing the token
+///   account holding it as `position_nft_account`. Anyone else fails with `Unauthorized`.
+///   - `liquidity`: The liquidity to remove, at most the position liquidity.
+///   - `amount_x_min` / `amount_y_min`: The least the withdrawal must pay out.
+///
+/// * `collect_position_fees` - Sends the swap fees earned by a position to its owner. Only the
+///   position owner, or the holder of its NFT, can call it, as for `remove_range_liquidity`.
 ///
 /// * `confidential_swap` - Performs a confidential swap in the pool.
+///   - `circuit_id`: The swap circuit the proof is for, see `SwapCircuit`. The proof is checked
+///     against the verifying key version of that circuit the pool accepts.
 ///   - `proof_a`: The first part of the zero-knowledge proof (64 bytes).
 ///   - `proof_b`: The second part of the zero-knowledge proof (128 bytes).
 ///   - `proof_c`: The third part of the zero-knowledge proof (64 bytes).
-///   - `public_inputs`: The public inputs for the zero-knowledge proof (2 32-byte arrays).
-
-pub mod instructions;
-pub mod state;
-pub mod errors;
-pub mod events;
-pub mod constants;
-pub mod tests;
-
-use instructions::*;
-
-declare_id!("GKkoBt4y1oXfUDkRjZG2wj6PQxw39dxZw6MNMM6tYaBy");
-
+///   - `public_inputs`: The circuit outputs, 32-byte arrays in the circuit layout. For the
+///     constant product circuit, `newBalanceX`, `newBalanceY`, `amountReceived`, `protocolFee`
+///     and `nullifier`. A wrong count fails with `InvalidPublicInputsLength`. The reserves the
+///     proof was made against are taken from the pool account, not from the caller. The
+///     nullifier is recorded in a `[b"nullifier", nullifier]` PDA, so each proof can only be
+///     used once. The proof is also bound to the `user` signer and the token account receiving
+///     the output.
+///   - `is_swap_x_to_y`: The swap direction, checked against the `isSwapXtoY` input of the
+///     proof. A proof made for the other direction fails with `InvalidProof`.
+///   - `expiry_slot`: The last slot the proof can be used in, committed to by the proof. Later
+///     swaps fail with `ProofExpired`.
+///
+///   Constant product pools take proofs of the `ZKConstantProductAMM` circuit and StableSwap
+///   pools proofs of the `ZKStableSwap` circuit, made at the amplification coefficient of the
+///   current slot. The StableSwap outputs have the same layout. Using the circuit of the other
+///   curve fails with `InvalidPoolType`. Exact-output and route circuits are not accepted here
+///   and fail with `UnknownCircuit`, nor do they work on StableSwap pools.
+///
+/// * `confidential_swap_exact_out` - Performs a confidential swap for a private output amount,
+///   proven with the `ZKConstantProductAMMExactOut` circuit. The input is the smallest amount
+///   giving that output, rounded up, and must not exceed a private maximum. Takes the same
+///   accounts and arguments as `confidential_swap` without `circuit_id`, and the outputs have the
+///   same layout.
+///
+/// * `confidential_route_swap` - Swaps through several pools with a single proof of the
+///   `ZKConstantProductRoute` circuit, settling every pool or none. The user only sends the
+///   input to the first pool and receives the output from the last one, the pools in between
+///   pay each other.
+///   - `remaining_accounts`: The pools in route order, `ROUTE_ACCOUNTS_PER_HOP` accounts each:
+///     the pool (writable), its token accounts for the mints it takes in and pays out
+///     (writable), the mint it pays out and that mint's token program. Each pool takes in the
+///     mint the previous one pays out, and the route ends with `token_mint_out`. Anything else
+///     fails with `InvalidRoute`.
+///   - `proof_a`, `proof_b`, `proof_c`: The zero-knowledge proof, checked against the route
+///     verifying key version every pool accepts.
+///   - `public_inputs`: The circuit outputs, `newBalanceX`, `newBalanceY` and `protocolFee` of
+///     every pool, each in route order, then `amountReceived` and `nullifier`. The reserves,
+///     directions and fee rates are taken from the pool accounts.
+///   - `expiry_slot`: The last slot the proof can be used in.
+///
+///   Routes have exactly `ROUTE_HOPS` (two) pools: the circuit has a fixed number of hops and
+///   there is a single route verifying key. Longer routes have to be split into several swaps.
+///
+/// * `confidential_range_swap` - Swaps on a concentrated pool with a proof of the `ZKRangeSwap`
+///   circuit, which runs the swap at the current price and liquidity. The program runs the swap
+///   on the proven input as well and fails with `InvalidSwapAmount` unless it pays out exactly
+///   the proven `amountReceived`. Takes the same accounts and arguments as
+///   `confidential_swap_exact_out`.
+///   - `remaining_accounts`: The tick arrays of the swap, starting with the one holding the
+///     current tick and following each other in the swap direction. Running out of them fails
+///     with `InvalidTickArray`.
+///   - `public_inputs`: The circuit outputs `amountIn`, `amountReceived` and `nullifier`. The
+///     price and liquidity in range the proof was made against are taken from the pool.
+///
+///   The circuit only covers swaps that stay within the liquidity range of the current price,
+///   with liquidity below 2^80. A swap crossing an initialized tick pays out a different amount
+///   than the proven one and is rejected, larger swaps have to be split at the ticks. Both
+///   amounts end up public, only the minimum received stays private.
+///
+/// * `quote_swap` - Quotes a swap against the current pool reserves without changing anything,
+///   returning a `SwapQuote` through the return data. The amounts are the ones the swap circuit
+///   computes, so simulating it gives exactly what a proof for the same reserves will enforce.
+///   - `amount_in`: The amount sent by the user, protocol fee included.
+///   - `is_swap_x_to_y`: The swap direction.
+///   - `remaining_accounts`: For a concentrated pool, the tick arrays of the swap, as for
+///     `confidential_range_swap`.
+///
+///   StableSwap pools are quoted with the `ZKStableSwap` math at the amplification coefficient
+///   of the current slot.
+///
+/// * `migrate_pool` - Converts a pool created before fees, pauses, verifying key versions and
+///   the concentrated and StableSwap pools to the current `Pool` layout, growing the account.
+///   The reserves and liquidity carry over, the pool stays a constant product pool, and the
+///   protocol fee and verifying key versions come from the config as for a new pool. Only the
+///   config admin can call it, paying the extra rent.
+///   - `fee_bps`: The swap fee of the pool from now on, one of the config fee tiers.
+///
+///   Every other account of the program, the config and verifying key registry included, is
+///   new, so upgrading an existing deployment is `initialize_config`, uploading the verifying
+///   keys, then `migrate_pool` on every pool before it can be used again.
+///
+/// * `collect_protocol_fees` - Sends the protocol share of the swap fees to the config treasury.
+///   Only the config admin can call it.
+///
+/// * `initialize_config` - Creates the program config, with the caller as admin. Only the
+///   program upgrade authority can call it.
+///   - `treasury`: The owner of the token accounts receiving the protocol fees.
+///
+/// * `propose_admin` / `accept_admin` - Two-step handover of the config admin.
+///
+/// * `update_config` - Changes the treasury, fee tiers, protocol fee, feature switches or the
+///   verifying key versions given to new pools.
+///
+/// * `set_pool_pause` / `set_global_pause` - Pause swaps, deposits or withdrawals on one pool or on
+///   every pool. Only the config admin can call them.
+///   - `paused`: The paused operations, a combination of the `PAUSE_*` bits.
+///
+/// * `ramp_amp` - Moves the amplification coefficient of a StableSwap pool linearly from its
+///   current value to a target. Only the config admin can call it. A ramp lasts at least
+///   `MIN_AMP_RAMP_SLOTS`, starts at least that long after the previous one, and changes the
+///   coefficient by at most a factor of `MAX_AMP_CHANGE`.
+///   - `target_amp`: The coefficient at the end of the ramp.
+///   - `end_slot`: The slot the ramp ends at.
+///
+/// * `initialize_verifying_key` / `write_verifying_key` / `finalize_verifying_key` - Upload the
+///   Groth16 verifying key of a circuit version, in chunks small enough for a transaction. Only
+///   the config admin can call them.
+///   - `version`: The key version, also the seed of its `[b"verifying_key", version]` PDA.
+///   - `circuit_id`: The circuit the key verifies proofs of.
+///   - `nr_pubinputs`: The number of public signals of the circuit.
+///   - `hash`: The sha256 of the whole key data. The key can only be finalized, and then used by
+///     pools, once the written data matches it. A finalized key can no longer be written.
+///   - `offset` / `bytes`: The chunk to write and where it starts in the key data.
+///
+/// * `set_pool_verifying_key` - Switches a pool to a finalized verifying key version for the
+///   circuit of that key. New pools get the config `vk_versions`, set with `update_config`.
 #[program]
 pub mod darklake {
     use super::*;
 
-    pub fn initialize_pool(ctx: Context<InitializePool>) -> Result<()> {
-        ctx.accounts.initialize_pool(ctx.bumps.pool)
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        fee_bps: u16,
+        tick_spacing: u16,
+        initial_sqrt_price_x64: u128,
+        curve: u8,
+        amp: u32,
+    ) -> Result<()> {
+        ctx.accounts.initialize_pool(
+            fee_bps,
+            tick_spacing,
+            initial_sqrt_price_x64,
+            curve,
+            amp,
+            ctx.bumps.pool,
+        )
     }
 
-    pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_0: u64, amount_1: u64) -> Result<()> {
-        ctx.accounts.add_liquidity(amount_0, amount_1)
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        amount_0: u64,
+        amount_1: u64,
+        min_liquidity_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.add_liquidity(amount_0, amount_1, min_liquidity_out)
     }
 
-    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, amount: u64) -> Result<()> {
-        ctx.accounts.remove_liquidity(amount)
+    pub fn remove_liquidity(
+        ctx: Context<RemoveLiquidity>,
+        amount: u64,
+        min_amount_x: u64,
+        min_amount_y: u64,
+    ) -> Result<()> {
+        ctx.accounts.remove_liquidity(amount, min_amount_x, min_amount_y)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn confidential_swap(
+        ctx: Context<ConfidentialSwap>,
+        circuit_id: u8,
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+        public_inputs: Vec<[u8; 32]>,
+        is_swap_x_to_y: bool,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        ctx.accounts.confidential_swap(
+            circuit_id,
+            proof_a,
+            proof_b,
+            proof_c,
+            public_inputs,
+            is_swap_x_to_y,
+            expiry_slot,
+        )
+    }
+
+    pub fn confidential_swap_exact_out(
         ctx: Context<ConfidentialSwap>,
         proof_a: [u8; 64],
         proof_b: [u8; 128],
         proof_c: [u8; 64],
-        public_inputs: [[u8; 32]; 3]
+        public_inputs: Vec<[u8; 32]>,
+        is_swap_x_to_y: bool,
+        expiry_slot: u64,
     ) -> Result<()> {
-        ctx.accounts.confidential_swap(proof_a, proof_b, proof_c, public_inputs)
+        ctx.accounts.confidential_swap_exact_out(
+            proof_a,
+            proof_b,
+            proof_c,
+            public_inputs,
+            is_swap_x_to_y,
+            expiry_slot,
+        )
+    }
+
+    pub fn confidential_route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConfidentialRouteSwap<'info>>,
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+        public_inputs: Vec<[u8; 32]>,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        ctx.accounts.confidential_route_swap(
+            ctx.remaining_accounts,
+            proof_a,
+            proof_b,
+            proof_c,
+            public_inputs,
+            expiry_slot,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn confidential_range_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConfidentialSwap<'info>>,
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+        public_inputs: Vec<[u8; 32]>,
+        is_swap_x_to_y: bool,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        ctx.accounts.confidential_range_swap(
+            ctx.remaining_accounts,
+            proof_a,
+            proof_b,
+            proof_c,
+            public_inputs,
+            is_swap_x_to_y,
+            expiry_slot,
+        )
+    }
+
+    pub fn quote_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, QuoteSwap<'info>>,
+        amount_in: u64,
+        is_swap_x_to_y: bool,
+    ) -> Result<SwapQuote> {
+        ctx.accounts.quote_swap(ctx.remaining_accounts, amount_in, is_swap_x_to_y)
+    }
+
+    pub fn initialize_tick_array(
+        ctx: Context<InitializeTickArray>,
+        start_tick_index: i32,
+    ) -> Result<()> {
+        ctx.accounts.initialize_tick_array(start_tick_index, ctx.bumps.tick_array)
+    }
+
+    pub fn open_range_position(
+        ctx: Context<OpenRangePosition>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        ctx.accounts.open_range_position(tick_lower, tick_upper, ctx.bumps.position)
+    }
+
+    pub fn add_range_liquidity(
+        ctx: Context<AddRangeLiquidity>,
+        liquidity: u128,
+        amount_x_max: u64,
+        amount_y_max: u64,
+    ) -> Result<()> {
+        ctx.accounts.add_range_liquidity(liquidity, amount_x_max, amount_y_max)
+    }
+
+    pub fn mint_position_nft(ctx: Context<MintPositionNft>) -> Result<()> {
+        ctx.accounts.mint_position_nft()
+    }
+
+    pub fn remove_range_liquidity(
+        ctx: Context<RemoveRangeLiquidity>,
+        liquidity: u128,
+        amount_x_min: u64,
+        amount_y_min: u64,
+    ) -> Result<()> {
+        ctx.accounts.remove_range_liquidity(liquidity, amount_x_min, amount_y_min)
+    }
+
+    pub fn collect_position_fees(ctx: Context<CollectPositionFees>) -> Result<()> {
+        ctx.accounts.collect_position_fees()
     }
 
     pub fn upgrade_pool(ctx: Context<UpgradePool>) -> Result<()> {
         ctx.accounts.upgrade_pool()
     }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.migrate_pool(fee_bps)
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        ctx.accounts.collect_protocol_fees()
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.initialize_config(treasury, ctx.bumps.config)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.propose_admin(new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.accept_admin()
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+        ctx.accounts.update_config(params)
+    }
+
+    pub fn set_pool_pause(ctx: Context<SetPoolPause>, paused: u8) -> Result<()> {
+        ctx.accounts.set_pool_pause(paused)
+    }
+
+    pub fn set_global_pause(ctx: Context<SetGlobalPause>, paused: u8) -> Result<()> {
+        ctx.accounts.set_global_pause(paused)
+    }
+
+    pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u32, end_slot: u64) -> Result<()> {
+        ctx.accounts.ramp_amp(target_amp, end_slot)
+    }
+
+    pub fn initialize_verifying_key(
+        ctx: Context<InitializeVerifyingKey>,
+        version: u32,
+        circuit_id: u8,
+        nr_pubinputs: u8,
+        hash: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.initialize_verifying_key(
+            version,
+            circuit_id,
+            nr_pubinputs,
+            hash,
+            ctx.bumps.verifying_key,
+        )
+    }
+
+    pub fn write_verifying_key(
+        ctx: Context<WriteVerifyingKey>,
+        version: u32,
+        offset: u32,
+        bytes: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.write_verifying_key(version, offset, bytes)
+    }
+
+    pub fn finalize_verifying_key(ctx: Context<FinalizeVerifyingKey>, version: u32) -> Result<()> {
+        ctx.accounts.finalize_verifying_key(version)
+    }
+
+    pub fn set_pool_verifying_key(ctx: Context<SetPoolVerifyingKey>) -> Result<()> {
+        ctx.accounts.set_pool_verifying_key()
+    }
 }
@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::errors::ErrorCode;
+
+/// `a * b / denominator`, rounded down.
+pub fn mul_div_floor(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    if denominator == 0 {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+
+    let product = a.checked_mul(b).ok_or(ErrorCode::MathOverflow)?;
+    Ok(product / denominator)
+}
+
+/// `a * b / denominator`, rounded up.
+pub fn mul_div_ceil(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    if denominator == 0 {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+
+    let product = a.checked_mul(b).ok_or(ErrorCode::MathOverflow)?;
+    Ok(product.div_ceil(denominator))
+}
+
+/// `a * b` as its high and low 128 bits, which order like the full product.
+pub fn widening_mul(a: u128, b: u64) -> (u128, u128) {
+    let b = b as u128;
+    let low = (a as u64 as u128) * b;
+    let high = (a >> 64) * b;
+    let (low, carry) = low.overflowing_add(high << 64);
+    ((high >> 64) + carry as u128, low)
+}
+
+/// `a * b / denominator`, rounded down, for products that do not fit in a `u128`.
+pub fn wide_mul_div_floor(a: u128, b: u64, denominator: u128) -> Result<u128> {
+    let (high, low) = widening_mul(a, b);
+    // The quotient only fits in a u128 when the high half is below the denominator
+    if high >= denominator {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+
+    // Long division of the low half, one bit at a time, carrying the remainder in `high`
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1 << bit;
+        }
+    }
+    Ok(quotient)
+}
+
+/// Narrows a `u128` to a `u64`.
+pub fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Integer square root, rounded down.
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    // Start from a power of two above the root, Newton's method then decreases
+    // monotonically towards it.
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Share of `reserve` owned by `amount` out of `total`, rounded down so the pool never
+/// pays out more than it holds.
+pub fn proportional_share(amount: u64, reserve: u64, total: u128) -> Result<u64> {
+    to_u64(mul_div_floor(amount as u128, reserve as u128, total)?)
+}
+
+/// Liquidity created by the first deposit into an empty pool, the geometric mean of the
+/// deposited amounts.
+///
+/// Returns the total liquidity and the part minted to the depositor, the difference being
+/// `MINIMUM_LIQUIDITY`, which is never minted and so stays locked in the pool.
+pub fn initial_liquidity(amount_x: u64, amount_y: u64) -> Result<(u128, u64)> {
+    let liquidity = isqrt(amount_x as u128 * amount_y as u128);
+
+    let minted = liquidity
+        .checked_sub(MINIMUM_LIQUIDITY as u128)
+        .filter(|minted| *minted > 0)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+    // The root of a product of two u64 always fits in a u64
+    Ok((liquidity, to_u64(minted)?))
+}
+
+/// Amount of the other token matching `amount` at the `reserve_in`:`reserve_out` ratio,
+/// rounded down.
+pub fn quote(amount: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+
+    to_u64(mul_div_floor(amount as u128, reserve_out as u128, reserve_in as u128)?)
+}
+
+/// Largest amounts, up to `amount_x_desired` and `amount_y_desired`, matching the
+/// `reserve_x`:`reserve_y` ratio. One side is always taken in full.
+pub fn optimal_deposit_amounts(
+    amount_x_desired: u64,
+    amount_y_desired: u64,
+    reserve_x: u64,
+    reserve_y: u64,
+) -> Result<(u64, u64)> {
+    let amount_y_optimal = quote(amount_x_desired, reserve_x, reserve_y);
+    if let Ok(amount_y_optimal) = amount_y_optimal {
+        if amount_y_optimal <= amount_y_desired {
+            return Ok((amount_x_desired, amount_y_optimal));
+        }
+    }
+
+    // Either amount_y_desired < amount_x_desired * reserve_y / reserve_x, or that
+    // quote does not even fit in a u64, so this stays below amount_x_desired
+    let amount_x_optimal = quote(amount_y_desired, reserve_y, reserve_x)?;
+    Ok((amount_x_optimal, amount_y_desired))
+}
+
+/// LP tokens minted for a deposit of at most `amount_x_desired` and `amount_y_desired` into a
+/// pool holding `liquidity`, and the amounts actually taken for them.
+///
+/// The desired amounts are first brought to the pool ratio, then trimmed to what the minted
+/// liquidity is worth, rounded up so the depositor never gets a larger share of the reserves
+/// than they paid for.
+pub fn deposit_amounts(
+    amount_x_desired: u64,
+    amount_y_desired: u64,
+    reserve_x: u64,
+    reserve_y: u64,
+    liquidity: u128,
+) -> Result<(u64, u64, u64)> {
+    if reserve_x == 0 || reserve_y == 0 || liquidity == 0 {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+
+    let (amount_x, amount_y) =
+        optimal_deposit_amounts(amount_x_desired, amount_y_desired, reserve_x, reserve_y)?;
+
+    let liquidity_x = mul_div_floor(amount_x as u128, liquidity, reserve_x as u128)?;
+    let liquidity_y = mul_div_floor(amount_y as u128, liquidity, reserve_y as u128)?;
+    let minted = to_u64(liquidity_x.min(liquidity_y))?;
+
+    if minted == 0 {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+
+    // minted * reserve / liquidity <= amount, so both amounts fit in a u64
+    let used_x = to_u64(mul_div_ceil(minted as u128, reserve_x as u128, liquidity)?)?;
+    let used_y = to_u64(mul_div_ceil(minted as u128, reserve_y as u128, liquidity)?)?;
+
+    Ok((minted, used_x, used_y))
+}
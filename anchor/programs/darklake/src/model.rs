@@ -0,0 +1,507 @@
+//! Reference model of `ZKConstantProductAMM` in `circuits/swap.circom`,
+//! `ZKConstantProductAMMExactOut` in `circuits/swap_exact_out.circom`,
+//! `ZKConstantProductRoute` in `circuits/route.circom` and `ZKStableSwap` in
+//! `circuits/stable_swap.circom`, for checking the circuits and the program against each
+//! other off chain.
+//!
+//! Every signal is computed as the circuit computes it, in the BN254 scalar field, and every
+//! constraint is checked with the same bit widths as the circomlib templates, so the model
+//! accepts exactly the inputs a witness can be built for. The nullifier is left out, it is a
+//! Poseidon hash unrelated to the swap amounts.
+
+use anchor_lang::prelude::*;
+use num_bigint::BigUint;
+
+use crate::errors::ErrorCode;
+use crate::stable_math::{compute_d, compute_y, MAX_AMP, MIN_AMP};
+
+/// Modulus of the BN254 scalar field, the field circom signals live in.
+const SCALAR_FIELD_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/// Denominator of the fee rates in the circuit, basis points.
+const BPS: u64 = 10_000;
+
+/// Private and public inputs of the circuit that determine the swap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelInputs {
+    pub amount_in: u64,
+    pub min_received: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+}
+
+/// Private and public inputs of the exact output circuit that determine the swap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExactOutModelInputs {
+    pub amount_out: u64,
+    pub max_amount_in: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+}
+
+/// Private and public inputs of the StableSwap circuit that determine the swap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StableModelInputs {
+    pub amount_in: u64,
+    pub min_received: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub amp: u64,
+}
+
+/// Public inputs of the route circuit for one of its pools.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RouteHopInputs {
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub is_swap_x_to_y: bool,
+    pub fee_bps: u16,
+    pub protocol_fee_bps: u16,
+}
+
+/// Circuit outputs, nullifier aside. The reserve receiving the input can grow past a `u64`,
+/// which the program then rejects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelOutputs {
+    pub new_balance_x: u128,
+    pub new_balance_y: u128,
+    pub amount_received: u128,
+    pub protocol_fee: u128,
+}
+
+/// Runs the circuit on `inputs`.
+///
+/// Fails with `InvalidFee` when a fee rate is above 100%, `InsufficientLiquidity` when a
+/// division by zero would be needed, `SlippageExceeded` when less than `min_received` comes
+/// out, and `MathOverflow` when a value does not pass a range check.
+pub fn swap(inputs: &ModelInputs) -> Result<ModelOutputs> {
+    let field = Field::new();
+
+    let amount_in = field.element(inputs.amount_in);
+    let min_received = field.element(inputs.min_received);
+    let balance_x = field.element(inputs.reserve_x);
+    let balance_y = field.element(inputs.reserve_y);
+    let is_swap_x_to_y = field.element(inputs.is_swap_x_to_y as u64);
+    let fee_rate = field.element(inputs.fee_bps as u64);
+    let protocol_fee_rate = field.element(inputs.protocol_fee_bps as u64);
+    let bps = field.element(BPS);
+
+    if !field.less_eq_than(&fee_rate, &bps, 14)?
+        || !field.less_eq_than(&protocol_fee_rate, &bps, 14)?
+    {
+        return Err(ErrorCode::InvalidFee.into());
+    }
+
+    // Mux1: c[0] + s * (c[1] - c[0])
+    let input_balance = field.mux1(&balance_y, &balance_x, &is_swap_x_to_y);
+    let output_balance = field.mux1(&balance_x, &balance_y, &is_swap_x_to_y);
+
+    let fee_numerator = field.mul(&amount_in, &fee_rate);
+    let protocol_fee = field.reciprocal_division(
+        &field.mul(&fee_numerator, &protocol_fee_rate),
+        &field.element(BPS * BPS),
+    )?;
+
+    let new_input_balance = field.sub(&field.add(&input_balance, &amount_in), &protocol_fee);
+
+    let amount_in_with_fee = field.mul(&amount_in, &field.sub(&bps, &fee_rate));
+    let output_numerator = field.mul(&amount_in_with_fee, &output_balance);
+    let output_denominator = field.add(&field.mul(&input_balance, &bps), &amount_in_with_fee);
+    let amount_out = field.reciprocal_division(&output_numerator, &output_denominator)?;
+
+    let new_output_balance = field.sub(&output_balance, &amount_out);
+
+    let not_x_to_y = field.sub(&field.element(1), &is_swap_x_to_y);
+    let new_balance_x = field.add(
+        &field.mul(&is_swap_x_to_y, &new_input_balance),
+        &field.mul(&not_x_to_y, &new_output_balance),
+    );
+    let new_balance_y = field.add(
+        &field.mul(&is_swap_x_to_y, &new_output_balance),
+        &field.mul(&not_x_to_y, &new_input_balance),
+    );
+
+    if !field.greater_eq_than(&amount_out, &min_received, 252)? {
+        return Err(ErrorCode::SlippageExceeded.into());
+    }
+
+    field.num2bits(&amount_in, 252)?;
+    field.num2bits(&min_received, 252)?;
+
+    let zero = field.element(0);
+    if !field.greater_eq_than(&new_balance_x, &zero, 252)?
+        || !field.greater_eq_than(&new_balance_y, &zero, 252)?
+    {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+
+    Ok(ModelOutputs {
+        new_balance_x: to_u128(&new_balance_x)?,
+        new_balance_y: to_u128(&new_balance_y)?,
+        amount_received: to_u128(&amount_out)?,
+        protocol_fee: to_u128(&protocol_fee)?,
+    })
+}
+
+/// Runs the exact output circuit on `inputs`. Returns the outputs with the amount the user
+/// sends, which the circuit keeps private.
+///
+/// Fails like `swap`, with `InsufficientLiquidity` when `amount_out` is not below the output
+/// reserve and `SlippageExceeded` when more than `max_amount_in` has to go in. An input that
+/// does not fit in a `u128` fails with `MathOverflow`, the program could not accept it either.
+pub fn swap_exact_out(inputs: &ExactOutModelInputs) -> Result<(ModelOutputs, u128)> {
+    let field = Field::new();
+
+    let amount_out = field.element(inputs.amount_out);
+    let max_amount_in = field.element(inputs.max_amount_in);
+    let balance_x = field.element(inputs.reserve_x);
+    let balance_y = field.element(inputs.reserve_y);
+    let is_swap_x_to_y = field.element(inputs.is_swap_x_to_y as u64);
+    let fee_rate = field.element(inputs.fee_bps as u64);
+    let protocol_fee_rate = field.element(inputs.protocol_fee_bps as u64);
+    let bps = field.element(BPS);
+
+    if !field.less_eq_than(&fee_rate, &bps, 14)?
+        || !field.less_eq_than(&protocol_fee_rate, &bps, 14)?
+    {
+        return Err(ErrorCode::InvalidFee.into());
+    }
+
+    field.num2bits(&amount_out, 252)?;
+    field.num2bits(&max_amount_in, 252)?;
+
+    let input_balance = field.mux1(&balance_y, &balance_x, &is_swap_x_to_y);
+    let output_balance = field.mux1(&balance_x, &balance_y, &is_swap_x_to_y);
+
+    if !field.less_than(&amount_out, &output_balance, 252)? {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+
+    let new_output_balance = field.sub(&output_balance, &amount_out);
+
+    // Rounded up: (dividend + divisor - 1) / divisor
+    let input_dividend = field.mul(&field.mul(&input_balance, &bps), &amount_out);
+    let input_divisor = field.mul(&new_output_balance, &field.sub(&bps, &fee_rate));
+    let amount_in = field.reciprocal_division(
+        &field.sub(&field.add(&input_dividend, &input_divisor), &field.element(1)),
+        &input_divisor,
+    )?;
+
+    let fee_numerator = field.mul(&amount_in, &fee_rate);
+    let protocol_fee = field.reciprocal_division(
+        &field.mul(&fee_numerator, &protocol_fee_rate),
+        &field.element(BPS * BPS),
+    )?;
+
+    let new_input_balance = field.sub(&field.add(&input_balance, &amount_in), &protocol_fee);
+
+    let not_x_to_y = field.sub(&field.element(1), &is_swap_x_to_y);
+    let new_balance_x = field.add(
+        &field.mul(&is_swap_x_to_y, &new_input_balance),
+        &field.mul(&not_x_to_y, &new_output_balance),
+    );
+    let new_balance_y = field.add(
+        &field.mul(&is_swap_x_to_y, &new_output_balance),
+        &field.mul(&not_x_to_y, &new_input_balance),
+    );
+
+    if !field.less_eq_than(&amount_in, &max_amount_in, 252)? {
+        return Err(ErrorCode::SlippageExceeded.into());
+    }
+
+    let zero = field.element(0);
+    if !field.greater_eq_than(&new_balance_x, &zero, 252)?
+        || !field.greater_eq_than(&new_balance_y, &zero, 252)?
+    {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+
+    Ok((
+        ModelOutputs {
+            new_balance_x: to_u128(&new_balance_x)?,
+            new_balance_y: to_u128(&new_balance_y)?,
+            amount_received: to_u128(&amount_out)?,
+            protocol_fee: to_u128(&protocol_fee)?,
+        },
+        to_u128(&amount_in)?,
+    ))
+}
+
+/// Runs the route circuit on `hops`, in route order: each pool swaps the whole output of the
+/// previous one with the math of `swap`. Returns the outputs of every pool, the last
+/// `amount_received` being what the user gets.
+///
+/// Fails like `swap`, with `SlippageExceeded` only when the last pool pays out less than
+/// `min_received`.
+pub fn swap_route(
+    amount_in: u64,
+    min_received: u64,
+    hops: &[RouteHopInputs],
+) -> Result<Vec<ModelOutputs>> {
+    let mut outputs: Vec<ModelOutputs> = Vec::with_capacity(hops.len());
+    for hop in hops {
+        let hop_amount_in = match outputs.last() {
+            Some(previous) => u64::try_from(previous.amount_received)
+                .map_err(|_| ErrorCode::MathOverflow)?,
+            None => amount_in,
+        };
+        outputs.push(swap(&ModelInputs {
+            amount_in: hop_amount_in,
+            min_received: 0,
+            reserve_x: hop.reserve_x,
+            reserve_y: hop.reserve_y,
+            is_swap_x_to_y: hop.is_swap_x_to_y,
+            fee_bps: hop.fee_bps,
+            protocol_fee_bps: hop.protocol_fee_bps,
+        })?);
+    }
+
+    let received = outputs.last().map_or(0, |last| last.amount_received);
+    if received < min_received as u128 {
+        return Err(ErrorCode::SlippageExceeded.into());
+    }
+
+    Ok(outputs)
+}
+
+/// Runs the StableSwap circuit on `inputs`, with the invariant and the new output balance the
+/// prover supplies taken from `stable_math`.
+///
+/// Fails like `swap`, with `InvalidAmp` when the amplification coefficient is out of range
+/// and `InsufficientLiquidity` when the pool is empty or cannot pay the output out.
+pub fn swap_stable(inputs: &StableModelInputs) -> Result<ModelOutputs> {
+    let field = Field::new();
+
+    let amount_in = field.element(inputs.amount_in);
+    let min_received = field.element(inputs.min_received);
+    let balance_x = field.element(inputs.reserve_x);
+    let balance_y = field.element(inputs.reserve_y);
+    let is_swap_x_to_y = field.element(inputs.is_swap_x_to_y as u64);
+    let fee_rate = field.element(inputs.fee_bps as u64);
+    let protocol_fee_rate = field.element(inputs.protocol_fee_bps as u64);
+    let amp = field.element(inputs.amp);
+    let bps = field.element(BPS);
+
+    if !field.less_eq_than(&fee_rate, &bps, 14)?
+        || !field.less_eq_than(&protocol_fee_rate, &bps, 14)?
+    {
+        return Err(ErrorCode::InvalidFee.into());
+    }
+
+    field.num2bits(&amp, 20)?;
+    if !field.greater_eq_than(&amp, &field.element(MIN_AMP), 20)?
+        || !field.less_eq_than(&amp, &field.element(MAX_AMP), 20)?
+    {
+        return Err(ErrorCode::InvalidAmp.into());
+    }
+
+    field.num2bits(&amount_in, 64)?;
+    field.num2bits(&min_received, 64)?;
+
+    let input_balance = field.mux1(&balance_y, &balance_x, &is_swap_x_to_y);
+    let output_balance = field.mux1(&balance_x, &balance_y, &is_swap_x_to_y);
+
+    let fee_numerator = field.mul(&amount_in, &fee_rate);
+    let protocol_fee = field.reciprocal_division(
+        &field.mul(&fee_numerator, &protocol_fee_rate),
+        &field.element(BPS * BPS),
+    )?;
+
+    let new_input_balance = field.sub(&field.add(&input_balance, &amount_in), &protocol_fee);
+
+    // The fee stays out of the balance the invariant is kept at, so the invariant grows by it
+    let amount_in_after_fee = field.reciprocal_division(
+        &field.mul(&amount_in, &field.sub(&bps, &fee_rate)),
+        &bps,
+    )?;
+
+    let invariant = field.element_u128(compute_d(inputs.amp, inputs.reserve_x, inputs.reserve_y)?);
+    field.num2bits(&invariant, 66)?;
+    if invariant == field.element(0) {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+
+    // G(D) <= 0 < G(D + 1)
+    let next_invariant = field.add(&invariant, &field.element(1));
+    if !field.less_eq_than(
+        &field.invariant_positive(&balance_x, &balance_y, &amp, &invariant),
+        &field.invariant_negative(&balance_x, &balance_y, &amp),
+        252,
+    )? || !field.less_than(
+        &field.invariant_negative(&balance_x, &balance_y, &amp),
+        &field.invariant_positive(&balance_x, &balance_y, &amp, &next_invariant),
+        252,
+    )? {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+
+    // H(y') >= 0 > H(y' - 1), y' being at least one since H(0) = -D^3
+    let balance_after_fee = field.add(&input_balance, &amount_in_after_fee);
+    let new_output_balance = field.element_u128(compute_y(
+        inputs.amp,
+        to_u128(&balance_after_fee)?,
+        to_u128(&invariant)?,
+    )?);
+    field.num2bits(&new_output_balance, 64)?;
+    let previous_output_balance = field.sub(&new_output_balance, &field.element(1));
+    if field.less_than(
+        &field.balance_positive(&balance_after_fee, &new_output_balance, &amp, &invariant),
+        &field.balance_negative(&balance_after_fee, &new_output_balance, &amp, &invariant),
+        252,
+    )? || !field.less_than(
+        &field.balance_positive(&balance_after_fee, &previous_output_balance, &amp, &invariant),
+        &field.balance_negative(&balance_after_fee, &previous_output_balance, &amp, &invariant),
+        252,
+    )? {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+
+    if !field.less_eq_than(&new_output_balance, &output_balance, 64)? {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+    let amount_out = field.sub(&output_balance, &new_output_balance);
+
+    let not_x_to_y = field.sub(&field.element(1), &is_swap_x_to_y);
+    let new_balance_x = field.add(
+        &field.mul(&is_swap_x_to_y, &new_input_balance),
+        &field.mul(&not_x_to_y, &new_output_balance),
+    );
+    let new_balance_y = field.add(
+        &field.mul(&is_swap_x_to_y, &new_output_balance),
+        &field.mul(&not_x_to_y, &new_input_balance),
+    );
+
+    if !field.greater_eq_than(&amount_out, &min_received, 64)? {
+        return Err(ErrorCode::SlippageExceeded.into());
+    }
+
+    Ok(ModelOutputs {
+        new_balance_x: to_u128(&new_balance_x)?,
+        new_balance_y: to_u128(&new_balance_y)?,
+        amount_received: to_u128(&amount_out)?,
+        protocol_fee: to_u128(&protocol_fee)?,
+    })
+}
+
+/// Arithmetic modulo the scalar field, with the circomlib templates the circuit uses.
+struct Field {
+    modulus: BigUint,
+}
+
+impl Field {
+    fn new() -> Self {
+        Field {
+            modulus: BigUint::parse_bytes(SCALAR_FIELD_MODULUS.as_bytes(), 10)
+                .expect("modulus is a decimal number"),
+        }
+    }
+
+    fn element(&self, value: u64) -> BigUint {
+        BigUint::from(value)
+    }
+
+    fn element_u128(&self, value: u128) -> BigUint {
+        BigUint::from(value)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + b) % &self.modulus
+    }
+
+    fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + &self.modulus - b) % &self.modulus
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.modulus
+    }
+
+    fn mux1(&self, c0: &BigUint, c1: &BigUint, s: &BigUint) -> BigUint {
+        self.add(c0, &self.mul(s, &self.sub(c1, c0)))
+    }
+
+    /// `Num2Bits(n)`: the value must fit in `n` bits.
+    fn num2bits(&self, value: &BigUint, n: u32) -> Result<()> {
+        if value.bits() > n as u64 {
+            return Err(ErrorCode::MathOverflow.into());
+        }
+        Ok(())
+    }
+
+    /// `LessThan(n)`: decomposes `a + 2^n - b` in `n + 1` bits and reads the top one.
+    fn less_than(&self, a: &BigUint, b: &BigUint, n: u32) -> Result<bool> {
+        let shifted = self.sub(&self.add(a, &(BigUint::from(1u8) << n)), b);
+        self.num2bits(&shifted, n + 1)?;
+        Ok(shifted.bits() <= n as u64)
+    }
+
+    /// `LessEqThan(n)`: `LessThan(n)` of `a` and `b + 1`.
+    fn less_eq_than(&self, a: &BigUint, b: &BigUint, n: u32) -> Result<bool> {
+        self.less_than(a, &self.add(b, &self.element(1)), n)
+    }
+
+    /// `GreaterEqThan(n)`: `LessThan(n)` of `b` and `a + 1`.
+    fn greater_eq_than(&self, a: &BigUint, b: &BigUint, n: u32) -> Result<bool> {
+        self.less_than(b, &self.add(a, &self.element(1)), n)
+    }
+
+    /// Positive terms of the StableSwap `G(d)`: `d^3 + 4xy(4A - 1)d`.
+    fn invariant_positive(&self, x: &BigUint, y: &BigUint, amp: &BigUint, d: &BigUint) -> BigUint {
+        let xy = self.mul(x, y);
+        let d_cube = self.mul(&self.mul(d, d), d);
+        let amp_term = self.sub(&self.mul(amp, &self.element(4)), &self.element(1));
+        self.add(&d_cube, &self.mul(&self.mul(&self.mul(&xy, &amp_term), d), &self.element(4)))
+    }
+
+    /// Negative term of the StableSwap `G(d)`: `16Axy(x + y)`.
+    fn invariant_negative(&self, x: &BigUint, y: &BigUint, amp: &BigUint) -> BigUint {
+        let xy = self.mul(x, y);
+        self.mul(&self.mul(&self.mul(&xy, &self.add(x, y)), amp), &self.element(16))
+    }
+
+    /// Positive terms of the StableSwap `H(y)`: `16Axy^2 + 16Ax^2y + 4dxy`.
+    fn balance_positive(&self, x: &BigUint, y: &BigUint, amp: &BigUint, d: &BigUint) -> BigUint {
+        let xy = self.mul(x, y);
+        let amp_xy = self.mul(&self.mul(amp, &xy), &self.element(16));
+        self.add(
+            &self.mul(&amp_xy, &self.add(x, y)),
+            &self.mul(&self.mul(d, &xy), &self.element(4)),
+        )
+    }
+
+    /// Negative terms of the StableSwap `H(y)`: `16Adxy + d^3`.
+    fn balance_negative(&self, x: &BigUint, y: &BigUint, amp: &BigUint, d: &BigUint) -> BigUint {
+        let xy = self.mul(x, y);
+        let d_cube = self.mul(&self.mul(d, d), d);
+        self.add(&self.mul(&self.mul(&self.mul(amp, d), &xy), &self.element(16)), &d_cube)
+    }
+
+    /// `ReciprocalDivision(252)` in `circuits/division.circom`: floor division of the field
+    /// representatives, with a non-zero divisor and a remainder below it.
+    fn reciprocal_division(&self, dividend: &BigUint, divisor: &BigUint) -> Result<BigUint> {
+        if *divisor == self.element(0) {
+            return Err(ErrorCode::InsufficientLiquidity.into());
+        }
+
+        let quotient = dividend / divisor;
+        let remainder = dividend % divisor;
+        if !self.less_than(&remainder, divisor, 252)? {
+            return Err(ErrorCode::MathOverflow.into());
+        }
+        Ok(quotient)
+    }
+}
+
+fn to_u128(value: &BigUint) -> Result<u128> {
+    u128::try_from(value).map_err(|_| ErrorCode::MathOverflow.into())
+}
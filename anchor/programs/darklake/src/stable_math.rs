@@ -0,0 +1,197 @@
+//! Invariant math of StableSwap pools.
+//!
+//! For reserves `x` and `y` and the amplification coefficient `A`, the invariant `D` solves the
+//! two coin StableSwap equation `4A(x + y) + D = 4AD + D^3 / (4xy)`. A large `A` keeps the
+//! curve close to a constant sum around the balanced point, away from it the curve turns into
+//! a constant product.
+//!
+//! Multiplied out, `D` is the root of `G(D) = D^3 + 4xy(4A - 1)D - 16Axy(x + y)`, which grows
+//! with `D`. The invariant of a pool is the largest integer where `G` is not positive, the
+//! same value the `ZKStableSwap` circuit checks, so the program and the circuit round alike.
+
+use std::cmp::Ordering;
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{FEE_DENOMINATOR, MINIMUM_LIQUIDITY};
+use crate::errors::ErrorCode;
+use crate::math::{mul_div_floor, to_u64};
+use crate::tick_math::U256;
+
+/// Lowest amplification coefficient, where the curve is closest to a constant product.
+pub const MIN_AMP: u64 = 1;
+
+/// Highest amplification coefficient. The circuit range checks it in 20 bits.
+pub const MAX_AMP: u64 = 1_000_000;
+
+fn check_amp(amp: u64) -> Result<()> {
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return Err(ErrorCode::InvalidAmp.into());
+    }
+    Ok(())
+}
+
+/// Compares the positive and negative terms of `G(d)`.
+fn compare_invariant(amp: u64, x: u128, y: u128, d: u128) -> Ordering {
+    let amp = U256::from(amp);
+    let xy = U256::from(x) * U256::from(y);
+    let d = U256::from(d);
+    let positive = d * d * d + xy * (amp * 4 - 1) * d * 4;
+    let negative = xy * (U256::from(x) + U256::from(y)) * amp * 16;
+    positive.cmp(&negative)
+}
+
+/// Invariant of reserves `x` and `y` under `amp`: the largest `d` with `G(d) <= 0`.
+pub fn compute_d(amp: u64, x: u64, y: u64) -> Result<u128> {
+    check_amp(amp)?;
+    let (x, y) = (x as u128, y as u128);
+    if x == 0 || y == 0 {
+        return Ok(0);
+    }
+
+    // G is convex on d >= 0 and G(x + y) = (x + y)(x - y)^2 >= 0, so Newton's method from
+    // x + y decreases towards the root. Rounded down, it stops on the largest d below it.
+    let amp_u256 = U256::from(amp);
+    let xy = U256::from(x) * U256::from(y);
+    let constant = xy * U256::from(x + y) * amp_u256 * 16;
+    let linear = xy * (amp_u256 * 4 - 1) * 4;
+    let mut d = U256::from(x + y);
+    loop {
+        let numerator = d * d * d * 2 + constant;
+        let denominator = d * d * 3 + linear;
+        let next = numerator / denominator;
+        if next >= d {
+            break;
+        }
+        d = next;
+    }
+
+    let d = d.as_u128();
+    if compare_invariant(amp, x, y, d) == Ordering::Greater
+        || compare_invariant(amp, x, y, d + 1) != Ordering::Greater
+    {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+    Ok(d)
+}
+
+/// Compares the positive and negative terms of `H(y) = 16Axy^2 + 16Ax^2y + 4Dxy - 16ADxy - D^3`,
+/// the invariant equation at `d` multiplied by `4xy`. `H(y) >= 0` exactly when the invariant
+/// of `x` and `y` is at least `d`.
+fn compare_balance(amp: u64, x: u128, y: u128, d: u128) -> Ordering {
+    let amp = U256::from(amp);
+    let (x, y, d) = (U256::from(x), U256::from(y), U256::from(d));
+    let xy = x * y;
+    let positive: U256 = amp * xy * y * 16 + amp * xy * x * 16 + d * xy * 4;
+    let negative: U256 = amp * d * xy * 16 + d * d * d;
+    positive.cmp(&negative)
+}
+
+/// Smallest balance of the other token keeping the invariant at least `d` when the pool holds
+/// `x`. Rounded up, so a swap paying out down to it never lowers the invariant.
+pub fn compute_y(amp: u64, x: u128, d: u128) -> Result<u128> {
+    check_amp(amp)?;
+    if x == 0 || d == 0 {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+
+    // H(y) = a y^2 + b y - c is a convex parabola below zero at y = 0. From a y where it is
+    // positive, Newton's method rounded up stays above the root.
+    let amp_u256 = U256::from(amp);
+    let (x_u256, d_u256) = (U256::from(x), U256::from(d));
+    let a: U256 = amp_u256 * x_u256 * 16;
+    let b_positive: U256 = amp_u256 * x_u256 * x_u256 * 16 + d_u256 * x_u256 * 4;
+    let b_negative: U256 = amp_u256 * d_u256 * x_u256 * 16;
+    let c = d_u256 * d_u256 * d_u256;
+
+    // For y >= d, H(y) >= 4dxy - d^3, positive from d^2 / 4x on
+    let start = (d_u256 * d_u256 + x_u256 * 4 - 1) / (x_u256 * 4);
+    let mut y = start.max(d_u256);
+    loop {
+        // y - H(y) / H'(y) = (a y^2 + c) / (2 a y + b)
+        let numerator = a
+            .checked_mul(y)
+            .and_then(|ay| ay.checked_mul(y))
+            .and_then(|ay2| ay2.checked_add(c))
+            .ok_or(ErrorCode::MathOverflow)?;
+        let slope: U256 = a * y * 2 + b_positive;
+        let denominator = slope
+            .checked_sub(b_negative)
+            .filter(|denominator| !denominator.is_zero())
+            .ok_or(ErrorCode::MathOverflow)?;
+        let next = (numerator + denominator - 1) / denominator;
+        if next >= y {
+            break;
+        }
+        y = next;
+    }
+
+    if y > U256::from(u128::MAX) {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+    let mut y = y.as_u128();
+    while y > 0 && compare_balance(amp, x, y - 1, d) != Ordering::Less {
+        y -= 1;
+    }
+    Ok(y)
+}
+
+/// LP tokens minted for depositing `amount_x` and `amount_y` into a StableSwap pool holding
+/// `liquidity`, and the liquidity the pool gains for them.
+///
+/// The first deposit mints the invariant less `MINIMUM_LIQUIDITY`, which stays locked like in a
+/// constant product pool. Later deposits can be in any ratio and mint in proportion to how
+/// much they raise the invariant. The part of a deposit that is not in the pool ratio pays
+/// half the swap fee, as a swap would, so depositing one token and withdrawing both is no
+/// cheaper than swapping. That fee stays in the reserves.
+pub fn stable_deposit(
+    amp: u64,
+    amount_x: u64,
+    amount_y: u64,
+    reserve_x: u64,
+    reserve_y: u64,
+    liquidity: u128,
+    fee_bps: u16,
+) -> Result<(u64, u128)> {
+    let new_x = reserve_x.checked_add(amount_x).ok_or(ErrorCode::MathOverflow)?;
+    let new_y = reserve_y.checked_add(amount_y).ok_or(ErrorCode::MathOverflow)?;
+    let d_after = compute_d(amp, new_x, new_y)?;
+
+    if liquidity == 0 {
+        let minted = d_after
+            .checked_sub(MINIMUM_LIQUIDITY as u128)
+            .filter(|minted| *minted > 0)
+            .ok_or(ErrorCode::InsufficientLiquidity)?;
+        return Ok((to_u64(minted)?, d_after));
+    }
+
+    let d_before = compute_d(amp, reserve_x, reserve_y)?;
+    if d_before == 0 || d_after <= d_before {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+
+    // Fee on how far each balance ends up from the pool ratio, rounded up
+    let imbalance_fee = |reserve: u64, new_balance: u64| -> Result<u64> {
+        let ideal = mul_div_floor(d_after, reserve as u128, d_before)?;
+        let difference = ideal.abs_diff(new_balance as u128);
+        let fee = (difference * fee_bps as u128).div_ceil(2 * FEE_DENOMINATOR as u128);
+        to_u64(fee)
+    };
+    let fee_x = imbalance_fee(reserve_x, new_x)?;
+    let fee_y = imbalance_fee(reserve_y, new_y)?;
+    let d_less_fees = compute_d(
+        amp,
+        new_x.checked_sub(fee_x).ok_or(ErrorCode::MathOverflow)?,
+        new_y.checked_sub(fee_y).ok_or(ErrorCode::MathOverflow)?,
+    )?;
+
+    let minted = mul_div_floor(
+        liquidity,
+        d_less_fees.saturating_sub(d_before),
+        d_before,
+    )?;
+    if minted == 0 {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+    Ok((to_u64(minted)?, minted))
+}
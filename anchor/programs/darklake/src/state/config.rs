@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::FEE_TIER_COUNT;
+use crate::circuits::MAX_CIRCUITS;
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Config {
+    /// Authority allowed to run the admin instructions.
+    pub admin: Pubkey,
+    /// Admin proposed by `propose_admin`, waiting for `accept_admin`. Unset when
+    /// equal to `Pubkey::default()`.
+    pub pending_admin: Pubkey,
+    /// Owner of the token accounts that receive the protocol fees.
+    pub treasury: Pubkey,
+    /// Swap fees, in basis points, that new pools can be created with.
+    pub fee_tiers: [u16; FEE_TIER_COUNT],
+    /// Protocol share of the swap fee, in basis points, given to new pools.
+    pub protocol_fee_bps: u16,
+    /// Feature switches, see the `FEATURE_*` constants.
+    pub features: u8,
+    /// Operations paused on every pool, see the `PAUSE_*` constants.
+    pub paused: u8,
+    /// Versions of the verifying keys given to new pools, per circuit id.
+    pub vk_versions: [u32; MAX_CIRCUITS],
+    pub bump: u8,
+}
+
+impl Config {
+    pub fn is_fee_tier(&self, fee_bps: u16) -> bool {
+        self.fee_tiers.contains(&fee_bps)
+    }
+
+    pub fn has_feature(&self, feature: u8) -> bool {
+        self.features & feature != 0
+    }
+}
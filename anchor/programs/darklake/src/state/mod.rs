@@ -1,3 +1,13 @@
 pub mod pool;
+pub mod config;
+pub mod nullifier;
+pub mod verifying_key;
+pub mod tick_array;
+pub mod position;
 
-pub use pool::*;
\ No newline at end of file
+pub use pool::*;
+pub use config::*;
+pub use nullifier::*;
+pub use verifying_key::*;
+pub use tick_array::*;
+pub use position::*;
@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+/// Marks a swap proof nullifier as spent. Lives at `[b"nullifier", nullifier]`.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Nullifier {
+    /// Pool the proof was used on, the first one for a route.
+    pub pool: Pubkey,
+    /// Signer of the swap that spent it.
+    pub user: Pubkey,
+    pub slot: u64,
+}
@@ -1,8 +1,62 @@
 use anchor_lang::prelude::*;
 
+use crate::state::Config;
+use crate::circuits::MAX_CIRCUITS;
+use crate::constants::CURVE_STABLE_SWAP;
+use crate::errors::ErrorCode;
+
 #[account]
 #[derive(Default, InitSpace)]
 pub struct Pool {
+    pub token_mint_x: Pubkey,
+    pub token_mint_y: Pubkey,
+    /// Tokens held for the liquidity providers. On a concentrated pool, this includes the
+    /// LP fees positions have not collected yet.
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub liquidity: u128,
+    /// Swap fee in basis points, charged on the input amount and left in the reserves.
+    pub fee_bps: u16,
+    /// Share of the swap fee, in basis points, set aside for the protocol.
+    pub protocol_fee_bps: u16,
+    /// Protocol fees owed to the treasury. They sit in the pool token accounts but are not
+    /// part of the reserves.
+    pub protocol_fees_x: u64,
+    pub protocol_fees_y: u64,
+    /// Operations paused on this pool, see the `PAUSE_*` constants.
+    pub paused: u8,
+    /// Version of the verifying key swap proofs are checked against, per circuit id.
+    pub vk_versions: [u32; MAX_CIRCUITS],
+    pub bump: u8,
+    /// Ticks positions can start and end on are multiples of it. Zero for a constant product
+    /// pool, which has none of the concentrated liquidity state below.
+    pub tick_spacing: u16,
+    /// Highest tick whose square root price is at most `sqrt_price_x64`.
+    pub tick_current: i32,
+    /// Square root of the price of x in y, Q64.64.
+    pub sqrt_price_x64: u128,
+    /// Liquidity of the positions whose range holds the current price.
+    pub range_liquidity: u128,
+    /// LP fees earned per unit of liquidity since the pool was created, Q64.64. They wrap
+    /// around, only differences between two readings are meaningful.
+    pub fee_growth_global_x64_x: u128,
+    pub fee_growth_global_x64_y: u128,
+    /// Invariant of the reserves, see the `CURVE_*` constants. Concentrated pools are always
+    /// `CURVE_CONSTANT_PRODUCT`.
+    pub curve: u8,
+    /// Amplification coefficient of a StableSwap pool, ramping linearly from `amp_initial` at
+    /// `amp_ramp_start_slot` to `amp_target` at `amp_ramp_end_slot`. Both are the same value
+    /// outside of a ramp.
+    pub amp_initial: u32,
+    pub amp_target: u32,
+    pub amp_ramp_start_slot: u64,
+    pub amp_ramp_end_slot: u64,
+}
+
+/// Layout of the pool accounts created before fees, pauses and the other pool kinds, which
+/// `migrate_pool` converts to `Pool`.
+#[derive(AnchorDeserialize, Default, InitSpace)]
+pub struct LegacyPool {
     pub token_mint_x: Pubkey,
     pub token_mint_y: Pubkey,
     pub reserve_x: u64,
@@ -10,3 +64,75 @@ pub struct Pool {
     pub liquidity: u128,
     pub bump: u8,
 }
+
+impl LegacyPool {
+    /// The same constant product pool in the current layout, charging `fee_bps` and taking
+    /// the protocol fee and verifying keys of `config`, as a new pool would.
+    pub fn migrate(&self, fee_bps: u16, config: &Config) -> Pool {
+        Pool {
+            token_mint_x: self.token_mint_x,
+            token_mint_y: self.token_mint_y,
+            reserve_x: self.reserve_x,
+            reserve_y: self.reserve_y,
+            liquidity: self.liquidity,
+            fee_bps,
+            protocol_fee_bps: config.protocol_fee_bps,
+            vk_versions: config.vk_versions,
+            bump: self.bump,
+            ..Default::default()
+        }
+    }
+}
+
+impl Pool {
+    /// Fails when `operation` is paused on this pool or on every pool.
+    pub fn require_not_paused(&self, config: &Config, operation: u8) -> Result<()> {
+        if (self.paused | config.paused) & operation != 0 {
+            return Err(ErrorCode::PoolPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Whether liquidity is held in tick ranges rather than spread over the whole curve.
+    pub fn is_concentrated(&self) -> bool {
+        self.tick_spacing > 0
+    }
+
+    /// Fails unless the pool is of the kind the instruction works on.
+    pub fn require_concentrated(&self, concentrated: bool) -> Result<()> {
+        if self.is_concentrated() != concentrated {
+            return Err(ErrorCode::InvalidPoolType.into());
+        }
+        Ok(())
+    }
+
+    /// Whether the reserves follow the StableSwap invariant rather than the constant product.
+    pub fn is_stable(&self) -> bool {
+        self.curve == CURVE_STABLE_SWAP
+    }
+
+    /// Fails unless the pool has the curve the instruction works on.
+    pub fn require_stable(&self, stable: bool) -> Result<()> {
+        if self.is_stable() != stable {
+            return Err(ErrorCode::InvalidPoolType.into());
+        }
+        Ok(())
+    }
+
+    /// Amplification coefficient at `slot`, rounded down during a ramp.
+    pub fn amp(&self, slot: u64) -> u64 {
+        let (initial, target) = (self.amp_initial as u64, self.amp_target as u64);
+        if slot >= self.amp_ramp_end_slot {
+            return target;
+        }
+
+        let elapsed = slot.saturating_sub(self.amp_ramp_start_slot) as u128;
+        let duration = (self.amp_ramp_end_slot - self.amp_ramp_start_slot) as u128;
+        // The ramp is at most MAX_AMP, so the products fit and the result stays in range
+        if target >= initial {
+            initial + ((target - initial) as u128 * elapsed / duration) as u64
+        } else {
+            initial - ((initial - target) as u128 * elapsed).div_ceil(duration) as u64
+        }
+    }
+}
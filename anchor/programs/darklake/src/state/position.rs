@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::errors::ErrorCode;
+
+/// Liquidity provided by `owner` to a concentrated pool between two ticks. Lives at
+/// `[b"position", pool, owner, tick_lower, tick_upper]`, the ticks in little endian.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Position {
+    pub pool: Pubkey,
+    /// Opened the position, and owns it unless it has an NFT.
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    /// LP fees earned per unit of liquidity inside the range when the position was last
+    /// updated, Q64.64.
+    pub fee_growth_inside_last_x64_x: u128,
+    pub fee_growth_inside_last_x64_y: u128,
+    /// Fees earned and not collected yet, still counted in the pool reserves.
+    pub fees_owed_x: u64,
+    pub fees_owed_y: u64,
+    pub bump: u8,
+    /// Mint of the NFT representing the position, `[b"position_mint", position]`, or the
+    /// default key while it has none. Whoever holds the NFT owns the position.
+    pub position_mint: Pubkey,
+}
+
+impl Position {
+    pub fn has_nft(&self) -> bool {
+        self.position_mint != Pubkey::default()
+    }
+
+    /// Checks `user` owns the position: holds its NFT in `nft_account` when it has one,
+    /// otherwise opened it.
+    pub fn require_owner(&self, user: &Pubkey, nft_account: Option<&TokenAccount>) -> Result<()> {
+        if !self.has_nft() {
+            if self.owner != *user {
+                return Err(ErrorCode::Unauthorized.into());
+            }
+            return Ok(());
+        }
+
+        match nft_account {
+            Some(account)
+                if account.mint == self.position_mint
+                    && account.owner == *user
+                    && account.amount == 1 =>
+            {
+                Ok(())
+            }
+            _ => Err(ErrorCode::Unauthorized.into()),
+        }
+    }
+}
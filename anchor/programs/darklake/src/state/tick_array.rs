@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+use crate::tick_math::{MAX_TICK, MIN_TICK};
+
+/// Number of ticks held by a tick array.
+pub const TICK_ARRAY_SIZE: usize = 16;
+
+/// Liquidity changes and fee checkpoints of one tick.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, InitSpace)]
+pub struct Tick {
+    /// Liquidity added when the price crosses the tick going up, removed going down.
+    pub liquidity_net: i128,
+    /// Liquidity of all the positions starting or ending on the tick. The tick is
+    /// initialized, and stops swaps, while it is not zero.
+    pub liquidity_gross: u128,
+    /// LP fees earned per unit of liquidity on the other side of the tick from the current
+    /// price, Q64.64 and wrapping like the pool fee growth.
+    pub fee_growth_outside_x64_x: u128,
+    pub fee_growth_outside_x64_y: u128,
+}
+
+impl Tick {
+    pub fn is_initialized(&self) -> bool {
+        self.liquidity_gross > 0
+    }
+}
+
+/// `TICK_ARRAY_SIZE` consecutive ticks of a concentrated pool, `tick_spacing` apart.
+/// Lives at `[b"tick_array", pool, start_tick_index]`, the index in little endian.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct TickArray {
+    pub pool: Pubkey,
+    /// First tick of the array, a multiple of `TICK_ARRAY_SIZE * tick_spacing`.
+    pub start_tick_index: i32,
+    pub ticks: [Tick; TICK_ARRAY_SIZE],
+    pub bump: u8,
+}
+
+impl TickArray {
+    /// Number of ticks covered by an array, initialized or not.
+    pub fn span(tick_spacing: u16) -> i32 {
+        TICK_ARRAY_SIZE as i32 * tick_spacing as i32
+    }
+
+    /// Start of the array holding `tick`.
+    pub fn start_index_for(tick: i32, tick_spacing: u16) -> i32 {
+        tick.div_euclid(Self::span(tick_spacing)) * Self::span(tick_spacing)
+    }
+
+    /// Checks `start_tick_index` starts an array of a pool with `tick_spacing` and that the
+    /// array holds at least one usable tick.
+    pub fn check_start_index(start_tick_index: i32, tick_spacing: u16) -> Result<()> {
+        let span = Self::span(tick_spacing);
+        if tick_spacing == 0
+            || start_tick_index.rem_euclid(span) != 0
+            || start_tick_index > MAX_TICK
+            || start_tick_index + span <= MIN_TICK
+        {
+            return Err(ErrorCode::InvalidTickArray.into());
+        }
+        Ok(())
+    }
+
+    /// Whether `tick` falls in this array.
+    pub fn contains(&self, tick: i32, tick_spacing: u16) -> bool {
+        tick >= self.start_tick_index && tick < self.start_tick_index + Self::span(tick_spacing)
+    }
+
+    /// Slot of `tick`, which must fall in this array and be a multiple of `tick_spacing`.
+    pub fn offset(&self, tick: i32, tick_spacing: u16) -> Result<usize> {
+        if !self.contains(tick, tick_spacing) {
+            return Err(ErrorCode::InvalidTickArray.into());
+        }
+        if tick % tick_spacing as i32 != 0 {
+            return Err(ErrorCode::InvalidTick.into());
+        }
+        Ok(((tick - self.start_tick_index) / tick_spacing as i32) as usize)
+    }
+
+    pub fn tick(&self, tick: i32, tick_spacing: u16) -> Result<&Tick> {
+        Ok(&self.ticks[self.offset(tick, tick_spacing)?])
+    }
+
+    pub fn tick_mut(&mut self, tick: i32, tick_spacing: u16) -> Result<&mut Tick> {
+        let offset = self.offset(tick, tick_spacing)?;
+        Ok(&mut self.ticks[offset])
+    }
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use groth16_solana::groth16::Groth16Verifyingkey;
+
+/// Length of the fixed part of the key data: `alpha` in G1, `beta`, `gamma` and `delta` in G2.
+pub const VERIFYING_KEY_HEADER_LEN: usize = 64 + 3 * 128;
+
+/// Length of a G1 point in the key data.
+pub const G1_LEN: usize = 64;
+
+/// Groth16 verifying key of a circuit version, written by the admin in chunks since it does
+/// not fit in a single transaction. Pools only accept proofs checked against the version they
+/// reference, so a fixed circuit can be rolled out without redeploying the program.
+#[account]
+#[derive(Default)]
+pub struct VerifyingKey {
+    /// Version the key is stored and referenced under, unique across circuits.
+    pub version: u32,
+    /// Circuit the key verifies proofs of, see `SwapCircuit`.
+    pub circuit_id: u8,
+    /// Number of public signals of the circuit.
+    pub nr_pubinputs: u8,
+    /// sha256 of `data`, committed to when the key is created and checked when it is finalized.
+    pub hash: [u8; 32],
+    /// Set once `data` matches `hash`. The key can no longer be written and can be used by pools.
+    pub finalized: bool,
+    pub bump: u8,
+    /// `alpha_g1 || beta_g2 || gamma_g2 || delta_g2 || ic`, with `nr_pubinputs + 1` points in
+    /// `ic`, in the encoding used by `groth16-solana`.
+    pub data: Vec<u8>,
+}
+
+impl VerifyingKey {
+    /// Length of the key data of a circuit with `nr_pubinputs` public signals.
+    pub fn data_len(nr_pubinputs: u8) -> usize {
+        VERIFYING_KEY_HEADER_LEN + (nr_pubinputs as usize + 1) * G1_LEN
+    }
+
+    /// Account space, discriminator included.
+    pub fn space(nr_pubinputs: u8) -> usize {
+        8 + 4 + 1 + 1 + 32 + 1 + 1 + 4 + Self::data_len(nr_pubinputs)
+    }
+
+    /// The `ic` points, copied out since `Groth16Verifyingkey` only borrows them.
+    pub fn ic(&self) -> Vec<[u8; G1_LEN]> {
+        self.data[VERIFYING_KEY_HEADER_LEN..]
+            .chunks_exact(G1_LEN)
+            .map(point)
+            .collect()
+    }
+
+    /// The key in the form taken by `Groth16Verifier`, `vk_ic` coming from `ic()`.
+    pub fn groth16<'a>(&self, vk_ic: &'a [[u8; G1_LEN]]) -> Groth16Verifyingkey<'a> {
+        Groth16Verifyingkey {
+            nr_pubinputs: self.nr_pubinputs as usize,
+            vk_alpha_g1: point(&self.data[..64]),
+            vk_beta_g2: point(&self.data[64..192]),
+            vk_gamme_g2: point(&self.data[192..320]),
+            vk_delta_g2: point(&self.data[320..448]),
+            vk_ic,
+        }
+    }
+}
+
+/// Encodes a key into the layout stored in `VerifyingKey::data`.
+pub fn verifying_key_data(key: &Groth16Verifyingkey) -> Vec<u8> {
+    let mut data = Vec::with_capacity(VERIFYING_KEY_HEADER_LEN + key.vk_ic.len() * G1_LEN);
+    data.extend_from_slice(&key.vk_alpha_g1);
+    data.extend_from_slice(&key.vk_beta_g2);
+    data.extend_from_slice(&key.vk_gamme_g2);
+    data.extend_from_slice(&key.vk_delta_g2);
+    for ic in key.vk_ic {
+        data.extend_from_slice(ic);
+    }
+    data
+}
+
+fn point<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut point = [0u8; N];
+    point.copy_from_slice(bytes);
+    point
+}
@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests {
+    use crate::circuits::{
+        u64_to_field, SwapBindings, SwapCircuit, SwapOutputs, SwapPublicSignals,
+        CIRCUIT_CONSTANT_PRODUCT, CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT, MAX_CIRCUITS, SWAP_OUTPUTS,
+        SWAP_PUBLIC_SIGNALS,
+    };
+    use crate::errors::ErrorCode;
+    use crate::tests::proof_fixtures::{read_proof, verifying_key};
+    use crate::tests::verifying_key_fixture::VERIFYINGKEY;
+    use anchor_lang::error::Error;
+
+    fn outputs() -> SwapOutputs {
+        SwapOutputs {
+            new_balance_x: 1_100,
+            new_balance_y: 910,
+            amount_received: 90,
+            protocol_fee: 2,
+            nullifier: [7u8; 32],
+        }
+    }
+
+    fn bindings() -> SwapBindings {
+        SwapBindings {
+            reserve_x: 1_000,
+            reserve_y: 1_000,
+            is_swap_x_to_y: true,
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            user_hash: [9u8; 32],
+            expiry_slot: 500,
+            amp: 0,
+        }
+    }
+
+    fn encoded_outputs() -> Vec<[u8; 32]> {
+        vec![
+            u64_to_field(1_100),
+            u64_to_field(910),
+            u64_to_field(90),
+            u64_to_field(2),
+            [7u8; 32],
+        ]
+    }
+
+    #[test]
+    fn circuit_ids_round_trip() {
+        let circuit = SwapCircuit::from_id(CIRCUIT_CONSTANT_PRODUCT).unwrap();
+        assert_eq!(circuit, SwapCircuit::ConstantProduct);
+        assert_eq!(circuit.id(), CIRCUIT_CONSTANT_PRODUCT);
+        assert!((circuit.id() as usize) < MAX_CIRCUITS);
+        assert!(!circuit.is_exact_out());
+
+        let circuit = SwapCircuit::from_id(CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT).unwrap();
+        assert_eq!(circuit, SwapCircuit::ConstantProductExactOut);
+        assert_eq!(circuit.id(), CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT);
+        assert!((circuit.id() as usize) < MAX_CIRCUITS);
+        assert!(circuit.is_exact_out());
+
+        assert_eq!(
+            SwapCircuit::from_id(u8::MAX).unwrap_err(),
+            Error::from(ErrorCode::UnknownCircuit)
+        );
+    }
+
+    #[test]
+    fn constant_product_outputs_are_decoded_in_circuit_order() {
+        let circuit = SwapCircuit::ConstantProduct;
+        assert_eq!(circuit.outputs(), SWAP_OUTPUTS);
+        assert_eq!(circuit.decode_outputs(&encoded_outputs()).unwrap(), outputs());
+    }
+
+    #[test]
+    fn exact_out_shares_the_constant_product_layout() {
+        let circuit = SwapCircuit::ConstantProductExactOut;
+        assert_eq!(circuit.decode_outputs(&encoded_outputs()).unwrap(), outputs());
+        assert_eq!(
+            circuit.public_signals_for(&outputs(), &bindings()).unwrap(),
+            SwapCircuit::ConstantProduct.public_signals_for(&outputs(), &bindings()).unwrap()
+        );
+    }
+
+    #[test]
+    fn outputs_of_the_wrong_length_are_rejected() {
+        let circuit = SwapCircuit::ConstantProduct;
+        let mut too_long = encoded_outputs();
+        too_long.push([0u8; 32]);
+
+        for outputs in [vec![], encoded_outputs()[..4].to_vec(), too_long] {
+            assert_eq!(
+                circuit.decode_outputs(&outputs).unwrap_err(),
+                Error::from(ErrorCode::InvalidPublicInputsLength)
+            );
+        }
+    }
+
+    #[test]
+    fn constant_product_signals_follow_the_circuit_layout() {
+        let circuit = SwapCircuit::ConstantProduct;
+        let signals = circuit.public_signals_for(&outputs(), &bindings()).unwrap();
+
+        assert_eq!(signals.len(), circuit.public_signals());
+        assert_eq!(signals.len(), SWAP_PUBLIC_SIGNALS);
+        assert_eq!(
+            signals,
+            SwapPublicSignals::new(&outputs(), &bindings()).to_fields().to_vec()
+        );
+        assert_eq!(&signals[..SWAP_OUTPUTS], encoded_outputs().as_slice());
+    }
+
+    #[test]
+    fn signals_not_matching_the_key_are_rejected() {
+        let circuit = SwapCircuit::ConstantProduct;
+        let signals = circuit.public_signals_for(&outputs(), &bindings()).unwrap();
+
+        let mut too_long = signals.clone();
+        too_long.push([0u8; 32]);
+        assert_eq!(
+            circuit
+                .verify(&[0u8; 64], &[0u8; 128], &[0u8; 64], &too_long, &VERIFYINGKEY)
+                .unwrap_err(),
+            Error::from(ErrorCode::InvalidPublicInputsLength)
+        );
+        assert_eq!(
+            circuit
+                .verify(&[0u8; 64], &[0u8; 128], &[0u8; 64], &signals[1..], &VERIFYINGKEY)
+                .unwrap_err(),
+            Error::from(ErrorCode::InvalidPublicInputsLength)
+        );
+    }
+
+    #[test]
+    fn exact_out_proof_verifies_through_the_circuit() {
+        let proof = read_proof("zk_proof_output_exact_out.json");
+        let key = verifying_key("swap_exact_out_verification_key.json");
+        let circuit = SwapCircuit::ConstantProductExactOut;
+
+        let outputs = circuit.decode_outputs(&proof.public_inputs[..SWAP_OUTPUTS]).unwrap();
+        assert_eq!(outputs.amount_received, 100_000);
+        let bindings = SwapBindings {
+            reserve_x: 1_100_000,
+            reserve_y: 1_900_000,
+            is_swap_x_to_y: true,
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            user_hash: u64_to_field(987_654_321),
+            expiry_slot: 1_000,
+            amp: 0,
+        };
+        let signals = circuit.public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(signals, proof.public_inputs);
+        assert!(circuit
+            .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+            .unwrap());
+
+        // Reserves that moved since the proof was made
+        let moved = SwapBindings { reserve_y: 1_900_001, ..bindings };
+        let signals = circuit.public_signals_for(&outputs, &moved).unwrap();
+        assert_eq!(
+            circuit
+                .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+                .unwrap_err(),
+            Error::from(ErrorCode::InvalidProof)
+        );
+
+        // The exact input circuit has a key of its own
+        assert!(SwapCircuit::ConstantProduct
+            .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &proof.public_inputs, &VERIFYINGKEY)
+            .is_err());
+    }
+
+    #[test]
+    fn stable_swap_proof_verifies_through_the_circuit() {
+        let proof = read_proof("zk_proof_output_stable_swap.json");
+        let key = verifying_key("stable_swap_verification_key.json");
+        let circuit = SwapCircuit::StableSwap;
+
+        let outputs = circuit.decode_outputs(&proof.public_inputs[..SWAP_OUTPUTS]).unwrap();
+        assert_eq!(outputs.amount_received, 9_969);
+        let bindings = SwapBindings {
+            reserve_x: 1_000_000,
+            reserve_y: 1_000_000,
+            is_swap_x_to_y: true,
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            user_hash: u64_to_field(987_654_321),
+            expiry_slot: 1_000,
+            amp: 100,
+        };
+        let signals = circuit.public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(signals, proof.public_inputs);
+        assert!(circuit
+            .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+            .unwrap());
+
+        // An amplification coefficient ramped since the proof was made
+        let ramped = SwapBindings { amp: 101, ..bindings };
+        let signals = circuit.public_signals_for(&outputs, &ramped).unwrap();
+        assert_eq!(
+            circuit
+                .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+                .unwrap_err(),
+            Error::from(ErrorCode::InvalidProof)
+        );
+    }
+}
@@ -0,0 +1,409 @@
+#[cfg(test)]
+mod tests {
+    use crate::circuits::{
+        u128_to_field, u64_to_field, RangeSwapBindings, RangeSwapOutputs, SwapCircuit,
+        CIRCUIT_RANGE_SWAP, MAX_CIRCUITS, RANGE_SWAP_OUTPUTS, RANGE_SWAP_PUBLIC_SIGNALS,
+    };
+    use crate::concentrated::{
+        apply_range_swap, check_tick_range, compute_range_swap, modify_position, range_ticks_mut,
+    };
+    use crate::errors::ErrorCode;
+    use crate::instructions::{range_swap_quote, swap_quote};
+    use crate::state::{Pool, Position, TickArray};
+    use crate::tests::proof_fixtures::{read_proof, verifying_key};
+    use crate::tick_math::sqrt_price_at_tick;
+    use anchor_lang::error::Error;
+    use anchor_lang::prelude::{AccountDeserialize, Pubkey};
+    use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+    use anchor_spl::token::TokenAccount;
+    use proptest::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+
+    const SPACING: u16 = 10;
+
+    /// A concentrated pool at price 1 with arrays covering ticks -480 to 479.
+    fn pool_and_arrays() -> (Pool, Vec<TickArray>) {
+        let pool = Pool {
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            tick_spacing: SPACING,
+            sqrt_price_x64: 1 << 64,
+            ..Default::default()
+        };
+        let arrays = [-480, -320, -160, 0, 160, 320]
+            .iter()
+            .map(|&start_tick_index| TickArray { start_tick_index, ..Default::default() })
+            .collect();
+        (pool, arrays)
+    }
+
+    fn array_index(arrays: &[TickArray], tick: i32) -> usize {
+        arrays.iter().position(|array| array.contains(tick, SPACING)).unwrap()
+    }
+
+    /// Runs `modify_position` against the arrays holding the position ticks, and adds the
+    /// amounts to the reserves like the instructions do.
+    fn modify(
+        pool: &mut Pool,
+        arrays: &mut [TickArray],
+        position: &mut Position,
+        delta: i128,
+    ) -> Result<(u64, u64), Error> {
+        let lower_index = array_index(arrays, position.tick_lower);
+        let upper_index = array_index(arrays, position.tick_upper);
+        let (amount_x, amount_y) = if lower_index == upper_index {
+            let (lower, upper) = range_ticks_mut(
+                &mut arrays[lower_index],
+                None,
+                position.tick_lower,
+                position.tick_upper,
+                SPACING,
+            )?;
+            modify_position(pool, position, lower, upper, delta)?
+        } else {
+            let (head, tail) = arrays.split_at_mut(upper_index);
+            let (lower, upper) = range_ticks_mut(
+                &mut head[lower_index],
+                Some(&mut tail[0]),
+                position.tick_lower,
+                position.tick_upper,
+                SPACING,
+            )?;
+            modify_position(pool, position, lower, upper, delta)?
+        };
+        if delta >= 0 {
+            pool.reserve_x += amount_x;
+            pool.reserve_y += amount_y;
+        } else {
+            pool.reserve_x -= amount_x;
+            pool.reserve_y -= amount_y;
+        }
+        Ok((amount_x, amount_y))
+    }
+
+    fn open(pool: &mut Pool, arrays: &mut [TickArray], lower: i32, upper: i32, liquidity: i128) -> Position {
+        let mut position = Position { tick_lower: lower, tick_upper: upper, ..Default::default() };
+        modify(pool, arrays, &mut position, liquidity).unwrap();
+        position
+    }
+
+    /// The arrays of a swap from the current tick, in the swap direction.
+    fn swap_arrays(pool: &Pool, arrays: &[TickArray], is_swap_x_to_y: bool) -> Vec<TickArray> {
+        let first = array_index(arrays, pool.tick_current);
+        if is_swap_x_to_y {
+            arrays[..=first].iter().rev().cloned().collect()
+        } else {
+            arrays[first..].to_vec()
+        }
+    }
+
+    fn swap(pool: &mut Pool, arrays: &mut [TickArray], amount_in: u64, is_swap_x_to_y: bool) -> u64 {
+        let mut loaded = swap_arrays(pool, arrays, is_swap_x_to_y);
+        let swap = compute_range_swap(pool, &mut loaded, amount_in, is_swap_x_to_y).unwrap();
+        apply_range_swap(pool, &swap, is_swap_x_to_y).unwrap();
+        for array in loaded {
+            let index = array_index(arrays, array.start_tick_index);
+            arrays[index] = array;
+        }
+        swap.amount_out
+    }
+
+    #[test]
+    fn positions_take_amounts_by_where_the_price_is() {
+        let (mut pool, mut arrays) = pool_and_arrays();
+
+        let mut around = Position { tick_lower: -100, tick_upper: 100, ..Default::default() };
+        assert_eq!(modify(&mut pool, &mut arrays, &mut around, 1_000_000_000).unwrap(), (4_987_273, 4_987_273));
+        assert_eq!(pool.range_liquidity, 1_000_000_000);
+
+        // Entirely above the price: only x, and no liquidity at the current price
+        let mut above = Position { tick_lower: 100, tick_upper: 200, ..Default::default() };
+        let (amount_x, amount_y) = modify(&mut pool, &mut arrays, &mut above, 1_000_000_000).unwrap();
+        assert!(amount_x > 0);
+        assert_eq!(amount_y, 0);
+        assert_eq!(pool.range_liquidity, 1_000_000_000);
+
+        let tick = arrays[array_index(&arrays, 100)].tick(100, SPACING).unwrap();
+        assert_eq!(tick.liquidity_gross, 2_000_000_000);
+        assert_eq!(tick.liquidity_net, 0);
+
+        // Withdrawing pays out one less than was deposited at most
+        assert_eq!(modify(&mut pool, &mut arrays, &mut around, -1_000_000_000).unwrap(), (4_987_272, 4_987_272));
+        assert_eq!(pool.range_liquidity, 0);
+        assert!(!arrays[array_index(&arrays, -100)].tick(-100, SPACING).unwrap().is_initialized());
+        assert_eq!(
+            modify(&mut pool, &mut arrays, &mut around, -1).unwrap_err(),
+            Error::from(ErrorCode::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn tick_ranges_must_be_spaced_and_ordered() {
+        assert!(check_tick_range(-100, 100, SPACING).is_ok());
+        for (lower, upper) in [(100, 100), (100, -100), (-105, 100), (-443_640, 0), (0, 443_640)] {
+            assert_eq!(
+                check_tick_range(lower, upper, SPACING).unwrap_err(),
+                Error::from(ErrorCode::InvalidTick)
+            );
+        }
+        assert_eq!(
+            check_tick_range(-100, 100, 0).unwrap_err(),
+            Error::from(ErrorCode::InvalidTick)
+        );
+    }
+
+    #[test]
+    fn swaps_cross_ticks_and_change_the_liquidity() {
+        let (mut pool, mut arrays) = pool_and_arrays();
+        open(&mut pool, &mut arrays, -100, 100, 1_000_000_000);
+        open(&mut pool, &mut arrays, -300, 300, 2_000_000_000);
+        assert_eq!(pool.range_liquidity, 3_000_000_000);
+
+        // A small swap stays inside both ranges
+        let amount_out = swap(&mut pool, &mut arrays, 100_000, true);
+        assert!(amount_out > 99_000 && amount_out < 100_000);
+        assert_eq!(pool.range_liquidity, 3_000_000_000);
+        assert!(pool.tick_current < 0 && pool.tick_current > -100);
+
+        // A large one leaves the narrow range, only the wide one is left
+        swap(&mut pool, &mut arrays, 30_000_000, true);
+        assert!(pool.tick_current < -100 && pool.tick_current >= -300);
+        assert_eq!(pool.range_liquidity, 2_000_000_000);
+        let crossed = arrays[array_index(&arrays, -100)].tick(-100, SPACING).unwrap();
+        // Its outside growth is what had accrued when it was crossed, less than the total now
+        assert!(crossed.fee_growth_outside_x64_x > 0);
+        assert!(crossed.fee_growth_outside_x64_x < pool.fee_growth_global_x64_x);
+
+        // And back up, into the narrow range again
+        swap(&mut pool, &mut arrays, 30_000_000, false);
+        assert!(pool.tick_current >= -100);
+        assert_eq!(pool.range_liquidity, 3_000_000_000);
+        assert_eq!(pool.tick_current, crate::tick_math::tick_at_sqrt_price(pool.sqrt_price_x64).unwrap());
+    }
+
+    #[test]
+    fn swaps_need_arrays_covering_them() {
+        let (mut pool, mut arrays) = pool_and_arrays();
+        open(&mut pool, &mut arrays, -100, 100, 1_000_000_000);
+
+        // Past the position there is no liquidity left, the swap runs to the end of the arrays
+        let mut loaded = swap_arrays(&pool, &arrays, true);
+        assert_eq!(
+            compute_range_swap(&pool, &mut loaded, 10_000_000, true).unwrap_err(),
+            Error::from(ErrorCode::InvalidTickArray)
+        );
+
+        // Arrays must start at the current tick and follow each other
+        let mut wrong_start = arrays[..1].to_vec();
+        assert_eq!(
+            compute_range_swap(&pool, &mut wrong_start, 1_000, true).unwrap_err(),
+            Error::from(ErrorCode::InvalidTickArray)
+        );
+        let mut gap = vec![arrays[3].clone(), arrays[1].clone()];
+        assert_eq!(
+            compute_range_swap(&pool, &mut gap, 1_000, true).unwrap_err(),
+            Error::from(ErrorCode::InvalidTickArray)
+        );
+        assert_eq!(
+            compute_range_swap(&pool, &mut [], 1_000, true).unwrap_err(),
+            Error::from(ErrorCode::InvalidTickArray)
+        );
+    }
+
+    #[test]
+    fn fees_go_to_the_positions_in_range() {
+        let (mut pool, mut arrays) = pool_and_arrays();
+        let mut narrow = open(&mut pool, &mut arrays, -100, 100, 1_000_000_000);
+        let mut outside = open(&mut pool, &mut arrays, 200, 300, 1_000_000_000);
+
+        swap(&mut pool, &mut arrays, 1_000_000, true);
+        assert_eq!(pool.protocol_fees_x, 600);
+
+        // 0.3% of the input, less the protocol share, minus rounding
+        modify(&mut pool, &mut arrays, &mut narrow, 0).unwrap();
+        assert_eq!(narrow.fees_owed_x, 2_399);
+        assert_eq!(narrow.fees_owed_y, 0);
+        modify(&mut pool, &mut arrays, &mut outside, 0).unwrap();
+        assert_eq!(outside.fees_owed_x, 0);
+    }
+
+    #[test]
+    fn quotes_match_the_swap() {
+        let (mut pool, mut arrays) = pool_and_arrays();
+        open(&mut pool, &mut arrays, -100, 100, 1_000_000_000);
+        let loaded = swap_arrays(&pool, &arrays, false);
+
+        let quote = range_swap_quote(&pool, &loaded, 1_000_000, false).unwrap();
+        assert_eq!(quote.fee, 3_000);
+        assert_eq!(quote.protocol_fee, 600);
+        assert_eq!(quote.new_reserve_y, pool.reserve_y + 1_000_000 - 600);
+        assert_eq!(quote.new_reserve_x, pool.reserve_x - quote.amount_out);
+        assert_eq!(quote.amount_out, swap(&mut pool, &mut arrays, 1_000_000, false));
+
+        // Each pool type only takes its own swaps
+        assert_eq!(
+            swap_quote(&pool, 1_000, true).unwrap_err(),
+            Error::from(ErrorCode::InvalidPoolType)
+        );
+        assert_eq!(
+            range_swap_quote(&Pool::default(), &loaded, 1_000, true).unwrap_err(),
+            Error::from(ErrorCode::InvalidPoolType)
+        );
+    }
+
+    fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> TokenAccount {
+        let account = SplAccount { mint, owner, amount, state: AccountState::Initialized, ..Default::default() };
+        let mut data = [0u8; SplAccount::LEN];
+        account.pack_into_slice(&mut data);
+        TokenAccount::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn positions_with_an_nft_belong_to_its_holder() {
+        let owner = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+        let mut position = Position { owner, ..Default::default() };
+
+        // Without an NFT only the owner passes, whatever token account comes along
+        assert!(position.require_owner(&owner, None).is_ok());
+        assert_eq!(
+            position.require_owner(&buyer, None).unwrap_err(),
+            Error::from(ErrorCode::Unauthorized)
+        );
+
+        position.position_mint = Pubkey::new_unique();
+        let mint = position.position_mint;
+        assert!(position.has_nft());
+
+        // Once sold, the owner no longer passes and the buyer does
+        assert!(position.require_owner(&buyer, Some(&token_account(mint, buyer, 1))).is_ok());
+        let unauthorized = Error::from(ErrorCode::Unauthorized);
+        assert_eq!(position.require_owner(&owner, None).unwrap_err(), unauthorized);
+        assert_eq!(
+            position.require_owner(&owner, Some(&token_account(mint, buyer, 1))).unwrap_err(),
+            unauthorized
+        );
+        assert_eq!(
+            position.require_owner(&owner, Some(&token_account(mint, owner, 0))).unwrap_err(),
+            unauthorized
+        );
+        assert_eq!(
+            position
+                .require_owner(&buyer, Some(&token_account(Pubkey::new_unique(), buyer, 1)))
+                .unwrap_err(),
+            unauthorized
+        );
+    }
+
+    #[test]
+    fn range_swap_circuit_has_its_own_id_and_layout() {
+        let circuit = SwapCircuit::from_id(CIRCUIT_RANGE_SWAP).unwrap();
+        assert_eq!(circuit, SwapCircuit::RangeSwap);
+        assert!((circuit.id() as usize) < MAX_CIRCUITS);
+        assert_eq!(circuit.outputs(), RANGE_SWAP_OUTPUTS);
+        assert_eq!(circuit.public_signals(), RANGE_SWAP_PUBLIC_SIGNALS);
+
+        let encoded = [u64_to_field(1_000), u64_to_field(990), [7u8; 32]];
+        let outputs = circuit.decode_range_swap_outputs(&encoded).unwrap();
+        assert_eq!(
+            outputs,
+            RangeSwapOutputs { amount_in: 1_000, amount_received: 990, nullifier: [7u8; 32] }
+        );
+
+        let bindings = RangeSwapBindings {
+            sqrt_price_x64: sqrt_price_at_tick(-100).unwrap(),
+            liquidity: u128::MAX,
+            is_swap_x_to_y: true,
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            user_hash: [8u8; 32],
+            expiry_slot: 500,
+        };
+        let signals = circuit.range_swap_public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(signals.len(), RANGE_SWAP_PUBLIC_SIGNALS);
+        assert_eq!(&signals[..RANGE_SWAP_OUTPUTS], encoded.as_slice());
+        assert_eq!(signals[3], u128_to_field(bindings.sqrt_price_x64));
+        assert_eq!(signals[4][16..], [0xffu8; 16]);
+        assert_eq!(signals[9], u64_to_field(500));
+
+        // Range outputs cannot be read as single pool or route outputs, nor the other way
+        assert_eq!(
+            circuit.decode_outputs(&[[0u8; 32]; RANGE_SWAP_OUTPUTS]).unwrap_err(),
+            Error::from(ErrorCode::UnknownCircuit)
+        );
+        assert_eq!(
+            SwapCircuit::ConstantProduct
+                .decode_range_swap_outputs(&[[0u8; 32]; 5])
+                .unwrap_err(),
+            Error::from(ErrorCode::UnknownCircuit)
+        );
+    }
+
+    #[test]
+    fn range_swap_proof_verifies_through_the_circuit() {
+        let proof = read_proof("zk_proof_output_range_swap.json");
+        let key = verifying_key("range_swap_verification_key.json");
+        let circuit = SwapCircuit::RangeSwap;
+
+        let (mut pool, mut arrays) = pool_and_arrays();
+        open(&mut pool, &mut arrays, -100, 100, 1_000_000_000);
+        let bindings = RangeSwapBindings {
+            sqrt_price_x64: pool.sqrt_price_x64,
+            liquidity: pool.range_liquidity,
+            is_swap_x_to_y: true,
+            fee_bps: pool.fee_bps,
+            protocol_fee_bps: pool.protocol_fee_bps,
+            user_hash: u64_to_field(987_654_321),
+            expiry_slot: 1_000,
+        };
+
+        let outputs = circuit
+            .decode_range_swap_outputs(&proof.public_inputs[..RANGE_SWAP_OUTPUTS])
+            .unwrap();
+        let signals = circuit.range_swap_public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(signals, proof.public_inputs);
+        assert!(circuit
+            .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+            .unwrap());
+
+        // The proof was quoted against this pool, the program pays out what it proves
+        assert_eq!(swap(&mut pool, &mut arrays, outputs.amount_in, true), outputs.amount_received);
+
+        // The pool moved since the proof was made
+        let moved = RangeSwapBindings { sqrt_price_x64: pool.sqrt_price_x64, ..bindings };
+        let signals = circuit.range_swap_public_signals_for(&outputs, &moved).unwrap();
+        assert_eq!(
+            circuit
+                .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+                .unwrap_err(),
+            Error::from(ErrorCode::InvalidProof)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn pools_can_always_pay_back_their_positions(
+            amounts in proptest::collection::vec((1u64..2_000_000, any::<bool>()), 1..6),
+        ) {
+            let (mut pool, mut arrays) = pool_and_arrays();
+            let mut positions = [
+                open(&mut pool, &mut arrays, -100, 100, 1_000_000_000),
+                open(&mut pool, &mut arrays, -300, 200, 2_000_000_000),
+                open(&mut pool, &mut arrays, -470, 470, 500_000_000),
+            ];
+
+            for (amount_in, is_swap_x_to_y) in amounts {
+                swap(&mut pool, &mut arrays, amount_in, is_swap_x_to_y);
+            }
+
+            // Withdrawing everything and every fee never takes more than the reserves hold
+            for position in positions.iter_mut() {
+                let liquidity = position.liquidity as i128;
+                modify(&mut pool, &mut arrays, position, -liquidity).unwrap();
+                pool.reserve_x = pool.reserve_x.checked_sub(position.fees_owed_x).unwrap();
+                pool.reserve_y = pool.reserve_y.checked_sub(position.fees_owed_y).unwrap();
+            }
+            prop_assert_eq!(pool.range_liquidity, 0);
+        }
+    }
+}
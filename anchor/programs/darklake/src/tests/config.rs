@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use crate::constants::{DEFAULT_FEE_TIERS, FEATURE_PERMISSIONLESS_POOLS};
+    use crate::state::Config;
+
+    #[test]
+    fn fee_tiers_only_accept_listed_fees() {
+        let config = Config {
+            fee_tiers: DEFAULT_FEE_TIERS,
+            ..Default::default()
+        };
+
+        for fee_bps in DEFAULT_FEE_TIERS {
+            assert!(config.is_fee_tier(fee_bps));
+        }
+        assert!(!config.is_fee_tier(31));
+        assert!(!config.is_fee_tier(0));
+    }
+
+    #[test]
+    fn features_are_independent_bits() {
+        let mut config = Config::default();
+        assert!(!config.has_feature(FEATURE_PERMISSIONLESS_POOLS));
+
+        config.features = 0b10;
+        assert!(!config.has_feature(FEATURE_PERMISSIONLESS_POOLS));
+
+        config.features |= FEATURE_PERMISSIONLESS_POOLS;
+        assert!(config.has_feature(FEATURE_PERMISSIONLESS_POOLS));
+    }
+}
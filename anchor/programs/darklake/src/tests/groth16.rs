@@ -1,13 +1,14 @@
 #[cfg(test)]
 mod tests {
-    use crate::constants::VERIFYINGKEY;
+    use crate::circuits::SWAP_PUBLIC_SIGNALS;
+    use crate::tests::verifying_key_fixture::VERIFYINGKEY;
     use groth16_solana::groth16::Groth16Verifier;
     use serde_json::Value;
     use std::fs::File;
     use std::io::Read;
     use std::convert::TryInto;
 
-    fn read_proof_from_json() -> ([u8; 64], [u8; 128], [u8; 64], [[u8; 32]; 3]) {
+    fn read_proof_from_json() -> ([u8; 64], [u8; 128], [u8; 64], [[u8; 32]; SWAP_PUBLIC_SIGNALS]) {
         let file_path = "./src/tests/zk_proof_output.json";
         
         // Check if the file exists
@@ -45,7 +46,7 @@ mod tests {
             .collect::<Vec<u8>>().try_into().expect("Failed to convert pi_c to [u8; 64]");
 
         // Extract public inputs
-        let public_inputs: [[u8; 32]; 3] = json["publicInputs"].as_array()
+        let public_inputs: [[u8; 32]; SWAP_PUBLIC_SIGNALS] = json["publicInputs"].as_array()
             .expect("publicInputs is not an array")
             .iter()
             .map(|v| {
@@ -58,7 +59,7 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>()
             .try_into()
-            .expect("Failed to convert public inputs to [[u8; 32]; 3]");
+            .expect("Failed to convert public inputs to [[u8; 32]; SWAP_PUBLIC_SIGNALS]");
 
         (proof_a, proof_b, proof_c, public_inputs)
     }
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests {
+    use crate::constants::MINIMUM_LIQUIDITY;
+    use crate::errors::ErrorCode;
+    use crate::math::{deposit_amounts, initial_liquidity, isqrt, optimal_deposit_amounts, quote};
+    use proptest::prelude::*;
+
+    /// Reference square root by bisection, slow but obviously correct.
+    fn reference_isqrt(n: u128) -> u128 {
+        let (mut low, mut high) = (0u128, 1u128 << 64);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if mid.checked_mul(mid).is_some_and(|square| square <= n) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    #[test]
+    fn isqrt_edge_cases() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(2), 1);
+        assert_eq!(isqrt(3), 1);
+        assert_eq!(isqrt(4), 2);
+        assert_eq!(isqrt(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn first_deposit_locks_minimum_liquidity() {
+        let (liquidity, minted) = initial_liquidity(1_000_000, 4_000_000).unwrap();
+        assert_eq!(liquidity, 2_000_000);
+        assert_eq!(minted, 2_000_000 - MINIMUM_LIQUIDITY);
+
+        // Amounts whose product used to overflow a u64
+        let (liquidity, minted) = initial_liquidity(u64::MAX, u64::MAX).unwrap();
+        assert_eq!(liquidity, u64::MAX as u128);
+        assert_eq!(minted, u64::MAX - MINIMUM_LIQUIDITY);
+    }
+
+    #[test]
+    fn first_deposit_must_exceed_minimum_liquidity() {
+        assert_eq!(
+            initial_liquidity(1_000, 1_000).unwrap_err(),
+            ErrorCode::InsufficientLiquidity.into()
+        );
+        assert!(initial_liquidity(0, u64::MAX).is_err());
+        assert!(initial_liquidity(1_001, 1_001).is_ok());
+    }
+
+    #[test]
+    fn deposit_takes_only_the_proportional_amounts() {
+        // Pool at 1:2, depositing 100 X with 1000 Y only takes 200 Y
+        let (minted, used_x, used_y) = deposit_amounts(100, 1_000, 1_000, 2_000, 500).unwrap();
+        assert_eq!(minted, 50);
+        assert_eq!((used_x, used_y), (100, 200));
+
+        // Amounts are rounded up in favour of the pool
+        let (minted, used_x, used_y) = deposit_amounts(10, 10, 3, 3, 2).unwrap();
+        assert_eq!(minted, 6);
+        assert_eq!((used_x, used_y), (9, 9));
+    }
+
+    #[test]
+    fn optimal_amounts_take_one_side_in_full() {
+        assert_eq!(quote(100, 1_000, 2_000).unwrap(), 200);
+        assert_eq!(quote(1, 3, 2).unwrap(), 0);
+
+        // Excess Y
+        assert_eq!(optimal_deposit_amounts(100, 1_000, 1_000, 2_000).unwrap(), (100, 200));
+        // Excess X
+        assert_eq!(optimal_deposit_amounts(1_000, 100, 1_000, 2_000).unwrap(), (50, 100));
+        // Exactly at the ratio
+        assert_eq!(optimal_deposit_amounts(100, 200, 1_000, 2_000).unwrap(), (100, 200));
+        // The Y quote for all of X does not fit in a u64
+        assert_eq!(optimal_deposit_amounts(u64::MAX, 100, 1, 2).unwrap(), (50, 100));
+
+        assert_eq!(
+            quote(1, 0, 1).unwrap_err(),
+            ErrorCode::InsufficientLiquidity.into()
+        );
+        assert_eq!(
+            quote(u64::MAX, 1, 2).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
+    }
+
+    #[test]
+    fn deposit_too_small_for_one_lp_token_fails() {
+        assert_eq!(
+            deposit_amounts(1, 1, 1_000, 1_000, 10).unwrap_err(),
+            ErrorCode::InsufficientLiquidity.into()
+        );
+        assert!(deposit_amounts(1, 1, 0, 1_000, 10).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn isqrt_matches_reference(n in any::<u128>()) {
+            prop_assert_eq!(isqrt(n), reference_isqrt(n));
+        }
+
+        #[test]
+        fn initial_liquidity_matches_reference(amount_x in any::<u64>(), amount_y in any::<u64>()) {
+            let root = reference_isqrt(amount_x as u128 * amount_y as u128);
+
+            match initial_liquidity(amount_x, amount_y) {
+                Ok((liquidity, minted)) => {
+                    prop_assert_eq!(liquidity, root);
+                    prop_assert_eq!(minted as u128 + MINIMUM_LIQUIDITY as u128, root);
+                }
+                Err(_) => prop_assert!(root <= MINIMUM_LIQUIDITY as u128),
+            }
+        }
+
+        #[test]
+        fn optimal_amounts_match_the_pool_ratio(
+            amount_x in any::<u64>(),
+            amount_y in any::<u64>(),
+            reserve_x in 1..u64::MAX,
+            reserve_y in 1..u64::MAX,
+        ) {
+            if let Ok((used_x, used_y)) = optimal_deposit_amounts(amount_x, amount_y, reserve_x, reserve_y) {
+                prop_assert!(used_x <= amount_x && used_y <= amount_y);
+                prop_assert!(used_x == amount_x || used_y == amount_y);
+                // Neither side is worth more than the other at the pool ratio
+                if used_x == amount_x {
+                    prop_assert!(used_y as u128 * reserve_x as u128 <= used_x as u128 * reserve_y as u128);
+                } else {
+                    prop_assert!(used_x as u128 * reserve_y as u128 <= used_y as u128 * reserve_x as u128);
+                }
+            }
+        }
+
+        #[test]
+        fn deposit_never_dilutes_existing_lps(
+            amount_x in 1..u64::MAX,
+            amount_y in 1..u64::MAX,
+            reserve_x in 1..u64::MAX,
+            reserve_y in 1..u64::MAX,
+            liquidity in 1..u64::MAX as u128,
+        ) {
+            if let Ok((minted, used_x, used_y)) =
+                deposit_amounts(amount_x, amount_y, reserve_x, reserve_y, liquidity)
+            {
+                prop_assert!(used_x <= amount_x && used_y <= amount_y);
+                // The new share of each reserve is at most what was paid for it
+                prop_assert!(minted as u128 * reserve_x as u128 <= used_x as u128 * liquidity);
+                prop_assert!(minted as u128 * reserve_y as u128 <= used_y as u128 * liquidity);
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use crate::errors::ErrorCode;
+    use crate::math::{
+        mul_div_ceil, mul_div_floor, optimal_deposit_amounts, proportional_share, to_u64,
+        wide_mul_div_floor,
+    };
+    use proptest::prelude::*;
+
+    #[test]
+    fn mul_div_rounding() {
+        assert_eq!(mul_div_floor(7, 3, 2).unwrap(), 10);
+        assert_eq!(mul_div_ceil(7, 3, 2).unwrap(), 11);
+        assert_eq!(mul_div_floor(6, 3, 2).unwrap(), 9);
+        assert_eq!(mul_div_ceil(6, 3, 2).unwrap(), 9);
+        assert_eq!(mul_div_floor(0, 3, 2).unwrap(), 0);
+        assert_eq!(mul_div_ceil(0, 3, 2).unwrap(), 0);
+        assert_eq!(mul_div_ceil(1, 1, u128::MAX).unwrap(), 1);
+    }
+
+    #[test]
+    fn mul_div_edge_cases() {
+        assert_eq!(mul_div_floor(u128::MAX, 1, 1).unwrap(), u128::MAX);
+        assert_eq!(mul_div_ceil(u128::MAX, 1, 1).unwrap(), u128::MAX);
+        assert_eq!(mul_div_floor(u128::MAX, 1, u128::MAX).unwrap(), 1);
+        assert_eq!(
+            mul_div_floor(u64::MAX as u128, u64::MAX as u128, u64::MAX as u128).unwrap(),
+            u64::MAX as u128
+        );
+
+        assert_eq!(mul_div_floor(1, 1, 0).unwrap_err(), ErrorCode::MathOverflow.into());
+        assert_eq!(mul_div_ceil(1, 1, 0).unwrap_err(), ErrorCode::MathOverflow.into());
+        assert_eq!(mul_div_floor(u128::MAX, 2, 2).unwrap_err(), ErrorCode::MathOverflow.into());
+        assert_eq!(mul_div_ceil(u128::MAX, 2, 2).unwrap_err(), ErrorCode::MathOverflow.into());
+    }
+
+    #[test]
+    fn wide_mul_div_past_the_u128_limit() {
+        assert_eq!(wide_mul_div_floor(7, 3, 2).unwrap(), 10);
+        assert_eq!(wide_mul_div_floor(u128::MAX, u64::MAX, u64::MAX as u128).unwrap(), u128::MAX);
+        assert_eq!(
+            wide_mul_div_floor(u128::MAX, u64::MAX, u128::MAX).unwrap(),
+            u64::MAX as u128
+        );
+        assert_eq!(wide_mul_div_floor(u128::MAX, 2, u128::MAX - 1).unwrap(), 2);
+
+        assert_eq!(wide_mul_div_floor(1, 1, 0).unwrap_err(), ErrorCode::MathOverflow.into());
+        assert_eq!(
+            wide_mul_div_floor(u128::MAX, 2, 1).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
+    }
+
+    #[test]
+    fn to_u64_bounds() {
+        assert_eq!(to_u64(u64::MAX as u128).unwrap(), u64::MAX);
+        assert_eq!(to_u64(u64::MAX as u128 + 1).unwrap_err(), ErrorCode::MathOverflow.into());
+    }
+
+    #[test]
+    fn proportional_share_edge_cases() {
+        assert_eq!(proportional_share(1, 10, 3).unwrap(), 3);
+        assert_eq!(proportional_share(3, 10, 3).unwrap(), 10);
+        assert_eq!(proportional_share(0, 10, 3).unwrap(), 0);
+        assert_eq!(proportional_share(u64::MAX, u64::MAX, u64::MAX as u128).unwrap(), u64::MAX);
+
+        assert_eq!(proportional_share(1, 10, 0).unwrap_err(), ErrorCode::MathOverflow.into());
+        assert_eq!(proportional_share(2, u64::MAX, 1).unwrap_err(), ErrorCode::MathOverflow.into());
+    }
+
+    #[test]
+    fn optimal_amounts_survive_an_overflowing_quote() {
+        // amount_x * reserve_y / reserve_x does not fit in a u64, the Y side limits instead
+        assert_eq!(optimal_deposit_amounts(u64::MAX, 10, 1, 1_000).unwrap(), (0, 10));
+        assert_eq!(optimal_deposit_amounts(u64::MAX, 1_000, 1, 1_000).unwrap(), (1, 1_000));
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_brackets_the_exact_quotient(a in any::<u64>(), b in any::<u64>(), denominator in 1..u128::MAX) {
+            let (a, b) = (a as u128, b as u128);
+            let floor = mul_div_floor(a, b, denominator).unwrap();
+            let ceil = mul_div_ceil(a, b, denominator).unwrap();
+
+            prop_assert!(floor * denominator <= a * b);
+            prop_assert!(a * b - floor * denominator < denominator);
+            prop_assert!(ceil == floor || ceil == floor + 1);
+            prop_assert_eq!(ceil == floor, (a * b) % denominator == 0);
+        }
+
+        #[test]
+        fn wide_mul_div_matches_mul_div_below_the_u128_limit(a in any::<u64>(), b in any::<u64>(), denominator in 1..u128::MAX) {
+            prop_assert_eq!(
+                wide_mul_div_floor(a as u128, b, denominator).unwrap(),
+                mul_div_floor(a as u128, b as u128, denominator).unwrap()
+            );
+        }
+
+        #[test]
+        fn mul_div_overflows_only_when_the_product_does(a in any::<u128>(), b in any::<u128>(), denominator in 1..u128::MAX) {
+            prop_assert_eq!(mul_div_floor(a, b, denominator).is_ok(), a.checked_mul(b).is_some());
+            prop_assert_eq!(mul_div_ceil(a, b, denominator).is_ok(), a.checked_mul(b).is_some());
+        }
+
+        #[test]
+        fn shares_never_exceed_the_reserve(reserve in any::<u64>(), total in 1..u64::MAX as u128, amount in any::<u64>()) {
+            let amount = (amount as u128 % (total + 1)) as u64;
+            let share = proportional_share(amount, reserve, total).unwrap();
+            prop_assert!(share <= reserve);
+
+            // Two partial withdrawals never pay out more than one full withdrawal
+            let rest = proportional_share((total - amount as u128) as u64, reserve, total).unwrap();
+            prop_assert!(share as u128 + rest as u128 <= reserve as u128);
+        }
+    }
+}
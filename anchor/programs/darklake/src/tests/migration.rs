@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+
+    use crate::circuits::MAX_CIRCUITS;
+    use crate::constants::{CURVE_CONSTANT_PRODUCT, DEFAULT_FEE_TIERS};
+    use crate::state::{Config, LegacyPool};
+
+    #[test]
+    fn legacy_pools_keep_their_reserves() {
+        let token_mint_x = Pubkey::new_unique();
+        let token_mint_y = Pubkey::new_unique();
+
+        // The layout of the pool accounts before the migration, without the discriminator
+        let mut data = Vec::new();
+        data.extend_from_slice(token_mint_x.as_ref());
+        data.extend_from_slice(token_mint_y.as_ref());
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&4_000u64.to_le_bytes());
+        data.extend_from_slice(&2_000u128.to_le_bytes());
+        data.push(254);
+        assert_eq!(data.len(), LegacyPool::INIT_SPACE);
+
+        let config = Config {
+            fee_tiers: DEFAULT_FEE_TIERS,
+            protocol_fee_bps: 1_000,
+            vk_versions: [3; MAX_CIRCUITS],
+            ..Default::default()
+        };
+        let pool = LegacyPool::deserialize(&mut data.as_slice())
+            .unwrap()
+            .migrate(DEFAULT_FEE_TIERS[0], &config);
+
+        assert_eq!(pool.token_mint_x, token_mint_x);
+        assert_eq!(pool.token_mint_y, token_mint_y);
+        assert_eq!((pool.reserve_x, pool.reserve_y, pool.liquidity), (1_000, 4_000, 2_000));
+        assert_eq!(pool.bump, 254);
+        assert_eq!(pool.fee_bps, DEFAULT_FEE_TIERS[0]);
+        assert_eq!(pool.protocol_fee_bps, 1_000);
+        assert_eq!(pool.vk_versions, [3; MAX_CIRCUITS]);
+        assert_eq!((pool.protocol_fees_x, pool.protocol_fees_y, pool.paused), (0, 0, 0));
+        assert!(!pool.is_concentrated());
+        assert_eq!(pool.curve, CURVE_CONSTANT_PRODUCT);
+    }
+}
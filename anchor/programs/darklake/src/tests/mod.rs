@@ -1 +1,20 @@
 pub mod groth16;
+pub mod public_signals;
+pub mod swap_fee;
+pub mod config;
+pub mod pause;
+pub mod liquidity;
+pub mod math;
+pub mod verifying_key;
+pub mod circuits;
+pub mod model;
+pub mod quote;
+pub mod route;
+pub mod tick_math;
+pub mod concentrated;
+pub mod stable_swap;
+pub mod migration;
+#[cfg(test)]
+pub mod verifying_key_fixture;
+#[cfg(test)]
+pub mod proof_fixtures;
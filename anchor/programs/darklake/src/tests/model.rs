@@ -0,0 +1,254 @@
+#[cfg(test)]
+mod tests {
+    use crate::errors::ErrorCode;
+    use crate::instructions::check_swap_reserves;
+    use crate::model::{swap, swap_exact_out, ExactOutModelInputs, ModelInputs, ModelOutputs};
+    use proptest::prelude::*;
+
+    fn inputs(amount_in: u64, reserve_x: u64, reserve_y: u64, is_swap_x_to_y: bool) -> ModelInputs {
+        ModelInputs {
+            amount_in,
+            reserve_x,
+            reserve_y,
+            is_swap_x_to_y,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn matches_the_constant_product_formula() {
+        assert_eq!(
+            swap(&inputs(100_000, 1_000_000, 2_000_000, true)).unwrap(),
+            ModelOutputs {
+                new_balance_x: 1_100_000,
+                new_balance_y: 1_818_182,
+                amount_received: 181_818,
+                protocol_fee: 0,
+            }
+        );
+        assert_eq!(
+            swap(&inputs(100_000, 1_000_000, 2_000_000, false)).unwrap(),
+            ModelOutputs {
+                new_balance_x: 952_381,
+                new_balance_y: 2_100_000,
+                amount_received: 47_619,
+                protocol_fee: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn protocol_fee_is_kept_out_of_the_input_reserve() {
+        let outputs = swap(&ModelInputs {
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            ..inputs(100_000, 1_000_000, 2_000_000, true)
+        })
+        .unwrap();
+
+        assert_eq!(outputs.protocol_fee, 60);
+        assert_eq!(outputs.new_balance_x, 1_100_000 - 60);
+        assert_eq!(outputs.amount_received, 181_322);
+    }
+
+    #[test]
+    fn rejects_what_the_circuit_rejects() {
+        let base = inputs(100_000, 1_000_000, 2_000_000, true);
+
+        let fee_above_100_percent = ModelInputs { fee_bps: 10_001, ..base.clone() };
+        assert_eq!(swap(&fee_above_100_percent).unwrap_err(), ErrorCode::InvalidFee.into());
+
+        let protocol_fee_above_100_percent = ModelInputs { protocol_fee_bps: 10_001, ..base.clone() };
+        assert_eq!(swap(&protocol_fee_above_100_percent).unwrap_err(), ErrorCode::InvalidFee.into());
+
+        let below_min_received = ModelInputs { min_received: 181_819, ..base.clone() };
+        assert_eq!(swap(&below_min_received).unwrap_err(), ErrorCode::SlippageExceeded.into());
+        let at_min_received = ModelInputs { min_received: 181_818, ..base.clone() };
+        swap(&at_min_received).unwrap();
+
+        // Nothing to divide by once the whole input goes to fees on an empty reserve
+        let empty = ModelInputs { fee_bps: 10_000, ..inputs(100_000, 0, 2_000_000, true) };
+        assert_eq!(swap(&empty).unwrap_err(), ErrorCode::InsufficientLiquidity.into());
+    }
+
+    #[test]
+    fn empty_input_reserve_gives_away_the_output_reserve() {
+        let outputs = swap(&inputs(1, 0, 2_000_000, true)).unwrap();
+
+        assert_eq!(outputs.amount_received, 2_000_000);
+        assert_eq!(outputs.new_balance_y, 0);
+    }
+
+    #[test]
+    fn input_reserve_can_outgrow_a_u64() {
+        let outputs = swap(&inputs(u64::MAX, u64::MAX, u64::MAX, true)).unwrap();
+
+        assert_eq!(outputs.new_balance_x, u64::MAX as u128 * 2);
+        assert_eq!(outputs.amount_received, (u64::MAX / 2) as u128);
+    }
+
+    fn exact_out(amount_out: u64, reserve_x: u64, reserve_y: u64, is_swap_x_to_y: bool) -> ExactOutModelInputs {
+        ExactOutModelInputs {
+            amount_out,
+            max_amount_in: u64::MAX,
+            reserve_x,
+            reserve_y,
+            is_swap_x_to_y,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exact_out_rounds_the_input_up() {
+        let (outputs, amount_in) = swap_exact_out(&ExactOutModelInputs {
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            ..exact_out(181_322, 1_000_000, 2_000_000, true)
+        })
+        .unwrap();
+
+        // Just under 100_000 before rounding, and the exact input swap of 100_000 gives 181_322 back
+        assert_eq!(amount_in, 100_000);
+        assert_eq!(
+            outputs,
+            ModelOutputs {
+                new_balance_x: 1_099_940,
+                new_balance_y: 1_818_678,
+                amount_received: 181_322,
+                protocol_fee: 60,
+            }
+        );
+    }
+
+    #[test]
+    fn exact_out_rejects_what_the_circuit_rejects() {
+        let base = exact_out(181_818, 1_000_000, 2_000_000, true);
+
+        let above_max_input = ExactOutModelInputs { max_amount_in: 99_999, ..base.clone() };
+        assert_eq!(swap_exact_out(&above_max_input).unwrap_err(), ErrorCode::SlippageExceeded.into());
+        let at_max_input = ExactOutModelInputs { max_amount_in: 100_000, ..base.clone() };
+        swap_exact_out(&at_max_input).unwrap();
+
+        let whole_reserve = exact_out(2_000_000, 1_000_000, 2_000_000, true);
+        assert_eq!(swap_exact_out(&whole_reserve).unwrap_err(), ErrorCode::InsufficientLiquidity.into());
+
+        let all_fees = ExactOutModelInputs { fee_bps: 10_000, ..base.clone() };
+        assert_eq!(swap_exact_out(&all_fees).unwrap_err(), ErrorCode::InsufficientLiquidity.into());
+
+        let fee_above_100_percent = ExactOutModelInputs { fee_bps: 10_001, ..base };
+        assert_eq!(swap_exact_out(&fee_above_100_percent).unwrap_err(), ErrorCode::InvalidFee.into());
+    }
+
+    proptest! {
+        #[test]
+        fn outputs_pass_the_program_checks(
+            amount_in in any::<u64>(),
+            reserve_x in 1..u64::MAX,
+            reserve_y in 1..u64::MAX,
+            is_swap_x_to_y in any::<bool>(),
+            fee_bps in 0..=10_000u16,
+            protocol_fee_bps in 0..=10_000u16,
+        ) {
+            let outputs = swap(&ModelInputs {
+                amount_in,
+                min_received: 0,
+                reserve_x,
+                reserve_y,
+                is_swap_x_to_y,
+                fee_bps,
+                protocol_fee_bps,
+            })
+            .unwrap();
+
+            // The program narrows the balances to u64, larger ones never reach the reserves check
+            if let (Ok(new_x), Ok(new_y)) = (
+                u64::try_from(outputs.new_balance_x),
+                u64::try_from(outputs.new_balance_y),
+            ) {
+                prop_assert!(check_swap_reserves(
+                    reserve_x,
+                    reserve_y,
+                    new_x,
+                    new_y,
+                    outputs.amount_received as u64,
+                    outputs.protocol_fee as u64,
+                    is_swap_x_to_y,
+                    fee_bps,
+                )
+                .is_ok());
+            }
+        }
+
+        #[test]
+        fn exact_out_charges_the_smallest_input_giving_the_output(
+            amount_out in any::<u64>(),
+            reserve_x in 1..u64::MAX,
+            reserve_y in 1..u64::MAX,
+            is_swap_x_to_y in any::<bool>(),
+            fee_bps in 0..10_000u16,
+            protocol_fee_bps in 0..=10_000u16,
+        ) {
+            let Ok((outputs, amount_in)) = swap_exact_out(&ExactOutModelInputs {
+                fee_bps,
+                protocol_fee_bps,
+                ..exact_out(amount_out, reserve_x, reserve_y, is_swap_x_to_y)
+            }) else {
+                return Ok(());
+            };
+            // Inputs past a u64 cannot be sent
+            let Ok(amount_in) = u64::try_from(amount_in) else {
+                return Ok(());
+            };
+
+            let exact_in = |amount_in| {
+                swap(&ModelInputs {
+                    fee_bps,
+                    protocol_fee_bps,
+                    ..inputs(amount_in, reserve_x, reserve_y, is_swap_x_to_y)
+                })
+                .unwrap()
+            };
+            prop_assert!(exact_in(amount_in).amount_received >= amount_out as u128);
+            if amount_in > 0 {
+                prop_assert!(exact_in(amount_in - 1).amount_received < amount_out as u128);
+            }
+
+            if let (Ok(new_x), Ok(new_y)) = (
+                u64::try_from(outputs.new_balance_x),
+                u64::try_from(outputs.new_balance_y),
+            ) {
+                prop_assert!(check_swap_reserves(
+                    reserve_x,
+                    reserve_y,
+                    new_x,
+                    new_y,
+                    amount_out,
+                    outputs.protocol_fee as u64,
+                    is_swap_x_to_y,
+                    fee_bps,
+                )
+                .is_ok());
+            }
+        }
+
+        #[test]
+        fn min_received_is_enforced(
+            amount_in in any::<u64>(),
+            reserve_x in 1..u64::MAX,
+            reserve_y in 1..u64::MAX,
+            min_received in any::<u64>(),
+        ) {
+            let quote = swap(&inputs(amount_in, reserve_x, reserve_y, true)).unwrap();
+            let result = swap(&ModelInputs {
+                min_received,
+                ..inputs(amount_in, reserve_x, reserve_y, true)
+            });
+
+            if (min_received as u128) <= quote.amount_received {
+                prop_assert_eq!(result.unwrap(), quote);
+            } else {
+                prop_assert_eq!(result.unwrap_err(), ErrorCode::SlippageExceeded.into());
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use crate::constants::{PAUSE_DEPOSITS, PAUSE_SWAPS, PAUSE_WITHDRAWALS};
+    use crate::errors::ErrorCode;
+    use crate::state::{Config, Pool};
+
+    #[test]
+    fn unpaused_pool_allows_everything() {
+        let pool = Pool::default();
+        let config = Config::default();
+
+        for operation in [PAUSE_SWAPS, PAUSE_DEPOSITS, PAUSE_WITHDRAWALS] {
+            assert!(pool.require_not_paused(&config, operation).is_ok());
+        }
+    }
+
+    #[test]
+    fn paused_swaps_still_allow_withdrawals() {
+        let pool = Pool {
+            paused: PAUSE_SWAPS | PAUSE_DEPOSITS,
+            ..Default::default()
+        };
+        let config = Config::default();
+
+        assert_eq!(
+            pool.require_not_paused(&config, PAUSE_SWAPS).unwrap_err(),
+            ErrorCode::PoolPaused.into()
+        );
+        assert!(pool.require_not_paused(&config, PAUSE_DEPOSITS).is_err());
+        assert!(pool.require_not_paused(&config, PAUSE_WITHDRAWALS).is_ok());
+    }
+
+    #[test]
+    fn global_pause_applies_to_every_pool() {
+        let pool = Pool::default();
+        let config = Config {
+            paused: PAUSE_WITHDRAWALS,
+            ..Default::default()
+        };
+
+        assert!(pool.require_not_paused(&config, PAUSE_SWAPS).is_ok());
+        assert!(pool.require_not_paused(&config, PAUSE_WITHDRAWALS).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! Proofs made with the checked-in circuit artifacts, in the layout `snarkjs-convert proof`
+//! writes, and the verification keys of their circuits.
+
+use serde_json::Value;
+use snarkjs_convert::VerifyingKey;
+use std::convert::TryInto;
+
+pub struct ProofFixture {
+    pub proof_a: [u8; 64],
+    pub proof_b: [u8; 128],
+    pub proof_c: [u8; 64],
+    pub public_inputs: Vec<[u8; 32]>,
+}
+
+/// Reads `src/tests/<name>`.
+pub fn read_proof(name: &str) -> ProofFixture {
+    let path = format!("./src/tests/{}", name);
+    let contents = std::fs::read_to_string(&path).expect("Failed to read JSON file");
+    let json: Value = serde_json::from_str(&contents).expect("Failed to parse JSON");
+    let bytes = |value: &Value| -> Vec<u8> {
+        value.as_array().expect("value is not an array")
+            .iter().map(|v| v.as_u64().expect("value is not a u64") as u8)
+            .collect()
+    };
+
+    ProofFixture {
+        proof_a: bytes(&json["pi_a"]).try_into().expect("Failed to convert pi_a to [u8; 64]"),
+        proof_b: bytes(&json["pi_b"]).try_into().expect("Failed to convert pi_b to [u8; 128]"),
+        proof_c: bytes(&json["pi_c"]).try_into().expect("Failed to convert pi_c to [u8; 64]"),
+        public_inputs: json["publicInputs"].as_array()
+            .expect("publicInputs is not an array")
+            .iter()
+            .map(|v| bytes(v).try_into().expect("Failed to convert public input"))
+            .collect(),
+    }
+}
+
+/// Parses `circuits/<name>`.
+pub fn verifying_key(name: &str) -> VerifyingKey {
+    let path = format!("../../../circuits/{}", name);
+    let contents = std::fs::read_to_string(&path).expect("Failed to read verification key");
+    snarkjs_convert::parse_verifying_key(&contents).expect("Failed to parse verification key")
+}
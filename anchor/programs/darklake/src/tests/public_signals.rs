@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::verifying_key_fixture::VERIFYINGKEY;
+    use crate::circuits::{field_to_u64, u64_to_field, SwapPublicSignals, SWAP_PUBLIC_SIGNALS};
+    use crate::instructions::{check_proof_expiry, user_hash};
+    use anchor_lang::prelude::Pubkey;
+    use groth16_solana::groth16::Groth16Verifier;
+    use serde_json::Value;
+    use std::convert::TryInto;
+
+    fn read_proof_from_json() -> ([u8; 64], [u8; 128], [u8; 64], [[u8; 32]; SWAP_PUBLIC_SIGNALS]) {
+        let contents = std::fs::read_to_string("./src/tests/zk_proof_output.json")
+            .expect("Failed to read JSON file");
+        let json: Value = serde_json::from_str(&contents).expect("Failed to parse JSON");
+
+        let bytes = |value: &Value| -> Vec<u8> {
+            value.as_array().expect("value is not an array")
+                .iter().map(|v| v.as_u64().expect("value is not a u64") as u8)
+                .collect()
+        };
+
+        let public_inputs: [[u8; 32]; SWAP_PUBLIC_SIGNALS] = json["publicInputs"].as_array()
+            .expect("publicInputs is not an array")
+            .iter()
+            .map(|v| bytes(v).try_into().expect("Failed to convert public input"))
+            .collect::<Vec<[u8; 32]>>()
+            .try_into()
+            .expect("Failed to convert public inputs to [[u8; 32]; SWAP_PUBLIC_SIGNALS]");
+
+        (
+            bytes(&json["pi_a"]).try_into().expect("Failed to convert pi_a to [u8; 64]"),
+            bytes(&json["pi_b"]).try_into().expect("Failed to convert pi_b to [u8; 128]"),
+            bytes(&json["pi_c"]).try_into().expect("Failed to convert pi_c to [u8; 64]"),
+            public_inputs,
+        )
+    }
+
+    fn verify(
+        proof_a: &[u8; 64],
+        proof_b: &[u8; 128],
+        proof_c: &[u8; 64],
+        public_inputs: &[[u8; 32]; SWAP_PUBLIC_SIGNALS],
+    ) -> bool {
+        Groth16Verifier::new(proof_a, proof_b, proof_c, public_inputs, &VERIFYINGKEY)
+            .and_then(|mut verifier| verifier.verify())
+            .unwrap_or(false)
+    }
+
+    fn signals() -> SwapPublicSignals {
+        SwapPublicSignals {
+            new_balance_x: 1_100,
+            new_balance_y: 910,
+            amount_received: 90,
+            protocol_fee: 0,
+            nullifier: [7u8; 32],
+            reserve_x: 1_000,
+            reserve_y: 1_000,
+            is_swap_x_to_y: true,
+            fee_bps: 30,
+            protocol_fee_bps: 2_000,
+            user_hash: [9u8; 32],
+            expiry_slot: 500,
+        }
+    }
+
+    #[test]
+    fn swap_public_signals_follow_circuit_layout() {
+        let fields = signals().to_fields();
+
+        assert_eq!(field_to_u64(&fields[0]).unwrap(), 1_100);
+        assert_eq!(field_to_u64(&fields[1]).unwrap(), 910);
+        assert_eq!(field_to_u64(&fields[2]).unwrap(), 90);
+        assert_eq!(field_to_u64(&fields[3]).unwrap(), 0);
+        assert_eq!(fields[4], [7u8; 32]);
+        assert_eq!(field_to_u64(&fields[5]).unwrap(), 1_000);
+        assert_eq!(field_to_u64(&fields[6]).unwrap(), 1_000);
+        assert_eq!(field_to_u64(&fields[7]).unwrap(), 1);
+        assert_eq!(field_to_u64(&fields[8]).unwrap(), 30);
+        assert_eq!(field_to_u64(&fields[9]).unwrap(), 2_000);
+        assert_eq!(fields[10], [9u8; 32]);
+        assert_eq!(field_to_u64(&fields[11]).unwrap(), 500);
+
+        let reversed = SwapPublicSignals {
+            is_swap_x_to_y: false,
+            ..signals()
+        };
+        assert_eq!(field_to_u64(&reversed.to_fields()[7]).unwrap(), 0);
+    }
+
+    #[test]
+    fn swap_public_signals_commit_to_the_direction() {
+        let x_to_y = signals().to_fields();
+        let y_to_x = SwapPublicSignals { is_swap_x_to_y: false, ..signals() }.to_fields();
+
+        // A proof for one direction cannot be used for the other one
+        assert_ne!(x_to_y, y_to_x);
+        for index in (0..x_to_y.len()).filter(|index| *index != 7) {
+            assert_eq!(x_to_y[index], y_to_x[index]);
+        }
+    }
+
+    #[test]
+    fn swap_public_signals_change_with_pool_reserves() {
+        let current = signals().to_fields();
+        let stale = SwapPublicSignals { reserve_y: 1_001, ..signals() }.to_fields();
+        let forged = SwapPublicSignals { reserve_x: 5_000, ..signals() }.to_fields();
+
+        assert_ne!(current, stale);
+        assert_ne!(current, forged);
+        assert_ne!(current, SwapPublicSignals { fee_bps: 0, ..signals() }.to_fields());
+        assert_eq!(&current[..5], &stale[..5]);
+    }
+
+    #[test]
+    fn swap_public_signals_carry_the_nullifier() {
+        let replayed = signals().to_fields();
+        let fresh = SwapPublicSignals { nullifier: [8u8; 32], ..signals() }.to_fields();
+
+        assert_eq!(replayed, signals().to_fields());
+        assert_ne!(replayed, fresh);
+    }
+
+    #[test]
+    fn user_hash_binds_signer_and_destination() {
+        let user = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let hash = user_hash(&user, &destination);
+
+        // Always below the BN254 scalar field
+        assert_eq!(hash[0], 0);
+        assert_eq!(hash, user_hash(&user, &destination));
+
+        assert_ne!(hash, user_hash(&Pubkey::new_unique(), &destination));
+        assert_ne!(hash, user_hash(&user, &Pubkey::new_unique()));
+        assert_ne!(hash, user_hash(&destination, &user));
+    }
+
+    #[test]
+    fn swap_public_signals_carry_the_expiry_slot() {
+        let extended = SwapPublicSignals { expiry_slot: 501, ..signals() }.to_fields();
+
+        assert_ne!(signals().to_fields(), extended);
+        assert_eq!(&signals().to_fields()[..11], &extended[..11]);
+    }
+
+    #[test]
+    fn proof_expires_after_its_expiry_slot() {
+        assert!(check_proof_expiry(500, 0).is_ok());
+        assert!(check_proof_expiry(500, 500).is_ok());
+        assert!(check_proof_expiry(500, 501).is_err());
+        assert!(check_proof_expiry(0, u64::MAX).is_err());
+        assert!(check_proof_expiry(u64::MAX, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn field_to_u64_rejects_values_above_u64() {
+        assert_eq!(field_to_u64(&u64_to_field(u64::MAX)).unwrap(), u64::MAX);
+
+        let mut field = u64_to_field(1);
+        field[23] = 1;
+        assert!(field_to_u64(&field).is_err());
+
+        let mut field = u64_to_field(1);
+        field[0] = 1;
+        assert!(field_to_u64(&field).is_err());
+    }
+
+    #[test]
+    fn proof_is_rejected_when_any_public_signal_is_forged() {
+        let (proof_a, proof_b, proof_c, public_inputs) = read_proof_from_json();
+        assert!(verify(&proof_a, &proof_b, &proof_c, &public_inputs));
+
+        for index in 0..public_inputs.len() {
+            let mut flipped = public_inputs;
+            flipped[index][31] ^= 1;
+            let mut zeroed = public_inputs;
+            zeroed[index] = [0u8; 32];
+            for forged in [flipped, zeroed] {
+                assert!(
+                    !verify(&proof_a, &proof_b, &proof_c, &forged),
+                    "proof accepted with public signal {} forged",
+                    index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn proof_is_rejected_against_other_pool_reserves() {
+        let (proof_a, proof_b, proof_c, public_inputs) = read_proof_from_json();
+        let field = |index: usize| field_to_u64(&public_inputs[index]).unwrap();
+        let proven = SwapPublicSignals {
+            new_balance_x: field(0),
+            new_balance_y: field(1),
+            amount_received: field(2),
+            protocol_fee: field(3),
+            nullifier: public_inputs[4],
+            reserve_x: field(5),
+            reserve_y: field(6),
+            is_swap_x_to_y: field(7) == 1,
+            fee_bps: field(8) as u16,
+            protocol_fee_bps: field(9) as u16,
+            user_hash: public_inputs[10],
+            expiry_slot: field(11),
+        };
+        assert_eq!(proven.to_fields(), public_inputs);
+        assert!(verify(&proof_a, &proof_b, &proof_c, &proven.to_fields()));
+
+        // Reserves that moved since the proof was made
+        for forged in [
+            SwapPublicSignals { reserve_x: proven.reserve_x + 1, ..proven.clone() },
+            SwapPublicSignals { reserve_x: proven.reserve_x - 1, ..proven.clone() },
+            SwapPublicSignals { reserve_y: proven.reserve_y + 1, ..proven.clone() },
+            SwapPublicSignals { reserve_y: proven.reserve_y - 1, ..proven.clone() },
+            SwapPublicSignals {
+                reserve_x: proven.reserve_y,
+                reserve_y: proven.reserve_x,
+                ..proven.clone()
+            },
+        ] {
+            assert!(
+                !verify(&proof_a, &proof_b, &proof_c, &forged.to_fields()),
+                "proof accepted with reserves {} and {}",
+                forged.reserve_x,
+                forged.reserve_y
+            );
+        }
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::errors::ErrorCode;
+    use crate::instructions::{swap_quote, SwapQuote};
+    use crate::model::{swap, ModelInputs};
+    use crate::state::Pool;
+    use proptest::prelude::*;
+
+    fn pool(fee_bps: u16, protocol_fee_bps: u16) -> Pool {
+        Pool {
+            reserve_x: 1_000_000,
+            reserve_y: 2_000_000,
+            fee_bps,
+            protocol_fee_bps,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn quotes_the_circuit_outputs() {
+        assert_eq!(
+            swap_quote(&pool(30, 2_000), 100_000, true).unwrap(),
+            SwapQuote {
+                amount_in: 100_000,
+                amount_out: 181_322,
+                fee: 300,
+                protocol_fee: 60,
+                new_reserve_x: 1_099_940,
+                new_reserve_y: 1_818_678,
+            }
+        );
+        assert_eq!(
+            swap_quote(&pool(0, 0), 100_000, false).unwrap(),
+            SwapQuote {
+                amount_in: 100_000,
+                amount_out: 47_619,
+                fee: 0,
+                protocol_fee: 0,
+                new_reserve_x: 952_381,
+                new_reserve_y: 2_100_000,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_swaps_the_program_rejects() {
+        assert_eq!(
+            swap_quote(&pool(30, 2_000), 0, true).unwrap_err(),
+            ErrorCode::InvalidSwapAmount.into()
+        );
+
+        // The input reserve would not fit in the pool account
+        let full = Pool { reserve_x: u64::MAX - 10, ..pool(30, 2_000) };
+        assert_eq!(
+            swap_quote(&full, 100_000, true).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
+
+        let empty = Pool { reserve_x: 0, reserve_y: 0, ..pool(10_000, 0) };
+        assert!(swap_quote(&empty, 100_000, true).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn quotes_match_the_circuit_model(
+            amount_in in 1..u64::MAX,
+            reserve_x in any::<u64>(),
+            reserve_y in any::<u64>(),
+            is_swap_x_to_y in any::<bool>(),
+            fee_bps in 0..=10_000u16,
+            protocol_fee_bps in 0..=10_000u16,
+        ) {
+            let pool = Pool { reserve_x, reserve_y, fee_bps, protocol_fee_bps, ..Default::default() };
+            let quote = swap_quote(&pool, amount_in, is_swap_x_to_y);
+            let outputs = swap(&ModelInputs {
+                amount_in,
+                min_received: 0,
+                reserve_x,
+                reserve_y,
+                is_swap_x_to_y,
+                fee_bps,
+                protocol_fee_bps,
+            });
+
+            match (quote, outputs) {
+                (Ok(quote), Ok(outputs)) => {
+                    prop_assert_eq!(quote.amount_out as u128, outputs.amount_received);
+                    prop_assert_eq!(quote.protocol_fee as u128, outputs.protocol_fee);
+                    prop_assert_eq!(quote.new_reserve_x as u128, outputs.new_balance_x);
+                    prop_assert_eq!(quote.new_reserve_y as u128, outputs.new_balance_y);
+                }
+                // Only balances the pool account cannot hold separate the quote from the circuit
+                (Err(err), Ok(outputs)) => {
+                    prop_assert_eq!(err, ErrorCode::MathOverflow.into());
+                    prop_assert!(outputs.new_balance_x.max(outputs.new_balance_y) > u64::MAX as u128);
+                }
+                (Ok(_), Err(_)) => prop_assert!(false, "quoted a swap the circuit cannot prove"),
+                (Err(_), Err(_)) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+#[cfg(test)]
+mod tests {
+    use crate::circuits::{
+        u64_to_field, HopBindings, HopOutputs, RouteBindings, RouteOutputs, SwapCircuit,
+        CIRCUIT_CONSTANT_PRODUCT_ROUTE, MAX_CIRCUITS, ROUTE_HOPS, ROUTE_OUTPUTS,
+        ROUTE_PUBLIC_SIGNALS,
+    };
+    use crate::errors::ErrorCode;
+    use crate::instructions::check_route_reserves;
+    use crate::model::{swap_route, ModelOutputs, RouteHopInputs};
+    use crate::tests::proof_fixtures::{read_proof, verifying_key};
+    use anchor_lang::error::Error;
+    use proptest::prelude::*;
+
+    /// X to Y through a 1M/2M pool, then Y to Z through a pool holding 3M Z and 1M Y.
+    fn route() -> Vec<RouteHopInputs> {
+        vec![
+            RouteHopInputs {
+                reserve_x: 1_000_000,
+                reserve_y: 2_000_000,
+                is_swap_x_to_y: true,
+                ..Default::default()
+            },
+            RouteHopInputs {
+                reserve_x: 3_000_000,
+                reserve_y: 1_000_000,
+                is_swap_x_to_y: false,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn bindings(hops: &[RouteHopInputs]) -> Vec<HopBindings> {
+        hops.iter()
+            .map(|hop| HopBindings {
+                reserve_x: hop.reserve_x,
+                reserve_y: hop.reserve_y,
+                is_swap_x_to_y: hop.is_swap_x_to_y,
+                fee_bps: hop.fee_bps,
+                protocol_fee_bps: hop.protocol_fee_bps,
+            })
+            .collect()
+    }
+
+    fn hop_outputs(outputs: &[ModelOutputs]) -> Vec<HopOutputs> {
+        outputs
+            .iter()
+            .map(|hop| HopOutputs {
+                new_balance_x: hop.new_balance_x as u64,
+                new_balance_y: hop.new_balance_y as u64,
+                protocol_fee: hop.protocol_fee as u64,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn route_circuit_has_its_own_id_and_layout() {
+        let circuit = SwapCircuit::from_id(CIRCUIT_CONSTANT_PRODUCT_ROUTE).unwrap();
+        assert_eq!(circuit, SwapCircuit::ConstantProductRoute);
+        assert!((circuit.id() as usize) < MAX_CIRCUITS);
+        assert_eq!(circuit.hops(), ROUTE_HOPS);
+        assert_eq!(circuit.outputs(), ROUTE_OUTPUTS);
+        assert_eq!(circuit.public_signals(), ROUTE_PUBLIC_SIGNALS);
+        assert_eq!(SwapCircuit::ConstantProduct.hops(), 1);
+
+        // Single pool and route outputs cannot be mixed up
+        assert_eq!(
+            circuit.decode_outputs(&[[0u8; 32]; ROUTE_OUTPUTS]).unwrap_err(),
+            Error::from(ErrorCode::UnknownCircuit)
+        );
+        assert_eq!(
+            SwapCircuit::ConstantProduct
+                .decode_route_outputs(&[[0u8; 32]; 5])
+                .unwrap_err(),
+            Error::from(ErrorCode::UnknownCircuit)
+        );
+    }
+
+    #[test]
+    fn route_signals_list_every_hop_per_signal() {
+        let circuit = SwapCircuit::ConstantProductRoute;
+        let encoded: Vec<[u8; 32]> = (1..=6)
+            .map(u64_to_field)
+            .chain([u64_to_field(7), [9u8; 32]])
+            .collect();
+        let outputs = circuit.decode_route_outputs(&encoded).unwrap();
+        assert_eq!(
+            outputs,
+            RouteOutputs {
+                hops: vec![
+                    HopOutputs { new_balance_x: 1, new_balance_y: 3, protocol_fee: 5 },
+                    HopOutputs { new_balance_x: 2, new_balance_y: 4, protocol_fee: 6 },
+                ],
+                amount_received: 7,
+                nullifier: [9u8; 32],
+            }
+        );
+
+        let bindings = RouteBindings {
+            hops: bindings(&route()),
+            user_hash: [8u8; 32],
+            expiry_slot: 500,
+        };
+        let signals = circuit.route_public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(signals.len(), ROUTE_PUBLIC_SIGNALS);
+        assert_eq!(&signals[..ROUTE_OUTPUTS], encoded.as_slice());
+        assert_eq!(
+            &signals[ROUTE_OUTPUTS..],
+            [
+                u64_to_field(1_000_000),
+                u64_to_field(3_000_000),
+                u64_to_field(2_000_000),
+                u64_to_field(1_000_000),
+                u64_to_field(1),
+                u64_to_field(0),
+                u64_to_field(0),
+                u64_to_field(0),
+                u64_to_field(0),
+                u64_to_field(0),
+                [8u8; 32],
+                u64_to_field(500),
+            ]
+            .as_slice()
+        );
+
+        let short = RouteBindings { hops: bindings.hops[..1].to_vec(), ..bindings };
+        assert_eq!(
+            circuit.route_public_signals_for(&outputs, &short).unwrap_err(),
+            Error::from(ErrorCode::InvalidRoute)
+        );
+        assert_eq!(
+            circuit.decode_route_outputs(&encoded[1..]).unwrap_err(),
+            Error::from(ErrorCode::InvalidPublicInputsLength)
+        );
+    }
+
+    #[test]
+    fn route_moves_the_output_of_each_pool_into_the_next() {
+        let outputs = swap_route(100_000, 0, &route()).unwrap();
+        assert_eq!(outputs[0].amount_received, 181_818);
+        assert_eq!(outputs[1].amount_received, 461_538);
+        assert_eq!(outputs[1].new_balance_x, 2_538_462);
+
+        assert_eq!(
+            check_route_reserves(&bindings(&route()), &hop_outputs(&outputs), 461_538).unwrap(),
+            (100_000, vec![181_818, 461_538])
+        );
+
+        assert_eq!(
+            swap_route(100_000, 461_539, &route()).unwrap_err(),
+            Error::from(ErrorCode::SlippageExceeded)
+        );
+    }
+
+    #[test]
+    fn route_amounts_must_chain() {
+        let outputs = swap_route(100_000, 0, &route()).unwrap();
+        let hops = hop_outputs(&outputs);
+
+        // The second pool takes in one more than the first one pays out
+        let mut skimmed = hops.clone();
+        skimmed[1].new_balance_y += 1;
+        skimmed[1].new_balance_x -= 1;
+        assert_eq!(
+            check_route_reserves(&bindings(&route()), &skimmed, 461_539).unwrap_err(),
+            Error::from(ErrorCode::InvalidSwapAmount)
+        );
+
+        assert_eq!(
+            check_route_reserves(&bindings(&route()), &hops, 461_537).unwrap_err(),
+            Error::from(ErrorCode::InvalidSwapAmount)
+        );
+        assert_eq!(
+            check_route_reserves(&bindings(&route()), &hops[..1], 181_818).unwrap_err(),
+            Error::from(ErrorCode::InvalidRoute)
+        );
+        assert_eq!(
+            check_route_reserves(&[], &[], 0).unwrap_err(),
+            Error::from(ErrorCode::InvalidRoute)
+        );
+    }
+
+    #[test]
+    fn route_proof_verifies_through_the_circuit() {
+        let proof = read_proof("zk_proof_output_route.json");
+        let key = verifying_key("route_verification_key.json");
+        let circuit = SwapCircuit::ConstantProductRoute;
+
+        // X to Y through a 1.1M/1.9M pool, then Y to Z through a pool holding 2M Z and 0.5M Y
+        let hops = vec![
+            RouteHopInputs {
+                reserve_x: 1_100_000,
+                reserve_y: 1_900_000,
+                is_swap_x_to_y: true,
+                fee_bps: 30,
+                protocol_fee_bps: 2_000,
+            },
+            RouteHopInputs {
+                reserve_x: 2_000_000,
+                reserve_y: 500_000,
+                is_swap_x_to_y: false,
+                fee_bps: 5,
+                protocol_fee_bps: 1_000,
+            },
+        ];
+        let outputs = circuit
+            .decode_route_outputs(&proof.public_inputs[..ROUTE_OUTPUTS])
+            .unwrap();
+        let expected = swap_route(100_000, 30_000, &hops).unwrap();
+        assert_eq!(outputs.hops, hop_outputs(&expected));
+        assert_eq!(outputs.amount_received as u128, expected[1].amount_received);
+
+        let mut bindings = RouteBindings {
+            hops: bindings(&hops),
+            user_hash: u64_to_field(987_654_321),
+            expiry_slot: 1_000,
+        };
+        let signals = circuit.route_public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(signals, proof.public_inputs);
+        assert!(circuit
+            .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+            .unwrap());
+
+        // The same pools in the other order
+        bindings.hops.reverse();
+        let signals = circuit.route_public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(
+            circuit
+                .verify(&proof.proof_a, &proof.proof_b, &proof.proof_c, &signals, &key.groth16())
+                .unwrap_err(),
+            Error::from(ErrorCode::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn every_pool_of_the_route_charges_its_fee() {
+        let outputs = hop_outputs(&swap_route(100_000, 0, &route()).unwrap());
+
+        // Amounts proven without a fee, against pools that charge one
+        for hop in 0..ROUTE_HOPS {
+            let mut charged = route();
+            charged[hop].fee_bps = 30;
+            assert_eq!(
+                check_route_reserves(&bindings(&charged), &outputs, 461_538).unwrap_err(),
+                Error::from(ErrorCode::InvariantViolated)
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn model_routes_pass_the_program_checks(
+            amount_in in 1..u64::MAX / 2,
+            reserves in proptest::collection::vec((1..u64::MAX / 2, 1..u64::MAX / 2), ROUTE_HOPS),
+            directions in proptest::collection::vec(any::<bool>(), ROUTE_HOPS),
+            fee_bps in 0..=1_000u16,
+            protocol_fee_bps in 0..=10_000u16,
+        ) {
+            let hops: Vec<RouteHopInputs> = reserves
+                .iter()
+                .zip(&directions)
+                .map(|(&(reserve_x, reserve_y), &is_swap_x_to_y)| RouteHopInputs {
+                    reserve_x,
+                    reserve_y,
+                    is_swap_x_to_y,
+                    fee_bps,
+                    protocol_fee_bps,
+                })
+                .collect();
+            let outputs = swap_route(amount_in, 0, &hops).unwrap();
+
+            let received = outputs[ROUTE_HOPS - 1].amount_received as u64;
+            let (sent, amounts_out) =
+                check_route_reserves(&bindings(&hops), &hop_outputs(&outputs), received).unwrap();
+
+            // The protocol fee leaves the reserves, so the user sends exactly the input
+            prop_assert_eq!(sent, amount_in);
+            prop_assert_eq!(amounts_out.len(), ROUTE_HOPS);
+        }
+    }
+}
@@ -0,0 +1,413 @@
+#[cfg(test)]
+mod tests {
+    use crate::circuits::{
+        u64_to_field, SwapBindings, SwapCircuit, SwapOutputs, CIRCUIT_STABLE_SWAP,
+        STABLE_SWAP_PUBLIC_SIGNALS, SWAP_PUBLIC_SIGNALS,
+    };
+    use crate::constants::{CURVE_STABLE_SWAP, MINIMUM_LIQUIDITY, MIN_AMP_RAMP_SLOTS};
+    use crate::errors::ErrorCode;
+    use crate::instructions::{
+        check_stable_swap_reserves, check_swap_reserves, stable_swap_quote, start_amp_ramp,
+        swap_quote,
+    };
+    use crate::model::{self, ModelInputs, StableModelInputs};
+    use crate::stable_math::{compute_d, compute_y, stable_deposit, MAX_AMP};
+    use crate::state::Pool;
+    use proptest::prelude::*;
+
+    fn stable_pool(amp: u32) -> Pool {
+        Pool {
+            reserve_x: 1_000_000_000,
+            reserve_y: 1_000_000_000,
+            liquidity: 2_000_000_000,
+            fee_bps: 5,
+            protocol_fee_bps: 2_000,
+            curve: CURVE_STABLE_SWAP,
+            amp_initial: amp,
+            amp_target: amp,
+            ..Default::default()
+        }
+    }
+
+    fn stable_inputs(amount_in: u64, amp: u64) -> StableModelInputs {
+        StableModelInputs {
+            amount_in,
+            min_received: 0,
+            reserve_x: 1_000_000_000,
+            reserve_y: 1_000_000_000,
+            is_swap_x_to_y: true,
+            fee_bps: 5,
+            protocol_fee_bps: 2_000,
+            amp,
+        }
+    }
+
+    #[test]
+    fn balanced_pools_have_the_sum_as_invariant() {
+        for amp in [1, 100, MAX_AMP] {
+            assert_eq!(compute_d(amp, 1_000_000, 1_000_000).unwrap(), 2_000_000);
+            assert_eq!(compute_d(amp, u64::MAX, u64::MAX).unwrap(), 2 * u64::MAX as u128);
+        }
+        assert_eq!(compute_d(100, 0, 1_000_000).unwrap(), 0);
+        assert_eq!(
+            compute_d(0, 1_000_000, 1_000_000).unwrap_err(),
+            ErrorCode::InvalidAmp.into()
+        );
+    }
+
+    #[test]
+    fn imbalanced_pools_are_between_constant_product_and_constant_sum() {
+        let (x, y) = (1_000_000u64, 4_000_000u64);
+        let mut previous = 0;
+        for amp in [1, 10, 100, 1_000, MAX_AMP] {
+            let d = compute_d(amp, x, y).unwrap();
+            // 2 * sqrt(xy) for the constant product, x + y for the constant sum
+            assert!(d > 4_000_000 && d < 5_000_000);
+            assert!(d > previous);
+            previous = d;
+        }
+    }
+
+    #[test]
+    fn balances_are_rounded_up_to_keep_the_invariant() {
+        for amp in [1, 85, 2_000] {
+            let d = compute_d(amp, 3_000_000, 7_000_000).unwrap();
+            for x in [1_000_000u64, 3_000_000, 3_000_001, 9_500_000] {
+                let y = compute_y(amp, x as u128, d).unwrap() as u64;
+                assert!(compute_d(amp, x, y).unwrap() >= d);
+                assert!(compute_d(amp, x, y - 1).unwrap() < d);
+            }
+            assert_eq!(compute_y(amp, 3_000_000, d).unwrap(), 7_000_000);
+        }
+    }
+
+    #[test]
+    fn stable_swaps_pay_close_to_one_for_one() {
+        let amount_in = 10_000_000;
+        let stable = model::swap_stable(&stable_inputs(amount_in, 100)).unwrap();
+        let constant_product = model::swap(&ModelInputs {
+            amount_in,
+            min_received: 0,
+            reserve_x: 1_000_000_000,
+            reserve_y: 1_000_000_000,
+            is_swap_x_to_y: true,
+            fee_bps: 5,
+            protocol_fee_bps: 2_000,
+        })
+        .unwrap();
+
+        assert!(stable.amount_received > constant_product.amount_received);
+        assert!(stable.amount_received < amount_in as u128);
+        assert!(stable.amount_received > 9_990_000);
+        assert_eq!(stable.protocol_fee, constant_product.protocol_fee);
+        assert_eq!(stable.new_balance_x, 1_000_000_000 + amount_in as u128 - stable.protocol_fee);
+        assert_eq!(stable.new_balance_y, 1_000_000_000 - stable.amount_received);
+
+        // A higher amplification gets closer to one for one
+        let flatter = model::swap_stable(&stable_inputs(amount_in, 1_000)).unwrap();
+        assert!(flatter.amount_received > stable.amount_received);
+    }
+
+    #[test]
+    fn stable_swaps_never_lower_the_invariant() {
+        let outputs = model::swap_stable(&stable_inputs(300_000_000, 100)).unwrap();
+        let (new_x, new_y) = (outputs.new_balance_x as u64, outputs.new_balance_y as u64);
+        check_stable_swap_reserves(
+            1_000_000_000,
+            1_000_000_000,
+            new_x,
+            new_y,
+            outputs.amount_received as u64,
+            outputs.protocol_fee as u64,
+            true,
+            5,
+            100,
+        )
+        .unwrap();
+
+        // The constant product of the same reserves went down, which only a stable pool allows
+        assert_eq!(
+            check_swap_reserves(
+                1_000_000_000,
+                1_000_000_000,
+                new_x,
+                new_y,
+                outputs.amount_received as u64,
+                outputs.protocol_fee as u64,
+                true,
+                5,
+            )
+            .unwrap_err(),
+            ErrorCode::InvariantViolated.into()
+        );
+
+        // The circuit pays out down to the smallest balance keeping the invariant with the input
+        // net of the fee. One more token keeps the invariant of the raw reserves, not that one.
+        let d = compute_d(100, 1_000_000_000, 1_000_000_000).unwrap();
+        assert!(compute_y(100, new_x as u128, d).unwrap() < new_y as u128 - 1);
+        assert_eq!(
+            check_stable_swap_reserves(
+                1_000_000_000,
+                1_000_000_000,
+                new_x,
+                new_y - 1,
+                outputs.amount_received as u64 + 1,
+                outputs.protocol_fee as u64,
+                true,
+                5,
+                100,
+            )
+            .unwrap_err(),
+            ErrorCode::InvariantViolated.into()
+        );
+    }
+
+    #[test]
+    fn stable_swaps_check_slippage_and_liquidity() {
+        let inputs = StableModelInputs { min_received: 10_000_000, ..stable_inputs(10_000_000, 100) };
+        assert_eq!(
+            model::swap_stable(&inputs).unwrap_err(),
+            ErrorCode::SlippageExceeded.into()
+        );
+
+        let empty = StableModelInputs { reserve_x: 0, reserve_y: 0, ..stable_inputs(1_000, 100) };
+        assert_eq!(
+            model::swap_stable(&empty).unwrap_err(),
+            ErrorCode::InsufficientLiquidity.into()
+        );
+
+        assert_eq!(
+            model::swap_stable(&stable_inputs(1_000, MAX_AMP + 1)).unwrap_err(),
+            ErrorCode::InvalidAmp.into()
+        );
+    }
+
+    #[test]
+    fn quotes_use_the_curve_of_the_pool() {
+        let pool = stable_pool(100);
+        let quote = stable_swap_quote(&pool, 100, 10_000_000, false).unwrap();
+        let outputs = model::swap_stable(&StableModelInputs {
+            is_swap_x_to_y: false,
+            ..stable_inputs(10_000_000, 100)
+        })
+        .unwrap();
+        assert_eq!(quote.amount_out as u128, outputs.amount_received);
+        assert_eq!(quote.fee, 5_000);
+        assert_eq!(quote.new_reserve_x as u128, outputs.new_balance_x);
+
+        assert_eq!(
+            swap_quote(&pool, 10_000_000, false).unwrap_err(),
+            ErrorCode::InvalidPoolType.into()
+        );
+        let constant_product = Pool { curve: 0, ..pool };
+        assert_eq!(
+            stable_swap_quote(&constant_product, 100, 10_000_000, false).unwrap_err(),
+            ErrorCode::InvalidPoolType.into()
+        );
+    }
+
+    #[test]
+    fn amp_ramps_linearly() {
+        let pool = Pool {
+            amp_initial: 100,
+            amp_target: 200,
+            amp_ramp_start_slot: 1_000,
+            amp_ramp_end_slot: 2_000,
+            ..stable_pool(100)
+        };
+        assert_eq!(pool.amp(500), 100);
+        assert_eq!(pool.amp(1_000), 100);
+        assert_eq!(pool.amp(1_505), 150);
+        assert_eq!(pool.amp(2_000), 200);
+        assert_eq!(pool.amp(u64::MAX), 200);
+
+        // Rounded down on the way down too
+        let down = Pool { amp_initial: 200, amp_target: 100, ..pool };
+        assert_eq!(down.amp(1_001), 199);
+        assert_eq!(down.amp(1_500), 150);
+        assert_eq!(down.amp(2_000), 100);
+
+        assert_eq!(stable_pool(100).amp(0), 100);
+    }
+
+    #[test]
+    fn amp_ramps_are_slow_and_bounded() {
+        let slot = 10 * MIN_AMP_RAMP_SLOTS;
+        let end = slot + MIN_AMP_RAMP_SLOTS;
+
+        let mut pool = stable_pool(100);
+        assert_eq!(
+            start_amp_ramp(&mut pool, 200, end - 1, slot).unwrap_err(),
+            ErrorCode::InvalidAmpRamp.into()
+        );
+        assert_eq!(
+            start_amp_ramp(&mut pool, 1_001, end, slot).unwrap_err(),
+            ErrorCode::InvalidAmp.into()
+        );
+        assert_eq!(
+            start_amp_ramp(&mut pool, 9, end, slot).unwrap_err(),
+            ErrorCode::InvalidAmp.into()
+        );
+
+        start_amp_ramp(&mut pool, 1_000, end, slot).unwrap();
+        assert_eq!(
+            (pool.amp_initial, pool.amp_target, pool.amp_ramp_start_slot, pool.amp_ramp_end_slot),
+            (100, 1_000, slot, end)
+        );
+
+        // The next ramp starts from wherever the coefficient got to, once enough time passed
+        let halfway = slot + MIN_AMP_RAMP_SLOTS / 2;
+        assert_eq!(
+            start_amp_ramp(&mut pool, 100, halfway + MIN_AMP_RAMP_SLOTS, halfway).unwrap_err(),
+            ErrorCode::InvalidAmpRamp.into()
+        );
+        start_amp_ramp(&mut pool, 100, end + 2 * MIN_AMP_RAMP_SLOTS, end + 1).unwrap();
+        assert_eq!(pool.amp_initial, 1_000);
+        assert_eq!(pool.amp(end + 1), 1_000);
+
+        let mut constant_product = Pool { curve: 0, ..stable_pool(0) };
+        assert_eq!(
+            start_amp_ramp(&mut constant_product, 100, end, slot).unwrap_err(),
+            ErrorCode::InvalidPoolType.into()
+        );
+    }
+
+    #[test]
+    fn imbalanced_deposits_pay_a_fee() {
+        let (minted, liquidity) = stable_deposit(100, 1_000_000, 1_000_000, 0, 0, 0, 5).unwrap();
+        assert_eq!(liquidity, 2_000_000);
+        assert_eq!(minted, 2_000_000 - MINIMUM_LIQUIDITY);
+
+        // In the pool ratio, a deposit mints its share of the invariant
+        let (minted, added) =
+            stable_deposit(100, 500_000, 500_000, 1_000_000, 1_000_000, 2_000_000, 5).unwrap();
+        assert_eq!((minted, added), (1_000_000, 1_000_000));
+
+        // One sided, it pays half the swap fee on the part out of ratio
+        let (one_sided, _) =
+            stable_deposit(100, 1_000_000, 0, 1_000_000, 1_000_000, 2_000_000, 5).unwrap();
+        let (one_sided_free, _) =
+            stable_deposit(100, 1_000_000, 0, 1_000_000, 1_000_000, 2_000_000, 0).unwrap();
+        assert!(one_sided < one_sided_free);
+        assert!(one_sided_free < 1_000_000);
+
+        assert_eq!(
+            stable_deposit(100, 0, 0, 1_000_000, 1_000_000, 2_000_000, 5).unwrap_err(),
+            ErrorCode::InsufficientLiquidity.into()
+        );
+    }
+
+    #[test]
+    fn stable_swap_signals_append_the_amp() {
+        let circuit = SwapCircuit::from_id(CIRCUIT_STABLE_SWAP).unwrap();
+        assert_eq!(circuit, SwapCircuit::StableSwap);
+        assert_eq!(circuit.id(), CIRCUIT_STABLE_SWAP);
+        assert!(!circuit.is_exact_out());
+
+        let outputs = SwapOutputs {
+            new_balance_x: 1_100,
+            new_balance_y: 901,
+            amount_received: 99,
+            protocol_fee: 0,
+            nullifier: [7u8; 32],
+        };
+        let bindings = SwapBindings {
+            reserve_x: 1_000,
+            reserve_y: 1_000,
+            is_swap_x_to_y: true,
+            fee_bps: 5,
+            protocol_fee_bps: 0,
+            user_hash: [9u8; 32],
+            expiry_slot: 500,
+            amp: 85,
+        };
+
+        let signals = circuit.public_signals_for(&outputs, &bindings).unwrap();
+        assert_eq!(signals.len(), STABLE_SWAP_PUBLIC_SIGNALS);
+        assert_eq!(signals.len(), circuit.public_signals());
+        assert_eq!(
+            &signals[..SWAP_PUBLIC_SIGNALS],
+            SwapCircuit::ConstantProduct
+                .public_signals_for(&outputs, &bindings)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(signals[SWAP_PUBLIC_SIGNALS], u64_to_field(85));
+    }
+
+    proptest! {
+        #[test]
+        fn stable_swaps_always_pass_the_program_checks(
+            reserve_x in 1_000u64..1_000_000_000_000,
+            reserve_y in 1_000u64..1_000_000_000_000,
+            amount_in in 1u64..1_000_000_000_000,
+            amp in 1u64..=MAX_AMP,
+            is_swap_x_to_y in any::<bool>(),
+        ) {
+            let outputs = model::swap_stable(&StableModelInputs {
+                amount_in,
+                min_received: 0,
+                reserve_x,
+                reserve_y,
+                is_swap_x_to_y,
+                fee_bps: 30,
+                protocol_fee_bps: 2_000,
+                amp,
+            })
+            .unwrap();
+
+            check_stable_swap_reserves(
+                reserve_x,
+                reserve_y,
+                outputs.new_balance_x as u64,
+                outputs.new_balance_y as u64,
+                outputs.amount_received as u64,
+                outputs.protocol_fee as u64,
+                is_swap_x_to_y,
+                30,
+                amp,
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn stable_quotes_match_the_circuit_model(
+            amount_in in 1..u64::MAX,
+            reserve_x in any::<u64>(),
+            reserve_y in any::<u64>(),
+            amp in 1u64..=MAX_AMP,
+            is_swap_x_to_y in any::<bool>(),
+            fee_bps in 0..=10_000u16,
+            protocol_fee_bps in 0..=10_000u16,
+        ) {
+            let pool = Pool { reserve_x, reserve_y, fee_bps, protocol_fee_bps, ..stable_pool(amp as u32) };
+            let quote = stable_swap_quote(&pool, amp, amount_in, is_swap_x_to_y);
+            let outputs = model::swap_stable(&StableModelInputs {
+                amount_in,
+                min_received: 0,
+                reserve_x,
+                reserve_y,
+                is_swap_x_to_y,
+                fee_bps,
+                protocol_fee_bps,
+                amp,
+            });
+
+            match (quote, outputs) {
+                (Ok(quote), Ok(outputs)) => {
+                    prop_assert_eq!(quote.amount_out as u128, outputs.amount_received);
+                    prop_assert_eq!(quote.protocol_fee as u128, outputs.protocol_fee);
+                    prop_assert_eq!(quote.new_reserve_x as u128, outputs.new_balance_x);
+                    prop_assert_eq!(quote.new_reserve_y as u128, outputs.new_balance_y);
+                }
+                // Only balances the pool account cannot hold separate the quote from the circuit
+                (Err(err), Ok(outputs)) => {
+                    prop_assert_eq!(err, ErrorCode::MathOverflow.into());
+                    prop_assert!(outputs.new_balance_x.max(outputs.new_balance_y) > u64::MAX as u128);
+                }
+                (Ok(_), Err(_)) => prop_assert!(false, "quoted a swap the circuit cannot prove"),
+                (Err(_), Err(_)) => {}
+            }
+        }
+    }
+}
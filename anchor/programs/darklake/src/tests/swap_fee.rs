@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use crate::constants::FEE_DENOMINATOR;
+    use crate::instructions::check_swap_reserves;
+
+    /// Mirrors the output computed by the swap circuit.
+    fn amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u16) -> u64 {
+        let amount_in_with_fee = amount_in as u128 * (FEE_DENOMINATOR - fee_bps as u64) as u128;
+        let numerator = amount_in_with_fee * reserve_out as u128;
+        let denominator = reserve_in as u128 * FEE_DENOMINATOR as u128 + amount_in_with_fee;
+        (numerator / denominator) as u64
+    }
+
+    /// Mirrors the protocol share of the fee computed by the swap circuit.
+    fn protocol_fee(amount_in: u64, fee_bps: u16, protocol_fee_bps: u16) -> u64 {
+        (amount_in as u128 * fee_bps as u128 * protocol_fee_bps as u128
+            / (FEE_DENOMINATOR as u128 * FEE_DENOMINATOR as u128)) as u64
+    }
+
+    #[test]
+    fn fee_reduces_amount_out() {
+        let without_fee = amount_out(100_000, 1_000_000, 2_000_000, 0);
+        let with_fee = amount_out(100_000, 1_000_000, 2_000_000, 30);
+
+        assert_eq!(without_fee, 181_818);
+        assert!(with_fee < without_fee);
+    }
+
+    #[test]
+    fn fee_stays_in_reserves_and_grows_the_product() {
+        let (reserve_x, reserve_y) = (1_000_000u64, 2_000_000u64);
+        let amount_in = 100_000;
+
+        for fee_bps in [0u16, 5, 30, 100, 1_000] {
+            let out = amount_out(amount_in, reserve_x, reserve_y, fee_bps);
+            let new_x = reserve_x + amount_in;
+            let new_y = reserve_y - out;
+
+            check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, 0, true, fee_bps).unwrap();
+            assert!(new_x as u128 * new_y as u128 >= reserve_x as u128 * reserve_y as u128);
+
+            let out = amount_out(amount_in, reserve_y, reserve_x, fee_bps);
+            let new_x = reserve_x - out;
+            let new_y = reserve_y + amount_in;
+
+            check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, 0, false, fee_bps).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_amount_received_not_matching_reserves() {
+        let out = amount_out(100_000, 1_000_000, 2_000_000, 30);
+
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_100_000, 2_000_000 - out, out + 1, 0, true, 30).is_err());
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_100_000, 2_000_000 - out, out - 1, 0, true, 30).is_err());
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_100_000, 2_000_001, 0, 0, true, 30).is_err());
+
+        let out = amount_out(100_000, 2_000_000, 1_000_000, 30);
+
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_000_000 - out, 2_100_000, out + 1, 0, false, 30).is_err());
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_000_000 - out, 2_100_000, out - 1, 0, false, 30).is_err());
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_000_001, 2_100_000, 0, 0, false, 30).is_err());
+    }
+
+    #[test]
+    fn rejects_reserves_that_shrink_the_product() {
+        // Paying out more than the curve allows keeps the delta consistent but breaks x * y >= k
+        let out = amount_out(100_000, 1_000_000, 2_000_000, 0) + 10;
+
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_100_000, 2_000_000 - out, out, 0, true, 0).is_err());
+
+        let out = amount_out(100_000, 2_000_000, 1_000_000, 0) + 10;
+
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_000_000 - out, 2_100_000, out, 0, false, 0).is_err());
+    }
+
+    #[test]
+    fn rejects_payouts_that_skip_the_fee() {
+        // The fee-free output keeps x * y >= k but not the invariant on the fee-adjusted input
+        let out = amount_out(100_000, 1_000_000, 2_000_000, 0);
+        assert!(1_100_000u128 * (2_000_000 - out) as u128 >= 1_000_000u128 * 2_000_000);
+
+        check_swap_reserves(1_000_000, 2_000_000, 1_100_000, 2_000_000 - out, out, 0, true, 0).unwrap();
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_100_000, 2_000_000 - out, out, 0, true, 30).is_err());
+
+        let out = amount_out(100_000, 1_000_000, 2_000_000, 30) + 1;
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 1_100_000, 2_000_000 - out, out, 0, true, 30).is_err());
+    }
+
+    #[test]
+    fn rejects_reserves_where_the_input_side_shrinks() {
+        assert!(check_swap_reserves(1_000_000, 2_000_000, 999_999, 2_000_000, 0, 0, true, 30).is_err());
+    }
+
+    #[test]
+    fn checks_the_invariant_of_pools_near_the_u64_limit() {
+        // reserve_x * reserve_y * 10000 is past u128::MAX
+        let (reserve_x, reserve_y) = (1u64 << 60, 1u64 << 60);
+        let amount_in = 1u64 << 50;
+
+        let out = amount_out(amount_in, reserve_x, reserve_y, 30);
+        let new_x = reserve_x + amount_in;
+        check_swap_reserves(reserve_x, reserve_y, new_x, reserve_y - out, out, 0, true, 30).unwrap();
+        assert!(check_swap_reserves(reserve_x, reserve_y, new_x, reserve_y - out - 1, out + 1, 0, true, 30).is_err());
+    }
+
+    #[test]
+    fn rejects_reserves_moving_against_the_direction() {
+        let (reserve_x, reserve_y) = (1_000_000u64, 2_000_000u64);
+
+        // An X to Y swap applied as Y to X and the other way around
+        let out = amount_out(100_000, reserve_x, reserve_y, 30);
+        let (new_x, new_y) = (reserve_x + 100_000, reserve_y - out);
+        check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, 0, true, 30).unwrap();
+        assert!(check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, 0, false, 30).is_err());
+
+        let out = amount_out(100_000, reserve_y, reserve_x, 30);
+        let (new_x, new_y) = (reserve_x - out, reserve_y + 100_000);
+        check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, 0, false, 30).unwrap();
+        assert!(check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, 0, true, 30).is_err());
+
+        // A swap paying nothing out cannot drain the input side either
+        assert!(check_swap_reserves(reserve_x, reserve_y, reserve_x - 1, reserve_y, 0, 0, true, 30).is_err());
+        assert!(check_swap_reserves(reserve_x, reserve_y, reserve_x, reserve_y - 1, 0, 0, false, 30).is_err());
+    }
+
+    #[test]
+    fn protocol_fee_is_kept_out_of_reserves() {
+        let (reserve_x, reserve_y) = (1_000_000u64, 2_000_000u64);
+        let amount_in = 100_000;
+
+        let out = amount_out(amount_in, reserve_x, reserve_y, 30);
+        let protocol_share = protocol_fee(amount_in, 30, 2_000);
+        assert_eq!(protocol_share, 60);
+
+        // Only the LP share of the fee is added to the input reserve
+        let new_x = reserve_x + amount_in - protocol_share;
+        let new_y = reserve_y - out;
+        check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, protocol_share, true, 30).unwrap();
+        // The input is only complete with the protocol share added back
+        assert!(check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, 0, true, 30).is_err());
+
+        // Even when the protocol takes the whole fee the product does not shrink
+        let protocol_share = protocol_fee(amount_in, 30, FEE_DENOMINATOR as u16);
+        let new_x = reserve_x + amount_in - protocol_share;
+        check_swap_reserves(reserve_x, reserve_y, new_x, new_y, out, protocol_share, true, 30).unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use crate::errors::ErrorCode;
+    use crate::tick_math::{
+        amount_x_delta, amount_y_delta, compute_swap_step, sqrt_price_at_tick,
+        tick_at_sqrt_price, MAX_SQRT_PRICE_X64, MAX_TICK, MIN_SQRT_PRICE_X64, MIN_TICK,
+    };
+    use anchor_lang::error::Error;
+    use proptest::prelude::*;
+
+    const ONE_X64: u128 = 1 << 64;
+
+    #[test]
+    fn tick_prices_follow_powers_of_one_basis_point() {
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), ONE_X64);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE_X64);
+
+        // sqrt(1.0001^i) against floating point, whose own error grows with the tick
+        for tick in [-200_000, -6_932, -1, 1, 100, 6_932, 200_000] {
+            let expected = 1.0001f64.powf(tick as f64 / 2.0) * ONE_X64 as f64;
+            let actual = sqrt_price_at_tick(tick).unwrap() as f64;
+            assert!((actual - expected).abs() / expected < 1e-10, "tick {tick}");
+        }
+
+        assert_eq!(
+            sqrt_price_at_tick(MAX_TICK + 1).unwrap_err(),
+            Error::from(ErrorCode::InvalidTick)
+        );
+        assert_eq!(
+            tick_at_sqrt_price(MIN_SQRT_PRICE_X64 - 1).unwrap_err(),
+            Error::from(ErrorCode::InvalidSqrtPrice)
+        );
+    }
+
+    #[test]
+    fn amounts_between_prices_round_for_the_pool() {
+        let (lower, upper) = (sqrt_price_at_tick(-100).unwrap(), sqrt_price_at_tick(100).unwrap());
+        let liquidity = 1_000_000_000;
+
+        // liquidity * (1.0001^50 - 1.0001^-50) = 9_999_541.69 on both sides at price 1
+        let x_up = amount_x_delta(lower, upper, liquidity, true).unwrap();
+        let x_down = amount_x_delta(upper, lower, liquidity, false).unwrap();
+        assert_eq!(x_up, x_down + 1);
+        assert_eq!(x_down, 9_999_541);
+        let y_up = amount_y_delta(lower, upper, liquidity, true).unwrap();
+        assert_eq!(y_up, x_up);
+    }
+
+    #[test]
+    fn swap_steps_stop_at_the_target_or_use_the_whole_amount() {
+        let price = ONE_X64;
+        let target = sqrt_price_at_tick(-10).unwrap();
+        let liquidity = 1_000_000_000;
+
+        // Not enough to reach the target: all of it is used, the rest of it is fee
+        let step = compute_swap_step(price, target, liquidity, 10_000, 30).unwrap();
+        assert!(step.sqrt_price_next_x64 > target && step.sqrt_price_next_x64 < price);
+        assert_eq!(step.amount_in + step.fee_amount, 10_000);
+        assert_eq!(step.fee_amount, 30);
+        assert_eq!(step.amount_out, 9_969);
+
+        // More than enough: the step ends on the target and leaves the rest
+        let step = compute_swap_step(price, target, liquidity, 1_000_000, 30).unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert_eq!(step.amount_in, amount_x_delta(target, price, liquidity, true).unwrap());
+        assert!(step.amount_in + step.fee_amount < 1_000_000);
+
+        assert_eq!(
+            compute_swap_step(price, target, liquidity, 10_000, 10_000).unwrap_err(),
+            Error::from(ErrorCode::InvalidFee)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn tick_and_price_round_trip(tick in MIN_TICK..=MAX_TICK) {
+            let price = sqrt_price_at_tick(tick).unwrap();
+            prop_assert_eq!(tick_at_sqrt_price(price).unwrap(), tick);
+            if tick < MAX_TICK {
+                let next = sqrt_price_at_tick(tick + 1).unwrap();
+                prop_assert!(next > price);
+                prop_assert_eq!(tick_at_sqrt_price(next - 1).unwrap(), tick);
+            }
+        }
+
+        #[test]
+        fn swap_steps_never_pay_out_more_than_they_take(
+            tick in -50_000i32..50_000,
+            distance in 1i32..5_000,
+            zero_for_one in any::<bool>(),
+            liquidity in 1u128..1u128 << 80,
+            amount in 1u128..1u128 << 64,
+            fee_bps in 0u16..=1_000,
+        ) {
+            let price = sqrt_price_at_tick(tick).unwrap();
+            let target_tick = if zero_for_one { tick - distance } else { tick + distance };
+            let target = sqrt_price_at_tick(target_tick).unwrap();
+
+            let step = compute_swap_step(price, target, liquidity, amount, fee_bps).unwrap();
+            prop_assert!(step.amount_in + step.fee_amount <= amount);
+
+            // Whatever leaves the pool is covered by what the price move holds, rounded down
+            let next = step.sqrt_price_next_x64;
+            if zero_for_one {
+                prop_assert!(next >= target && next <= price);
+                prop_assert!(step.amount_out <= amount_y_delta(next, price, liquidity, false).unwrap());
+            } else {
+                prop_assert!(next <= target && next >= price);
+                prop_assert!(step.amount_out <= amount_x_delta(price, next, liquidity, false).unwrap());
+            }
+        }
+    }
+}
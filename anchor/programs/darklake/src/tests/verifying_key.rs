@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use crate::circuits::{CIRCUIT_CONSTANT_PRODUCT, SWAP_PUBLIC_SIGNALS};
+    use crate::state::{verifying_key_data, VerifyingKey, VERIFYING_KEY_HEADER_LEN};
+    use crate::tests::verifying_key_fixture::VERIFYINGKEY;
+    use anchor_lang::solana_program::hash::hash;
+    use anchor_lang::AccountSerialize;
+    use groth16_solana::groth16::Groth16Verifier;
+    use serde_json::Value;
+    use std::convert::TryInto;
+
+    fn fixture_account() -> VerifyingKey {
+        let data = verifying_key_data(&VERIFYINGKEY);
+        VerifyingKey {
+            version: 1,
+            circuit_id: CIRCUIT_CONSTANT_PRODUCT,
+            nr_pubinputs: (VERIFYINGKEY.vk_ic.len() - 1) as u8,
+            hash: hash(&data).to_bytes(),
+            finalized: true,
+            bump: 255,
+            data,
+        }
+    }
+
+    #[test]
+    fn key_data_round_trips() {
+        let account = fixture_account();
+        assert_eq!(account.data.len(), VerifyingKey::data_len(account.nr_pubinputs));
+        assert_eq!(account.data.len(), VERIFYING_KEY_HEADER_LEN + VERIFYINGKEY.vk_ic.len() * 64);
+
+        let vk_ic = account.ic();
+        let key = account.groth16(&vk_ic);
+        assert_eq!(key.vk_alpha_g1, VERIFYINGKEY.vk_alpha_g1);
+        assert_eq!(key.vk_beta_g2, VERIFYINGKEY.vk_beta_g2);
+        assert_eq!(key.vk_gamme_g2, VERIFYINGKEY.vk_gamme_g2);
+        assert_eq!(key.vk_delta_g2, VERIFYINGKEY.vk_delta_g2);
+        assert_eq!(key.vk_ic, VERIFYINGKEY.vk_ic);
+    }
+
+    #[test]
+    fn space_fits_the_serialized_account() {
+        let account = fixture_account();
+        let mut serialized = Vec::new();
+        account.try_serialize(&mut serialized).unwrap();
+
+        assert_eq!(serialized.len(), VerifyingKey::space(account.nr_pubinputs));
+    }
+
+    #[test]
+    fn proof_verifies_against_the_stored_key() {
+        let contents = std::fs::read_to_string("./src/tests/zk_proof_output.json")
+            .expect("Failed to read JSON file");
+        let json: Value = serde_json::from_str(&contents).expect("Failed to parse JSON");
+        let bytes = |value: &Value| -> Vec<u8> {
+            value.as_array().expect("value is not an array")
+                .iter().map(|v| v.as_u64().expect("value is not a u64") as u8)
+                .collect()
+        };
+
+        let proof_a: [u8; 64] = bytes(&json["pi_a"]).try_into().unwrap();
+        let proof_b: [u8; 128] = bytes(&json["pi_b"]).try_into().unwrap();
+        let proof_c: [u8; 64] = bytes(&json["pi_c"]).try_into().unwrap();
+        let public_inputs: [[u8; 32]; SWAP_PUBLIC_SIGNALS] = json["publicInputs"].as_array().unwrap()
+            .iter()
+            .map(|v| bytes(v).try_into().unwrap())
+            .collect::<Vec<[u8; 32]>>()
+            .try_into()
+            .unwrap();
+
+        let account = fixture_account();
+        let vk_ic = account.ic();
+        let key = account.groth16(&vk_ic);
+        let mut verifier =
+            Groth16Verifier::new(&proof_a, &proof_b, &proof_c, &public_inputs, &key).unwrap();
+        assert!(verifier.verify().unwrap());
+
+        // A key written with a corrupted chunk no longer matches its hash
+        let mut corrupted = account.data.clone();
+        corrupted[VERIFYING_KEY_HEADER_LEN] ^= 1;
+        assert_ne!(hash(&corrupted).to_bytes(), account.hash);
+    }
+
+    #[test]
+    fn fixture_matches_the_checked_in_verification_key() {
+        let key = snarkjs_convert::parse_verifying_key(include_str!(
+            "../../../../../circuits/verification_key.json"
+        ))
+        .unwrap();
+
+        assert_eq!(key.to_bytes(), verifying_key_data(&VERIFYINGKEY));
+    }
+}
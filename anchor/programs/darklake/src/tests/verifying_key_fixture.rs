@@ -0,0 +1,88 @@
+//! Key of the circuit `zk_proof_output.json` was generated with.
+
+use groth16_solana::groth16::Groth16Verifyingkey;
+
+pub const VERIFYINGKEY: Groth16Verifyingkey =  Groth16Verifyingkey {
+	nr_pubinputs: 12,
+
+	vk_alpha_g1: [
+		0,66,39,232,219,138,62,156,193,49,165,102,189,102,187,164,71,153,141,77,3,114,181,230,99,97,58,83,189,22,219,54,
+		25,198,107,84,166,96,25,153,39,93,247,111,136,96,134,138,95,17,240,215,207,225,125,216,197,152,39,162,202,226,97,225,
+	],
+
+	vk_beta_g2: [
+		30,17,60,23,211,123,120,112,220,36,66,227,189,20,63,148,201,220,151,179,236,79,12,212,225,85,235,220,175,62,64,187,
+		8,45,112,84,101,193,6,107,177,120,243,159,101,128,160,161,195,61,57,222,48,108,188,124,188,38,197,70,215,67,134,173,
+		19,137,180,80,49,122,111,44,154,172,250,230,43,62,119,24,212,67,226,179,167,5,100,8,45,42,149,58,34,98,81,14,
+		17,90,65,168,132,56,221,114,65,251,217,133,30,65,121,194,182,6,140,117,76,76,117,143,166,17,232,90,39,180,235,186,
+	],
+
+	vk_gamme_g2: [
+		25,142,147,147,146,13,72,58,114,96,191,183,49,251,93,37,241,170,73,51,53,169,231,18,151,228,133,183,174,243,18,194,
+		24,0,222,239,18,31,30,118,66,106,0,102,94,92,68,121,103,67,34,212,247,94,218,221,70,222,189,92,217,146,246,237,
+		9,6,137,208,88,95,240,117,236,158,153,173,105,12,51,149,188,75,49,51,112,179,142,243,85,172,218,220,209,34,151,91,
+		18,200,94,165,219,140,109,235,74,171,113,128,141,203,64,143,227,209,231,105,12,67,211,123,76,230,204,1,102,250,125,170,
+	],
+
+	vk_delta_g2: [
+		2,66,29,109,43,37,132,240,26,147,171,136,11,29,240,48,114,104,255,49,97,0,228,129,18,14,38,226,254,189,178,3,
+		9,182,227,99,128,202,139,46,43,173,63,45,76,89,239,73,2,231,170,56,94,252,240,136,197,176,212,156,70,91,5,189,
+		30,18,135,17,26,150,159,149,24,163,74,123,1,202,131,85,71,143,41,255,119,63,77,139,74,243,18,111,221,129,31,138,
+		4,29,157,245,242,101,109,252,57,107,240,219,76,202,188,231,137,149,126,89,72,64,87,122,14,83,103,149,157,143,156,41,
+	],
+
+	vk_ic: &[
+		[
+			4,102,165,100,147,37,104,64,25,107,123,154,99,1,169,110,49,103,174,69,6,56,25,242,217,53,47,6,226,160,46,135,
+			13,203,172,6,98,153,59,19,189,91,55,249,189,185,116,119,220,96,180,168,219,96,235,113,143,173,251,191,1,193,134,220,
+		],
+		[
+			9,186,143,99,104,170,253,225,11,147,222,225,189,130,68,32,189,155,134,122,119,186,24,20,65,0,35,83,70,160,180,91,
+			40,172,63,198,40,99,62,217,244,78,121,56,189,158,69,208,247,169,112,113,95,85,126,23,62,83,220,17,108,82,198,134,
+		],
+		[
+			3,211,48,12,42,216,116,47,218,4,255,182,29,76,91,254,117,62,168,14,95,15,162,72,91,186,138,55,154,220,166,254,
+			39,93,149,166,114,32,161,147,142,138,240,58,146,121,129,145,9,136,114,16,180,123,57,44,72,47,36,232,155,246,254,8,
+		],
+		[
+			30,254,142,84,26,224,55,14,244,63,64,129,225,200,55,10,91,20,195,126,166,87,11,111,130,120,68,245,221,53,192,217,
+			5,181,36,157,126,98,19,195,209,90,107,149,95,147,219,173,125,210,99,135,104,167,26,211,163,152,176,249,30,119,86,176,
+		],
+		[
+			37,15,118,72,8,59,159,99,17,137,2,86,227,174,107,123,152,209,68,91,173,22,31,90,103,118,114,101,215,140,170,189,
+			5,166,241,212,177,60,187,162,8,217,89,100,128,38,138,212,32,158,93,223,9,118,75,195,197,176,154,248,195,145,213,176,
+		],
+		[
+			26,71,15,167,72,66,171,79,197,52,86,213,240,181,200,11,236,103,144,12,74,30,46,105,247,74,243,24,144,104,159,157,
+			11,207,234,173,173,28,53,161,146,58,159,227,25,147,143,185,127,23,14,123,25,14,229,4,158,40,184,12,137,239,7,27,
+		],
+		[
+			24,40,241,152,0,118,66,101,155,46,196,20,45,23,26,169,126,55,94,102,142,86,29,155,215,101,65,7,98,10,148,220,
+			20,209,205,120,204,30,245,252,26,201,56,205,131,148,155,140,240,231,190,116,129,65,87,33,141,242,212,196,138,213,113,177,
+		],
+		[
+			35,62,236,222,192,117,60,114,232,43,74,97,177,10,249,64,118,18,122,90,153,207,12,134,86,198,217,236,83,72,187,161,
+			16,89,138,202,155,236,28,116,34,143,9,95,84,186,238,248,94,194,69,168,56,104,35,255,124,178,45,152,88,80,86,40,
+		],
+		[
+			34,84,139,95,76,40,43,173,194,80,88,147,80,211,251,163,179,114,19,45,188,229,91,26,230,16,211,182,239,144,7,57,
+			14,44,175,184,109,24,166,211,121,169,219,158,26,88,215,200,154,92,187,2,215,44,202,95,169,206,165,91,220,100,125,37,
+		],
+		[
+			38,35,208,190,250,100,127,168,110,231,160,123,157,132,8,195,207,27,166,123,192,246,96,139,5,246,217,248,153,18,28,40,
+			5,215,54,91,208,207,206,161,193,137,40,128,21,121,133,208,20,165,135,27,239,36,143,172,177,224,246,84,196,68,250,157,
+		],
+		[
+			7,47,146,175,101,212,109,28,210,0,115,9,196,254,70,111,177,140,137,73,92,99,248,135,191,183,20,111,220,10,118,220,
+			16,69,250,113,134,29,187,48,114,85,254,1,105,130,208,204,130,91,254,196,96,74,153,192,13,92,229,189,41,160,168,184,
+		],
+		[
+			8,207,159,34,58,129,208,253,90,188,33,31,42,129,110,249,149,55,202,6,46,100,102,11,5,168,67,3,178,79,157,227,
+			37,191,71,123,45,20,219,204,145,130,230,66,63,192,52,110,140,126,229,231,245,59,255,240,155,235,197,207,47,110,168,141,
+		],
+		[
+			38,60,180,172,106,48,138,46,239,33,56,37,95,13,140,42,180,204,18,37,91,177,112,97,41,149,243,216,112,155,95,207,
+			26,97,123,49,85,162,242,33,154,218,33,187,147,236,138,251,123,221,176,180,118,162,92,214,240,111,104,83,205,124,86,249,
+		],
+	]
+};
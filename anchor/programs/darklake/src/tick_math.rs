@@ -0,0 +1,297 @@
+//! Price and amount math of concentrated liquidity pools.
+//!
+//! Prices are square roots of `y / x` in Q64.64 fixed point. Tick `i` is the price
+//! `1.0001^i`, so its square root is `1.0001^(i / 2)`. Amounts are rounded in favour of the
+//! pool: up for what goes in, down for what comes out.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::FEE_DENOMINATOR;
+use crate::errors::ErrorCode;
+
+// Kept out of the Anchor prelude, whose `Result` the macro would pick up
+#[allow(clippy::assign_op_pattern, clippy::manual_div_ceil)]
+mod u256 {
+    uint::construct_uint! {
+        /// Unsigned 256-bit integer, for products of Q64.64 prices and liquidity.
+        pub struct U256(4);
+    }
+}
+
+pub use u256::U256;
+
+/// Lowest tick, where the square root price is about `2^-32`.
+pub const MIN_TICK: i32 = -443_636;
+
+/// Highest tick, where the square root price is about `2^32`.
+pub const MAX_TICK: i32 = 443_636;
+
+/// Square root price at `MIN_TICK`.
+pub const MIN_SQRT_PRICE_X64: u128 = 4_295_048_017;
+
+/// Square root price at `MAX_TICK`.
+pub const MAX_SQRT_PRICE_X64: u128 = 79_226_673_515_401_279_992_447_579_062;
+
+/// `1 / sqrt(1.0001)^(2^i)` in Q128.128 for every bit `i` of a tick. Q64.64 ratios would lose
+/// about 1e-10 of the price at the ends of the tick range.
+const TICK_BIT_RATIOS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x09aa508b5b7a84e1c677de54f3e99bc9,
+    0x005d6af8dedb81196699c329225ee604,
+    0x00002216e584f5fa1ea926041bedfe98,
+    0x00000000048a170391f7dc42444e8fa2,
+];
+
+/// Square root price of `tick` in Q64.64, rounded up.
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(ErrorCode::InvalidTick.into());
+    }
+
+    // Multiply the ratios of the set bits of |tick|, all below 1 so nothing overflows, then
+    // invert for positive ticks
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = U256::one() << 128;
+    for (bit, bit_ratio) in TICK_BIT_RATIOS.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(*bit_ratio)) >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    let rounding = !(ratio & U256::from(u64::MAX)).is_zero();
+    to_u128((ratio >> 64) + U256::from(rounding as u8))
+}
+
+/// Highest tick whose square root price is at most `sqrt_price_x64`.
+pub fn tick_at_sqrt_price(sqrt_price_x64: u128) -> Result<i32> {
+    if !(MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64) {
+        return Err(ErrorCode::InvalidSqrtPrice.into());
+    }
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price_x64 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    if value > U256::from(u128::MAX) {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+    Ok(value.as_u128())
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> Result<U256> {
+    if denominator.is_zero() {
+        return Err(ErrorCode::MathOverflow.into());
+    }
+    let product = a.checked_mul(b).ok_or(ErrorCode::MathOverflow)?;
+    let (quotient, remainder) = product.div_mod(denominator);
+    if round_up && !remainder.is_zero() {
+        return Ok(quotient + 1);
+    }
+    Ok(quotient)
+}
+
+/// Amount of x held by `liquidity` between two square root prices:
+/// `liquidity * (upper - lower) / (lower * upper)`.
+pub fn amount_x_delta(
+    sqrt_price_a_x64: u128,
+    sqrt_price_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    let (lower, upper) = if sqrt_price_a_x64 <= sqrt_price_b_x64 {
+        (sqrt_price_a_x64, sqrt_price_b_x64)
+    } else {
+        (sqrt_price_b_x64, sqrt_price_a_x64)
+    };
+    if lower == 0 {
+        return Err(ErrorCode::InvalidSqrtPrice.into());
+    }
+
+    let numerator = U256::from(liquidity) << 64;
+    let scaled = mul_div(numerator, U256::from(upper - lower), U256::from(upper), round_up)?;
+    to_u128(mul_div(scaled, U256::one(), U256::from(lower), round_up)?)
+}
+
+/// Amount of y held by `liquidity` between two square root prices:
+/// `liquidity * (upper - lower)`.
+pub fn amount_y_delta(
+    sqrt_price_a_x64: u128,
+    sqrt_price_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    let difference = sqrt_price_a_x64.abs_diff(sqrt_price_b_x64);
+    to_u128(mul_div(
+        U256::from(liquidity),
+        U256::from(difference),
+        U256::one() << 64,
+        round_up,
+    )?)
+}
+
+/// Square root price after `amount` goes into `liquidity` at `sqrt_price_x64`. x going in
+/// lowers the price, `liquidity * price / (liquidity + amount * price)` rounded up, and y
+/// raises it, `price + amount / liquidity` rounded down, so the pool never gives out more.
+pub fn next_sqrt_price_from_input(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount: u128,
+    is_swap_x_to_y: bool,
+) -> Result<u128> {
+    if liquidity == 0 {
+        return Err(ErrorCode::InsufficientLiquidity.into());
+    }
+    if amount == 0 {
+        return Ok(sqrt_price_x64);
+    }
+
+    let liquidity_x64 = U256::from(liquidity) << 64;
+    if is_swap_x_to_y {
+        let denominator = liquidity_x64
+            .checked_add(
+                U256::from(amount)
+                    .checked_mul(U256::from(sqrt_price_x64))
+                    .ok_or(ErrorCode::MathOverflow)?,
+            )
+            .ok_or(ErrorCode::MathOverflow)?;
+        to_u128(mul_div(liquidity_x64, U256::from(sqrt_price_x64), denominator, true)?)
+    } else {
+        let increase = to_u128((U256::from(amount) << 64) / U256::from(liquidity))?;
+        sqrt_price_x64
+            .checked_add(increase)
+            .ok_or(ErrorCode::MathOverflow.into())
+    }
+}
+
+/// Amounts of x and y `liquidity` holds between two square root prices, when the pool is at
+/// `sqrt_price_x64`.
+pub fn amounts_for_liquidity(
+    sqrt_price_x64: u128,
+    sqrt_price_lower_x64: u128,
+    sqrt_price_upper_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u128, u128)> {
+    if sqrt_price_x64 < sqrt_price_lower_x64 {
+        // Below the range, the position only holds x
+        let amount_x =
+            amount_x_delta(sqrt_price_lower_x64, sqrt_price_upper_x64, liquidity, round_up)?;
+        Ok((amount_x, 0))
+    } else if sqrt_price_x64 < sqrt_price_upper_x64 {
+        let amount_x = amount_x_delta(sqrt_price_x64, sqrt_price_upper_x64, liquidity, round_up)?;
+        let amount_y = amount_y_delta(sqrt_price_lower_x64, sqrt_price_x64, liquidity, round_up)?;
+        Ok((amount_x, amount_y))
+    } else {
+        // Above the range, the position only holds y
+        let amount_y =
+            amount_y_delta(sqrt_price_lower_x64, sqrt_price_upper_x64, liquidity, round_up)?;
+        Ok((0, amount_y))
+    }
+}
+
+/// One step of a swap, within a price range of constant liquidity.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapStep {
+    pub sqrt_price_next_x64: u128,
+    /// Input used by the step, fee excluded.
+    pub amount_in: u128,
+    pub amount_out: u128,
+    /// Fee charged on the step input.
+    pub fee_amount: u128,
+}
+
+/// Swaps up to `amount_remaining`, fee included, from `sqrt_price_x64` towards
+/// `sqrt_price_target_x64`, stopping at the target when the amount is enough to reach it.
+pub fn compute_swap_step(
+    sqrt_price_x64: u128,
+    sqrt_price_target_x64: u128,
+    liquidity: u128,
+    amount_remaining: u128,
+    fee_bps: u16,
+) -> Result<SwapStep> {
+    if fee_bps as u64 >= FEE_DENOMINATOR {
+        return Err(ErrorCode::InvalidFee.into());
+    }
+    let is_swap_x_to_y = sqrt_price_target_x64 <= sqrt_price_x64;
+    let fee_complement = (FEE_DENOMINATOR - fee_bps as u64) as u128;
+    let amount_less_fee = to_u128(mul_div(
+        U256::from(amount_remaining),
+        U256::from(fee_complement),
+        U256::from(FEE_DENOMINATOR),
+        false,
+    )?)?;
+
+    let amount_to_target = if is_swap_x_to_y {
+        amount_x_delta(sqrt_price_target_x64, sqrt_price_x64, liquidity, true)?
+    } else {
+        amount_y_delta(sqrt_price_x64, sqrt_price_target_x64, liquidity, true)?
+    };
+
+    let reaches_target = amount_less_fee >= amount_to_target;
+    let sqrt_price_next_x64 = if reaches_target {
+        sqrt_price_target_x64
+    } else {
+        next_sqrt_price_from_input(sqrt_price_x64, liquidity, amount_less_fee, is_swap_x_to_y)?
+    };
+
+    let (amount_in, amount_out) = if is_swap_x_to_y {
+        (
+            amount_x_delta(sqrt_price_next_x64, sqrt_price_x64, liquidity, true)?,
+            amount_y_delta(sqrt_price_next_x64, sqrt_price_x64, liquidity, false)?,
+        )
+    } else {
+        (
+            amount_y_delta(sqrt_price_x64, sqrt_price_next_x64, liquidity, true)?,
+            amount_x_delta(sqrt_price_x64, sqrt_price_next_x64, liquidity, false)?,
+        )
+    };
+
+    // Short of the target, the whole remaining amount is used and the rest of it is fee
+    let fee_amount = if reaches_target {
+        to_u128(mul_div(
+            U256::from(amount_in),
+            U256::from(fee_bps),
+            U256::from(fee_complement),
+            true,
+        )?)?
+    } else {
+        amount_remaining
+            .checked_sub(amount_in)
+            .ok_or(ErrorCode::MathOverflow)?
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next_x64,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
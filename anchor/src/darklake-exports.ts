@@ -29,3 +29,107 @@ export function getDarklakeProgramId(cluster: Cluster | 'localnet') {
       return DARKLAKE_PROGRAM_ID;
   }
 }
+
+// PDA marking a swap proof nullifier (the 5th swap public signal) as spent.
+export function getNullifierAddress(programId: PublicKey, nullifier: Uint8Array) {
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from('nullifier'), nullifier],
+    programId
+  );
+  return address;
+}
+
+// Swap circuit ids, see `SwapCircuit` in the program.
+export const CIRCUIT_CONSTANT_PRODUCT = 0;
+export const CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT = 1;
+export const CIRCUIT_CONSTANT_PRODUCT_ROUTE = 2;
+export const CIRCUIT_RANGE_SWAP = 3;
+export const CIRCUIT_STABLE_SWAP = 4;
+
+// Pool invariants, the `curve` argument of `initializePool`.
+export const CURVE_CONSTANT_PRODUCT = 0;
+export const CURVE_STABLE_SWAP = 1;
+
+// Pools swapped through by a route proof, and the remaining accounts
+// `confidentialRouteSwap` takes for each of them: the pool, its token accounts
+// for the mints it takes in and pays out, the mint it pays out and its token
+// program.
+export const ROUTE_HOPS = 2;
+export const ROUTE_ACCOUNTS_PER_HOP = 5;
+
+// Ticks held by each tick array of a concentrated liquidity pool.
+export const TICK_ARRAY_SIZE = 16;
+
+// PDA of the tick array of `pool` starting at `startTickIndex`.
+export function getTickArrayAddress(
+  programId: PublicKey,
+  pool: PublicKey,
+  startTickIndex: number
+) {
+  const seed = Buffer.alloc(4);
+  seed.writeInt32LE(startTickIndex);
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from('tick_array'), pool.toBuffer(), seed],
+    programId
+  );
+  return address;
+}
+
+// PDA of the position of `owner` in `pool` between two ticks.
+export function getPositionAddress(
+  programId: PublicKey,
+  pool: PublicKey,
+  owner: PublicKey,
+  tickLower: number,
+  tickUpper: number
+) {
+  const lower = Buffer.alloc(4);
+  lower.writeInt32LE(tickLower);
+  const upper = Buffer.alloc(4);
+  upper.writeInt32LE(tickUpper);
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from('position'), pool.toBuffer(), owner.toBuffer(), lower, upper],
+    programId
+  );
+  return address;
+}
+
+// PDA of the NFT mint of a position, see `mintPositionNft`. Whoever holds the
+// NFT owns the position.
+export function getPositionMintAddress(programId: PublicKey, position: PublicKey) {
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from('position_mint'), position.toBuffer()],
+    programId
+  );
+  return address;
+}
+
+// PDA holding version `version` of a swap circuit verifying key.
+export function getVerifyingKeyAddress(programId: PublicKey, version: number) {
+  const seed = Buffer.alloc(4);
+  seed.writeUInt32LE(version);
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from('verifying_key'), seed],
+    programId
+  );
+  return address;
+}
+
+// How long a swap proof stays usable, about a minute at 400ms slots.
+export const PROOF_VALIDITY_SLOTS = 150;
+
+// Field-encoded sha256(user || destination), the swap public input binding a proof to
+// the signer and the token account receiving the output.
+export async function getUserHash(user: PublicKey, destination: PublicKey) {
+  const digest = new Uint8Array(
+    await crypto.subtle.digest(
+      'SHA-256',
+      Buffer.concat([user.toBuffer(), destination.toBuffer()])
+    )
+  );
+  // Clear the top byte so the hash fits in the BN254 scalar field
+  digest[0] = 0;
+  return BigInt(
+    '0x' + Array.from(digest, (b) => b.toString(16).padStart(2, '0')).join('')
+  );
+}
@@ -27,3 +27,24 @@ export function g2Uncompressed(curve: any, p2Raw: any): Uint8Array {
   curve.G2.toRprUncompressed(buff, 0, p2);
   return buff;
 }
+
+// Random value below the BN254 scalar field, used as the swap nullifier secret.
+export function randomFieldElement(): bigint {
+  const bytes = new Uint8Array(31);
+  crypto.getRandomValues(bytes);
+  return BigInt(
+    '0x' + Array.from(bytes, (b) => b.toString(16).padStart(2, '0')).join('')
+  );
+}
+
+// Verifying key in the layout stored by the program: alpha, beta, gamma, delta, then
+// the IC points, from a snarkjs verification_key.json.
+export function verifyingKeyData(curve: any, vk: any): Uint8Array {
+  return Buffer.concat([
+    g1Uncompressed(curve, vk.vk_alpha_1),
+    g2Uncompressed(curve, vk.vk_beta_2),
+    g2Uncompressed(curve, vk.vk_gamma_2),
+    g2Uncompressed(curve, vk.vk_delta_2),
+    ...vk.IC.map((point: any) => g1Uncompressed(curve, point)),
+  ]);
+}
@@ -31,9 +31,14 @@ describe('ZK Constant Sum AMM Swap', () => {
     const input = {
       privateInputAmount: 100,
       privateMinReceived: 1,
+      privateNullifierSecret: 1,
       publicBalanceX: 1000,
       publicBalanceY: 1000,
       isSwapXtoY: 1,
+      feeRate: 0,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
     };
 
     console.log('Initial state:', input);
@@ -75,9 +80,14 @@ describe('ZK Constant Sum AMM Swap', () => {
     const input = {
       privateInputAmount: 100000,
       privateMinReceived: 99000,
+      privateNullifierSecret: 1,
       publicBalanceX: 1100000,
       publicBalanceY: 1900000,
       isSwapXtoY: 1,
+      feeRate: 0,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
     };
 
     const witness = await circuit.calculateWitness(input);
@@ -114,9 +124,14 @@ describe('ZK Constant Sum AMM Swap', () => {
     const input = {
       privateInputAmount: 100,
       privateMinReceived: 90,
+      privateNullifierSecret: 1,
       publicBalanceX: 1000,
       publicBalanceY: 1000,
       isSwapXtoY: 0,
+      feeRate: 0,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
     };
 
     const witness = await circuit.calculateWitness(input);
@@ -147,6 +162,124 @@ describe('ZK Constant Sum AMM Swap', () => {
       true
     );
   });
+
+  it('should charge the fee on the input and keep it in the reserves', async () => {
+    const input = {
+      privateInputAmount: 100000,
+      privateMinReceived: 1,
+      privateNullifierSecret: 1,
+      publicBalanceX: 1000000,
+      publicBalanceY: 1000000,
+      isSwapXtoY: 1,
+      feeRate: 30,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
+    };
+
+    const witness = await circuit.calculateWitness(input);
+    await circuit.checkConstraints(witness);
+
+    await circuit.loadSymbols();
+
+    const newBalanceX = circuit.symbols['main.newBalanceX'];
+    const newBalanceY = circuit.symbols['main.newBalanceY'];
+    const amountReceived = circuit.symbols['main.amountReceived'];
+
+    // out = 100000 * 9970 * 1000000 / (1000000 * 10000 + 100000 * 9970)
+    expect(BigInt(witness[amountReceived.varIdx])).toBe(90661n);
+    // The whole input, fee included, is added to the input reserve
+    expect(BigInt(witness[newBalanceX.varIdx])).toBe(1100000n);
+    expect(BigInt(witness[newBalanceY.varIdx])).toBe(909339n);
+    expect(
+      BigInt(witness[newBalanceX.varIdx]) * BigInt(witness[newBalanceY.varIdx])
+    ).toBeGreaterThan(1000000n * 1000000n);
+  });
+
+  it('should keep the protocol fee out of the reserves', async () => {
+    const input = {
+      privateInputAmount: 100000,
+      privateMinReceived: 1,
+      privateNullifierSecret: 1,
+      publicBalanceX: 1000000,
+      publicBalanceY: 1000000,
+      isSwapXtoY: 1,
+      feeRate: 30,
+      protocolFeeRate: 2000,
+      userHash: 0,
+      expirySlot: 0,
+    };
+
+    const witness = await circuit.calculateWitness(input);
+    await circuit.checkConstraints(witness);
+
+    await circuit.loadSymbols();
+
+    const newBalanceX = circuit.symbols['main.newBalanceX'];
+    const newBalanceY = circuit.symbols['main.newBalanceY'];
+    const amountReceived = circuit.symbols['main.amountReceived'];
+    const protocolFee = circuit.symbols['main.protocolFee'];
+
+    // protocolFee = 100000 * 30 * 2000 / 10000^2
+    expect(BigInt(witness[protocolFee.varIdx])).toBe(60n);
+    // The protocol share does not change what the user receives
+    expect(BigInt(witness[amountReceived.varIdx])).toBe(90661n);
+    expect(BigInt(witness[newBalanceX.varIdx])).toBe(1099940n);
+    expect(BigInt(witness[newBalanceY.varIdx])).toBe(909339n);
+  });
+
+  it('should derive the nullifier from the secret only', async () => {
+    const input = {
+      privateInputAmount: 100,
+      privateMinReceived: 1,
+      privateNullifierSecret: 42,
+      publicBalanceX: 1000,
+      publicBalanceY: 1000,
+      isSwapXtoY: 1,
+      feeRate: 30,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
+    };
+
+    await circuit.loadSymbols();
+    const nullifier = circuit.symbols['main.nullifier'];
+
+    const witness = await circuit.calculateWitness(input);
+    await circuit.checkConstraints(witness);
+
+    // Same secret against other reserves, same nullifier
+    const movedWitness = await circuit.calculateWitness({
+      ...input,
+      publicBalanceX: 2000,
+    });
+    expect(movedWitness[nullifier.varIdx]).toEqual(witness[nullifier.varIdx]);
+
+    const freshWitness = await circuit.calculateWitness({
+      ...input,
+      privateNullifierSecret: 43,
+    });
+    expect(freshWitness[nullifier.varIdx]).not.toEqual(
+      witness[nullifier.varIdx]
+    );
+  });
+
+  it('should reject a fee above 100%', async () => {
+    const input = {
+      privateInputAmount: 100,
+      privateMinReceived: 0,
+      privateNullifierSecret: 1,
+      publicBalanceX: 1000,
+      publicBalanceY: 1000,
+      isSwapXtoY: 1,
+      feeRate: 10001,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
+    };
+
+    await expect(circuit.calculateWitness(input)).rejects.toThrow();
+  });
 });
 
 describe('ReciprocalDivision', () => {
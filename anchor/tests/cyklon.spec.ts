@@ -6,10 +6,22 @@ import {
   mintTo,
   getAccount,
   getOrCreateAssociatedTokenAccount,
+  getAssociatedTokenAddressSync,
   TOKEN_PROGRAM_ID,
   TOKEN_2022_PROGRAM_ID,
 } from '@solana/spl-token';
+import { createHash } from 'crypto';
 import { generateProof } from './proof';
+import {
+  CIRCUIT_CONSTANT_PRODUCT,
+  CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT,
+  getNullifierAddress,
+  getUserHash,
+  getVerifyingKeyAddress,
+  PROOF_VALIDITY_SLOTS,
+  ROUTE_ACCOUNTS_PER_HOP,
+} from '../src/darklake-exports';
+import { loadVerifyingKey, uploadVerifyingKey } from './verifying-key';
 
 const convertToSigner = (wallet: anchor.Wallet): anchor.web3.Signer => ({
   publicKey: wallet.publicKey,
@@ -30,6 +42,37 @@ describe('darklake', () => {
   const tokenMint1Decimals = 9; // Updated to 9 decimals
   let tokenXProgramId: anchor.web3.PublicKey;
   let tokenYProgramId: anchor.web3.PublicKey;
+  const poolFeeBps = 30;
+  const treasury = anchor.web3.Keypair.generate().publicKey;
+
+  const [configPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('config')],
+    program.programId
+  );
+  const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey('BPFLoaderUpgradeab1e11111111111111111111111')
+  );
+
+  const airdrop = async (publicKey: anchor.web3.PublicKey) => {
+    const airdropSignature = await provider.connection.requestAirdrop(
+      publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSignature);
+  };
+
+  const parseEvents = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new anchor.EventParser(
+      program.programId,
+      new anchor.BorshCoder(program.idl)
+    );
+    return Array.from(eventParser.parseLogs(tx?.meta?.logMessages ?? []));
+  };
 
   const setupMint = async () => {
     const airdropSignature = await provider.connection.requestAirdrop(
@@ -89,7 +132,7 @@ Token Y: ${tokenY.toBase58()}`
   const setupPool = async () => {
     try {
       await program.methods
-        .initializePool()
+        .initializePool(poolFeeBps, 0, new anchor.BN(0), 0, 0)
         .accountsPartial({
           tokenMintX: tokenX,
           tokenMintY: tokenY,
@@ -102,6 +145,105 @@ Token Y: ${tokenY.toBase58()}`
     }
   };
 
+  it('Initialize Config', async () => {
+    await program.methods
+      .initializeConfig(treasury)
+      .accountsPartial({
+        admin: payer.publicKey,
+        programData,
+      })
+      .rpc();
+
+    const configAccount = await program.account.config.fetch(configPubkey);
+    expect(configAccount.admin.equals(payer.publicKey)).toBe(true);
+    expect(configAccount.pendingAdmin.equals(anchor.web3.PublicKey.default)).toBe(
+      true
+    );
+    expect(configAccount.treasury.equals(treasury)).toBe(true);
+    expect(configAccount.feeTiers).toContain(poolFeeBps);
+    expect(configAccount.features).toBe(0);
+
+    // The config is a singleton
+    await expect(
+      program.methods
+        .initializeConfig(treasury)
+        .accountsPartial({
+          admin: payer.publicKey,
+          programData,
+        })
+        .rpc()
+    ).rejects.toThrow();
+  });
+
+  it('Upload Verifying Key', async () => {
+    const key = await loadVerifyingKey();
+    const verifyingKey = getVerifyingKeyAddress(program.programId, 0);
+    const hash = createHash('sha256').update(key.data).digest();
+
+    // Only the config admin can upload keys
+    const intruder = anchor.web3.Keypair.generate();
+    await airdrop(intruder.publicKey);
+    await expect(
+      program.methods
+        .initializeVerifyingKey(
+          0,
+          CIRCUIT_CONSTANT_PRODUCT,
+          key.nrPubinputs,
+          Array.from(hash)
+        )
+        .accountsPartial({ verifyingKey, admin: intruder.publicKey })
+        .signers([intruder])
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .initializeVerifyingKey(
+        0,
+        CIRCUIT_CONSTANT_PRODUCT,
+        key.nrPubinputs,
+        Array.from(hash)
+      )
+      .accountsPartial({ verifyingKey, admin: payer.publicKey })
+      .rpc();
+
+    // A key missing its last chunk does not match the committed hash
+    await program.methods
+      .writeVerifyingKey(0, 0, Buffer.from(key.data.subarray(0, 800)))
+      .accountsPartial({ verifyingKey, admin: payer.publicKey })
+      .rpc();
+    await expect(
+      program.methods
+        .finalizeVerifyingKey(0)
+        .accountsPartial({ verifyingKey, admin: payer.publicKey })
+        .rpc()
+    ).rejects.toThrow(/VerifyingKeyHashMismatch/);
+
+    await program.methods
+      .writeVerifyingKey(0, 800, Buffer.from(key.data.subarray(800)))
+      .accountsPartial({ verifyingKey, admin: payer.publicKey })
+      .rpc();
+    await program.methods
+      .finalizeVerifyingKey(0)
+      .accountsPartial({ verifyingKey, admin: payer.publicKey })
+      .rpc();
+
+    const verifyingKeyAccount = await program.account.verifyingKey.fetch(
+      verifyingKey
+    );
+    expect(verifyingKeyAccount.finalized).toBe(true);
+    expect(Buffer.from(verifyingKeyAccount.data).equals(Buffer.from(key.data))).toBe(
+      true
+    );
+
+    // Finalized keys are read-only
+    await expect(
+      program.methods
+        .writeVerifyingKey(0, 0, Buffer.alloc(32))
+        .accountsPartial({ verifyingKey, admin: payer.publicKey })
+        .rpc()
+    ).rejects.toThrow(/VerifyingKeyFinalized/);
+  });
+
   it('Initialize Pool', async () => {
     await setupMint();
     await setupPool();
@@ -109,6 +251,50 @@ Token Y: ${tokenY.toBase58()}`
     const poolAccount = await program.account.pool.fetch(poolPubkey);
     expect(poolAccount.tokenMintX.equals(tokenX)).toBe(true);
     expect(poolAccount.tokenMintY.equals(tokenY)).toBe(true);
+    expect(poolAccount.feeBps).toBe(poolFeeBps);
+    expect(poolAccount.vkVersions).toEqual([0, 0, 0, 0, 0, 0, 0, 0]);
+  });
+
+  it('Switch Pool Verifying Key', async () => {
+    const accounts = {
+      tokenMintX: tokenX,
+      tokenMintY: tokenY,
+      admin: payer.publicKey,
+    };
+
+    // Unfinalized keys cannot be used
+    const key = await loadVerifyingKey();
+    const draftKey = getVerifyingKeyAddress(program.programId, 2);
+    await program.methods
+      .initializeVerifyingKey(
+        2,
+        CIRCUIT_CONSTANT_PRODUCT,
+        key.nrPubinputs,
+        Array.from(Buffer.alloc(32))
+      )
+      .accountsPartial({ verifyingKey: draftKey, admin: payer.publicKey })
+      .rpc();
+    await expect(
+      program.methods
+        .setPoolVerifyingKey()
+        .accountsPartial({ ...accounts, verifyingKey: draftKey })
+        .rpc()
+    ).rejects.toThrow(/VerifyingKeyNotFinalized/);
+
+    // Roll the pool over to a new version, swaps keep working with it
+    const verifyingKey = await uploadVerifyingKey(
+      program,
+      payer.publicKey,
+      1,
+      key
+    );
+    await program.methods
+      .setPoolVerifyingKey()
+      .accountsPartial({ ...accounts, verifyingKey })
+      .rpc();
+
+    const poolAccount = await program.account.pool.fetch(poolPubkey);
+    expect(poolAccount.vkVersions[CIRCUIT_CONSTANT_PRODUCT]).toBe(1);
   });
 
   it('Add Liquidity', async () => {
@@ -183,7 +369,11 @@ Token Y: ${tokenY.toBase58()}`
 
     try {
       await program.methods
-        .addLiquidity(new anchor.BN(amountX), new anchor.BN(amountY))
+        .addLiquidity(
+          new anchor.BN(amountX),
+          new anchor.BN(amountY),
+          new anchor.BN(0)
+        )
         .accountsPartial({
           tokenMintX: tokenX,
           tokenMintY: tokenY,
@@ -204,6 +394,18 @@ Token Y: ${tokenY.toBase58()}`
       expect(updatedPoolAccount.reserveX.toNumber()).toBe(amountX);
       expect(updatedPoolAccount.reserveY.toNumber()).toBe(amountY);
 
+      // The first deposit mints isqrt(amountX * amountY) and locks 1000 of it
+      const [lpMintPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from('lp'), tokenX.toBuffer(), tokenY.toBuffer()],
+        program.programId
+      );
+      const liquidity = 44_721_359;
+      expect(updatedPoolAccount.liquidity.toNumber()).toBe(liquidity);
+      const lpBalance = await provider.connection.getTokenAccountBalance(
+        getAssociatedTokenAddressSync(lpMintPubkey, payer.publicKey)
+      );
+      expect(Number(lpBalance.value.amount)).toBe(liquidity - 1_000);
+
       const userAccountXInfo = await getAccount(
         provider.connection,
         userTokenAccountX.address,
@@ -289,6 +491,14 @@ Token Y: ${tokenY.toBase58()}`
       publicBalanceX: poolAccount.reserveX.toString(),
       publicBalanceY: poolAccount.reserveY.toString(),
       isSwapXtoY: 1, // Swapping tokenX for tokenY
+      feeRate: poolAccount.feeBps,
+      protocolFeeRate: poolAccount.protocolFeeBps,
+      userHash: (
+        await getUserHash(payer.publicKey, userTokenAccountY.address)
+      ).toString(),
+      expirySlot: (
+        (await provider.connection.getSlot()) + PROOF_VALIDITY_SLOTS
+      ).toString(),
     };
 
     const privateInputs = {
@@ -296,6 +506,12 @@ Token Y: ${tokenY.toBase58()}`
       privateMinReceived: '180000000', // Adjust this based on your expected output
     };
 
+    // Simulated, the quote is what the proof below has to come out to
+    const quote = await program.methods
+      .quoteSwap(new anchor.BN(privateInputs.privateInputAmount), true)
+      .accountsPartial({ pool: poolPubkey })
+      .view();
+
     const { proofA, proofB, proofC, publicSignals } = await generateProof(
       privateInputs,
       publicInputs
@@ -304,10 +520,13 @@ Token Y: ${tokenY.toBase58()}`
     try {
       const tx = await program.methods
         .confidentialSwap(
+          CIRCUIT_CONSTANT_PRODUCT,
           Array.from(proofA),
           Array.from(proofB),
           Array.from(proofC),
-          publicSignals.map((signal) => Array.from(signal))
+          publicSignals.map((signal) => Array.from(signal)),
+          publicInputs.isSwapXtoY === 1,
+          new anchor.BN(publicInputs.expirySlot)
         )
         .accountsPartial({
           tokenMintX: tokenX,
@@ -319,6 +538,11 @@ Token Y: ${tokenY.toBase58()}`
           userTokenAccountY: userTokenAccountY.address,
           poolTokenAccountX: poolTokenAccountX.address,
           poolTokenAccountY: poolTokenAccountY.address,
+          verifyingKey: getVerifyingKeyAddress(
+            program.programId,
+            poolAccount.vkVersions[CIRCUIT_CONSTANT_PRODUCT]
+          ),
+          nullifier: getNullifierAddress(program.programId, publicSignals[4]),
           user: payer.publicKey,
         })
         .transaction();
@@ -329,7 +553,38 @@ Token Y: ${tokenY.toBase58()}`
         })
       );
 
-      await provider.sendAndConfirm(tx);
+      const signature = await provider.sendAndConfirm(tx, [], {
+        commitment: 'confirmed',
+      });
+
+      const [swapEvent] = (await parseEvents(signature)).filter(
+        (event) => event.name === 'confidentialSwapEvent'
+      );
+      const poolAccountAfterSwap = await program.account.pool.fetch(poolPubkey);
+      expect(swapEvent.data.pool).toEqual(poolPubkey);
+      expect(swapEvent.data.isSwapXToY).toBe(true);
+      expect(swapEvent.data.reserveX.toString()).toEqual(
+        poolAccountAfterSwap.reserveX.toString()
+      );
+      expect(swapEvent.data.reserveY.toString()).toEqual(
+        poolAccountAfterSwap.reserveY.toString()
+      );
+      expect(swapEvent.data.slot.toNumber()).toBeGreaterThan(0);
+      expect(swapEvent.data.amountIn.toString()).toEqual(
+        quote.amountIn.toString()
+      );
+      expect(swapEvent.data.amountOut.toString()).toEqual(
+        quote.amountOut.toString()
+      );
+      expect(swapEvent.data.protocolFee.toString()).toEqual(
+        quote.protocolFee.toString()
+      );
+      expect(poolAccountAfterSwap.reserveX.toString()).toEqual(
+        quote.newReserveX.toString()
+      );
+      expect(poolAccountAfterSwap.reserveY.toString()).toEqual(
+        quote.newReserveY.toString()
+      );
 
       const userAccountXAfterSwap = await getAccount(
         provider.connection,
@@ -368,6 +623,480 @@ Token Y: ${tokenY.toBase58()}`
     }
   }, 10000000);
 
+  describe('Confidential Swap proof binding', () => {
+    const swapAccounts = async (user = payer.publicKey) => {
+      const [
+        userTokenAccountX,
+        userTokenAccountY,
+        poolTokenAccountX,
+        poolTokenAccountY,
+      ] = await Promise.all([
+        getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          convertToSigner(payer),
+          tokenX,
+          user,
+          true,
+          undefined,
+          undefined,
+          tokenXProgramId
+        ),
+        getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          convertToSigner(payer),
+          tokenY,
+          user,
+          true,
+          undefined,
+          undefined,
+          tokenYProgramId
+        ),
+        getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          convertToSigner(payer),
+          tokenX,
+          poolPubkey,
+          true,
+          undefined,
+          undefined,
+          tokenXProgramId
+        ),
+        getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          convertToSigner(payer),
+          tokenY,
+          poolPubkey,
+          true,
+          undefined,
+          undefined,
+          tokenYProgramId
+        ),
+      ]);
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+
+      return {
+        tokenMintX: tokenX,
+        tokenMintY: tokenY,
+        tokenMintXProgram: tokenXProgramId,
+        tokenMintYProgram: tokenYProgramId,
+        pool: poolPubkey,
+        verifyingKey: getVerifyingKeyAddress(
+          program.programId,
+          poolAccount.vkVersions[CIRCUIT_CONSTANT_PRODUCT]
+        ),
+        userTokenAccountX: userTokenAccountX.address,
+        userTokenAccountY: userTokenAccountY.address,
+        poolTokenAccountX: poolTokenAccountX.address,
+        poolTokenAccountY: poolTokenAccountY.address,
+        user,
+      };
+    };
+
+    const sendSwap = async (
+      proof: {
+        proofA: Uint8Array;
+        proofB: Uint8Array;
+        proofC: Uint8Array;
+        publicSignals: Uint8Array[];
+        isSwapXtoY: boolean;
+        expirySlot: string;
+        circuitId?: number;
+      },
+      signer?: anchor.web3.Keypair
+    ) => {
+      const accounts = await swapAccounts(signer?.publicKey ?? payer.publicKey);
+      const tx = await program.methods
+        .confidentialSwap(
+          proof.circuitId ?? CIRCUIT_CONSTANT_PRODUCT,
+          Array.from(proof.proofA),
+          Array.from(proof.proofB),
+          Array.from(proof.proofC),
+          proof.publicSignals.map((signal) => Array.from(signal)),
+          proof.isSwapXtoY,
+          new anchor.BN(proof.expirySlot)
+        )
+        .accountsPartial({
+          ...accounts,
+          nullifier: getNullifierAddress(
+            program.programId,
+            proof.publicSignals[4]
+          ),
+        })
+        .transaction();
+
+      tx.instructions.unshift(
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+          units: 2_000_000,
+        })
+      );
+
+      return provider.sendAndConfirm(tx, signer ? [signer] : [], {
+        commitment: 'confirmed',
+      });
+    };
+
+    const privateInputs = {
+      privateInputAmount: '10000',
+      privateMinReceived: '1',
+    };
+
+    // Public inputs for a swap against the given pool state, bound to `user`
+    const swapInputs = async (
+      poolAccount: Awaited<ReturnType<typeof program.account.pool.fetch>>,
+      isSwapXtoY = 1,
+      user = payer.publicKey
+    ) => ({
+      publicBalanceX: poolAccount.reserveX.toString(),
+      publicBalanceY: poolAccount.reserveY.toString(),
+      isSwapXtoY,
+      feeRate: poolAccount.feeBps,
+      protocolFeeRate: poolAccount.protocolFeeBps,
+      userHash: (
+        await getUserHash(
+          user,
+          isSwapXtoY
+            ? getAssociatedTokenAddressSync(tokenY, user, true, tokenYProgramId)
+            : getAssociatedTokenAddressSync(tokenX, user, true, tokenXProgramId)
+        )
+      ).toString(),
+      expirySlot: (
+        (await provider.connection.getSlot()) + PROOF_VALIDITY_SLOTS
+      ).toString(),
+    });
+
+    // A proof along with the direction and expiry slot it was made for, which are
+    // sent next to it
+    const proveSwap = async (
+      proofPrivateInputs: Parameters<typeof generateProof>[0],
+      publicInputs: Parameters<typeof generateProof>[1]
+    ) => ({
+      ...(await generateProof(proofPrivateInputs, publicInputs)),
+      isSwapXtoY: publicInputs.isSwapXtoY === 1,
+      expirySlot: publicInputs.expirySlot,
+    });
+
+    it('rejects a proof made against stale reserves', async () => {
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+
+      const staleProof = await proveSwap(
+        privateInputs,
+        await swapInputs(poolAccount)
+      );
+
+      // Move the reserves so the proof above no longer matches the pool
+      await sendSwap(
+        await proveSwap(privateInputs, await swapInputs(poolAccount))
+      );
+
+      const poolAfterSwap = await program.account.pool.fetch(poolPubkey);
+      expect(poolAfterSwap.reserveX.eq(poolAccount.reserveX)).toBe(false);
+
+      await expect(sendSwap(staleProof)).rejects.toThrow();
+
+      const poolAfterReplay = await program.account.pool.fetch(poolPubkey);
+      expect(poolAfterReplay.reserveX.eq(poolAfterSwap.reserveX)).toBe(true);
+      expect(poolAfterReplay.reserveY.eq(poolAfterSwap.reserveY)).toBe(true);
+    }, 10000000);
+
+    it('rejects a replayed proof', async () => {
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+      const proof = await proveSwap(
+        privateInputs,
+        await swapInputs(poolAccount)
+      );
+
+      await sendSwap(proof);
+
+      const nullifierAccount = await program.account.nullifier.fetch(
+        getNullifierAddress(program.programId, proof.publicSignals[4])
+      );
+      expect(nullifierAccount.pool).toEqual(poolPubkey);
+
+      await expect(sendSwap(proof)).rejects.toThrow(/NullifierAlreadyUsed/);
+    }, 10000000);
+
+    // Replays only: a copied proof that has not landed yet, sent by someone else, is
+    // covered by the front-running test below
+    it('rejects a used proof or nullifier secret, whoever sends it', async () => {
+      const replayer = anchor.web3.Keypair.generate();
+      await airdrop(replayer.publicKey);
+
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+      const nullifierSecret = '123456789';
+      const proof = await proveSwap(
+        { ...privateInputs, privateNullifierSecret: nullifierSecret },
+        await swapInputs(poolAccount)
+      );
+
+      await sendSwap(proof);
+      await expect(sendSwap(proof, replayer)).rejects.toThrow(
+        /NullifierAlreadyUsed/
+      );
+
+      // A new proof reusing the secret against the new reserves is a replay too
+      const poolAfterSwap = await program.account.pool.fetch(poolPubkey);
+      const reusedSecretProof = await proveSwap(
+        { ...privateInputs, privateNullifierSecret: nullifierSecret },
+        await swapInputs(poolAfterSwap)
+      );
+      await expect(sendSwap(reusedSecretProof)).rejects.toThrow(
+        /NullifierAlreadyUsed/
+      );
+    }, 10000000);
+
+    it('rejects a proof submitted by someone other than its user', async () => {
+      const searcher = anchor.web3.Keypair.generate();
+      await airdrop(searcher.publicKey);
+
+      // A fresh proof for the payer, taken from the mempool before it lands
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+      const proof = await proveSwap(
+        privateInputs,
+        await swapInputs(poolAccount)
+      );
+
+      await expect(sendSwap(proof, searcher)).rejects.toThrow(/InvalidProof/);
+
+      const poolAfter = await program.account.pool.fetch(poolPubkey);
+      expect(poolAfter.reserveX.eq(poolAccount.reserveX)).toBe(true);
+
+      // The proof is still good for the user it was made for
+      await sendSwap(proof);
+    }, 10000000);
+
+    it('swaps in both directions and rejects the other one', async () => {
+      for (const isSwapXtoY of [1, 0]) {
+        const poolAccount = await program.account.pool.fetch(poolPubkey);
+        const proof = await proveSwap(
+          privateInputs,
+          await swapInputs(poolAccount, isSwapXtoY)
+        );
+
+        // The direction is part of the proof, flipping it fails verification
+        await expect(
+          sendSwap({ ...proof, isSwapXtoY: !proof.isSwapXtoY })
+        ).rejects.toThrow(/InvalidProof/);
+
+        const signature = await sendSwap(proof);
+        const [swapEvent] = (await parseEvents(signature)).filter(
+          (event) => event.name === 'confidentialSwapEvent'
+        );
+        expect(swapEvent.data.isSwapXtoY).toBe(isSwapXtoY === 1);
+
+        // Input and output reserves for this direction
+        const reserves = (pool: typeof poolAccount) =>
+          isSwapXtoY
+            ? [pool.reserveX, pool.reserveY]
+            : [pool.reserveY, pool.reserveX];
+        const [reserveIn, reserveOut] = reserves(poolAccount);
+        const [reserveInAfter, reserveOutAfter] = reserves(
+          await program.account.pool.fetch(poolPubkey)
+        );
+        expect(reserveInAfter.gt(reserveIn)).toBe(true);
+        expect(reserveOutAfter.lt(reserveOut)).toBe(true);
+      }
+    }, 10000000);
+
+    it('rejects an expired proof', async () => {
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+      const currentSlot = await provider.connection.getSlot();
+
+      const expiredProof = await proveSwap(privateInputs, {
+        ...(await swapInputs(poolAccount)),
+        expirySlot: (currentSlot - 1).toString(),
+      });
+      await expect(sendSwap(expiredProof)).rejects.toThrow(/ProofExpired/);
+
+      // Pushing the expiry back does not help, the proof commits to it
+      await expect(
+        sendSwap({
+          ...expiredProof,
+          expirySlot: (currentSlot + PROOF_VALIDITY_SLOTS).toString(),
+        })
+      ).rejects.toThrow(/InvalidProof/);
+
+      const poolAfter = await program.account.pool.fetch(poolPubkey);
+      expect(poolAfter.reserveX.eq(poolAccount.reserveX)).toBe(true);
+    }, 10000000);
+
+    it('rejects a proof sent for another circuit or layout', async () => {
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+      const proof = await proveSwap(
+        privateInputs,
+        await swapInputs(poolAccount)
+      );
+
+      await expect(sendSwap({ ...proof, circuitId: 255 })).rejects.toThrow(
+        /UnknownCircuit/
+      );
+
+      // Exact output proofs have their own instruction
+      await expect(
+        sendSwap({ ...proof, circuitId: CIRCUIT_CONSTANT_PRODUCT_EXACT_OUT })
+      ).rejects.toThrow(/UnknownCircuit/);
+
+      // and are checked against the exact output key, not this one
+      const accounts = await swapAccounts(payer.publicKey);
+      await expect(
+        program.methods
+          .confidentialSwapExactOut(
+            Array.from(proof.proofA),
+            Array.from(proof.proofB),
+            Array.from(proof.proofC),
+            proof.publicSignals.map((signal) => Array.from(signal)),
+            proof.isSwapXtoY,
+            new anchor.BN(proof.expirySlot)
+          )
+          .accountsPartial({
+            ...accounts,
+            nullifier: getNullifierAddress(
+              program.programId,
+              proof.publicSignals[4]
+            ),
+          })
+          .preInstructions([
+            anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+              units: 2_000_000,
+            }),
+          ])
+          .rpc()
+      ).rejects.toThrow(/InvalidVerifyingKey/);
+
+      // The constant product circuit has exactly five outputs
+      await expect(
+        sendSwap({
+          ...proof,
+          publicSignals: [...proof.publicSignals, new Uint8Array(32)],
+        })
+      ).rejects.toThrow(/InvalidPublicInputsLength/);
+
+      // Neither attempt spent the nullifier
+      await sendSwap(proof);
+    }, 10000000);
+
+    it('rejects a proof made against forged reserves', async () => {
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+
+      const forgedProof = await proveSwap(privateInputs, {
+        ...(await swapInputs(poolAccount)),
+        publicBalanceX: poolAccount.reserveX.muln(10).toString(),
+      });
+
+      await expect(sendSwap(forgedProof)).rejects.toThrow();
+
+      const poolAfter = await program.account.pool.fetch(poolPubkey);
+      expect(poolAfter.reserveX.eq(poolAccount.reserveX)).toBe(true);
+      expect(poolAfter.reserveY.eq(poolAccount.reserveY)).toBe(true);
+    }, 10000000);
+
+    it('rejects a route that does not cover every hop', async () => {
+      const accounts = await swapAccounts();
+      const proof = await proveSwap(
+        privateInputs,
+        await swapInputs(await program.account.pool.fetch(poolPubkey))
+      );
+
+      // A single X to Y hop, the route circuit needs ROUTE_HOPS of them
+      const remainingAccounts = [
+        accounts.pool,
+        accounts.poolTokenAccountX,
+        accounts.poolTokenAccountY,
+        tokenY,
+        tokenYProgramId,
+      ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+      expect(remainingAccounts.length).toBe(ROUTE_ACCOUNTS_PER_HOP);
+
+      await expect(
+        program.methods
+          .confidentialRouteSwap(
+            Array.from(proof.proofA),
+            Array.from(proof.proofB),
+            Array.from(proof.proofC),
+            proof.publicSignals.map((signal) => Array.from(signal)),
+            new anchor.BN(proof.expirySlot)
+          )
+          .accountsPartial({
+            tokenMintIn: tokenX,
+            tokenMintOut: tokenY,
+            tokenMintInProgram: tokenXProgramId,
+            tokenMintOutProgram: tokenYProgramId,
+            verifyingKey: accounts.verifyingKey,
+            userTokenAccountIn: accounts.userTokenAccountX,
+            userTokenAccountOut: accounts.userTokenAccountY,
+            nullifier: getNullifierAddress(
+              program.programId,
+              proof.publicSignals[4]
+            ),
+            user: payer.publicKey,
+          })
+          .remainingAccounts(remainingAccounts)
+          .rpc()
+      ).rejects.toThrow(/InvalidRoute/);
+    }, 10000000);
+  });
+
+  it('Collect Protocol Fees', async () => {
+    const poolAccount = await program.account.pool.fetch(poolPubkey);
+    expect(poolAccount.protocolFeesX.toNumber()).toBeGreaterThan(0);
+
+    const accounts = {
+      tokenMintX: tokenX,
+      tokenMintY: tokenY,
+      tokenMintXProgram: tokenXProgramId,
+      tokenMintYProgram: tokenYProgramId,
+      pool: poolPubkey,
+      treasury,
+    };
+
+    // Only the config admin can collect, and only to the config treasury
+    const intruder = anchor.web3.Keypair.generate();
+    await airdrop(intruder.publicKey);
+
+    await expect(
+      program.methods
+        .collectProtocolFees()
+        .accountsPartial({ ...accounts, admin: intruder.publicKey })
+        .signers([intruder])
+        .rpc()
+    ).rejects.toThrow();
+
+    await expect(
+      program.methods
+        .collectProtocolFees()
+        .accountsPartial({
+          ...accounts,
+          treasury: intruder.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .collectProtocolFees()
+      .accountsPartial({ ...accounts, admin: payer.publicKey })
+      .rpc();
+
+    const treasuryTokenAccountX = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      convertToSigner(payer),
+      tokenX,
+      treasury,
+      true,
+      undefined,
+      undefined,
+      tokenXProgramId
+    );
+    expect(Number(treasuryTokenAccountX.amount)).toBe(
+      poolAccount.protocolFeesX.toNumber()
+    );
+
+    const poolAfterCollect = await program.account.pool.fetch(poolPubkey);
+    expect(poolAfterCollect.protocolFeesX.toNumber()).toBe(0);
+    expect(poolAfterCollect.protocolFeesY.toNumber()).toBe(0);
+    expect(poolAfterCollect.reserveX.eq(poolAccount.reserveX)).toBe(true);
+    expect(poolAfterCollect.reserveY.eq(poolAccount.reserveY)).toBe(true);
+  }, 10000000);
+
   it('Remove Liquidity', async () => {
     const poolAccount = await program.account.pool.fetch(poolPubkey);
 
@@ -444,7 +1173,7 @@ Token Y: ${tokenY.toBase58()}`
 
     try {
       await program.methods
-        .removeLiquidity(halfLpTokens)
+        .removeLiquidity(halfLpTokens, new anchor.BN(0), new anchor.BN(0))
         .accountsPartial({
           tokenMintX: tokenX,
           tokenMintY: tokenY,
@@ -493,4 +1222,318 @@ Token Y: ${tokenY.toBase58()}`
       throw error;
     }
   }, 10000000);
+
+  const liquidityAccounts = () => {
+    const [lpMintPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('lp'), tokenX.toBuffer(), tokenY.toBuffer()],
+      program.programId
+    );
+
+    return {
+      tokenMintX: tokenX,
+      tokenMintY: tokenY,
+      tokenMintXProgram: tokenXProgramId,
+      tokenMintYProgram: tokenYProgramId,
+      tokenMintLp: lpMintPubkey,
+      tokenMintLpProgram: TOKEN_PROGRAM_ID,
+      pool: poolPubkey,
+      userTokenAccountX: getAssociatedTokenAddressSync(
+        tokenX,
+        payer.publicKey,
+        false,
+        tokenXProgramId
+      ),
+      userTokenAccountY: getAssociatedTokenAddressSync(
+        tokenY,
+        payer.publicKey,
+        false,
+        tokenYProgramId
+      ),
+      userTokenAccountLp: getAssociatedTokenAddressSync(
+        lpMintPubkey,
+        payer.publicKey
+      ),
+      poolTokenAccountX: getAssociatedTokenAddressSync(
+        tokenX,
+        poolPubkey,
+        true,
+        tokenXProgramId
+      ),
+      poolTokenAccountY: getAssociatedTokenAddressSync(
+        tokenY,
+        poolPubkey,
+        true,
+        tokenYProgramId
+      ),
+      user: payer.publicKey,
+    };
+  };
+
+  describe('Liquidity slippage', () => {
+    it('rejects a withdrawal below the minimum amounts', async () => {
+      await expect(
+        program.methods
+          .removeLiquidity(
+            new anchor.BN(1000),
+            new anchor.BN('18446744073709551615'),
+            new anchor.BN(0)
+          )
+          .accountsPartial(liquidityAccounts())
+          .rpc()
+      ).rejects.toThrow(/SlippageExceeded/);
+    });
+
+    it('rejects a deposit minting less than the minimum liquidity', async () => {
+      await expect(
+        program.methods
+          .addLiquidity(
+            new anchor.BN(1000),
+            new anchor.BN(1000),
+            new anchor.BN('18446744073709551615')
+          )
+          .accountsPartial(liquidityAccounts())
+          .rpc()
+      ).rejects.toThrow(/SlippageExceeded/);
+    });
+
+    it('only takes the amounts matching the pool ratio', async () => {
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+      const amountX = new anchor.BN(1000);
+      // Far more Y than the pool ratio asks for
+      const amountY = poolAccount.reserveY
+        .mul(amountX)
+        .div(poolAccount.reserveX)
+        .muln(10);
+
+      const signature = await program.methods
+        .addLiquidity(amountX, amountY, new anchor.BN(1))
+        .accountsPartial(liquidityAccounts())
+        .rpc({ commitment: 'confirmed' });
+
+      const updatedPoolAccount = await program.account.pool.fetch(poolPubkey);
+      const addedX = updatedPoolAccount.reserveX.sub(poolAccount.reserveX);
+      const addedY = updatedPoolAccount.reserveY.sub(poolAccount.reserveY);
+
+      expect(addedX.lte(amountX)).toBe(true);
+      expect(addedY.lt(amountY.divn(5))).toBe(true);
+
+      // The event reports the amounts actually transferred
+      const [event] = (await parseEvents(signature)).filter(
+        (event) => event.name === 'liquidityAdded'
+      );
+
+      expect(event.data.pool).toEqual(poolPubkey);
+      expect(event.data.amountX.toString()).toEqual(addedX.toString());
+      expect(event.data.amountY.toString()).toEqual(addedY.toString());
+      expect(event.data.reserveX.toString()).toEqual(
+        updatedPoolAccount.reserveX.toString()
+      );
+      expect(event.data.lpSupply.toString()).toEqual(
+        updatedPoolAccount.liquidity.toString()
+      );
+    });
+  });
+
+  describe('Emergency pause', () => {
+    const PAUSE_SWAPS = 1 << 0;
+    const PAUSE_DEPOSITS = 1 << 1;
+    const PAUSE_WITHDRAWALS = 1 << 2;
+
+    const removeOneLpToken = () =>
+      program.methods
+        .removeLiquidity(new anchor.BN(1), new anchor.BN(0), new anchor.BN(0))
+        .accountsPartial(liquidityAccounts())
+        .rpc({ commitment: 'confirmed' });
+
+    const setPoolPause = (paused: number) =>
+      program.methods
+        .setPoolPause(paused)
+        .accountsPartial({
+          tokenMintX: tokenX,
+          tokenMintY: tokenY,
+          pool: poolPubkey,
+          admin: payer.publicKey,
+        })
+        .rpc({ commitment: 'confirmed' });
+
+    it('only lets the admin pause', async () => {
+      const intruder = anchor.web3.Keypair.generate();
+      await airdrop(intruder.publicKey);
+
+      await expect(
+        program.methods
+          .setPoolPause(PAUSE_SWAPS)
+          .accountsPartial({
+            tokenMintX: tokenX,
+            tokenMintY: tokenY,
+            pool: poolPubkey,
+            admin: intruder.publicKey,
+          })
+          .signers([intruder])
+          .rpc()
+      ).rejects.toThrow();
+
+      await expect(
+        program.methods
+          .setGlobalPause(PAUSE_SWAPS)
+          .accountsPartial({ admin: intruder.publicKey })
+          .signers([intruder])
+          .rpc()
+      ).rejects.toThrow();
+
+      // Unknown bits are rejected
+      await expect(setPoolPause(1 << 3)).rejects.toThrow();
+    });
+
+    it('keeps withdrawals open while swaps and deposits are paused', async () => {
+      await setPoolPause(PAUSE_SWAPS | PAUSE_DEPOSITS);
+
+      const poolAccount = await program.account.pool.fetch(poolPubkey);
+      expect(poolAccount.paused).toEqual(PAUSE_SWAPS | PAUSE_DEPOSITS);
+
+      await expect(
+        program.methods
+          .addLiquidity(
+            new anchor.BN(1000),
+            new anchor.BN(1000),
+            new anchor.BN(0)
+          )
+          .accountsPartial(liquidityAccounts())
+          .rpc()
+      ).rejects.toThrow(/PoolPaused/);
+
+      await removeOneLpToken();
+
+      await setPoolPause(0);
+    });
+
+    it('pauses withdrawals on every pool', async () => {
+      await program.methods
+        .setGlobalPause(PAUSE_WITHDRAWALS)
+        .accountsPartial({ admin: payer.publicKey })
+        .rpc({ commitment: 'confirmed' });
+
+      const configAccount = await program.account.config.fetch(configPubkey);
+      expect(configAccount.paused).toEqual(PAUSE_WITHDRAWALS);
+
+      await expect(removeOneLpToken()).rejects.toThrow(/PoolPaused/);
+
+      await program.methods
+        .setGlobalPause(0)
+        .accountsPartial({ admin: payer.publicKey })
+        .rpc({ commitment: 'confirmed' });
+
+      await removeOneLpToken();
+    });
+  });
+
+  describe('Config administration', () => {
+    it('rejects config updates from anyone but the admin', async () => {
+      const intruder = anchor.web3.Keypair.generate();
+      await airdrop(intruder.publicKey);
+
+      await expect(
+        program.methods
+          .updateConfig({
+            treasury: intruder.publicKey,
+            feeTiers: null,
+            protocolFeeBps: null,
+            features: null,
+            vkVersions: null,
+          })
+          .accountsPartial({ admin: intruder.publicKey })
+          .signers([intruder])
+          .rpc()
+      ).rejects.toThrow();
+
+      await expect(
+        program.methods
+          .proposeAdmin(intruder.publicKey)
+          .accountsPartial({ admin: intruder.publicKey })
+          .signers([intruder])
+          .rpc()
+      ).rejects.toThrow();
+    });
+
+    it('updates fee tiers and feature switches', async () => {
+      await program.methods
+        .updateConfig({
+          treasury: null,
+          feeTiers: [1, 5, 30, 250],
+          protocolFeeBps: 1000,
+          features: 1,
+          vkVersions: [1, 0, 0, 0, 0, 0, 0, 0],
+        })
+        .accountsPartial({ admin: payer.publicKey })
+        .rpc();
+
+      const configAccount = await program.account.config.fetch(configPubkey);
+      expect(configAccount.feeTiers).toEqual([1, 5, 30, 250]);
+      expect(configAccount.protocolFeeBps).toBe(1000);
+      expect(configAccount.features).toBe(1);
+      // New pools get the version the existing pool was switched to
+      expect(configAccount.vkVersions).toEqual([1, 0, 0, 0, 0, 0, 0, 0]);
+      expect(configAccount.treasury.equals(treasury)).toBe(true);
+
+      await expect(
+        program.methods
+          .updateConfig({
+            treasury: null,
+            feeTiers: [1, 5, 30, 5000],
+            protocolFeeBps: null,
+            features: null,
+            vkVersions: null,
+          })
+          .accountsPartial({ admin: payer.publicKey })
+          .rpc()
+      ).rejects.toThrow();
+    });
+
+    it('hands the admin over in two steps', async () => {
+      const newAdmin = anchor.web3.Keypair.generate();
+      const outsider = anchor.web3.Keypair.generate();
+      await airdrop(newAdmin.publicKey);
+      await airdrop(outsider.publicKey);
+
+      await program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accountsPartial({ admin: payer.publicKey })
+        .rpc();
+
+      let configAccount = await program.account.config.fetch(configPubkey);
+      expect(configAccount.admin.equals(payer.publicKey)).toBe(true);
+      expect(configAccount.pendingAdmin.equals(newAdmin.publicKey)).toBe(true);
+
+      await expect(
+        program.methods
+          .acceptAdmin()
+          .accountsPartial({ pendingAdmin: outsider.publicKey })
+          .signers([outsider])
+          .rpc()
+      ).rejects.toThrow();
+
+      await program.methods
+        .acceptAdmin()
+        .accountsPartial({ pendingAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+
+      configAccount = await program.account.config.fetch(configPubkey);
+      expect(configAccount.admin.equals(newAdmin.publicKey)).toBe(true);
+      expect(
+        configAccount.pendingAdmin.equals(anchor.web3.PublicKey.default)
+      ).toBe(true);
+
+      // Hand it back so the remaining tests keep running as admin
+      await program.methods
+        .proposeAdmin(payer.publicKey)
+        .accountsPartial({ admin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+      await program.methods
+        .acceptAdmin()
+        .accountsPartial({ pendingAdmin: payer.publicKey })
+        .rpc();
+    });
+  });
 });
@@ -38,9 +38,14 @@ describe.skip('ZKConstantSumAMM Verifier', () => {
     const input = {
       privateInputAmount: 100000, // Example value
       privateMinReceived: 99000, // Example value
+      privateNullifierSecret: '12345678901234567890',
       publicBalanceX: 1100000, // Changed from 1000000 to match public signal
       publicBalanceY: 1900000, // Changed from 2000000 to match public signal
       isSwapXtoY: 1, // Swapping X to Y
+      feeRate: 30,
+      protocolFeeRate: 2000,
+      userHash: '987654321',
+      expirySlot: 1000,
     };
 
     console.log('Input:', JSON.stringify(input, null, 2));
@@ -115,17 +120,34 @@ describe.skip('ZKConstantSumAMM Verifier', () => {
     console.log(`Proof and public inputs written to: ${tempFilePath}`);
 
     console.log('Public signals:', publicSignals);
-    expect(publicSignals).toEqual(['1200000', '1800000', '100000']);
+    expect(publicSignals).toEqual([
+      '1199940',
+      '1742103',
+      '157897',
+      '60',
+      '17610922722311195426938483481431943255028223790571250909270476711880232282197',
+      '1100000',
+      '1900000',
+      '1',
+      '30',
+      '2000',
+      '987654321',
+      '1000',
+    ]);
   });
 
   it('should generate and verify a valid proof using snarkjs library', async () => {
     const input = {
-      privateAmount: 100000,
+      privateInputAmount: 100000,
       privateMinReceived: 99000,
+      privateNullifierSecret: '1',
       publicBalanceX: 1100000,
       publicBalanceY: 1900000,
       isSwapXtoY: 1,
-      totalLiquidity: 3000000,
+      feeRate: 0,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
     };
 
     const wasmPath = path.join(
@@ -155,12 +177,16 @@ describe.skip('ZKConstantSumAMM Verifier', () => {
 
   it('should generate and verify a valid proof using snarkjs CLI', async () => {
     const input = {
-      privateAmount: 100000,
+      privateInputAmount: 100000,
       privateMinReceived: 99000,
+      privateNullifierSecret: '1',
       publicBalanceX: 1100000,
       publicBalanceY: 1900000,
       isSwapXtoY: 1,
-      totalLiquidity: 3000000,
+      feeRate: 0,
+      protocolFeeRate: 0,
+      userHash: 0,
+      expirySlot: 0,
     };
 
     const snarkjsCli = path.join(__dirname, '../../snarkjs/build/cli.cjs');
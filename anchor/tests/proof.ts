@@ -7,14 +7,23 @@ import {
   negateAndSerializeG1,
   g2Uncompressed,
   to32ByteBuffer,
+  randomFieldElement,
 } from '../src/utils';
 
 export async function generateProof(
-  privateInputs: { privateInputAmount: string; privateMinReceived: string },
+  privateInputs: {
+    privateInputAmount: string;
+    privateMinReceived: string;
+    privateNullifierSecret?: string;
+  },
   publicInputs: {
     publicBalanceX: string;
     publicBalanceY: string;
     isSwapXtoY: number;
+    feeRate: number;
+    protocolFeeRate: number;
+    userHash: string;
+    expirySlot: string;
   }
 ): Promise<{
   proofA: Uint8Array;
@@ -30,9 +39,16 @@ export async function generateProof(
   const input = {
     privateInputAmount: privateInputs.privateInputAmount,
     privateMinReceived: privateInputs.privateMinReceived,
+    // A fresh secret per proof, reusing one makes the swap fail as a replay
+    privateNullifierSecret:
+      privateInputs.privateNullifierSecret ?? randomFieldElement().toString(),
     publicBalanceX: publicInputs.publicBalanceX,
     publicBalanceY: publicInputs.publicBalanceY,
     isSwapXtoY: publicInputs.isSwapXtoY.toString(),
+    feeRate: publicInputs.feeRate.toString(),
+    protocolFeeRate: publicInputs.protocolFeeRate.toString(),
+    userHash: publicInputs.userHash.toString(),
+    expirySlot: publicInputs.expirySlot.toString(),
   };
 
   const { proof, publicSignals } = await snarkjs.groth16.fullProve(
@@ -56,9 +72,14 @@ export async function generateProof(
 
   await curve.terminate();
 
-  const formattedPublicSignals = publicSignalsUnstrigified.map((signal) => {
-    return to32ByteBuffer(BigInt(signal));
-  });
+  // The program rebuilds the reserves and direction from the pool account, so
+  // only the circuit outputs (newBalanceX, newBalanceY, amountReceived,
+  // protocolFee, nullifier) are sent.
+  const formattedPublicSignals = publicSignalsUnstrigified
+    .slice(0, 5)
+    .map((signal) => {
+      return to32ByteBuffer(BigInt(signal));
+    });
 
   return {
     proofA: new Uint8Array(proofA),
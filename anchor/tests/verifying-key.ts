@@ -0,0 +1,65 @@
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { createHash } from 'crypto';
+import * as path from 'path';
+import { buildBn128, utils } from 'ffjavascript';
+const { unstringifyBigInts } = utils;
+import { Darklake } from '../target/types/darklake';
+import { verifyingKeyData } from '../src/utils';
+import {
+  CIRCUIT_CONSTANT_PRODUCT,
+  getVerifyingKeyAddress,
+} from '../src/darklake-exports';
+
+// Keeps each write well under the transaction size limit.
+const CHUNK_SIZE = 800;
+
+export async function loadVerifyingKey(): Promise<{
+  nrPubinputs: number;
+  data: Uint8Array;
+}> {
+  const vk = unstringifyBigInts(
+    require(path.join(__dirname, '../../circuits', 'verification_key.json'))
+  );
+
+  const curve = await buildBn128();
+  const data = verifyingKeyData(curve, vk);
+  await curve.terminate();
+
+  return { nrPubinputs: vk.nPublic, data };
+}
+
+// Creates, writes and finalizes version `version` of a swap circuit verifying key.
+export async function uploadVerifyingKey(
+  program: Program<Darklake>,
+  admin: anchor.web3.PublicKey,
+  version: number,
+  { nrPubinputs, data }: { nrPubinputs: number; data: Uint8Array },
+  circuitId = CIRCUIT_CONSTANT_PRODUCT
+) {
+  const verifyingKey = getVerifyingKeyAddress(program.programId, version);
+  const hash = createHash('sha256').update(data).digest();
+
+  await program.methods
+    .initializeVerifyingKey(version, circuitId, nrPubinputs, Array.from(hash))
+    .accountsPartial({ verifyingKey, admin })
+    .rpc();
+
+  for (let offset = 0; offset < data.length; offset += CHUNK_SIZE) {
+    await program.methods
+      .writeVerifyingKey(
+        version,
+        offset,
+        Buffer.from(data.subarray(offset, offset + CHUNK_SIZE))
+      )
+      .accountsPartial({ verifyingKey, admin })
+      .rpc();
+  }
+
+  await program.methods
+    .finalizeVerifyingKey(version)
+    .accountsPartial({ verifyingKey, admin })
+    .rpc();
+
+  return verifyingKey;
+}
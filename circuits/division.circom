@@ -21,6 +21,11 @@ template ReciprocalDivision(n) {
     // Constrain the result
     dividend === quotient * divisor + remainder;
     
+    // Keep the remainder in n bits. LessThan only holds for inputs below 2^n, and a
+    // remainder wrapped around the field would let the quotient be raised by one
+    component remainderBits = Num2Bits(n);
+    remainderBits.in <== remainder;
+
     // Ensure remainder is less than divisor
     component lessThan = LessThan(n);
     lessThan.in[0] <== remainder;
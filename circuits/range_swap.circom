@@ -0,0 +1,140 @@
+pragma circom 2.0.0;
+
+include "node_modules/circomlib/circuits/comparators.circom";
+include "node_modules/circomlib/circuits/bitify.circom";
+include "node_modules/circomlib/circuits/mux1.circom";
+include "node_modules/circomlib/circuits/poseidon.circom";
+include "division.circom";
+
+// Swap on a concentrated liquidity pool that stays within the liquidity range of the current
+// price, with the rounding of compute_swap_step in the program. Swaps crossing an initialized
+// tick change the liquidity on the way, the program then pays out a different amount than
+// amountReceived and rejects the proof.
+template ZKRangeSwap() {
+    // Private inputs
+    signal input privateInputAmount;
+    signal input privateMinReceived;
+    signal input privateNullifierSecret; // Random per swap, never reused
+
+    // Public inputs
+    signal input sqrtPriceX64; // Square root price of the pool in Q64.64
+    signal input liquidity; // Liquidity at the current price
+    signal input isSwapXtoY; // 1 if swapping X to Y, 0 if swapping Y to X
+    signal input feeRate; // Swap fee in basis points, charged on the input amount
+    signal input protocolFeeRate; // Protocol share of the swap fee in basis points
+    signal input userHash; // sha256(user || destination) with the top byte cleared
+    signal input expirySlot; // Last slot the proof can be used in
+
+    isSwapXtoY * (isSwapXtoY - 1) === 0;
+
+    // Outputs
+    signal output amountIn;
+    signal output amountReceived;
+    signal output nullifier;
+
+    // Spent nullifiers are recorded by the program, so a proof can only be used once
+    component nullifierHash = Poseidon(1);
+    nullifierHash.inputs[0] <== privateNullifierSecret;
+    nullifier <== nullifierHash.out;
+
+    // userHash and expirySlot are not used by the swap, constrain them so the proof commits
+    // to them
+    signal userHashSquare <== userHash * userHash;
+    signal expirySlotSquare <== expirySlot * expirySlot;
+
+    // Below 10000, as compute_swap_step requires
+    component feeRateCheck = LessThan(14);
+    feeRateCheck.in[0] <== feeRate;
+    feeRateCheck.in[1] <== 10000;
+    feeRateCheck.out === 1;
+
+    component protocolFeeRateCheck = LessEqThan(14);
+    protocolFeeRateCheck.in[0] <== protocolFeeRate;
+    protocolFeeRateCheck.in[1] <== 10000;
+    protocolFeeRateCheck.out === 1;
+
+    // Token amounts are u64 on-chain
+    component privateInputAmountCheck = Num2Bits(64);
+    privateInputAmountCheck.in <== privateInputAmount;
+
+    component privateMinReceivedCheck = Num2Bits(64);
+    privateMinReceivedCheck.in <== privateMinReceived;
+
+    // Prices stay below MAX_SQRT_PRICE_X64. Liquidity is bounded so every product below stays
+    // under the field size, larger pools cannot be swapped with this circuit
+    component sqrtPriceCheck = Num2Bits(96);
+    sqrtPriceCheck.in <== sqrtPriceX64;
+
+    component liquidityCheck = Num2Bits(80);
+    liquidityCheck.in <== liquidity;
+
+    // amountLessFee = in * (10000 - fee) / 10000
+    component feeDivision = ReciprocalDivision(252);
+    feeDivision.dividend <== privateInputAmount * (10000 - feeRate);
+    feeDivision.divisor <== 10000;
+    signal amountLessFee <== feeDivision.quotient;
+
+    signal liquidityX64 <== liquidity * 18446744073709551616;
+
+    // X in lowers the price, rounded up:
+    // next = ceil(liquidity * 2^64 * price / (liquidity * 2^64 + amountLessFee * price))
+    signal downNumerator <== liquidityX64 * sqrtPriceX64;
+    signal downDenominator <== liquidityX64 + amountLessFee * sqrtPriceX64;
+    component downPrice = ReciprocalDivision(252);
+    downPrice.dividend <== downNumerator + downDenominator - 1;
+    downPrice.divisor <== downDenominator;
+    signal sqrtPriceDown <== downPrice.quotient;
+
+    component sqrtPriceDownCheck = Num2Bits(96);
+    sqrtPriceDownCheck.in <== sqrtPriceDown;
+
+    // Y out, rounded down: liquidity * (price - next) / 2^64
+    component yOut = ReciprocalDivision(252);
+    yOut.dividend <== liquidity * (sqrtPriceX64 - sqrtPriceDown);
+    yOut.divisor <== 18446744073709551616;
+
+    component yOutCheck = Num2Bits(112);
+    yOutCheck.in <== yOut.quotient;
+
+    // Y in raises the price, rounded down: next = price + amountLessFee * 2^64 / liquidity
+    component upIncrease = ReciprocalDivision(252);
+    upIncrease.dividend <== amountLessFee * 18446744073709551616;
+    upIncrease.divisor <== liquidity;
+
+    component upIncreaseCheck = Num2Bits(128);
+    upIncreaseCheck.in <== upIncrease.quotient;
+    signal sqrtPriceUp <== sqrtPriceX64 + upIncrease.quotient;
+
+    // X out, rounded down: liquidity * 2^64 * (next - price) / next / price
+    component xOutScaled = ReciprocalDivision(252);
+    xOutScaled.dividend <== liquidityX64 * (sqrtPriceUp - sqrtPriceX64);
+    xOutScaled.divisor <== sqrtPriceUp;
+
+    component xOutScaledCheck = Num2Bits(144);
+    xOutScaledCheck.in <== xOutScaled.quotient;
+
+    component xOut = ReciprocalDivision(252);
+    xOut.dividend <== xOutScaled.quotient;
+    xOut.divisor <== sqrtPriceX64;
+
+    component muxOutput = Mux1();
+    muxOutput.c[0] <== xOut.quotient;
+    muxOutput.c[1] <== yOut.quotient;
+    muxOutput.s <== isSwapXtoY;
+
+    component amountReceivedCheck = Num2Bits(64);
+    amountReceivedCheck.in <== muxOutput.out;
+
+    // Verify minimum received amount
+    component checkMinReceived = GreaterEqThan(64);
+    checkMinReceived.in[0] <== muxOutput.out;
+    checkMinReceived.in[1] <== privateMinReceived;
+    checkMinReceived.out === 1;
+
+    amountIn <== privateInputAmount;
+    amountReceived <== muxOutput.out;
+}
+
+// Public signals, in the order the program binds them: amountIn, amountReceived, nullifier,
+// sqrtPriceX64, liquidity, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot.
+component main {public [sqrtPriceX64, liquidity, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot]} = ZKRangeSwap();
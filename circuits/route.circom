@@ -0,0 +1,159 @@
+pragma circom 2.0.0;
+
+include "node_modules/circomlib/circuits/comparators.circom";
+include "node_modules/circomlib/circuits/bitify.circom";
+include "node_modules/circomlib/circuits/mux1.circom";
+include "node_modules/circomlib/circuits/poseidon.circom";
+include "division.circom";
+
+// One pool of a route, the swap math of ZKConstantProductAMM for a given input amount
+template ConstantProductHop() {
+    signal input amountIn;
+    signal input balanceX;
+    signal input balanceY;
+    signal input isSwapXtoY;
+    signal input feeRate;
+    signal input protocolFeeRate;
+
+    signal output newBalanceX;
+    signal output newBalanceY;
+    signal output amountOut;
+    signal output protocolFee;
+
+    // The direction selects between the two balances, so it must be a bit
+    isSwapXtoY * (isSwapXtoY - 1) === 0;
+
+    // The fee can never exceed the whole input amount
+    component feeRateCheck = LessEqThan(14);
+    feeRateCheck.in[0] <== feeRate;
+    feeRateCheck.in[1] <== 10000;
+    feeRateCheck.out === 1;
+
+    component protocolFeeRateCheck = LessEqThan(14);
+    protocolFeeRateCheck.in[0] <== protocolFeeRate;
+    protocolFeeRateCheck.in[1] <== 10000;
+    protocolFeeRateCheck.out === 1;
+
+    component muxInput = Mux1();
+    muxInput.c[0] <== balanceY;
+    muxInput.c[1] <== balanceX;
+    muxInput.s <== isSwapXtoY;
+    signal inputBalance <== muxInput.out;
+
+    component muxOutput = Mux1();
+    muxOutput.c[0] <== balanceX;
+    muxOutput.c[1] <== balanceY;
+    muxOutput.s <== isSwapXtoY;
+    signal outputBalance <== muxOutput.out;
+
+    // protocolFee = in * fee * protocolFeeRate / 10000^2, kept outside of the reserves
+    signal feeNumerator <== amountIn * feeRate;
+    component protocolFeeDivision = ReciprocalDivision(252);
+    protocolFeeDivision.dividend <== feeNumerator * protocolFeeRate;
+    protocolFeeDivision.divisor <== 100000000;
+    protocolFee <== protocolFeeDivision.quotient;
+
+    signal newInputBalance <== inputBalance + amountIn - protocolFee;
+
+    // out = in * (10000 - fee) * y / (x * 10000 + in * (10000 - fee))
+    signal amountInWithFee <== amountIn * (10000 - feeRate);
+    signal outputNumerator <== amountInWithFee * outputBalance;
+    signal outputDenominator <== inputBalance * 10000 + amountInWithFee;
+
+    component division = ReciprocalDivision(252);
+    division.dividend <== outputNumerator;
+    division.divisor <== outputDenominator;
+    amountOut <== division.quotient;
+
+    signal newOutputBalance <== outputBalance - amountOut;
+
+    signal intermediate1 <== (1 - isSwapXtoY) * newOutputBalance;
+    newBalanceX <== isSwapXtoY * newInputBalance + intermediate1;
+    signal intermediate2 <== (1 - isSwapXtoY) * newInputBalance;
+    newBalanceY <== isSwapXtoY * newOutputBalance + intermediate2;
+
+    component positiveBalance1 = GreaterEqThan(252);
+    positiveBalance1.in[0] <== newBalanceX;
+    positiveBalance1.in[1] <== 0;
+    positiveBalance1.out === 1;
+
+    component positiveBalance2 = GreaterEqThan(252);
+    positiveBalance2.in[0] <== newBalanceY;
+    positiveBalance2.in[1] <== 0;
+    positiveBalance2.out === 1;
+}
+
+// Exact input swap through `hops` pools, each one swapping the whole output of the previous
+// one. Only the first input and the last output are amounts of the user, the ones in between
+// stay inside the proof.
+template ZKConstantProductRoute(hops) {
+    // Private inputs
+    signal input privateInputAmount;
+    signal input privateMinReceived;
+    signal input privateNullifierSecret; // Random per swap, never reused
+
+    // Public inputs, one per pool in route order
+    signal input publicBalanceX[hops];
+    signal input publicBalanceY[hops];
+    signal input isSwapXtoY[hops];
+    signal input feeRate[hops];
+    signal input protocolFeeRate[hops];
+    signal input userHash; // sha256(user || destination) with the top byte cleared
+    signal input expirySlot; // Last slot the proof can be used in
+
+    // Outputs
+    signal output newBalanceX[hops];
+    signal output newBalanceY[hops];
+    signal output protocolFee[hops];
+    signal output amountReceived;
+    signal output nullifier;
+
+    // Spent nullifiers are recorded by the program, so a proof can only be used once
+    component nullifierHash = Poseidon(1);
+    nullifierHash.inputs[0] <== privateNullifierSecret;
+    nullifier <== nullifierHash.out;
+
+    // userHash and expirySlot are not used by the swap, constrain them so the proof commits
+    // to them
+    signal userHashSquare <== userHash * userHash;
+    signal expirySlotSquare <== expirySlot * expirySlot;
+
+    component hop[hops];
+    signal amounts[hops + 1];
+    amounts[0] <== privateInputAmount;
+    for (var i = 0; i < hops; i++) {
+        hop[i] = ConstantProductHop();
+        hop[i].amountIn <== amounts[i];
+        hop[i].balanceX <== publicBalanceX[i];
+        hop[i].balanceY <== publicBalanceY[i];
+        hop[i].isSwapXtoY <== isSwapXtoY[i];
+        hop[i].feeRate <== feeRate[i];
+        hop[i].protocolFeeRate <== protocolFeeRate[i];
+
+        newBalanceX[i] <== hop[i].newBalanceX;
+        newBalanceY[i] <== hop[i].newBalanceY;
+        protocolFee[i] <== hop[i].protocolFee;
+        amounts[i + 1] <== hop[i].amountOut;
+    }
+
+    amountReceived <== amounts[hops];
+
+    // Verify minimum received amount
+    component checkMinReceived = GreaterEqThan(252);
+    checkMinReceived.in[0] <== amountReceived;
+    checkMinReceived.in[1] <== privateMinReceived;
+    checkMinReceived.out === 1;
+
+    // Range check for private inputs
+    component privateInputAmountCheck = Num2Bits(252);
+    privateInputAmountCheck.in <== privateInputAmount;
+
+    component privateMinReceivedCheck = Num2Bits(252);
+    privateMinReceivedCheck.in <== privateMinReceived;
+}
+
+// Public signal order, each array in route order: newBalanceX[], newBalanceY[],
+// protocolFee[], amountReceived, nullifier, publicBalanceX[], publicBalanceY[], isSwapXtoY[],
+// feeRate[], protocolFeeRate[], userHash, expirySlot. The hop count must match ROUTE_HOPS in
+// the program.
+component main {public [publicBalanceX, publicBalanceY, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot]} = ZKConstantProductRoute(2);
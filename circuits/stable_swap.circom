@@ -0,0 +1,233 @@
+pragma circom 2.0.0;
+
+include "node_modules/circomlib/circuits/comparators.circom";
+include "node_modules/circomlib/circuits/bitify.circom";
+include "node_modules/circomlib/circuits/mux1.circom";
+include "node_modules/circomlib/circuits/poseidon.circom";
+include "division.circom";
+
+// Positive and negative terms of G(d) = d^3 + 4xy(4A - 1)d - 16Axy(x + y). The StableSwap
+// invariant of x and y is the largest d where G is not positive.
+template InvariantTerms() {
+    signal input x;
+    signal input y;
+    signal input amp;
+    signal input d;
+    signal output positive;
+    signal output negative;
+
+    signal xy <== x * y;
+    signal dSquare <== d * d;
+    signal dCube <== dSquare * d;
+    signal xyAmp <== xy * (4 * amp - 1);
+    positive <== dCube + 4 * xyAmp * d;
+
+    signal xyAmpSum <== xy * amp;
+    negative <== 16 * xyAmpSum * (x + y);
+}
+
+// Positive and negative terms of H(y) = 16Axy^2 + 16Ax^2y + 4dxy - 16Adxy - d^3, which is
+// -G(d) for the balances x and y. H(y) >= 0 exactly when the invariant of x and y is at
+// least d.
+template BalanceTerms() {
+    signal input x;
+    signal input y;
+    signal input amp;
+    signal input d;
+    signal output positive;
+    signal output negative;
+
+    signal xy <== x * y;
+    signal ampXy <== amp * xy;
+    signal ampXySum <== ampXy * (x + y);
+    signal dXy <== d * xy;
+    positive <== 16 * ampXySum + 4 * dXy;
+
+    signal dSquare <== d * d;
+    signal dCube <== dSquare * d;
+    signal ampDXy <== amp * dXy;
+    negative <== 16 * ampDXy + dCube;
+}
+
+// Swap on a StableSwap pool. Solving the invariant takes Newton iterations, so the prover
+// supplies the invariant and the new output balance and the circuit only checks they are the
+// exact roots, rounded in favour of the pool.
+template ZKStableSwap() {
+    // Private inputs
+    signal input privateInputAmount;
+    signal input privateMinReceived;
+    signal input privateNullifierSecret; // Random per swap, never reused
+    signal input privateInvariant; // Invariant of the pool balances
+    signal input privateNewOutputBalance; // Smallest output balance keeping the invariant
+
+    // Public inputs
+    signal input publicBalanceX;
+    signal input publicBalanceY;
+    signal input isSwapXtoY; // 1 if swapping X to Y, 0 if swapping Y to X
+    signal input feeRate; // Swap fee in basis points, charged on the input amount
+    signal input protocolFeeRate; // Protocol share of the swap fee in basis points
+    signal input userHash; // sha256(user || destination) with the top byte cleared
+    signal input expirySlot; // Last slot the proof can be used in
+    signal input amp; // Amplification coefficient at the slot of the swap
+
+    isSwapXtoY * (isSwapXtoY - 1) === 0;
+
+    // Outputs
+    signal output newBalanceX;
+    signal output newBalanceY;
+    signal output amountReceived;
+    signal output protocolFee;
+    signal output nullifier;
+
+    // Spent nullifiers are recorded by the program, so a proof can only be used once
+    component nullifierHash = Poseidon(1);
+    nullifierHash.inputs[0] <== privateNullifierSecret;
+    nullifier <== nullifierHash.out;
+
+    // Not used by the swap, constrain them so the proof commits to them
+    signal userHashSquare <== userHash * userHash;
+    signal expirySlotSquare <== expirySlot * expirySlot;
+
+    component feeRateCheck = LessEqThan(14);
+    feeRateCheck.in[0] <== feeRate;
+    feeRateCheck.in[1] <== 10000;
+    feeRateCheck.out === 1;
+
+    component protocolFeeRateCheck = LessEqThan(14);
+    protocolFeeRateCheck.in[0] <== protocolFeeRate;
+    protocolFeeRateCheck.in[1] <== 10000;
+    protocolFeeRateCheck.out === 1;
+
+    // MIN_AMP <= amp <= MAX_AMP
+    component ampBits = Num2Bits(20);
+    ampBits.in <== amp;
+
+    component ampMinCheck = GreaterEqThan(20);
+    ampMinCheck.in[0] <== amp;
+    ampMinCheck.in[1] <== 1;
+    ampMinCheck.out === 1;
+
+    component ampMaxCheck = LessEqThan(20);
+    ampMaxCheck.in[0] <== amp;
+    ampMaxCheck.in[1] <== 1000000;
+    ampMaxCheck.out === 1;
+
+    // Token amounts are u64 on-chain. With every value in range, none of the invariant terms
+    // below can wrap around the field.
+    component privateInputAmountCheck = Num2Bits(64);
+    privateInputAmountCheck.in <== privateInputAmount;
+
+    component privateMinReceivedCheck = Num2Bits(64);
+    privateMinReceivedCheck.in <== privateMinReceived;
+
+    component muxInput = Mux1();
+    muxInput.c[0] <== publicBalanceY;
+    muxInput.c[1] <== publicBalanceX;
+    muxInput.s <== isSwapXtoY;
+    signal inputBalance <== muxInput.out;
+
+    component muxOutput = Mux1();
+    muxOutput.c[0] <== publicBalanceX;
+    muxOutput.c[1] <== publicBalanceY;
+    muxOutput.s <== isSwapXtoY;
+    signal outputBalance <== muxOutput.out;
+
+    // protocolFee = in * fee * protocolFeeRate / 10000^2, kept outside of the reserves
+    signal feeNumerator <== privateInputAmount * feeRate;
+    component protocolFeeDivision = ReciprocalDivision(252);
+    protocolFeeDivision.dividend <== feeNumerator * protocolFeeRate;
+    protocolFeeDivision.divisor <== 100000000;
+    protocolFee <== protocolFeeDivision.quotient;
+
+    signal newInputBalance <== inputBalance + privateInputAmount - protocolFee;
+
+    // The fee is left out of the balance the invariant is kept at, so the invariant grows by it
+    component amountInAfterFeeDivision = ReciprocalDivision(252);
+    amountInAfterFeeDivision.dividend <== privateInputAmount * (10000 - feeRate);
+    amountInAfterFeeDivision.divisor <== 10000;
+    signal amountInAfterFee <== amountInAfterFeeDivision.quotient;
+
+    // The invariant of the pool: G(D) <= 0 < G(D + 1), D not zero
+    component invariantBits = Num2Bits(66);
+    invariantBits.in <== privateInvariant;
+
+    component invariantIsZero = IsZero();
+    invariantIsZero.in <== privateInvariant;
+    invariantIsZero.out === 0;
+
+    component invariantTerms = InvariantTerms();
+    invariantTerms.x <== publicBalanceX;
+    invariantTerms.y <== publicBalanceY;
+    invariantTerms.amp <== amp;
+    invariantTerms.d <== privateInvariant;
+
+    component invariantCheck = LessEqThan(252);
+    invariantCheck.in[0] <== invariantTerms.positive;
+    invariantCheck.in[1] <== invariantTerms.negative;
+    invariantCheck.out === 1;
+
+    component nextInvariantTerms = InvariantTerms();
+    nextInvariantTerms.x <== publicBalanceX;
+    nextInvariantTerms.y <== publicBalanceY;
+    nextInvariantTerms.amp <== amp;
+    nextInvariantTerms.d <== privateInvariant + 1;
+
+    component nextInvariantCheck = LessThan(252);
+    nextInvariantCheck.in[0] <== nextInvariantTerms.negative;
+    nextInvariantCheck.in[1] <== nextInvariantTerms.positive;
+    nextInvariantCheck.out === 1;
+
+    // The new output balance: H(y') >= 0 > H(y' - 1). y' is at least one, H(0) = -D^3.
+    signal balanceAfterFee <== inputBalance + amountInAfterFee;
+
+    component newOutputBalanceBits = Num2Bits(64);
+    newOutputBalanceBits.in <== privateNewOutputBalance;
+
+    component balanceTerms = BalanceTerms();
+    balanceTerms.x <== balanceAfterFee;
+    balanceTerms.y <== privateNewOutputBalance;
+    balanceTerms.amp <== amp;
+    balanceTerms.d <== privateInvariant;
+
+    component balanceCheck = LessThan(252);
+    balanceCheck.in[0] <== balanceTerms.positive;
+    balanceCheck.in[1] <== balanceTerms.negative;
+    balanceCheck.out === 0;
+
+    component previousBalanceTerms = BalanceTerms();
+    previousBalanceTerms.x <== balanceAfterFee;
+    previousBalanceTerms.y <== privateNewOutputBalance - 1;
+    previousBalanceTerms.amp <== amp;
+    previousBalanceTerms.d <== privateInvariant;
+
+    component previousBalanceCheck = LessThan(252);
+    previousBalanceCheck.in[0] <== previousBalanceTerms.positive;
+    previousBalanceCheck.in[1] <== previousBalanceTerms.negative;
+    previousBalanceCheck.out === 1;
+
+    component outputBalanceCheck = LessEqThan(64);
+    outputBalanceCheck.in[0] <== privateNewOutputBalance;
+    outputBalanceCheck.in[1] <== outputBalance;
+    outputBalanceCheck.out === 1;
+
+    signal amountOut <== outputBalance - privateNewOutputBalance;
+
+    // Assign new balances
+    signal intermediate1 <== (1 - isSwapXtoY) * privateNewOutputBalance;
+    newBalanceX <== isSwapXtoY * newInputBalance + intermediate1;
+    signal intermediate2 <== (1 - isSwapXtoY) * newInputBalance;
+    newBalanceY <== isSwapXtoY * privateNewOutputBalance + intermediate2;
+
+    amountReceived <== amountOut;
+
+    // Verify minimum received amount
+    component checkMinReceived = GreaterEqThan(64);
+    checkMinReceived.in[0] <== amountReceived;
+    checkMinReceived.in[1] <== privateMinReceived;
+    checkMinReceived.out === 1;
+}
+
+// Public signal order: newBalanceX, newBalanceY, amountReceived, protocolFee, nullifier,
+// publicBalanceX, publicBalanceY, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot,
+// amp. The same as ZKConstantProductAMM with amp appended.
+component main {public [publicBalanceX, publicBalanceY, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot, amp]} = ZKStableSwap();
@@ -3,25 +3,55 @@ pragma circom 2.0.0;
 include "node_modules/circomlib/circuits/comparators.circom";
 include "node_modules/circomlib/circuits/bitify.circom";
 include "node_modules/circomlib/circuits/mux1.circom";
+include "node_modules/circomlib/circuits/poseidon.circom";
 include "division.circom";
 
 template ZKConstantProductAMM() {
     // Private inputs
     signal input privateInputAmount;
     signal input privateMinReceived;
+    signal input privateNullifierSecret; // Random per swap, never reused
 
     // Public inputs
     signal input publicBalanceX;
     signal input publicBalanceY;
     signal input isSwapXtoY; // 1 if swapping X to Y, 0 if swapping Y to X
+    signal input feeRate; // Swap fee in basis points, charged on the input amount
+    signal input protocolFeeRate; // Protocol share of the swap fee in basis points
+    signal input userHash; // sha256(user || destination) with the top byte cleared
+    signal input expirySlot; // Last slot the proof can be used in
+
+    // The direction selects between the two balances, so it must be a bit
+    isSwapXtoY * (isSwapXtoY - 1) === 0;
 
     // Outputs
     signal output newBalanceX;
     signal output newBalanceY;
     signal output amountReceived;
+    signal output protocolFee;
+    signal output nullifier;
+
+    // Spent nullifiers are recorded by the program, so a proof can only be used once
+    component nullifierHash = Poseidon(1);
+    nullifierHash.inputs[0] <== privateNullifierSecret;
+    nullifier <== nullifierHash.out;
+
+    // userHash is not used by the swap itself, constrain it so the proof commits to it
+    signal userHashSquare <== userHash * userHash;
+
+    // Same for expirySlot, which the program checks against the current slot
+    signal expirySlotSquare <== expirySlot * expirySlot;
+
+    // The fee can never exceed the whole input amount
+    component feeRateCheck = LessEqThan(14);
+    feeRateCheck.in[0] <== feeRate;
+    feeRateCheck.in[1] <== 10000;
+    feeRateCheck.out === 1;
 
-    // Calculate constant product
-    signal constantProduct <== publicBalanceX * publicBalanceY;
+    component protocolFeeRateCheck = LessEqThan(14);
+    protocolFeeRateCheck.in[0] <== protocolFeeRate;
+    protocolFeeRateCheck.in[1] <== 10000;
+    protocolFeeRateCheck.out === 1;
 
     // Determine swap direction and calculate amounts
     component muxInput = Mux1();
@@ -36,14 +66,29 @@ template ZKConstantProductAMM() {
     muxOutput.s <== isSwapXtoY;
     signal outputBalance <== muxOutput.out;
 
-    // Calculate new input balance
-    signal newInputBalance <== inputBalance + privateInputAmount;
+    // The protocol keeps its share of the fee outside of the reserves:
+    // protocolFee = in * fee * protocolFeeRate / 10000^2
+    signal feeNumerator <== privateInputAmount * feeRate;
+    component protocolFeeDivision = ReciprocalDivision(252);
+    protocolFeeDivision.dividend <== feeNumerator * protocolFeeRate;
+    protocolFeeDivision.divisor <== 100000000;
+    protocolFee <== protocolFeeDivision.quotient;
+
+    // The rest of the input amount, LP fee included, stays in the reserves
+    signal newInputBalance <== inputBalance + privateInputAmount - protocolFee;
+
+    // Calculate the output amount on the fee-adjusted input:
+    // out = in * (10000 - fee) * y / (x * 10000 + in * (10000 - fee))
+    signal amountInWithFee <== privateInputAmount * (10000 - feeRate);
+    signal outputNumerator <== amountInWithFee * outputBalance;
+    signal outputDenominator <== inputBalance * 10000 + amountInWithFee;
 
-    // Calculate new output balance (y = k / x)
     component division = ReciprocalDivision(252);
-    division.dividend <== constantProduct;
-    division.divisor <== newInputBalance;
-    signal newOutputBalance <== division.quotient;
+    division.dividend <== outputNumerator;
+    division.divisor <== outputDenominator;
+    signal amountOut <== division.quotient;
+
+    signal newOutputBalance <== outputBalance - amountOut;
 
     // Assign new balances
     signal intermediate1 <== (1 - isSwapXtoY) * newOutputBalance;
@@ -52,9 +97,7 @@ template ZKConstantProductAMM() {
     newBalanceY <== isSwapXtoY * newOutputBalance + intermediate2;
 
     // Calculate amount received
-    signal intermediate3 <== isSwapXtoY * (publicBalanceY - newOutputBalance);
-    signal intermediate4 <== (1 - isSwapXtoY) * (publicBalanceX - newOutputBalance);
-    amountReceived <== intermediate3 + intermediate4;
+    amountReceived <== amountOut;
 
     // Verify minimum received amount
     component checkMinReceived = GreaterEqThan(252);
@@ -79,9 +122,10 @@ template ZKConstantProductAMM() {
     positiveBalance2.in[0] <== newBalanceY;
     positiveBalance2.in[1] <== 0;
     positiveBalance2.out === 1;
-
-    // Verify constant product
-    // newBalanceX * newBalanceY === constantProduct;
 }
 
-component main = ZKConstantProductAMM();
\ No newline at end of file
+// The reserves and direction are public so the program can bind the proof to the
+// pool state it is applied to. Public signal order: newBalanceX, newBalanceY,
+// amountReceived, protocolFee, nullifier, publicBalanceX, publicBalanceY, isSwapXtoY,
+// feeRate, protocolFeeRate, userHash, expirySlot.
+component main {public [publicBalanceX, publicBalanceY, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot]} = ZKConstantProductAMM();
\ No newline at end of file
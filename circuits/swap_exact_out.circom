@@ -0,0 +1,137 @@
+pragma circom 2.0.0;
+
+include "node_modules/circomlib/circuits/comparators.circom";
+include "node_modules/circomlib/circuits/bitify.circom";
+include "node_modules/circomlib/circuits/mux1.circom";
+include "node_modules/circomlib/circuits/poseidon.circom";
+include "division.circom";
+
+// Same swap as ZKConstantProductAMM, with the amount received fixed by the user and the
+// input derived from it, rounded up so the pool never gives out more than it is paid for.
+template ZKConstantProductAMMExactOut() {
+    // Private inputs
+    signal input privateOutputAmount;
+    signal input privateMaxInput;
+    signal input privateNullifierSecret; // Random per swap, never reused
+
+    // Public inputs
+    signal input publicBalanceX;
+    signal input publicBalanceY;
+    signal input isSwapXtoY; // 1 if swapping X to Y, 0 if swapping Y to X
+    signal input feeRate; // Swap fee in basis points, charged on the input amount
+    signal input protocolFeeRate; // Protocol share of the swap fee in basis points
+    signal input userHash; // sha256(user || destination) with the top byte cleared
+    signal input expirySlot; // Last slot the proof can be used in
+
+    // The direction selects between the two balances, so it must be a bit
+    isSwapXtoY * (isSwapXtoY - 1) === 0;
+
+    // Outputs, in the same order as ZKConstantProductAMM
+    signal output newBalanceX;
+    signal output newBalanceY;
+    signal output amountReceived;
+    signal output protocolFee;
+    signal output nullifier;
+
+    // Spent nullifiers are recorded by the program, so a proof can only be used once
+    component nullifierHash = Poseidon(1);
+    nullifierHash.inputs[0] <== privateNullifierSecret;
+    nullifier <== nullifierHash.out;
+
+    // userHash is not used by the swap itself, constrain it so the proof commits to it
+    signal userHashSquare <== userHash * userHash;
+
+    // Same for expirySlot, which the program checks against the current slot
+    signal expirySlotSquare <== expirySlot * expirySlot;
+
+    // The fee can never exceed the whole input amount
+    component feeRateCheck = LessEqThan(14);
+    feeRateCheck.in[0] <== feeRate;
+    feeRateCheck.in[1] <== 10000;
+    feeRateCheck.out === 1;
+
+    component protocolFeeRateCheck = LessEqThan(14);
+    protocolFeeRateCheck.in[0] <== protocolFeeRate;
+    protocolFeeRateCheck.in[1] <== 10000;
+    protocolFeeRateCheck.out === 1;
+
+    // Range check for private inputs, before they are compared
+    component privateOutputAmountCheck = Num2Bits(252);
+    privateOutputAmountCheck.in <== privateOutputAmount;
+
+    component privateMaxInputCheck = Num2Bits(252);
+    privateMaxInputCheck.in <== privateMaxInput;
+
+    // Determine swap direction
+    component muxInput = Mux1();
+    muxInput.c[0] <== publicBalanceY;
+    muxInput.c[1] <== publicBalanceX;
+    muxInput.s <== isSwapXtoY;
+    signal inputBalance <== muxInput.out;
+
+    component muxOutput = Mux1();
+    muxOutput.c[0] <== publicBalanceX;
+    muxOutput.c[1] <== publicBalanceY;
+    muxOutput.s <== isSwapXtoY;
+    signal outputBalance <== muxOutput.out;
+
+    // The pool can only give out part of its output reserve
+    component checkOutput = LessThan(252);
+    checkOutput.in[0] <== privateOutputAmount;
+    checkOutput.in[1] <== outputBalance;
+    checkOutput.out === 1;
+
+    signal newOutputBalance <== outputBalance - privateOutputAmount;
+
+    // Smallest fee-adjusted input giving the output, the exact input formula solved for in:
+    // in = ceil(x * 10000 * out / ((y - out) * (10000 - fee)))
+    signal inputNumerator <== inputBalance * 10000;
+    signal inputDividend <== inputNumerator * privateOutputAmount;
+    signal inputDivisor <== newOutputBalance * (10000 - feeRate);
+
+    component division = ReciprocalDivision(252);
+    division.dividend <== inputDividend + inputDivisor - 1;
+    division.divisor <== inputDivisor;
+    signal amountIn <== division.quotient;
+
+    // The protocol keeps its share of the fee outside of the reserves:
+    // protocolFee = in * fee * protocolFeeRate / 10000^2
+    signal feeNumerator <== amountIn * feeRate;
+    component protocolFeeDivision = ReciprocalDivision(252);
+    protocolFeeDivision.dividend <== feeNumerator * protocolFeeRate;
+    protocolFeeDivision.divisor <== 100000000;
+    protocolFee <== protocolFeeDivision.quotient;
+
+    // The rest of the input amount, LP fee included, stays in the reserves
+    signal newInputBalance <== inputBalance + amountIn - protocolFee;
+
+    // Assign new balances
+    signal intermediate1 <== (1 - isSwapXtoY) * newOutputBalance;
+    newBalanceX <== isSwapXtoY * newInputBalance + intermediate1;
+    signal intermediate2 <== (1 - isSwapXtoY) * newInputBalance;
+    newBalanceY <== isSwapXtoY * newOutputBalance + intermediate2;
+
+    amountReceived <== privateOutputAmount;
+
+    // Verify maximum input amount
+    component checkMaxInput = LessEqThan(252);
+    checkMaxInput.in[0] <== amountIn;
+    checkMaxInput.in[1] <== privateMaxInput;
+    checkMaxInput.out === 1;
+
+    // Sanity checks
+    component positiveBalance1 = GreaterEqThan(252);
+    positiveBalance1.in[0] <== newBalanceX;
+    positiveBalance1.in[1] <== 0;
+    positiveBalance1.out === 1;
+
+    component positiveBalance2 = GreaterEqThan(252);
+    positiveBalance2.in[0] <== newBalanceY;
+    positiveBalance2.in[1] <== 0;
+    positiveBalance2.out === 1;
+}
+
+// Same public signals as ZKConstantProductAMM, so the program decodes both alike:
+// newBalanceX, newBalanceY, amountReceived, protocolFee, nullifier, publicBalanceX,
+// publicBalanceY, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot.
+component main {public [publicBalanceX, publicBalanceY, isSwapXtoY, feeRate, protocolFeeRate, userHash, expirySlot]} = ZKConstantProductAMMExactOut();
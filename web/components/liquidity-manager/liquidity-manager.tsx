@@ -41,6 +41,12 @@ import { usePostHog } from 'posthog-js/react';
 
 const tokens: Token[] = tokenList;
 
+// Tolerated move of the pool reserves between signing and execution, in basis points
+const LIQUIDITY_SLIPPAGE_BPS = 50;
+
+const withSlippage = (amount: BN) =>
+  amount.muln(10_000 - LIQUIDITY_SLIPPAGE_BPS).divn(10_000);
+
 export function LiquidityManager() {
   const [pair, setPair] = useState<{ tokenX: Token; tokenY: Token }>({
     tokenX: tokens.find((t) => t.symbol === 'PYUSD') || tokens[0],
@@ -149,6 +155,9 @@ export function LiquidityManager() {
         parseFloat(inputLpTokens) * Math.pow(10, pair.tokenY.decimals)
       );
 
+      const poolAccount = await program.account.pool.fetchNullable(poolPubkey);
+      const hasLiquidity = poolAccount && !poolAccount.liquidity.isZero();
+
       if (isAdding) {
         if (isXNative) {
           // Wrap SOL to WSOL before adding liquidity
@@ -178,8 +187,17 @@ export function LiquidityManager() {
           tx.add(wrapSolIx, syncNativeIx);
         }
 
+        const minLiquidityOut = hasLiquidity
+          ? withSlippage(
+              BN.min(
+                amountX.mul(poolAccount.liquidity).div(poolAccount.reserveX),
+                amountY.mul(poolAccount.liquidity).div(poolAccount.reserveY)
+              )
+            )
+          : new BN(0);
+
         const addLiquidityIx = await program.methods
-          .addLiquidity(amountX, amountY)
+          .addLiquidity(amountX, amountY, minLiquidityOut)
           .accounts({
             tokenMintX: tokenX,
             tokenMintY: tokenY,
@@ -203,8 +221,16 @@ export function LiquidityManager() {
           parseFloat(inputLpTokens) * Math.pow(10, 9)
         );
 
+        const [minAmountX, minAmountY] = hasLiquidity
+          ? [poolAccount.reserveX, poolAccount.reserveY].map((reserve) =>
+              withSlippage(
+                lpTokensToRemove.mul(reserve).div(poolAccount.liquidity)
+              )
+            )
+          : [new BN(0), new BN(0)];
+
         const removeLiquidityIx = await program.methods
-          .removeLiquidity(lpTokensToRemove)
+          .removeLiquidity(lpTokensToRemove, minAmountX, minAmountY)
           .accounts({
             tokenMintX: tokenX,
             tokenMintY: tokenY,
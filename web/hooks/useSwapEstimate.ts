@@ -6,7 +6,7 @@ import { useAnchorProvider } from '../components/solana/solana-provider';
 import { useCluster } from '../components/cluster/cluster-data-access';
 import { getDarklakeProgram, getDarklakeProgramId } from '@darklakefi/anchor';
 import { NATIVE_MINT } from '@solana/spl-token';
-import { generateProof } from '../lib/prepare-proof';
+import { BN } from '@coral-xyz/anchor';
 
 export function useSwapEstimate(
   sourceToken: Token,
@@ -52,40 +52,16 @@ export function useSwapEstimate(
           programId
         );
 
-        // Fetch pool account data
-        const poolAccount = await program.account.pool.fetch(poolPubkey);
-
-        // Prepare inputs for proof generation
-        const publicInputs = {
-          publicBalanceX: poolAccount.reserveX.toString(),
-          publicBalanceY: poolAccount.reserveY.toString(),
-          isSwapXtoY: isSwapXtoY,
-        };
-
         const sourceAmountBN = BigInt(
           Math.floor(parseFloat(sourceAmount) * 10 ** sourceToken.decimals)
         );
 
-        const privateInputs = {
-          privateInputAmount: sourceAmountBN.toString(),
-          privateMinReceived: '0', // We're not enforcing a minimum for estimation
-        };
-
-        // Generate proof
-        const { publicSignals } = await generateProof(
-          privateInputs,
-          publicInputs
-        );
-
-        // Extract the third element (index 2) from publicSignals
-        const amountReceivedSignal = publicSignals[2];
-
-        // Convert the last 8 bytes of the Uint8Array to a BigInt
-        const estimatedAmountBN = amountReceivedSignal
-          .slice(-8)
-          .reduce((acc, value, index) => {
-            return acc + (BigInt(value) << BigInt(8 * (7 - index)));
-          }, BigInt(0));
+        // Simulate the quote, it uses the same math as the swap circuit
+        const quote = await program.methods
+          .quoteSwap(new BN(sourceAmountBN.toString()), isSwapXtoY === 1)
+          .accountsPartial({ pool: poolPubkey })
+          .view();
+        const estimatedAmountBN = BigInt(quote.amountOut.toString());
 
         // Convert back to human-readable format
         const estimatedAmount =
@@ -1,8 +1,16 @@
 'use client';
 
-import { AnchorProvider, utils } from '@coral-xyz/anchor';
+import { AnchorProvider, BN, utils } from '@coral-xyz/anchor';
 import { PublicKey, SystemProgram, Transaction } from '@solana/web3.js';
-import { getDarklakeProgram, getDarklakeProgramId } from '@darklakefi/anchor';
+import {
+  getDarklakeProgram,
+  getDarklakeProgramId,
+  CIRCUIT_CONSTANT_PRODUCT,
+  getNullifierAddress,
+  getUserHash,
+  getVerifyingKeyAddress,
+  PROOF_VALIDITY_SLOTS,
+} from '@darklakefi/anchor';
 import { useAnchorProvider } from '../components/solana/solana-provider';
 import { useCluster } from '../components/cluster/cluster-data-access';
 import { getAssociatedTokenAddress } from '@solana/spl-token';
@@ -115,11 +123,23 @@ export async function prepareConfidentialSwap(
       isSwapXtoY: ${isSwapXtoY}
     `);
 
+    // The proof can only be used for a short while after it is made
+    const expirySlot =
+      BigInt(await provider.connection.getSlot()) + BigInt(PROOF_VALIDITY_SLOTS);
+
     // Prepare inputs for proof generation
     const publicInputs = {
       publicBalanceX: poolAccount.reserveX.toString(),
       publicBalanceY: poolAccount.reserveY.toString(),
       isSwapXtoY: isSwapXtoY,
+      feeRate: poolAccount.feeBps,
+      protocolFeeRate: poolAccount.protocolFeeBps,
+      // Only this wallet can submit the proof, paying out to this token account
+      userHash: await getUserHash(
+        payer.publicKey,
+        isSwapXtoY ? userTokenAccountY : userTokenAccountX
+      ),
+      expirySlot,
     };
 
     const privateInputs = {
@@ -148,11 +168,14 @@ export async function prepareConfidentialSwap(
     transaction.add(
       await program.methods
         .confidentialSwap(
+          CIRCUIT_CONSTANT_PRODUCT,
           Array.from(proofA),
           Array.from(proofB),
           Array.from(proofC),
           // eslint-disable-next-line @typescript-eslint/no-explicit-any
-          publicSignals.map((signal: any) => Array.from(signal))
+          publicSignals.map((signal: any) => Array.from(signal)),
+          isSwapXtoY === 1,
+          new BN(expirySlot.toString())
         )
         .accountsPartial({
           tokenMintX: tokenX,
@@ -164,6 +187,11 @@ export async function prepareConfidentialSwap(
           userTokenAccountY: userTokenAccountY,
           poolTokenAccountX: poolTokenAccountX,
           poolTokenAccountY: poolTokenAccountY,
+          verifyingKey: getVerifyingKeyAddress(
+            program.programId,
+            poolAccount.vkVersions[CIRCUIT_CONSTANT_PRODUCT]
+          ),
+          nullifier: getNullifierAddress(program.programId, publicSignals[4]),
           user: payer.publicKey,
           associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -5,6 +5,7 @@ import {
   negateAndSerializeG1,
   g2Uncompressed,
   to32ByteBuffer,
+  randomFieldElement,
 } from '@darklakefi/anchor';
 
 declare const snarkjs: {
@@ -20,11 +21,19 @@ declare const snarkjs: {
 };
 
 export async function generateProof(
-  privateInputs: { privateInputAmount: string; privateMinReceived: string },
+  privateInputs: {
+    privateInputAmount: string;
+    privateMinReceived: string;
+    privateNullifierSecret?: string;
+  },
   publicInputs: {
     publicBalanceX: bigint;
     publicBalanceY: bigint;
     isSwapXtoY: number;
+    feeRate: number;
+    protocolFeeRate: number;
+    userHash: bigint;
+    expirySlot: bigint;
   }
 ): Promise<{
   proofA: Uint8Array;
@@ -44,9 +53,16 @@ export async function generateProof(
   const input = {
     privateInputAmount: privateInputs.privateInputAmount,
     privateMinReceived: privateInputs.privateMinReceived,
+    // A fresh secret per proof, reusing one makes the swap fail as a replay
+    privateNullifierSecret:
+      privateInputs.privateNullifierSecret ?? randomFieldElement().toString(),
     publicBalanceX: publicInputs.publicBalanceX.toString(),
     publicBalanceY: publicInputs.publicBalanceY.toString(),
     isSwapXtoY: publicInputs.isSwapXtoY.toString(),
+    feeRate: publicInputs.feeRate.toString(),
+    protocolFeeRate: publicInputs.protocolFeeRate.toString(),
+    userHash: publicInputs.userHash.toString(),
+    expirySlot: publicInputs.expirySlot.toString(),
   };
 
   const { proof, publicSignals } = await snarkjs.groth16.fullProve(
@@ -68,12 +84,15 @@ export async function generateProof(
   const proofB = g2Uncompressed(curve, proofProc.pi_b);
   const proofC = g1Uncompressed(curve, proofProc.pi_c);
 
-  // eslint-disable-next-line @typescript-eslint/no-explicit-any
-  const formattedPublicSignals = publicSignalsUnstrigified.map(
-    (signal: any) => {
+  // The program rebuilds the reserves and direction from the pool account, so
+  // only the circuit outputs (newBalanceX, newBalanceY, amountReceived,
+  // protocolFee, nullifier) are sent.
+  const formattedPublicSignals = publicSignalsUnstrigified
+    .slice(0, 5)
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    .map((signal: any) => {
       return to32ByteBuffer(BigInt(signal));
-    }
-  );
+    });
 
   return {
     proofA: new Uint8Array(proofA),